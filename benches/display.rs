@@ -0,0 +1,39 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ruast::*;
+
+fn type_heavy_crate() -> Crate {
+    let mut krate = Crate::new();
+    for i in 0..200 {
+        let tuple = Type::Tuple(vec![Type::usize(), Type::string(), Type::ref_(Type::str())]);
+        let dyn_ = Type::TraitObject(TraitObject::dyn_(vec![
+            GenericBound::Trait(PolyTraitRef::simple("Send")),
+            GenericBound::Trait(PolyTraitRef::simple("Sync")),
+            GenericBound::Outlives("static".into()),
+        ]));
+        let f = Fn::simple(
+            format!("f{i}"),
+            FnDecl::regular(
+                vec![
+                    Param::ident("a", tuple),
+                    Param::ident("b", Type::box_(dyn_)),
+                ],
+                Some(Type::ImplTrait(ImplTrait::new(vec![GenericBound::Trait(
+                    PolyTraitRef::simple("Iterator"),
+                )]))),
+            ),
+            Block::empty(),
+        );
+        krate.add_item(f);
+    }
+    krate
+}
+
+fn bench_display(c: &mut Criterion) {
+    let krate = type_heavy_crate();
+    c.bench_function("display crate", |b| {
+        b.iter(|| black_box(&krate).to_string())
+    });
+}
+
+criterion_group!(benches, bench_display);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use proc_macro2::{Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
 use quote::ToTokens;
 
-use crate::{BinOpToken, Lit, LitKind, Token};
+use crate::{BinOpToken, Lit, LitKind, RenderError, Token};
 
 impl BinOpToken {
     fn to_tokens_spacing(&self, spacing: Spacing, tokens: &mut TokenStream) {
@@ -128,6 +128,14 @@ impl ToTokens for Token {
                 Span::call_site(),
             ))]),
             Self::DocComment(_) => {}
+            // text that does not lex fails where the tokens are compiled, not here
+            Self::Verbatim(text) => match text.parse::<TokenStream>() {
+                Ok(verbatim) => tokens.extend(verbatim),
+                Err(_) => {
+                    let message = RenderError::InvalidVerbatim(text.clone()).to_string();
+                    tokens.extend(quote::quote!(::core::compile_error! { #message }));
+                }
+            },
             Self::Joint(token) => token.to_tokens(tokens),
             Self::Eof => {}
         }
@@ -136,6 +144,12 @@ impl ToTokens for Token {
 
 impl ToTokens for Lit {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        if self.suffix().is_some() {
+            tokens.extend([TokenTree::Literal(
+                self.symbol.parse().expect("invalid literal"),
+            )]);
+            return;
+        }
         match self.kind {
             LitKind::Integer => tokens.extend([TokenTree::Literal(Literal::isize_unsuffixed(
                 self.symbol.parse().unwrap(),
@@ -143,10 +157,16 @@ impl ToTokens for Lit {
             LitKind::Float => tokens.extend([TokenTree::Literal(Literal::f64_unsuffixed(
                 self.symbol.parse().unwrap(),
             ))]),
-            LitKind::Char => tokens.extend([TokenTree::Literal(Literal::character(
-                self.symbol.chars().next().unwrap(),
-            ))]),
             LitKind::Str => tokens.extend([TokenTree::Literal(Literal::string(&self.symbol))]),
+            LitKind::Byte
+            | LitKind::Char
+            | LitKind::StrRaw(_)
+            | LitKind::ByteStr
+            | LitKind::ByteStrRaw(_)
+            | LitKind::CStr
+            | LitKind::CStrRaw(_) => tokens.extend([TokenTree::Literal(
+                self.to_string().parse().expect("invalid literal"),
+            )]),
             _ => todo!(),
         }
     }
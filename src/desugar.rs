@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use crate::visit::{self, Visit, VisitMut};
+use crate::*;
+
+/// Replaces the `impl Trait` types of a parameter with type parameters named `{ident}`,
+/// `{ident}1`, .., skipping the names in `taken`.
+struct ImplTraitLifter<'a> {
+    ident: std::string::String,
+    taken: &'a mut HashSet<std::string::String>,
+    params: Vec<TypeParam>,
+}
+
+impl ImplTraitLifter<'_> {
+    fn fresh_ident(&mut self) -> std::string::String {
+        let ident = (0..)
+            .map(|n| match n {
+                0 => self.ident.clone(),
+                n => format!("{}{n}", self.ident),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+}
+
+impl VisitMut for ImplTraitLifter<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        visit::walk_type_mut(self, ty);
+        if let Type::ImplTrait(impl_trait) = ty {
+            let ident = self.fresh_ident();
+            let bounds = std::mem::take(&mut impl_trait.bounds);
+            self.params.push(TypeParam::new(ident.clone(), bounds));
+            *ty = Type::simple_path(ident);
+        }
+    }
+}
+
+/// Every identifier a new type parameter could shadow: generic parameters and path segments.
+#[derive(Default)]
+struct UsedIdents(HashSet<std::string::String>);
+
+impl<'ast> Visit<'ast> for UsedIdents {
+    fn visit_generic_param(&mut self, param: &'ast GenericParam) {
+        match param {
+            GenericParam::TypeParam(TypeParam { ident, .. })
+            | GenericParam::ConstParam(ConstParam { ident, .. }) => {
+                self.0.insert(ident.to_string());
+            }
+            GenericParam::LifetimeParam(_) => {}
+        }
+        visit::walk_generic_param(self, param);
+    }
+
+    fn visit_path(&mut self, path: &'ast Path) {
+        for segment in path.segments.iter() {
+            self.0.insert(segment.ident.to_string());
+        }
+        visit::walk_path(self, path);
+    }
+}
+
+/// `reader` -> `Reader`, `max_len` -> `MaxLen`
+fn upper_camel_case(ident: &str) -> std::string::String {
+    ident
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .flat_map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect()
+}
+
+impl Fn {
+    /// Replaces every `impl Trait` in the parameter types with a new type parameter named after
+    /// its parameter, e.g. `fn f(reader: impl Read)` becomes `fn f<Reader: Read>(reader: Reader)`,
+    /// so that callers can name the types with a turbofish. Returns the number of type
+    /// parameters introduced.
+    ///
+    /// A name already used by a generic parameter or a path anywhere in the function gets a
+    /// numeric suffix instead, e.g. `fn open(path: impl AsRef<Path>)` becomes
+    /// `fn open<Path1: AsRef<Path>>(path: Path1)`.
+    pub fn desugar_impl_trait_params(&mut self) -> usize {
+        let mut used = UsedIdents::default();
+        used.visit_fn(self);
+        let mut taken = used.0;
+        let mut lifted = vec![];
+        for param in self.fn_decl.inputs.iter_mut() {
+            let ident = match &param.pat {
+                Pat::Ident(ident) => upper_camel_case(ident.ident.as_ref()),
+                _ => std::string::String::new(),
+            };
+            let mut lifter = ImplTraitLifter {
+                ident: if ident.is_empty() { "T".into() } else { ident },
+                taken: &mut taken,
+                params: vec![],
+            };
+            lifter.visit_type_mut(&mut param.ty);
+            lifted.extend(lifter.params);
+        }
+        let count = lifted.len();
+        self.generics
+            .params
+            .extend(lifted.into_iter().map(GenericParam::from));
+        count
+    }
+}
+
+impl Fn {
+    /// Rewrites `async fn f(..) -> T` as `fn f(..) -> Pin<Box<dyn Future<Output = T> + ..>>`,
+    /// wrapping the body in `Box::pin(async move { .. })`, for targets older than Rust 1.75
+    /// where traits can't declare `async fn`. Returns whether the function was `async`.
+    ///
+    /// The future also gets a `Send` bound if `send` is set, and borrows `self` if the receiver
+    /// is a reference. Futures borrowing other parameters need an explicit lifetime.
+    pub fn desugar_async(&mut self, send: bool) -> bool {
+        if !self.is_async {
+            return false;
+        }
+        self.is_async = false;
+        let policy = PathPolicy::Std;
+        let output = self.fn_decl.output.take().unwrap_or_else(Type::unit);
+        let future = PathSegment::generic(
+            "Future",
+            vec![AssocConstraint::new("Output", output).into()],
+        );
+        let mut bounds = vec![PolyTraitRef::simple(policy.core_item("future", future)).into()];
+        if send {
+            bounds.push(PolyTraitRef::simple("Send").into());
+        }
+        if let Some(SelfParam::Ref(lifetime, _)) = self.fn_decl.receiver() {
+            bounds.push(GenericBound::Outlives(
+                lifetime.unwrap_or_else(|| "_".into()),
+            ));
+        }
+        let pin = PathSegment::generic("Pin", vec![GenericArg::Type(Type::dyn_box(bounds))]);
+        self.fn_decl.output = Some(Type::Path(policy.core_item("pin", pin)));
+        if let Some(body) = self.body.take() {
+            let boxed = Path::single("Box").chain("pin");
+            self.body = Some(Block::single(boxed.call1(Async::new_move(body))));
+        }
+        true
+    }
+}
+
+/// The `async fn`s among `items`, see [`Fn::desugar_async`].
+fn desugar_async_items(items: &mut [AssocItem], send: bool) -> usize {
+    let mut count = 0;
+    for item in items.iter_mut() {
+        if let AssocItemKind::Fn(fn_) = &mut item.kind {
+            count += usize::from(fn_.desugar_async(send));
+        }
+    }
+    count
+}
+
+impl TraitDef {
+    /// Desugars every `async fn` of the trait with [`Fn::desugar_async`]. Returns the number
+    /// of rewritten functions.
+    pub fn desugar_async_fns(&mut self, send: bool) -> usize {
+        desugar_async_items(&mut self.items, send)
+    }
+}
+
+impl Impl {
+    /// Desugars every `async fn` of the impl with [`Fn::desugar_async`], matching a trait
+    /// desugared by [`TraitDef::desugar_async_fns`]. Returns the number of rewritten functions.
+    pub fn desugar_async_fns(&mut self, send: bool) -> usize {
+        desugar_async_items(&mut self.items, send)
+    }
+}
@@ -1,12 +1,14 @@
 use core::fmt::Write;
 use std::fmt;
-use std::ops::{Add, Deref, DerefMut, Div, Mul, Neg, Sub};
+use std::ops::{Deref, DerefMut, Neg, Not};
 
 use crate::stmt::{Block, EmptyItem, FnDecl, Pat, Use};
 use crate::token::{BinOpToken, Delimiter, KeywordToken, Token, TokenStream};
 use crate::ty::Type;
+use crate::visit::{self, Visit, VisitMut};
 use crate::{
-    impl_display_for_enum, impl_obvious_conversion, LabelledBlock, UsePath, UseRename, UseTree,
+    impl_display_for_enum, impl_obvious_conversion, render::is_token_text, LabelledBlock,
+    PathPolicy, RenderError, UsePath, UseRename, UseTree,
 };
 
 #[cfg(feature = "fuzzing")]
@@ -186,6 +188,18 @@ pub trait MethodCallable {
     {
         self.method_call(seg, vec![arg1.into(), arg2.into()])
     }
+    /// `self.ident::<generic_args>(args)`
+    fn method_call_generic(
+        self,
+        ident: impl Into<String>,
+        generic_args: Vec<GenericArg>,
+        args: Vec<Expr>,
+    ) -> MethodCall
+    where
+        Self: Sized,
+    {
+        self.method_call(PathSegment::generic(ident, generic_args), args)
+    }
 }
 
 impl<E: Into<Expr>> MethodCallable for E {
@@ -206,11 +220,16 @@ impl<E: Into<Expr>> Castable for E {
 
 pub trait Accessible {
     fn field(self, ident: impl Into<String>) -> Field;
+    fn field_idx(self, index: usize) -> Field;
 }
 
 impl<E: Into<Expr>> Accessible for E {
     fn field(self, ident: impl Into<String>) -> Field {
-        Field::new(self, ident)
+        Field::new(self, ident.into())
+    }
+
+    fn field_idx(self, index: usize) -> Field {
+        Field::new(self, index)
     }
 }
 
@@ -342,6 +361,20 @@ pub trait Addressable {
     {
         self.addr_of(BorrowKind::Raw, Mutability::Mut)
     }
+    /// `&raw const self`
+    fn raw_const(self) -> AddrOf
+    where
+        Self: Sized,
+    {
+        self.ptr_immut()
+    }
+    /// `&raw mut self`
+    fn raw_mut(self) -> AddrOf
+    where
+        Self: Sized,
+    {
+        self.ptr_mut()
+    }
 }
 
 impl<E: Into<Expr>> Addressable for E {
@@ -350,6 +383,24 @@ impl<E: Into<Expr>> Addressable for E {
     }
 }
 
+/// Methods of raw pointers, e.g. `(&raw const buf).cast_ptr(Type::u32()).read_unaligned()`.
+pub trait PointerOperable {
+    /// `self.cast::<ty>()`
+    fn cast_ptr(self, ty: impl Into<Type>) -> MethodCall;
+    /// `self.read_unaligned()`
+    fn read_unaligned(self) -> MethodCall;
+}
+
+impl<E: Into<Expr>> PointerOperable for E {
+    fn cast_ptr(self, ty: impl Into<Type>) -> MethodCall {
+        self.method_call_generic("cast", vec![GenericArg::Type(ty.into())], vec![])
+    }
+
+    fn read_unaligned(self) -> MethodCall {
+        self.method_call0(PathSegment::simple("read_unaligned"))
+    }
+}
+
 pub trait IntoConst {
     fn into_const(self) -> ConstBlock;
 }
@@ -416,7 +467,7 @@ pub trait Parenthesize {
 
 impl<E: Into<Expr>> Parenthesize for E {
     fn paren(self) -> Paren {
-        Paren(Box::new(self.into()))
+        Paren::new(self)
     }
 }
 
@@ -450,26 +501,45 @@ impl From<AttrArgs> for TokenStream {
         match attr {
             AttrArgs::Empty => TokenStream::from(vec![]),
             AttrArgs::Delimited(delim) => delim.into(),
-            AttrArgs::Eq(expr) => TokenStream::from(expr).and(TokenStream::from(vec![Token::Eq])),
+            AttrArgs::Eq(expr) => TokenStream::from(vec![Token::Eq]).and(TokenStream::from(expr)),
         }
     }
 }
 
+/// Whether an attribute applies to the item it precedes (`#[..]`) or to the item, block or
+/// crate it is written in (`#![..]`).
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AttrStyle {
+    #[default]
+    Outer,
+    Inner,
+}
+
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Attribute {
     pub kind: AttrKind,
+    pub style: AttrStyle,
 }
 
 impl fmt::Display for Attribute {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.kind.fmt(f)
+        match (&self.kind, self.style) {
+            (AttrKind::Normal(item), AttrStyle::Inner) => {
+                write!(f, "#![{}{}]", item.path, item.args)
+            }
+            (AttrKind::DocComment(comment), AttrStyle::Inner) => {
+                escape_doc_comment(&inner_doc_comment(comment)).fmt(f)
+            }
+            (kind, _) => kind.fmt(f),
+        }
     }
 }
 
 impl From<AttrKind> for Attribute {
     fn from(kind: AttrKind) -> Self {
-        Self { kind }
+        Self::new(kind)
     }
 }
 
@@ -481,22 +551,309 @@ impl From<AttributeItem> for Attribute {
 
 impl From<Attribute> for TokenStream {
     fn from(value: Attribute) -> Self {
-        TokenStream::from(value.kind)
+        match (value.kind, value.style) {
+            (AttrKind::Normal(item), AttrStyle::Inner) => {
+                let mut ts = TokenStream::from(item);
+                // `#` `[` .. => `#` `!` `[` ..
+                ts.insert(1, Token::Not.into_joint());
+                ts
+            }
+            (AttrKind::DocComment(comment), AttrStyle::Inner) => TokenStream::from(
+                Token::doc_comment(escape_doc_comment(&inner_doc_comment(&comment))),
+            ),
+            (kind, _) => TokenStream::from(kind),
+        }
     }
 }
 
 impl Attribute {
     pub fn new(kind: impl Into<AttrKind>) -> Self {
-        Self { kind: kind.into() }
+        Self {
+            kind: kind.into(),
+            style: AttrStyle::Outer,
+        }
     }
 
     pub fn normal(item: AttributeItem) -> Self {
         Self::new(AttrKind::Normal(item))
     }
 
+    /// `#![item]`
+    pub fn inner(item: AttributeItem) -> Self {
+        Self::normal(item).into_inner()
+    }
+
+    /// The same attribute in the `#![..]` style. Doc comments are rendered as `//!` or `/*!`.
+    pub fn into_inner(mut self) -> Self {
+        self.style = AttrStyle::Inner;
+        self
+    }
+
+    pub fn is_inner(&self) -> bool {
+        self.style == AttrStyle::Inner
+    }
+
     pub fn doc_comment(comment: impl Into<String>) -> Self {
         Self::new(AttrKind::DocComment(comment.into()))
     }
+
+    /// `/// text`, one comment line per line of `text`, after [`sanitize_doc`].
+    pub fn doc(text: &str) -> Self {
+        let text = sanitize_doc(text);
+        let mut comment = std::string::String::new();
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                comment.push('\n');
+            }
+            if line.is_empty() {
+                comment.push_str("///");
+            } else {
+                comment.push_str("/// ");
+                comment.push_str(line);
+            }
+        }
+        Self::doc_comment(comment)
+    }
+
+    /// `//! text`, the inner form of [`Attribute::doc`].
+    pub fn inner_doc(text: &str) -> Self {
+        Self::doc(text).into_inner()
+    }
+
+    /// Like [`Attribute::doc`], but rejects text that [`sanitize_doc`] would have to change.
+    pub fn try_doc(text: &str) -> Result<Self, DocError> {
+        validate_doc(text)?;
+        Ok(Self::doc(text))
+    }
+
+    /// `/** text */`, after [`sanitize_doc`] and with `/*` and `*/` defused.
+    pub fn doc_block(text: &str) -> Self {
+        let text = sanitize_doc(text)
+            .replace("*/", "*\\/")
+            .replace("/*", "/\\*");
+        Self::doc_comment(format!("/**\n{text}\n*/"))
+    }
+
+    /// `#[non_exhaustive]`
+    pub fn non_exhaustive() -> Self {
+        Self::normal(AttributeItem::simple(Path::single("non_exhaustive")))
+    }
+
+    /// `#[must_use]`
+    pub fn must_use() -> Self {
+        Self::normal(AttributeItem::simple(Path::single("must_use")))
+    }
+
+    /// `#[must_use = "message"]`
+    pub fn must_use_msg(message: impl Into<String>) -> Self {
+        Meta::name_value("must_use", Lit::str(message)).into()
+    }
+
+    /// `#[deprecated]`, or `#[deprecated(since = "..", note = "..")]` with the given fields.
+    pub fn deprecated(since: Option<&str>, note: Option<&str>) -> Self {
+        let args = [("since", since), ("note", note)]
+            .into_iter()
+            .filter_map(|(name, value)| Some(Meta::name_value(name, Lit::str(value?))))
+            .collect::<Vec<_>>();
+        if args.is_empty() {
+            Meta::path("deprecated").into()
+        } else {
+            Meta::list("deprecated", args).into()
+        }
+    }
+
+    /// `#[inline]`
+    pub fn inline() -> Self {
+        Self::normal(AttributeItem::simple(Path::single("inline")))
+    }
+
+    /// `#[inline(always)]`
+    pub fn inline_always() -> Self {
+        Meta::list("inline", [Meta::path("always")]).into()
+    }
+
+    /// `#[inline(never)]`
+    pub fn inline_never() -> Self {
+        Meta::list("inline", [Meta::path("never")]).into()
+    }
+
+    /// `#[track_caller]`
+    pub fn track_caller() -> Self {
+        Self::normal(AttributeItem::simple(Path::single("track_caller")))
+    }
+
+    /// `#[derive(A, b::C, ..)]`. Single identifiers containing `::`, such as
+    /// `"serde::Serialize"`, are split into path segments.
+    pub fn derive<P: Into<Path>>(traits: impl IntoIterator<Item = P>) -> Self {
+        let traits = traits.into_iter().map(split_path).collect();
+        Self::normal(derive_attr(traits))
+    }
+
+    /// `#[allow(a, b::c, ..)]`. Like in [`Attribute::derive`], tool lints can be given as
+    /// `"clippy::lint"`.
+    pub fn allow<P: Into<Path>>(lints: impl IntoIterator<Item = P>) -> Self {
+        lint_attr("allow", lints)
+    }
+
+    /// `#[warn(..)]`, see [`Attribute::allow`].
+    pub fn warn<P: Into<Path>>(lints: impl IntoIterator<Item = P>) -> Self {
+        lint_attr("warn", lints)
+    }
+
+    /// `#[deny(..)]`, see [`Attribute::allow`].
+    pub fn deny<P: Into<Path>>(lints: impl IntoIterator<Item = P>) -> Self {
+        lint_attr("deny", lints)
+    }
+
+    /// `#[forbid(..)]`, see [`Attribute::allow`].
+    pub fn forbid<P: Into<Path>>(lints: impl IntoIterator<Item = P>) -> Self {
+        lint_attr("forbid", lints)
+    }
+
+    /// `#[cfg_attr(predicate, attr)]`, e.g. `#[cfg_attr(test, derive(Debug))]`.
+    ///
+    /// A doc comment becomes `doc = "..."`, since `cfg_attr` only takes attributes in meta form.
+    pub fn cfg_attr(predicate: impl Into<TokenStream>, attr: impl Into<Attribute>) -> Self {
+        let item = match attr.into().kind {
+            AttrKind::Normal(item) => item,
+            AttrKind::DocComment(comment) => AttributeItem::new(
+                Path::single("doc"),
+                AttrArgs::Eq(Lit::str(doc_comment_text(&comment)).into()),
+            ),
+        };
+        let mut tokens = predicate.into().into_joint();
+        tokens.push(Token::Comma);
+        tokens.extend(item.into_meta_tokens().into_joint());
+        Self::normal(AttributeItem::new(
+            Path::single("cfg_attr"),
+            AttrArgs::Delimited(DelimArgs::new(MacDelimiter::Parenthesis, tokens)),
+        ))
+    }
+
+    /// `#[cfg_attr(feature = "...", attr)]`
+    pub fn cfg_attr_feature(feature: impl Into<String>, attr: impl Into<Attribute>) -> Self {
+        Self::cfg_attr(feature_predicate(feature), attr)
+    }
+
+    /// `#[repr(repr)]`
+    pub fn repr(repr: Repr) -> Self {
+        Meta::list("repr", [Meta::from(repr)]).into()
+    }
+
+    /// `#[doc = "text"]`, which unlike doc comments is kept when converted to tokens.
+    pub fn doc_attr(text: &str) -> Self {
+        let text = sanitize_doc(text);
+        Self::normal(AttributeItem::new(
+            Path::single("doc"),
+            AttrArgs::Eq(Lit::str(text).into()),
+        ))
+    }
+}
+
+/// The argument of a `#[repr(...)]` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Repr {
+    C,
+    Transparent,
+    /// `packed(n)`
+    Packed(u32),
+    /// `align(n)`
+    Align(u32),
+    /// The discriminant type of an enum, e.g. `u8`.
+    Int(IntTy),
+}
+
+impl fmt::Display for Repr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::C => write!(f, "C"),
+            Self::Transparent => write!(f, "transparent"),
+            Self::Packed(n) => write!(f, "packed({n})"),
+            Self::Align(n) => write!(f, "align({n})"),
+            Self::Int(ty) => write!(f, "{ty}"),
+        }
+    }
+}
+
+impl From<Repr> for Meta {
+    fn from(value: Repr) -> Self {
+        match value {
+            Repr::C => Meta::path("C"),
+            Repr::Transparent => Meta::path("transparent"),
+            Repr::Packed(n) => Meta::list("packed", [Lit::int(n.to_string())]),
+            Repr::Align(n) => Meta::list("align", [Lit::int(n.to_string())]),
+            Repr::Int(ty) => Meta::path(ty.as_str()),
+        }
+    }
+}
+
+impl From<Repr> for TokenStream {
+    fn from(value: Repr) -> Self {
+        Meta::from(value).into()
+    }
+}
+
+/// Text that would break a doc comment if embedded as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocError {
+    CarriageReturn,
+    /// An odd number of backticks on the given (1-based) line, outside of code fences.
+    UnmatchedBacktick(usize),
+    UnclosedFence,
+}
+
+impl fmt::Display for DocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CarriageReturn => write!(f, "doc text contains a carriage return"),
+            Self::UnmatchedBacktick(line) => write!(f, "unmatched backtick on doc line {line}"),
+            Self::UnclosedFence => write!(f, "unclosed code fence in doc text"),
+        }
+    }
+}
+
+impl std::error::Error for DocError {}
+
+/// Checks that `text` can be embedded in a doc comment as it is.
+pub fn validate_doc(text: &str) -> Result<(), DocError> {
+    if text.contains('\r') {
+        return Err(DocError::CarriageReturn);
+    }
+    let mut in_fence = false;
+    for (i, line) in text.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && line.matches('`').count() % 2 == 1 {
+            return Err(DocError::UnmatchedBacktick(i + 1));
+        }
+    }
+    if in_fence {
+        return Err(DocError::UnclosedFence);
+    }
+    Ok(())
+}
+
+/// Fixes what [`validate_doc`] rejects: carriage returns become newlines,
+/// the last unmatched backtick of a line is escaped and an unclosed code fence is closed.
+pub fn sanitize_doc(text: &str) -> std::string::String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines = vec![];
+    let mut in_fence = false;
+    for line in text.split('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            lines.push(line.to_string());
+        } else if !in_fence && line.matches('`').count() % 2 == 1 {
+            let i = line.rfind('`').unwrap();
+            lines.push(format!("{}\\{}", &line[..i], &line[i..]));
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    if in_fence {
+        lines.push("```".to_string());
+    }
+    lines.join("\n")
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -519,9 +876,95 @@ impl fmt::Display for AttrKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
             AttrKind::Normal(item) => item.fmt(f),
-            AttrKind::DocComment(comment) => comment.fmt(f),
+            AttrKind::DocComment(comment) => escape_doc_comment(comment).fmt(f),
+        }
+    }
+}
+
+/// Keeps a stored doc comment from leaking into the surrounding code.
+///
+/// Carriage returns become newlines. In a block comment, every `/*` and `*/` but the enclosing
+/// ones is defused, since block comments nest (and a missing `*/` is added); otherwise,
+/// continuation lines that are not comments get a `///`.
+fn escape_doc_comment(comment: &str) -> std::string::String {
+    let comment = comment.replace("\r\n", "\n").replace('\r', "\n");
+    if let Some(rest) = comment.trim_start().strip_prefix("/*") {
+        let open = &comment[..comment.len() - rest.len()];
+        let (body, close) = match rest.strip_suffix("*/") {
+            Some(body) => (body, "*/"),
+            None => (rest, "\n*/"),
+        };
+        let body = body.replace("*/", "*\\/").replace("/*", "/\\*");
+        return format!("{open}{body}{close}");
+    }
+    let mut escaped = std::string::String::with_capacity(comment.len());
+    for (i, line) in comment.split('\n').enumerate() {
+        if i > 0 {
+            escaped.push('\n');
+            if !line.trim_start().starts_with("//") {
+                escaped.push_str(if line.is_empty() { "///" } else { "/// " });
+            }
         }
+        escaped.push_str(line);
     }
+    escaped
+}
+
+/// The text of a doc comment as rustc passes it to `#[doc = "..."]`: without the `///`, `//!`,
+/// `/**` or `*/` markers, but with the space after them.
+fn doc_comment_text(comment: &str) -> std::string::String {
+    let comment = comment.trim_start();
+    if let Some(body) = comment
+        .strip_prefix("/**")
+        .or_else(|| comment.strip_prefix("/*!"))
+    {
+        return body.strip_suffix("*/").unwrap_or(body).to_string();
+    }
+    comment
+        .split('\n')
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if let Some(rest) = trimmed
+                .strip_prefix("///")
+                .or_else(|| trimmed.strip_prefix("//!"))
+            {
+                rest.to_string()
+            } else if line.is_empty() {
+                std::string::String::new()
+            } else {
+                format!(" {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The inner form of a stored doc comment: `///` lines become `//!` lines and `/**` becomes
+/// `/*!`. Other lines get a `//!`, and comments that already are inner are kept as they are.
+fn inner_doc_comment(comment: &str) -> std::string::String {
+    let comment = comment.trim_start();
+    if let Some(body) = comment.strip_prefix("/**") {
+        return format!("/*!{body}");
+    }
+    if comment.starts_with("/*") {
+        return comment.to_string();
+    }
+    comment
+        .split('\n')
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if let Some(rest) = trimmed.strip_prefix("///") {
+                format!("//!{rest}")
+            } else if trimmed.starts_with("//!") {
+                trimmed.to_string()
+            } else if line.is_empty() {
+                "//!".to_string()
+            } else {
+                format!("//! {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 impl From<AttributeItem> for AttrKind {
@@ -534,7 +977,9 @@ impl From<AttrKind> for TokenStream {
     fn from(value: AttrKind) -> Self {
         match value {
             AttrKind::Normal(item) => TokenStream::from(item),
-            AttrKind::DocComment(comment) => TokenStream::from(Token::DocComment(comment)),
+            AttrKind::DocComment(comment) => {
+                TokenStream::from(Token::doc_comment(escape_doc_comment(&comment)))
+            }
         }
     }
 }
@@ -566,8 +1011,7 @@ impl From<AttributeItem> for TokenStream {
             Token::Pound.into_joint(),
             Token::OpenDelim(Delimiter::Bracket).into_joint(),
         ]);
-        ts.extend(TokenStream::from(attr.path).into_joint());
-        ts.extend(TokenStream::from(attr.args));
+        ts.extend(attr.into_meta_tokens().into_joint());
         ts.push(Token::CloseDelim(Delimiter::Bracket));
         ts
     }
@@ -585,126 +1029,651 @@ impl AttributeItem {
         Self::new(path, AttrArgs::Empty)
     }
 
+    /// `#[cfg(predicate)]`
+    pub fn cfg(predicate: impl Into<TokenStream>) -> Self {
+        let arg = DelimArgs::new(MacDelimiter::Parenthesis, predicate.into().into_joint());
+        Self::new(Path::single("cfg"), AttrArgs::Delimited(arg))
+    }
+
+    /// `#[cfg(not(predicate))]`
+    pub fn cfg_not(predicate: impl Into<TokenStream>) -> Self {
+        let mut tokens = TokenStream::from(vec![
+            Token::ident("not").into_joint(),
+            Token::OpenDelim(Delimiter::Parenthesis).into_joint(),
+        ]);
+        tokens.extend(predicate.into().into_joint());
+        tokens.push(Token::CloseDelim(Delimiter::Parenthesis));
+        Self::cfg(tokens)
+    }
+
     /// `#[cfg(feature = "...")]`
     pub fn cfg_feature(feature: impl Into<String>) -> Self {
-        let tokens = TokenStream::from(vec![
-            Token::ident("feature"),
-            Token::Eq,
-            Token::Lit(Lit::str(feature)).into_joint(),
-        ]);
-        let arg = DelimArgs::new(MacDelimiter::Parenthesis, tokens);
-        Self::new(Path::single("cfg"), AttrArgs::Delimited(arg))
+        Self::cfg(feature_predicate(feature))
+    }
+
+    /// `path args`, the tokens between `#[` and `]`.
+    fn into_meta_tokens(self) -> TokenStream {
+        let mut ts = if matches!(self.args, AttrArgs::Eq(_)) {
+            TokenStream::from(self.path)
+        } else {
+            TokenStream::from(self.path).into_joint()
+        };
+        ts.extend(TokenStream::from(self.args));
+        ts
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Expr {
-    pub attrs: Vec<AttributeItem>,
-    pub kind: ExprKind,
+/// `feature = "..."`
+fn feature_predicate(feature: impl Into<String>) -> TokenStream {
+    Meta::name_value("feature", Lit::str(feature)).into()
 }
 
-#[cfg(feature = "fuzzing")]
-impl<'a> arbitrary::Arbitrary<'a> for Expr {
-    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
-        let kind = ExprKind::arbitrary(u)?;
-        let attrs = match kind {
-            ExprKind::Range(Range { start: None, .. }) => vec![],
-            _ => Vec::<AttributeItem>::arbitrary(u)?,
-        };
-        Ok(Self { attrs, kind })
+/// `path`, with a single identifier containing `::` split into segments.
+fn split_path(path: impl Into<Path>) -> Path {
+    let path = path.into();
+    match &path.segments[..] {
+        [segment] if segment.args.is_none() && segment.ident.contains("::") => Path {
+            segments: segment.ident.split("::").map(PathSegment::from).collect(),
+        },
+        _ => path,
     }
 }
 
-#[cfg(feature = "fuzzing")]
-impl Expr {
-    pub fn arbitrary_const(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
-        Ok(Self::new(ExprKind::arbitrary_const(u)?))
-    }
+/// `#[level(lints, ..)]`
+fn lint_attr<P: Into<Path>>(level: &str, lints: impl IntoIterator<Item = P>) -> Attribute {
+    Meta::list(level, lints.into_iter().map(split_path)).into()
 }
 
-impl HasPrecedence for Expr {
-    fn precedence(&self) -> OperatorPrecedence {
-        self.kind.precedence()
+/// A structured attribute: `path`, `path = lit` or `path(nested, ..)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Meta {
+    Path(Path),
+    NameValue(Path, Lit),
+    List(Path, Vec<NestedMeta>),
+}
+
+/// An element of a [`Meta::List`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NestedMeta {
+    Meta(Meta),
+    /// A bare literal, e.g. `8` in `align(8)`.
+    Lit(Lit),
+}
+
+impl fmt::Display for Meta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "{path}"),
+            Self::NameValue(path, lit) => write!(f, "{path} = {lit}"),
+            Self::List(path, nested) => {
+                write!(f, "{path}(")?;
+                for (i, meta) in nested.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{meta}")?;
+                }
+                write!(f, ")")
+            }
+        }
     }
 }
 
-impl fmt::Display for Expr {
+impl fmt::Display for NestedMeta {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for attr in self.attrs.iter() {
-            writeln!(f, "{attr}")?;
+        match self {
+            Self::Meta(meta) => meta.fmt(f),
+            Self::Lit(lit) => lit.fmt(f),
         }
-        self.kind.fmt(f)
     }
 }
 
-impl<E: Into<ExprKind>> From<E> for Expr {
-    fn from(item: E) -> Self {
-        Self::new(item)
+impl From<Path> for Meta {
+    fn from(path: Path) -> Self {
+        Self::Path(path)
     }
 }
 
-impl From<Expr> for TokenStream {
-    fn from(value: Expr) -> Self {
-        let mut ts = TokenStream::new();
-        for attr in value.attrs.iter() {
-            ts.extend(TokenStream::from(attr.clone()));
-        }
-        ts.extend(TokenStream::from(value.kind));
-        ts
+impl From<Meta> for NestedMeta {
+    fn from(meta: Meta) -> Self {
+        Self::Meta(meta)
     }
 }
 
-impl Expr {
-    pub fn new(kind: impl Into<ExprKind>) -> Self {
-        Self {
-            attrs: Vec::new(),
-            kind: kind.into(),
-        }
+impl From<Path> for NestedMeta {
+    fn from(path: Path) -> Self {
+        Self::Meta(Meta::Path(path))
     }
+}
 
-    pub fn add_attr(&mut self, attr: AttributeItem) {
-        self.attrs.push(attr);
+impl From<Lit> for NestedMeta {
+    fn from(lit: Lit) -> Self {
+        Self::Lit(lit)
     }
+}
 
-    pub fn remove_attr(&mut self, attr: &AttributeItem) {
-        self.attrs.retain(|a| a != attr);
+/// `path`, `path = lit` or `path(..)`, the tokens between `#[` and `]`.
+impl From<Meta> for TokenStream {
+    fn from(value: Meta) -> Self {
+        AttributeItem::from(value).into_meta_tokens()
     }
+}
 
-    pub fn is_compound(&self) -> bool {
-        matches!(
-            &self.kind,
-            ExprKind::Binary(_) | ExprKind::Unary(_) | ExprKind::Field(_) | ExprKind::Range(_)
-        )
+impl From<NestedMeta> for TokenStream {
+    fn from(value: NestedMeta) -> Self {
+        match value {
+            NestedMeta::Meta(meta) => meta.into(),
+            NestedMeta::Lit(lit) => TokenStream::from(Token::Lit(lit)),
+        }
     }
+}
 
-    pub fn should_wrap(&self) -> bool {
-        match &self.kind {
-            ExprKind::Return(Return { expr }) | ExprKind::Yield(Yield { expr })
-                if expr.is_none() =>
-            {
-                true
+impl From<Meta> for AttributeItem {
+    fn from(value: Meta) -> Self {
+        match value {
+            Meta::Path(path) => Self::simple(path),
+            Meta::NameValue(path, lit) => Self::new(path, AttrArgs::Eq(lit.into())),
+            Meta::List(path, nested) => {
+                let mut tokens = TokenStream::new();
+                for (i, meta) in nested.into_iter().enumerate() {
+                    if i > 0 {
+                        tokens.push(Token::Comma);
+                    }
+                    tokens.extend(TokenStream::from(meta).into_joint());
+                }
+                Self::new(
+                    path,
+                    AttrArgs::Delimited(DelimArgs::new(MacDelimiter::Parenthesis, tokens)),
+                )
             }
-            ExprKind::Struct(_) => true,
-            _ => false,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Const(pub Expr);
-
-#[cfg(feature = "fuzzing")]
-impl<'a> arbitrary::Arbitrary<'a> for Const {
-    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
-        Ok(Self(Expr::arbitrary_const(u)?))
+impl From<Meta> for Attribute {
+    fn from(value: Meta) -> Self {
+        Self::normal(value.into())
     }
 }
 
-impl fmt::Display for Const {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+impl Meta {
+    pub fn path(path: impl Into<Path>) -> Self {
+        Self::Path(path.into())
     }
-}
+
+    pub fn name_value(path: impl Into<Path>, lit: Lit) -> Self {
+        Self::NameValue(path.into(), lit)
+    }
+
+    pub fn list<N: Into<NestedMeta>>(
+        path: impl Into<Path>,
+        nested: impl IntoIterator<Item = N>,
+    ) -> Self {
+        Self::List(path.into(), nested.into_iter().map(Into::into).collect())
+    }
+
+    /// The path the meta starts with, e.g. `derive` in `derive(Debug)`.
+    pub fn get_path(&self) -> &Path {
+        match self {
+            Self::Path(path) | Self::NameValue(path, _) | Self::List(path, _) => path,
+        }
+    }
+}
+
+impl AttributeItem {
+    /// The attribute as a [`Meta`], or `None` if its arguments are neither a literal after `=`
+    /// nor a parenthesized list of metas and literals.
+    pub fn to_meta(&self) -> Option<Meta> {
+        match &self.args {
+            AttrArgs::Empty => Some(Meta::Path(self.path.clone())),
+            AttrArgs::Eq(Expr {
+                attrs,
+                kind: ExprKind::Lit(lit),
+            }) if attrs.is_empty() => Some(Meta::NameValue(self.path.clone(), lit.clone())),
+            AttrArgs::Eq(_) => None,
+            AttrArgs::Delimited(DelimArgs {
+                delim: MacDelimiter::Parenthesis,
+                tokens,
+            }) => Some(Meta::List(self.path.clone(), parse_nested_metas(tokens)?)),
+            AttrArgs::Delimited(_) => None,
+        }
+    }
+}
+
+/// `nested, ..`, split on the commas outside of parentheses.
+fn parse_nested_metas(tokens: &[Token]) -> Option<Vec<NestedMeta>> {
+    let mut nested = vec![];
+    let mut start = 0;
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.as_unjoint() {
+            Token::OpenDelim(_) => depth += 1,
+            Token::CloseDelim(_) => depth = depth.checked_sub(1)?,
+            Token::Comma if depth == 0 => {
+                nested.push(parse_nested_meta(&tokens[start..i])?);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    // a trailing comma leaves nothing to parse
+    if start < tokens.len() {
+        nested.push(parse_nested_meta(&tokens[start..])?);
+    }
+    Some(nested)
+}
+
+fn parse_nested_meta(tokens: &[Token]) -> Option<NestedMeta> {
+    if let [token] = tokens {
+        if let Token::Lit(lit) = token.as_unjoint() {
+            return Some(NestedMeta::Lit(lit.clone()));
+        }
+    }
+    let mut segments = vec![];
+    let mut rest = tokens;
+    loop {
+        let Token::Ident(ident) = rest.first()?.as_unjoint() else {
+            return None;
+        };
+        segments.push(PathSegment::simple(ident.clone()));
+        rest = &rest[1..];
+        match rest.first().map(Token::as_unjoint) {
+            Some(Token::ModSep) => rest = &rest[1..],
+            _ => break,
+        }
+    }
+    let path = Path::new(segments);
+    let meta = match rest {
+        [] => Meta::Path(path),
+        [eq, lit] if matches!(eq.as_unjoint(), Token::Eq) => match lit.as_unjoint() {
+            Token::Lit(lit) => Meta::NameValue(path, lit.clone()),
+            _ => return None,
+        },
+        [open, inner @ .., close] => match (open.as_unjoint(), close.as_unjoint()) {
+            (
+                Token::OpenDelim(Delimiter::Parenthesis),
+                Token::CloseDelim(Delimiter::Parenthesis),
+            ) => Meta::List(path, parse_nested_metas(inner)?),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(NestedMeta::Meta(meta))
+}
+
+const STD_DERIVES: [&str; 9] = [
+    "Debug",
+    "Clone",
+    "Copy",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "Hash",
+    "Default",
+];
+const SERDE_DERIVES: [&str; 2] = ["Serialize", "Deserialize"];
+
+/// Controls how `#[derive(...)]` lists are ordered and split.
+///
+/// With `sort` enabled, derives are ordered as std traits (in their conventional order),
+/// then serde traits, then any other derive in the order it was given.
+/// With `max_width` set, the list is split across several `#[derive]` attributes
+/// so that no attribute is wider than the given number of characters (unless a single derive already is).
+/// A trait that is listed more than once is only emitted once.
+///
+/// The policy is not applied when rendering: it is an explicit pre-pass,
+/// either through [`DerivePolicy::apply`] or [`DerivePolicy::apply_to`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DerivePolicy {
+    pub sort: bool,
+    pub max_width: Option<usize>,
+}
+
+impl DerivePolicy {
+    pub fn new(sort: bool, max_width: Option<usize>) -> Self {
+        Self { sort, max_width }
+    }
+
+    pub fn sorted() -> Self {
+        Self::new(true, None)
+    }
+
+    pub fn with_sort(mut self, sort: bool) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    fn rank(path: &Path) -> (usize, usize) {
+        let Some(last) = path.segments.last() else {
+            return (2, 0);
+        };
+        let prefix = path.segments[..path.segments.len() - 1]
+            .first()
+            .map(|seg| &*seg.ident);
+        let name = &*last.ident;
+        if matches!(prefix, None | Some("std" | "core")) {
+            if let Some(i) = STD_DERIVES.iter().position(|&d| d == name) {
+                return (0, i);
+            }
+        }
+        if matches!(prefix, None | Some("serde")) {
+            if let Some(i) = SERDE_DERIVES.iter().position(|&d| d == name) {
+                return (1, i);
+            }
+        }
+        (2, 0)
+    }
+
+    /// Builds `#[derive(...)]` attributes for `traits` according to this policy.
+    pub fn apply<P: Into<Path>>(&self, traits: impl IntoIterator<Item = P>) -> Vec<AttributeItem> {
+        let mut unique = Vec::<Path>::new();
+        for path in traits.into_iter().map(Into::into) {
+            if !unique.contains(&path) {
+                unique.push(path);
+            }
+        }
+        let mut traits = unique;
+        if traits.is_empty() {
+            return vec![];
+        }
+        if self.sort {
+            // stable, so custom derives keep their relative order
+            traits.sort_by_key(Self::rank);
+        }
+        let Some(max_width) = self.max_width else {
+            return vec![derive_attr(traits)];
+        };
+        // `#[derive(` + `)]`
+        const OVERHEAD: usize = 11;
+        let mut groups = vec![];
+        let mut group = vec![];
+        let mut width = OVERHEAD;
+        for path in traits {
+            let len = path.to_string().len();
+            let sep = if group.is_empty() { 0 } else { 2 };
+            if !group.is_empty() && width + sep + len > max_width {
+                groups.push(derive_attr(std::mem::take(&mut group)));
+                width = OVERHEAD + len;
+            } else {
+                width += sep + len;
+            }
+            group.push(path);
+        }
+        groups.push(derive_attr(group));
+        groups
+    }
+
+    /// Merges every `#[derive(...)]` in `attrs` and re-emits them according to this policy,
+    /// at the position of the first one.
+    /// Derive attributes whose arguments are not a plain list of paths are left untouched.
+    pub fn apply_to(&self, attrs: &mut Vec<Attribute>) {
+        let mut first = None;
+        let mut traits = vec![];
+        let mut i = 0;
+        while i < attrs.len() {
+            let paths = match &attrs[i].kind {
+                AttrKind::Normal(item) => derive_paths(item),
+                AttrKind::DocComment(_) => None,
+            };
+            if let Some(paths) = paths {
+                attrs.remove(i);
+                first.get_or_insert(i);
+                traits.extend(paths);
+            } else {
+                i += 1;
+            }
+        }
+        if let Some(first) = first {
+            let derives = self.apply(traits).into_iter().map(Attribute::from);
+            attrs.splice(first..first, derives);
+        }
+    }
+}
+
+pub(crate) fn derive_attr(traits: Vec<Path>) -> AttributeItem {
+    Meta::list("derive", traits).into()
+}
+
+pub(crate) fn derive_paths(item: &AttributeItem) -> Option<Vec<Path>> {
+    if item.path != Path::single("derive") {
+        return None;
+    }
+    let AttrArgs::Delimited(DelimArgs {
+        delim: MacDelimiter::Parenthesis,
+        tokens,
+    }) = &item.args
+    else {
+        return None;
+    };
+    let mut paths = vec![];
+    let mut segments = vec![];
+    let mut expect_ident = true;
+    for token in tokens.iter() {
+        match (token.as_unjoint(), expect_ident) {
+            (Token::Ident(ident), true) => {
+                segments.push(PathSegment::simple(ident.clone()));
+                expect_ident = false;
+            }
+            (Token::ModSep, false) => expect_ident = true,
+            (Token::Comma, false) => {
+                paths.push(Path::new(std::mem::take(&mut segments)));
+                expect_ident = true;
+            }
+            _ => return None,
+        }
+    }
+    if !segments.is_empty() {
+        if expect_ident {
+            // dangling `::`
+            return None;
+        }
+        paths.push(Path::new(segments));
+    }
+    (!paths.is_empty()).then_some(paths)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Expr {
+    pub attrs: Vec<AttributeItem>,
+    pub kind: ExprKind,
+}
+
+#[cfg(feature = "fuzzing")]
+impl<'a> arbitrary::Arbitrary<'a> for Expr {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let kind = ExprKind::arbitrary(u)?;
+        let attrs = match kind {
+            ExprKind::Range(Range { start: None, .. }) => vec![],
+            _ => Vec::<AttributeItem>::arbitrary(u)?,
+        };
+        Ok(Self { attrs, kind })
+    }
+}
+
+#[cfg(feature = "fuzzing")]
+impl Expr {
+    pub fn arbitrary_const(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
+        Ok(Self::new(ExprKind::arbitrary_const(u)?))
+    }
+}
+
+impl HasPrecedence for Expr {
+    fn precedence(&self) -> OperatorPrecedence {
+        self.kind.precedence()
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for attr in self.attrs.iter() {
+            writeln!(f, "{attr}")?;
+        }
+        self.kind.fmt(f)
+    }
+}
+
+impl<E: Into<ExprKind>> From<E> for Expr {
+    fn from(item: E) -> Self {
+        Self::new(item)
+    }
+}
+
+impl From<Expr> for TokenStream {
+    fn from(value: Expr) -> Self {
+        let mut ts = TokenStream::new();
+        for attr in value.attrs.iter() {
+            ts.extend(TokenStream::from(attr.clone()));
+        }
+        ts.extend(TokenStream::from(value.kind));
+        ts
+    }
+}
+
+impl Expr {
+    pub fn new(kind: impl Into<ExprKind>) -> Self {
+        Self {
+            attrs: Vec::new(),
+            kind: kind.into(),
+        }
+    }
+
+    pub fn add_attr(&mut self, attr: AttributeItem) {
+        self.attrs.push(attr);
+    }
+
+    pub fn remove_attr(&mut self, attr: &AttributeItem) {
+        self.attrs.retain(|a| a != attr);
+    }
+
+    pub fn is_compound(&self) -> bool {
+        matches!(
+            &self.kind,
+            ExprKind::Binary(_) | ExprKind::Unary(_) | ExprKind::Field(_) | ExprKind::Range(_)
+        )
+    }
+
+    /// `..` of a functional record update binds tighter than ranges.
+    fn should_wrap_as_rest(&self) -> bool {
+        matches!(&self.kind, ExprKind::Range(_))
+    }
+
+    pub fn should_wrap(&self) -> bool {
+        match &self.kind {
+            ExprKind::Return(Return { expr }) | ExprKind::Yield(Yield { expr })
+                if expr.is_none() =>
+            {
+                true
+            }
+            ExprKind::Struct(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The number of nested expression levels, counting this expression itself.
+    pub fn depth(&self) -> usize {
+        ExprLevels::of(self).counts.len()
+    }
+
+    /// The number of expressions in this tree, including this expression itself.
+    pub fn node_count(&self) -> usize {
+        ExprLevels::of(self).counts.iter().sum()
+    }
+
+    /// Renders at most `max_nodes` expressions, replacing the subexpressions below
+    /// the deepest level that still fits with `...`. Useful for previews in logs and diagnostics.
+    pub fn render_truncated(&self, max_nodes: usize) -> std::string::String {
+        self.truncated(max_nodes).to_string()
+    }
+
+    /// The expression [`Expr::render_truncated`] renders, with the elided subexpressions
+    /// replaced by a `...` [`Verbatim`].
+    pub fn truncated(&self, max_nodes: usize) -> Expr {
+        let levels = ExprLevels::of(self).counts;
+        let mut total = 0;
+        let max_depth = levels
+            .iter()
+            .take_while(|&&count| {
+                total += count;
+                total <= max_nodes
+            })
+            .count();
+        let mut truncated = self.clone();
+        if max_depth < levels.len() {
+            ElideBelow {
+                depth: 0,
+                max_depth,
+            }
+            .visit_expr_mut(&mut truncated);
+        }
+        truncated
+    }
+}
+
+/// Number of expressions at each nesting level.
+#[derive(Default)]
+struct ExprLevels {
+    counts: Vec<usize>,
+    depth: usize,
+}
+
+impl ExprLevels {
+    fn of(expr: &Expr) -> Self {
+        let mut levels = Self::default();
+        levels.visit_expr(expr);
+        levels
+    }
+}
+
+impl<'ast> Visit<'ast> for ExprLevels {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if self.counts.len() <= self.depth {
+            self.counts.push(0);
+        }
+        self.counts[self.depth] += 1;
+        self.depth += 1;
+        visit::walk_expr(self, expr);
+        self.depth -= 1;
+    }
+}
+
+struct ElideBelow {
+    depth: usize,
+    max_depth: usize,
+}
+
+impl VisitMut for ElideBelow {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if self.depth >= self.max_depth {
+            *expr = Expr::new(Verbatim::new("..."));
+            return;
+        }
+        self.depth += 1;
+        visit::walk_expr_mut(self, expr);
+        self.depth -= 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Const(pub Expr);
+
+#[cfg(feature = "fuzzing")]
+impl<'a> arbitrary::Arbitrary<'a> for Const {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(Expr::arbitrary_const(u)?))
+    }
+}
+
+impl fmt::Display for Const {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
 
 impl From<Const> for TokenStream {
     fn from(value: Const) -> Self {
@@ -712,6 +1681,45 @@ impl From<Const> for TokenStream {
     }
 }
 
+impl Const {
+    /// Whether the expression has to be wrapped in braces as a generic argument or the default
+    /// of a const parameter, where only literals, `-literal`, single identifiers and blocks
+    /// may appear unbraced.
+    pub fn needs_braces(&self) -> bool {
+        match &self.0.kind {
+            ExprKind::Lit(_) | ExprKind::Underscore(_) | ExprKind::Verbatim(_) => false,
+            ExprKind::LabelledBlock(block) => block.label.is_some(),
+            ExprKind::Unary(Unary {
+                op: UnaryOpKind::Neg,
+                expr,
+            }) => !matches!(expr.kind, ExprKind::Lit(_)),
+            ExprKind::Path(path) => !matches!(&path.segments[..], [PathSegment { args: None, .. }]),
+            _ => true,
+        }
+    }
+
+    /// Writes the expression in generic argument position, `{ expr }` if it
+    /// [needs braces](Const::needs_braces).
+    pub(crate) fn fmt_arg(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.needs_braces() {
+            write!(f, "{{ {self} }}")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+
+    pub(crate) fn into_arg_tokens(self) -> TokenStream {
+        if !self.needs_braces() {
+            return TokenStream::from(self);
+        }
+        let mut ts = TokenStream::new();
+        ts.push(Token::OpenDelim(Delimiter::Brace));
+        ts.extend(TokenStream::from(self));
+        ts.push(Token::CloseDelim(Delimiter::Brace));
+        ts
+    }
+}
+
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct Array(pub Vec<Expr>);
@@ -879,16 +1887,13 @@ impl Binary {
 
 impl fmt::Display for Binary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.precedence() < self.lhs.precedence()
-        // foo as T < y ==> (foo as T) < y
-        || (self.op == BinOpKind::Lt && self.lhs.precedence() == OperatorPrecedence::Cast)
-        {
+        if self.wraps_lhs() {
             write!(f, "({})", self.lhs)?;
         } else {
             write!(f, "{}", self.lhs)?;
         }
         write!(f, " {} ", self.op)?;
-        if self.precedence() < self.rhs.precedence() {
+        if self.wraps_rhs() {
             write!(f, "({})", self.rhs)?;
         } else {
             write!(f, "{}", self.rhs)?;
@@ -900,11 +1905,8 @@ impl fmt::Display for Binary {
 impl From<Binary> for TokenStream {
     fn from(value: Binary) -> Self {
         let mut ts = TokenStream::new();
-        let precedence = value.precedence();
-        if precedence < value.lhs.precedence()
-        // foo as T < y ==> (foo as T) < y
-        || (value.op == BinOpKind::Lt && value.lhs.precedence() == OperatorPrecedence::Cast)
-        {
+        let (wraps_lhs, wraps_rhs) = (value.wraps_lhs(), value.wraps_rhs());
+        if wraps_lhs {
             ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
             ts.extend(TokenStream::from(*value.lhs).into_joint());
             ts.push(Token::CloseDelim(Delimiter::Parenthesis));
@@ -912,7 +1914,7 @@ impl From<Binary> for TokenStream {
             ts.extend(TokenStream::from(*value.lhs));
         }
         ts.push(Token::from(value.op));
-        if precedence < value.rhs.precedence() {
+        if wraps_rhs {
             ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
             ts.extend(TokenStream::from(*value.rhs).into_joint());
             ts.push(Token::CloseDelim(Delimiter::Parenthesis));
@@ -923,34 +1925,30 @@ impl From<Binary> for TokenStream {
     }
 }
 
-impl<E: Into<Expr>> Add<E> for Expr {
-    type Output = Binary;
-
-    fn add(self, rhs: E) -> Self::Output {
-        Binary::new(self, BinOpKind::Add, rhs)
-    }
-}
-impl<E: Into<Expr>> Sub<E> for Expr {
-    type Output = Binary;
-
-    fn sub(self, rhs: E) -> Self::Output {
-        Binary::new(self, BinOpKind::Sub, rhs)
-    }
-}
-impl<E: Into<Expr>> Mul<E> for Expr {
-    type Output = Binary;
+/// `lhs op rhs` through the `std::ops` operator traits, e.g. `a + b * c`.
+/// Rust's operator precedence decides the shape of the tree, and [`Binary`] adds parentheses
+/// as needed when rendering.
+macro_rules! impl_binary_operators {
+    ($($Ty: ty),* $(,)?) => {
+        $(
+            impl_binary_operators!(@impl $Ty; Add, add; Sub, sub; Mul, mul; Div, div; Rem, rem;
+                BitAnd, bitand; BitOr, bitor; BitXor, bitxor; Shl, shl; Shr, shr);
+        )*
+    };
+    (@impl $Ty: ty; $($Op: ident, $method: ident);*) => {
+        $(
+            impl<E: Into<Expr>> std::ops::$Op<E> for $Ty {
+                type Output = Binary;
 
-    fn mul(self, rhs: E) -> Self::Output {
-        Binary::new(self, BinOpKind::Mul, rhs)
-    }
+                fn $method(self, rhs: E) -> Self::Output {
+                    Binary::new(self, BinOpKind::$Op, rhs)
+                }
+            }
+        )*
+    };
 }
-impl<E: Into<Expr>> Div<E> for Expr {
-    type Output = Binary;
 
-    fn div(self, rhs: E) -> Self::Output {
-        Binary::new(self, BinOpKind::Div, rhs)
-    }
-}
+impl_binary_operators!(Expr, Lit, Path, Binary, Unary, Paren, Call, MethodCall, Field, Index);
 
 impl HasPrecedence for Binary {
     fn precedence(&self) -> OperatorPrecedence {
@@ -966,6 +1964,20 @@ impl Binary {
             rhs: Box::new(rhs.into()),
         }
     }
+
+    fn wraps_lhs(&self) -> bool {
+        let precedence = self.precedence();
+        precedence < self.lhs.precedence()
+            // comparisons are non-associative: (a == b) == c
+            || (precedence == OperatorPrecedence::Compare && precedence == self.lhs.precedence())
+            // foo as T < y ==> (foo as T) < y
+            || (self.op == BinOpKind::Lt && self.lhs.precedence() == OperatorPrecedence::Cast)
+    }
+
+    /// Binary operators are left-associative, so `a - (b - c)` keeps its parentheses.
+    fn wraps_rhs(&self) -> bool {
+        self.precedence() <= self.rhs.precedence()
+    }
 }
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
@@ -1054,14 +2066,31 @@ impl From<Unary> for TokenStream {
     }
 }
 
-impl Neg for Expr {
-    type Output = Unary;
+/// `-expr` and `!expr` through `std::ops::Neg` and `std::ops::Not`
+macro_rules! impl_unary_operators {
+    ($($Ty: ty),* $(,)?) => {
+        $(
+            impl Neg for $Ty {
+                type Output = Unary;
 
-    fn neg(self) -> Self::Output {
-        Unary::new(UnaryOpKind::Neg, self)
-    }
+                fn neg(self) -> Self::Output {
+                    Unary::new(UnaryOpKind::Neg, self)
+                }
+            }
+
+            impl Not for $Ty {
+                type Output = Unary;
+
+                fn not(self) -> Self::Output {
+                    Unary::new(UnaryOpKind::Not, self)
+                }
+            }
+        )*
+    };
 }
 
+impl_unary_operators!(Expr, Lit, Path, Binary, Unary, Paren, Call, MethodCall, Field, Index);
+
 impl Unary {
     pub fn new(op: UnaryOpKind, expr: impl Into<Expr>) -> Self {
         Self {
@@ -1186,6 +2215,7 @@ impl If {
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct While {
+    pub label: Option<Label>,
     pub cond: Box<Expr>,
     pub body: Block,
 }
@@ -1198,6 +2228,9 @@ impl HasPrecedence for While {
 
 impl fmt::Display for While {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "{label}: ")?;
+        }
         write!(f, "while ")?;
         if self.cond.should_wrap() {
             write!(f, "({})", self.cond)?;
@@ -1211,6 +2244,7 @@ impl fmt::Display for While {
 impl From<While> for TokenStream {
     fn from(value: While) -> Self {
         let mut ts = TokenStream::new();
+        push_label(&mut ts, value.label);
         ts.push(Token::Keyword(KeywordToken::While));
         if value.cond.should_wrap() {
             ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
@@ -1227,15 +2261,26 @@ impl From<While> for TokenStream {
 impl While {
     pub fn new(cond: impl Into<Expr>, body: Block) -> Self {
         Self {
+            label: None,
             cond: Box::new(cond.into()),
             body,
         }
     }
-}
+
+    pub fn set_label(&mut self, label: impl Into<Label>) {
+        self.label = Some(label.into());
+    }
+
+    pub fn with_label(mut self, label: impl Into<Label>) -> Self {
+        self.set_label(label);
+        self
+    }
+}
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ForLoop {
+    pub label: Option<Label>,
     pub pat: Box<Pat>,
     pub expr: Box<Expr>,
     pub body: Block,
@@ -1249,6 +2294,9 @@ impl HasPrecedence for ForLoop {
 
 impl fmt::Display for ForLoop {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "{label}: ")?;
+        }
         write!(f, "for {pat} in ", pat = self.pat,)?;
         if self.expr.should_wrap() {
             write!(f, "({})", self.expr)?;
@@ -1262,6 +2310,7 @@ impl fmt::Display for ForLoop {
 impl From<ForLoop> for TokenStream {
     fn from(value: ForLoop) -> Self {
         let mut ts = TokenStream::new();
+        push_label(&mut ts, value.label);
         ts.push(Token::Keyword(KeywordToken::For));
         ts.extend(TokenStream::from(*value.pat));
         ts.push(Token::Keyword(KeywordToken::In));
@@ -1280,16 +2329,27 @@ impl From<ForLoop> for TokenStream {
 impl ForLoop {
     pub fn new(pat: impl Into<Pat>, expr: impl Into<Expr>, body: Block) -> Self {
         Self {
+            label: None,
             pat: Box::new(pat.into()),
             expr: Box::new(expr.into()),
             body,
         }
     }
+
+    pub fn set_label(&mut self, label: impl Into<Label>) {
+        self.label = Some(label.into());
+    }
+
+    pub fn with_label(mut self, label: impl Into<Label>) -> Self {
+        self.set_label(label);
+        self
+    }
 }
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Loop {
+    pub label: Option<Label>,
     pub body: Block,
 }
 
@@ -1301,6 +2361,9 @@ impl HasPrecedence for Loop {
 
 impl fmt::Display for Loop {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "{label}: ")?;
+        }
         write!(f, "loop {body}", body = self.body)
     }
 }
@@ -1308,6 +2371,7 @@ impl fmt::Display for Loop {
 impl From<Loop> for TokenStream {
     fn from(value: Loop) -> Self {
         let mut ts = TokenStream::new();
+        push_label(&mut ts, value.label);
         ts.push(Token::Keyword(KeywordToken::Loop));
         ts.extend(TokenStream::from(value.body));
         ts
@@ -1324,7 +2388,109 @@ impl EmptyItem for Loop {
 
 impl Loop {
     pub fn new(body: Block) -> Self {
-        Self { body }
+        Self { label: None, body }
+    }
+
+    pub fn set_label(&mut self, label: impl Into<Label>) {
+        self.label = Some(label.into());
+    }
+
+    pub fn with_label(mut self, label: impl Into<Label>) -> Self {
+        self.set_label(label);
+        self
+    }
+}
+
+/// `'ident`, the label of a loop or block.
+///
+/// The identifier is stored without the leading apostrophe, so `Label::new("a")` and
+/// `Label::new("'a")` are the same label.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Label(String);
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}", self.0)
+    }
+}
+
+impl<S: Into<String>> From<S> for Label {
+    fn from(ident: S) -> Self {
+        Self::new(ident)
+    }
+}
+
+impl From<Label> for Token {
+    fn from(value: Label) -> Self {
+        Token::lifetime(value.0)
+    }
+}
+
+impl From<Label> for TokenStream {
+    fn from(value: Label) -> Self {
+        TokenStream::from(vec![Token::from(value)])
+    }
+}
+
+/// A label that is not an identifier, is a keyword such as `'static`, or is `'_`, see
+/// [`Label::try_new`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InvalidLabel {
+    /// The identifier, without the leading apostrophe.
+    pub ident: String,
+}
+
+impl fmt::Display for InvalidLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`'{}` is not a valid label", self.ident)
+    }
+}
+
+impl std::error::Error for InvalidLabel {}
+
+impl Label {
+    /// Does not check the label, see [`Label::try_new`] to reject invalid ones.
+    pub fn new(ident: impl Into<String>) -> Self {
+        let ident = ident.into();
+        match ident.strip_prefix('\'') {
+            Some(stripped) => Self(stripped.into()),
+            None => Self(ident),
+        }
+    }
+
+    /// Like [`Label::new`], but fails if the label is not an identifier, or is a keyword such
+    /// as `'static`, or is `'_`.
+    pub fn try_new(ident: impl Into<String>) -> Result<Self, InvalidLabel> {
+        let label = Self::new(ident);
+        if label.is_valid() {
+            Ok(label)
+        } else {
+            Err(InvalidLabel { ident: label.0 })
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let mut chars = self.0.chars();
+        chars
+            .next()
+            .is_some_and(|first| first.is_alphabetic() || first == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_')
+            && self.0 != "_"
+            && KeywordToken::try_from(&*self.0).is_err()
+    }
+
+    /// The identifier, without the leading apostrophe.
+    pub fn ident(&self) -> &str {
+        &self.0
+    }
+}
+
+/// `'label:`
+pub(crate) fn push_label(ts: &mut TokenStream, label: Option<Label>) {
+    if let Some(label) = label {
+        ts.push(Token::from(label).into_joint());
+        ts.push(Token::Colon);
     }
 }
 
@@ -1592,7 +2758,10 @@ impl From<Closure> for TokenStream {
             }
         }
         ts.push(Token::Or);
-        ts.push(Token::RArrow);
+        if let Some(output) = value.fn_decl.output {
+            ts.push(Token::RArrow);
+            ts.extend(TokenStream::from(output));
+        }
         ts.push(Token::OpenDelim(Delimiter::Brace));
         ts.extend(TokenStream::from(*value.body));
         ts.push(Token::CloseDelim(Delimiter::Brace));
@@ -1638,12 +2807,23 @@ impl Closure {
     pub fn new_move(fn_decl: FnDecl, body: impl Into<Expr>) -> Self {
         Self::new(false, false, false, true, fn_decl, body)
     }
+
+    /// Sets the explicit return type, `|..| -> output { .. }`.
+    pub fn set_output(&mut self, output: impl Into<Type>) {
+        self.fn_decl.output = Some(output.into());
+    }
+
+    pub fn with_output(mut self, output: impl Into<Type>) -> Self {
+        self.set_output(output);
+        self
+    }
 }
 
-/// `async { ... }`
+/// `async { ... }`, `async move { ... }`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Async {
+    pub is_move: bool,
     pub block: Block,
 }
 
@@ -1655,7 +2835,11 @@ impl HasPrecedence for Async {
 
 impl fmt::Display for Async {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "async {block}", block = self.block)
+        write!(f, "async ")?;
+        if self.is_move {
+            write!(f, "move ")?;
+        }
+        write!(f, "{block}", block = self.block)
     }
 }
 
@@ -1663,6 +2847,9 @@ impl From<Async> for TokenStream {
     fn from(value: Async) -> Self {
         let mut ts = TokenStream::new();
         ts.push(Token::Keyword(KeywordToken::Async));
+        if value.is_move {
+            ts.push(Token::Keyword(KeywordToken::Move));
+        }
         ts.extend(TokenStream::from(value.block));
         ts
     }
@@ -1678,7 +2865,18 @@ impl EmptyItem for Async {
 
 impl Async {
     pub fn new(block: Block) -> Self {
-        Self { block }
+        Self {
+            is_move: false,
+            block,
+        }
+    }
+
+    /// `async move { ... }`
+    pub fn new_move(block: Block) -> Self {
+        Self {
+            is_move: true,
+            block,
+        }
     }
 }
 
@@ -1773,12 +2971,58 @@ impl TryBlock {
     }
 }
 
-/// `expr.ident`
+/// `ident` or `0`
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Member {
+    Named(String),
+    Unnamed(usize),
+}
+
+impl fmt::Display for Member {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(ident) => write!(f, "{ident}"),
+            Self::Unnamed(index) => write!(f, "{index}"),
+        }
+    }
+}
+
+impl From<Member> for TokenStream {
+    fn from(value: Member) -> Self {
+        match value {
+            Member::Named(ident) => TokenStream::from(vec![Token::ident(ident)]),
+            Member::Unnamed(index) => {
+                TokenStream::from(vec![Token::lit(Lit::int(index.to_string()))])
+            }
+        }
+    }
+}
+
+impl From<String> for Member {
+    fn from(value: String) -> Self {
+        Self::Named(value)
+    }
+}
+
+impl From<&str> for Member {
+    fn from(value: &str) -> Self {
+        Self::Named(value.into())
+    }
+}
+
+impl From<usize> for Member {
+    fn from(value: usize) -> Self {
+        Self::Unnamed(value)
+    }
+}
+
+/// `expr.ident` or `expr.0`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Field {
     pub expr: Box<Expr>,
-    pub ident: String,
+    pub member: Member,
 }
 
 impl HasPrecedence for Field {
@@ -1787,22 +3031,27 @@ impl HasPrecedence for Field {
     }
 }
 
+/// Whether the receiver of `.field` or `.method()` needs parentheses. Postfix expressions
+/// (calls, field accesses, indexing and `?`) chain without them.
+fn wraps_receiver(receiver: &Expr) -> bool {
+    receiver.precedence() > OperatorPrecedence::Try
+}
+
 impl fmt::Display for Field {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.precedence() < self.expr.precedence() {
+        if wraps_receiver(&self.expr) {
             write!(f, "({})", self.expr)?;
         } else {
             write!(f, "{}", self.expr)?;
         }
-        write!(f, ".{}", self.ident)
+        write!(f, ".{}", self.member)
     }
 }
 
 impl From<Field> for TokenStream {
     fn from(value: Field) -> Self {
         let mut ts = TokenStream::new();
-        let precedence = value.precedence();
-        if precedence < value.expr.precedence() {
+        if wraps_receiver(&value.expr) {
             ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
             ts.extend(TokenStream::from(*value.expr).into_joint());
             ts.push(Token::CloseDelim(Delimiter::Parenthesis));
@@ -1810,16 +3059,16 @@ impl From<Field> for TokenStream {
             ts.extend(TokenStream::from(*value.expr).into_joint());
         }
         ts.push(Token::Dot.into_joint());
-        ts.push(Token::ident(value.ident));
+        ts.extend(TokenStream::from(value.member));
         ts
     }
 }
 
 impl Field {
-    pub fn new(expr: impl Into<Expr>, ident: impl Into<String>) -> Self {
+    pub fn new(expr: impl Into<Expr>, member: impl Into<Member>) -> Self {
         Self {
             expr: Box::new(expr.into()),
-            ident: ident.into(),
+            member: member.into(),
         }
     }
 }
@@ -2407,7 +3656,7 @@ impl AssignOp {
 }
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LitKind {
     Bool,
     Byte,
@@ -2416,13 +3665,19 @@ pub enum LitKind {
     Integer,
     Float,
     Str,
+    /// `r#"..."#`, with the number of `#`s
+    StrRaw(u8),
     ByteStr,
+    /// `br#"..."#`, with the number of `#`s
+    ByteStrRaw(u8),
     CStr,
+    /// `cr#"..."#`, with the number of `#`s
+    CStrRaw(u8),
     Err,
 }
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Lit {
     pub kind: LitKind,
     pub symbol: String,
@@ -2430,21 +3685,98 @@ pub struct Lit {
 
 impl HasPrecedence for Lit {
     fn precedence(&self) -> OperatorPrecedence {
-        OperatorPrecedence::Elemental
+        // `-5i32` is a negation, so `(-5i32).abs()` needs its parentheses
+        if self.symbol.starts_with('-') {
+            OperatorPrecedence::Unary
+        } else {
+            OperatorPrecedence::Elemental
+        }
     }
 }
 
 impl fmt::Display for Lit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.kind {
-            LitKind::Str => write!(f, "\"{}\"", self.symbol),
-            LitKind::CStr => write!(f, "c\"{}\"", self.symbol),
-            LitKind::ByteStr => write!(f, "b\"{}\"", self.symbol),
+            LitKind::Byte => {
+                write!(f, "b'")?;
+                write_escaped(f, &self.symbol, '\'', true)?;
+                write!(f, "'")
+            }
+            LitKind::Char => {
+                write!(f, "'")?;
+                write_escaped(f, &self.symbol, '\'', false)?;
+                write!(f, "'")
+            }
+            LitKind::Str => {
+                write!(f, "\"")?;
+                write_escaped(f, &self.symbol, '"', false)?;
+                write!(f, "\"")
+            }
+            LitKind::CStr => {
+                write!(f, "c\"")?;
+                write_escaped(f, &self.symbol, '"', false)?;
+                write!(f, "\"")
+            }
+            LitKind::ByteStr => {
+                write!(f, "b\"")?;
+                write_escaped(f, &self.symbol, '"', true)?;
+                write!(f, "\"")
+            }
+            LitKind::StrRaw(n) => fmt_raw(f, "r", n, &self.symbol),
+            LitKind::ByteStrRaw(n) => fmt_raw(f, "br", n, &self.symbol),
+            LitKind::CStrRaw(n) => fmt_raw(f, "cr", n, &self.symbol),
             _ => self.symbol.fmt(f),
         }
     }
 }
 
+fn fmt_raw(f: &mut fmt::Formatter<'_>, prefix: &str, hashes: u8, symbol: &str) -> fmt::Result {
+    let hashes = "#".repeat(hashes as usize);
+    write!(f, "{prefix}{hashes}\"{symbol}\"{hashes}")
+}
+
+/// Escapes `text` for use inside a literal delimited by `quote`.
+/// With `ascii_only`, as in byte literals, non-ASCII characters are written as `\x` escapes of their UTF-8 bytes.
+fn write_escaped(
+    f: &mut fmt::Formatter<'_>,
+    text: &str,
+    quote: char,
+    ascii_only: bool,
+) -> fmt::Result {
+    for c in text.chars() {
+        match c {
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            '\0' => write!(f, "\\0")?,
+            c if c == quote => write!(f, "\\{c}")?,
+            c if ascii_only && !c.is_ascii() => {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    write!(f, "\\x{byte:02x}")?;
+                }
+            }
+            c if c.is_control() && ascii_only => write!(f, "\\x{:02x}", c as u32)?,
+            c if c.is_control() => write!(f, "{}", c.escape_unicode())?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    Ok(())
+}
+
+/// The least number of `#`s needed so that `symbol` does not terminate a raw string early.
+fn raw_hashes(symbol: &str) -> u8 {
+    let mut needed = 0;
+    for (i, _) in symbol.match_indices('"') {
+        let run = symbol[i + 1..].bytes().take_while(|&b| b == b'#').count();
+        needed = needed.max(run + 1);
+    }
+    needed
+        .try_into()
+        .expect("too many `#`s for a raw string literal")
+}
+
 impl<S: Into<String>> From<S> for Lit {
     fn from(symbol: S) -> Self {
         Self {
@@ -2460,7 +3792,129 @@ impl From<Lit> for TokenStream {
     }
 }
 
+macro_rules! impl_suffixed_constructors {
+    ($kind:ident, $fmt:literal; $($ty:ident),*) => {
+        $(
+            #[doc = concat!("`value", stringify!($ty), "`")]
+            pub fn $ty(value: $ty) -> Self {
+                Self::new(LitKind::$kind, format!(concat!($fmt, "{}"), value, stringify!($ty)))
+            }
+        )*
+    };
+}
+
+/// A primitive integer type, e.g. the argument of `#[repr(u8)]`.
+pub type IntTy = IntSuffix;
+
+/// The type suffix of an integer literal, e.g. `u8` in `3u8`.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IntSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+}
+
+impl fmt::Display for IntSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl IntSuffix {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::I128 => "i128",
+            Self::Isize => "isize",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::U128 => "u128",
+            Self::Usize => "usize",
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::I128 | Self::Isize
+        )
+    }
+
+    /// Whether `value` fits the type. `isize` and `usize` are checked against the host's
+    /// pointer width.
+    pub fn contains(&self, value: i128) -> bool {
+        match self {
+            Self::I8 => i8::try_from(value).is_ok(),
+            Self::I16 => i16::try_from(value).is_ok(),
+            Self::I32 => i32::try_from(value).is_ok(),
+            Self::I64 => i64::try_from(value).is_ok(),
+            Self::I128 => true,
+            Self::Isize => isize::try_from(value).is_ok(),
+            Self::U8 => u8::try_from(value).is_ok(),
+            Self::U16 => u16::try_from(value).is_ok(),
+            Self::U32 => u32::try_from(value).is_ok(),
+            Self::U64 => u64::try_from(value).is_ok(),
+            Self::U128 => u128::try_from(value).is_ok(),
+            Self::Usize => usize::try_from(value).is_ok(),
+        }
+    }
+}
+
+/// An integer that does not fit the type of its suffix, see [`Lit::int_with_suffix`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IntOutOfRange {
+    pub value: i128,
+    pub suffix: IntSuffix,
+}
+
+impl fmt::Display for IntOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is out of range for `{}`", self.value, self.suffix)
+    }
+}
+
+impl std::error::Error for IntOutOfRange {}
+
+pub(crate) const NUMERIC_SUFFIXES: [&str; 14] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32",
+    "f64",
+];
+
 impl Lit {
+    impl_suffixed_constructors!(UInteger, "{}"; u8, u16, u32, u64, u128, usize);
+    impl_suffixed_constructors!(Integer, "{}"; i8, i16, i32, i64, i128, isize);
+
+    /// `valuef32`, e.g. `1.0f32`, or `None` for non-finite values, which have no literal. See
+    /// [`Expr::f32`] for a constructor that accepts any value.
+    pub fn f32(value: f32) -> Option<Self> {
+        value
+            .is_finite()
+            .then(|| Self::new(LitKind::Float, format!("{value:?}f32")))
+    }
+
+    /// `valuef64`, e.g. `1.0f64`, or `None` for non-finite values, which have no literal. See
+    /// [`Expr::f64`] for a constructor that accepts any value.
+    pub fn f64(value: f64) -> Option<Self> {
+        value
+            .is_finite()
+            .then(|| Self::new(LitKind::Float, format!("{value:?}f64")))
+    }
+
     pub fn new(kind: LitKind, symbol: impl Into<String>) -> Self {
         Self {
             kind,
@@ -2468,6 +3922,20 @@ impl Lit {
         }
     }
 
+    /// `{value}{suffix}`, e.g. `255u8`, or an error if `value` does not fit the type.
+    /// `u128` values beyond `i128::MAX` can be built with [`Lit::u128`].
+    pub fn int_with_suffix(value: i128, suffix: IntSuffix) -> Result<Self, IntOutOfRange> {
+        if !suffix.contains(value) {
+            return Err(IntOutOfRange { value, suffix });
+        }
+        let kind = if suffix.is_signed() {
+            LitKind::Integer
+        } else {
+            LitKind::UInteger
+        };
+        Ok(Self::new(kind, format!("{value}{suffix}")))
+    }
+
     pub fn uint(symbol: impl Into<String>) -> Self {
         Self::new(LitKind::UInteger, symbol)
     }
@@ -2476,8 +3944,15 @@ impl Lit {
         Self::new(LitKind::Integer, symbol)
     }
 
+    /// A float literal. A decimal point is added where `symbol` would otherwise read as an
+    /// integer, e.g. `1` becomes `1.0`.
     pub fn float(symbol: impl Into<String>) -> Self {
-        Self::new(LitKind::Float, symbol)
+        let symbol = symbol.into();
+        let lit = Self::new(LitKind::Float, symbol.clone());
+        if lit.suffix().is_some() || symbol.contains(['.', 'e', 'E']) {
+            return lit;
+        }
+        Self::new(LitKind::Float, format!("{symbol}.0"))
     }
 
     pub fn str(symbol: impl Into<String>) -> Self {
@@ -2492,6 +3967,49 @@ impl Lit {
         Self::new(LitKind::ByteStr, symbol)
     }
 
+    /// `r"..."`, with as many `#`s as `symbol` needs
+    pub fn raw_str(symbol: impl Into<String>) -> Self {
+        let symbol = symbol.into();
+        Self::new(LitKind::StrRaw(raw_hashes(&symbol)), symbol)
+    }
+
+    /// `br"..."`, with as many `#`s as `symbol` needs
+    pub fn raw_bstr(symbol: impl Into<String>) -> Self {
+        let symbol = symbol.into();
+        Self::new(LitKind::ByteStrRaw(raw_hashes(&symbol)), symbol)
+    }
+
+    /// `cr"..."`, with as many `#`s as `symbol` needs
+    pub fn raw_cstr(symbol: impl Into<String>) -> Self {
+        let symbol = symbol.into();
+        Self::new(LitKind::CStrRaw(raw_hashes(&symbol)), symbol)
+    }
+
+    /// `b'symbol'`
+    pub fn byte(symbol: impl Into<String>) -> Self {
+        Self::new(LitKind::Byte, symbol)
+    }
+
+    /// `'c'`
+    pub fn char(c: char) -> Self {
+        Self::new(LitKind::Char, c.to_string())
+    }
+
+    /// The type suffix of a numeric literal, e.g. `u8` for `3u8`.
+    pub fn suffix(&self) -> Option<&str> {
+        if !matches!(
+            self.kind,
+            LitKind::Integer | LitKind::UInteger | LitKind::Float
+        ) {
+            return None;
+        }
+        // `f32` and `f64` are digits in hexadecimal literals
+        let is_hex = self.symbol.trim_start_matches('-').starts_with("0x");
+        NUMERIC_SUFFIXES
+            .into_iter()
+            .find(|suffix| self.symbol.ends_with(suffix) && !(is_hex && suffix.starts_with('f')))
+    }
+
     pub fn bool(symbol: impl Into<String>) -> Self {
         Self::new(LitKind::Bool, symbol)
     }
@@ -2689,7 +4207,7 @@ impl HasPrecedence for MethodCall {
 
 impl fmt::Display for MethodCall {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.precedence() < self.receiver.precedence() {
+        if wraps_receiver(&self.receiver) {
             write!(f, "({})", self.receiver)?;
         } else {
             write!(f, "{}", self.receiver)?;
@@ -2708,8 +4226,7 @@ impl fmt::Display for MethodCall {
 impl From<MethodCall> for TokenStream {
     fn from(value: MethodCall) -> Self {
         let mut ts = TokenStream::new();
-        let precedence = value.precedence();
-        if precedence < value.receiver.precedence() {
+        if wraps_receiver(&value.receiver) {
             ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
             ts.extend(TokenStream::from(*value.receiver).into_joint());
             ts.push(Token::CloseDelim(Delimiter::Parenthesis).into_joint());
@@ -2738,6 +4255,25 @@ impl MethodCall {
             args,
         }
     }
+
+    /// `receiver.ident::<generic_args>(args)`
+    pub fn generic(
+        receiver: impl Into<Expr>,
+        ident: impl Into<String>,
+        generic_args: Vec<GenericArg>,
+        args: Vec<Expr>,
+    ) -> Self {
+        Self::new(receiver, PathSegment::generic(ident, generic_args), args)
+    }
+
+    pub fn set_generic_args(&mut self, generic_args: Vec<GenericArg>) {
+        self.seg.args = Some(GenericArgs::AngleBracketed(generic_args));
+    }
+
+    pub fn with_generic_args(mut self, generic_args: Vec<GenericArg>) -> Self {
+        self.set_generic_args(generic_args);
+        self
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -2777,14 +4313,7 @@ impl HasPrecedence for Path {
 
 impl fmt::Display for Path {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut iter = self.segments.iter();
-        if let Some(segment) = iter.next() {
-            write!(f, "{segment}")?;
-            for segment in iter {
-                write!(f, "::{segment}")?;
-            }
-        }
-        Ok(())
+        self.fmt_with(f, true)
     }
 }
 
@@ -2796,24 +4325,61 @@ impl<S: Into<PathSegment>> From<S> for Path {
 
 impl From<Path> for TokenStream {
     fn from(value: Path) -> Self {
+        value.into_tokens_with(true)
+    }
+}
+
+/// A path in type position, see [`Path::display_type`].
+pub struct TypePathDisplay<'a>(&'a Path);
+
+impl fmt::Display for TypePathDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_with(f, false)
+    }
+}
+
+impl Path {
+    pub const fn new(segments: Vec<PathSegment>) -> Self {
+        Self { segments }
+    }
+
+    /// Writes the path with `Vec::<T>` if `turbofish`, and `Vec<T>` otherwise.
+    pub(crate) fn fmt_with(&self, f: &mut fmt::Formatter<'_>, turbofish: bool) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                write!(f, "::")?;
+            }
+            segment.fmt_with(f, turbofish)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn into_tokens_with(self, turbofish: bool) -> TokenStream {
         let mut ts = TokenStream::new();
-        for (i, segment) in value.segments.iter().enumerate() {
+        let len = self.segments.len();
+        for (i, segment) in self.segments.into_iter().enumerate() {
             if i > 0 {
                 ts.push(Token::ModSep.into_joint());
             }
-            if i == value.segments.len() - 1 {
-                ts.extend(TokenStream::from(segment.clone()));
+            let tokens = segment.into_tokens_with(turbofish);
+            if i == len - 1 {
+                ts.extend(tokens);
             } else {
-                ts.extend(TokenStream::from(segment.clone()).into_joint());
+                ts.extend(tokens.into_joint());
             }
         }
         ts
     }
-}
 
-impl Path {
-    pub const fn new(segments: Vec<PathSegment>) -> Self {
-        Self { segments }
+    /// Displays the path as a type, `Vec<T>` rather than `Vec::<T>`, unless the segment asks for
+    /// a turbofish (see [`PathSegment::with_turbofish`]).
+    pub fn display_type(&self) -> TypePathDisplay<'_> {
+        TypePathDisplay(self)
+    }
+
+    /// The tokens of the path as a type, see [`Path::display_type`].
+    pub fn into_type_tokens(self) -> TokenStream {
+        self.into_tokens_with(false)
     }
 
     pub fn single(ident: impl Into<PathSegment>) -> Self {
@@ -2868,7 +4434,10 @@ impl Path {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PathSegment {
     pub ident: String,
-    pub args: Option<Vec<GenericArg>>,
+    pub args: Option<GenericArgs>,
+    /// Writes `ident::<args>` in type position too. Expressions and patterns always get the
+    /// turbofish, since they require it.
+    pub turbofish: bool,
 }
 
 #[cfg(feature = "fuzzing")]
@@ -2882,16 +4451,27 @@ impl PathSegment {
 
 impl fmt::Display for PathSegment {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, true)
+    }
+}
+
+impl PathSegment {
+    fn fmt_with(&self, f: &mut fmt::Formatter<'_>, turbofish: bool) -> fmt::Result {
+        let turbofish = turbofish || self.turbofish;
         write!(f, "{}", self.ident)?;
-        if let Some(args) = &self.args {
-            write!(f, "::<")?;
-            for (i, arg) in args.iter().enumerate() {
-                if i > 0 {
-                    write!(f, ", ")?;
+        match &self.args {
+            Some(GenericArgs::AngleBracketed(args)) => {
+                write!(f, "{}<", if turbofish { "::" } else { "" })?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{arg}")?;
                 }
-                write!(f, "{arg}")?;
+                write!(f, ">")?;
             }
-            write!(f, ">")?;
+            Some(GenericArgs::Parenthesized(args)) => write!(f, "{args}")?,
+            None => {}
         }
         Ok(())
     }
@@ -2902,27 +4482,43 @@ impl<S: Into<String>> From<S> for PathSegment {
         Self {
             ident: ident.into(),
             args: None,
+            turbofish: false,
         }
     }
 }
 
 impl From<PathSegment> for TokenStream {
     fn from(value: PathSegment) -> Self {
-        let mut ts = TokenStream::new();
+        value.into_tokens_with(true)
+    }
+}
 
-        if let Some(args) = value.args {
-            ts.push(Token::ident(value.ident).into_joint());
-            ts.push(Token::ModSep.into_joint());
-            ts.push(Token::Lt.into_joint());
-            for (i, arg) in args.iter().enumerate() {
-                if i > 0 {
-                    ts.push(Token::Comma);
+impl PathSegment {
+    fn into_tokens_with(self, turbofish: bool) -> TokenStream {
+        let mut ts = TokenStream::new();
+        let turbofish = turbofish || self.turbofish;
+        let value = self;
+
+        match value.args {
+            Some(GenericArgs::AngleBracketed(args)) => {
+                ts.push(Token::ident(value.ident).into_joint());
+                if turbofish {
+                    ts.push(Token::ModSep.into_joint());
+                }
+                ts.push(Token::Lt.into_joint());
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        ts.push(Token::Comma);
+                    }
+                    ts.extend(TokenStream::from(arg.clone()).into_joint());
                 }
-                ts.extend(TokenStream::from(arg.clone()).into_joint());
+                ts.push(Token::Gt);
             }
-            ts.push(Token::Gt);
-        } else {
-            ts.push(Token::ident(value.ident));
+            Some(GenericArgs::Parenthesized(args)) => {
+                ts.push(Token::ident(value.ident).into_joint());
+                ts.extend(TokenStream::from(args));
+            }
+            None => ts.push(Token::ident(value.ident)),
         }
         ts
     }
@@ -2932,7 +4528,8 @@ impl PathSegment {
     pub fn new(ident: impl Into<String>, args: Option<Vec<GenericArg>>) -> Self {
         Self {
             ident: ident.into(),
-            args,
+            args: args.map(GenericArgs::AngleBracketed),
+            turbofish: false,
         }
     }
 
@@ -2940,6 +4537,32 @@ impl PathSegment {
         Self::new(ident, None)
     }
 
+    /// `ident::<args>`, or `ident<args>` in type position (see [`PathSegment::with_turbofish`]).
+    pub fn generic(ident: impl Into<String>, args: Vec<GenericArg>) -> Self {
+        Self::new(ident, Some(args))
+    }
+
+    /// Whether the generic arguments keep the turbofish in type position, `Vec::<T>`.
+    pub fn with_turbofish(mut self, turbofish: bool) -> Self {
+        self.turbofish = turbofish;
+        self
+    }
+
+    /// `ident(inputs) -> output`, the sugar required for the `Fn` traits.
+    pub fn parenthesized(
+        ident: impl Into<String>,
+        inputs: Vec<Type>,
+        output: Option<Type>,
+    ) -> Self {
+        Self {
+            ident: ident.into(),
+            args: Some(GenericArgs::Parenthesized(ParenthesizedArgs::new(
+                inputs, output,
+            ))),
+            turbofish: false,
+        }
+    }
+
     #[cfg(feature = "checked-ident")]
     pub fn checked_simple(ident: impl Into<String>) -> Result<Self, String> {
         let ident = crate::check_ident(ident)?;
@@ -2947,11 +4570,95 @@ impl PathSegment {
     }
 }
 
+/// `<qself as trait_>::path`, `<qself>::path`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum BorrowKind {
-    Ref,
-    Raw,
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QPath {
+    pub qself: Box<Type>,
+    pub trait_: Option<Path>,
+    pub path: Path,
+}
+
+impl HasPrecedence for QPath {
+    fn precedence(&self) -> OperatorPrecedence {
+        OperatorPrecedence::Elemental
+    }
+}
+
+impl fmt::Display for QPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, true)
+    }
+}
+
+impl From<QPath> for TokenStream {
+    fn from(value: QPath) -> Self {
+        value.into_tokens_with(true)
+    }
+}
+
+impl QPath {
+    /// Writes the qualified path, with a turbofish in the trailing path if `turbofish`.
+    pub(crate) fn fmt_with(&self, f: &mut fmt::Formatter<'_>, turbofish: bool) -> fmt::Result {
+        write!(f, "<{}", self.qself)?;
+        if let Some(trait_) = &self.trait_ {
+            write!(f, " as {}", trait_.display_type())?;
+        }
+        write!(f, ">::")?;
+        self.path.fmt_with(f, turbofish)
+    }
+
+    pub(crate) fn into_tokens_with(self, turbofish: bool) -> TokenStream {
+        let mut ts = TokenStream::new();
+        ts.push(Token::Lt.into_joint());
+        if let Some(trait_) = self.trait_ {
+            ts.extend(TokenStream::from(*self.qself));
+            ts.push(Token::Keyword(KeywordToken::As));
+            ts.extend(trait_.into_type_tokens().into_joint());
+        } else {
+            ts.extend(TokenStream::from(*self.qself).into_joint());
+        }
+        ts.push(Token::Gt.into_joint());
+        ts.push(Token::ModSep.into_joint());
+        ts.extend(self.path.into_tokens_with(turbofish));
+        ts
+    }
+}
+
+impl QPath {
+    pub fn new(qself: impl Into<Type>, trait_: Option<Path>, path: impl Into<Path>) -> Self {
+        Self {
+            qself: Box::new(qself.into()),
+            trait_,
+            path: path.into(),
+        }
+    }
+
+    /// `<qself as trait_>::path`
+    pub fn as_trait(
+        qself: impl Into<Type>,
+        trait_: impl Into<Path>,
+        path: impl Into<Path>,
+    ) -> Self {
+        Self::new(qself, Some(trait_.into()), path)
+    }
+
+    /// `<qself>::path`
+    pub fn inherent(qself: impl Into<Type>, path: impl Into<Path>) -> Self {
+        Self::new(qself, None, path)
+    }
+
+    pub fn chain(mut self, ident: impl Into<PathSegment>) -> Self {
+        self.path.segments.push(ident.into());
+        self
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BorrowKind {
+    Ref,
+    Raw,
 }
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
@@ -2977,6 +4684,42 @@ impl fmt::Display for Mutability {
     }
 }
 
+/// The safety of an item declared in an `unsafe extern` block, `safe` or `unsafe`.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Safety {
+    #[default]
+    Default,
+    Safe,
+    Unsafe,
+}
+
+impl Safety {
+    pub const fn is_unsafe(&self) -> bool {
+        matches!(self, Self::Unsafe)
+    }
+}
+
+impl fmt::Display for Safety {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Safety::Default => write!(f, ""),
+            Safety::Safe => write!(f, "safe"),
+            Safety::Unsafe => write!(f, "unsafe"),
+        }
+    }
+}
+
+impl From<Safety> for TokenStream {
+    fn from(value: Safety) -> Self {
+        match value {
+            Safety::Default => TokenStream::new(),
+            Safety::Safe => TokenStream::from(Token::ident("safe")),
+            Safety::Unsafe => TokenStream::from(Token::Keyword(KeywordToken::Unsafe)),
+        }
+    }
+}
+
 /// `&expr`, `&mut expr`, `&raw const expr`, `&raw mut expr`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -3067,7 +4810,7 @@ impl AddrOf {
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Break {
-    pub label: Option<String>,
+    pub label: Option<Label>,
     pub expr: Option<Box<Expr>>,
 }
 
@@ -3081,7 +4824,7 @@ impl fmt::Display for Break {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "break")?;
         if let Some(label) = &self.label {
-            write!(f, " '{label}")?;
+            write!(f, " {label}")?;
         }
         if let Some(expr) = &self.expr {
             write!(f, " {expr}")?;
@@ -3095,7 +4838,7 @@ impl From<Break> for TokenStream {
         let mut ts = TokenStream::new();
         ts.push(Token::Keyword(KeywordToken::Break));
         if let Some(label) = value.label {
-            ts.push(Token::lifetime(label));
+            ts.push(label.into());
         }
         if let Some(expr) = value.expr {
             ts.extend(TokenStream::from(*expr));
@@ -3105,7 +4848,7 @@ impl From<Break> for TokenStream {
 }
 
 impl Break {
-    pub fn new(label: Option<String>, expr: Option<Expr>) -> Self {
+    pub fn new(label: Option<Label>, expr: Option<Expr>) -> Self {
         Self {
             label,
             expr: expr.map(Box::new),
@@ -3117,7 +4860,7 @@ impl Break {
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Continue {
-    pub label: Option<String>,
+    pub label: Option<Label>,
 }
 
 impl HasPrecedence for Continue {
@@ -3130,7 +4873,7 @@ impl fmt::Display for Continue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "continue")?;
         if let Some(label) = &self.label {
-            write!(f, " '{label}")?;
+            write!(f, " {label}")?;
         }
         Ok(())
     }
@@ -3141,24 +4884,101 @@ impl From<Continue> for TokenStream {
         let mut ts = TokenStream::new();
         ts.push(Token::Keyword(KeywordToken::Continue));
         if let Some(label) = value.label {
-            ts.push(Token::lifetime(label));
+            ts.push(label.into());
         }
         ts
     }
 }
 
 impl Continue {
-    pub fn new(label: Option<String>) -> Self {
+    pub fn new(label: Option<Label>) -> Self {
         Self { label }
     }
 }
 
+/// The generic arguments of a path segment.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GenericArgs {
+    /// `::<A, 'a, N>`
+    AngleBracketed(Vec<GenericArg>),
+    /// `(A, B) -> C`
+    Parenthesized(ParenthesizedArgs),
+}
+
+impl From<Vec<GenericArg>> for GenericArgs {
+    fn from(args: Vec<GenericArg>) -> Self {
+        Self::AngleBracketed(args)
+    }
+}
+
+impl From<ParenthesizedArgs> for GenericArgs {
+    fn from(args: ParenthesizedArgs) -> Self {
+        Self::Parenthesized(args)
+    }
+}
+
+/// `(inputs) -> output`, as in `Fn(&str) -> io::Result<()>`
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParenthesizedArgs {
+    pub inputs: Vec<Type>,
+    pub output: Option<Box<Type>>,
+}
+
+impl fmt::Display for ParenthesizedArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, input) in self.inputs.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{input}")?;
+        }
+        write!(f, ")")?;
+        if let Some(output) = &self.output {
+            write!(f, " -> {output}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<ParenthesizedArgs> for TokenStream {
+    fn from(value: ParenthesizedArgs) -> Self {
+        let mut ts = TokenStream::new();
+        ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+        for (i, input) in value.inputs.into_iter().enumerate() {
+            if i > 0 {
+                ts.push(Token::Comma);
+            }
+            ts.extend(TokenStream::from(input).into_joint());
+        }
+        ts.push(Token::CloseDelim(Delimiter::Parenthesis));
+        if let Some(output) = value.output {
+            ts.push(Token::RArrow);
+            ts.extend(TokenStream::from(*output));
+        }
+        ts
+    }
+}
+
+impl ParenthesizedArgs {
+    pub fn new(inputs: Vec<Type>, output: Option<Type>) -> Self {
+        Self {
+            inputs,
+            output: output.map(Box::new),
+        }
+    }
+}
+
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GenericArg {
     Lifetime(String),
     Type(Type),
     Const(Const),
+    /// `Item = T`
+    Constraint(AssocConstraint),
 }
 
 impl fmt::Display for GenericArg {
@@ -3166,17 +4986,68 @@ impl fmt::Display for GenericArg {
         match self {
             Self::Lifetime(lifetime) => write!(f, "'{lifetime}"),
             Self::Type(ty) => write!(f, "{ty}"),
-            Self::Const(constant) => write!(f, "{constant}"),
+            Self::Const(constant) => constant.fmt_arg(f),
+            Self::Constraint(constraint) => constraint.fmt(f),
+        }
+    }
+}
+
+impl From<AssocConstraint> for GenericArg {
+    fn from(constraint: AssocConstraint) -> Self {
+        Self::Constraint(constraint)
+    }
+}
+
+/// `ident = ty`, an associated type constraint such as `Iterator<Item = u8>`.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssocConstraint {
+    pub ident: String,
+    pub ty: Type,
+}
+
+impl fmt::Display for AssocConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.ident, self.ty)
+    }
+}
+
+impl From<AssocConstraint> for TokenStream {
+    fn from(value: AssocConstraint) -> Self {
+        let mut ts = TokenStream::from(vec![Token::ident(value.ident), Token::Eq]);
+        ts.extend(TokenStream::from(value.ty));
+        ts
+    }
+}
+
+impl AssocConstraint {
+    pub fn new(ident: impl Into<String>, ty: impl Into<Type>) -> Self {
+        Self {
+            ident: ident.into(),
+            ty: ty.into(),
         }
     }
 }
 
+impl From<Type> for GenericArg {
+    fn from(ty: Type) -> Self {
+        Self::Type(ty)
+    }
+}
+
+impl From<Const> for GenericArg {
+    fn from(constant: Const) -> Self {
+        Self::Const(constant)
+    }
+}
+
 impl From<GenericArg> for TokenStream {
     fn from(value: GenericArg) -> Self {
         match value {
             GenericArg::Lifetime(lifetime) => TokenStream::from(vec![Token::lifetime(lifetime)]),
             GenericArg::Type(ty) => TokenStream::from(ty),
-            GenericArg::Const(constant) => TokenStream::from(constant),
+            GenericArg::Const(constant) => constant.into_arg_tokens(),
+            GenericArg::Constraint(constraint) => TokenStream::from(constraint),
         }
     }
 }
@@ -3227,160 +5098,886 @@ impl fmt::Display for DelimArgs {
     }
 }
 
-impl Default for DelimArgs {
-    fn default() -> Self {
-        Self {
-            delim: MacDelimiter::Parenthesis,
-            tokens: TokenStream::new(),
-        }
+impl Default for DelimArgs {
+    fn default() -> Self {
+        Self {
+            delim: MacDelimiter::Parenthesis,
+            tokens: TokenStream::new(),
+        }
+    }
+}
+
+impl From<Vec<Token>> for DelimArgs {
+    fn from(tokens: Vec<Token>) -> Self {
+        Self {
+            delim: MacDelimiter::Parenthesis,
+            tokens: TokenStream::from(tokens),
+        }
+    }
+}
+
+impl From<DelimArgs> for TokenStream {
+    fn from(value: DelimArgs) -> Self {
+        let mut ts = TokenStream::new();
+        match value.delim {
+            MacDelimiter::Parenthesis => {
+                ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+            }
+            MacDelimiter::Bracket => {
+                ts.push(Token::OpenDelim(Delimiter::Bracket).into_joint());
+            }
+            MacDelimiter::Brace => {
+                ts.push(Token::OpenDelim(Delimiter::Brace).into_joint());
+            }
+        }
+        ts.extend(value.tokens.into_joint());
+        match value.delim {
+            MacDelimiter::Parenthesis => {
+                ts.push(Token::CloseDelim(Delimiter::Parenthesis).into_joint());
+            }
+            MacDelimiter::Bracket => {
+                ts.push(Token::CloseDelim(Delimiter::Bracket).into_joint());
+            }
+            MacDelimiter::Brace => {
+                ts.push(Token::CloseDelim(Delimiter::Brace).into_joint());
+            }
+        }
+        ts
+    }
+}
+
+impl DelimArgs {
+    pub fn new(delim: MacDelimiter, tokens: TokenStream) -> Self {
+        Self { delim, tokens }
+    }
+
+    pub fn parenthesis(tokens: TokenStream) -> Self {
+        Self::new(MacDelimiter::Parenthesis, tokens)
+    }
+
+    pub fn bracket(tokens: TokenStream) -> Self {
+        Self::new(MacDelimiter::Bracket, tokens)
+    }
+
+    pub fn brace(tokens: TokenStream) -> Self {
+        Self::new(MacDelimiter::Brace, tokens)
+    }
+
+    pub fn add_token(&mut self, token: Token) {
+        self.tokens.push(token);
+    }
+
+    pub fn remove_token(&mut self, token: &Token) {
+        self.tokens.retain(|t| t != token);
+    }
+
+    pub fn get_token(&self, index: usize) -> Option<&Token> {
+        self.tokens.get(index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MacCall {
+    /// `!` not included
+    pub path: Path,
+    pub args: DelimArgs,
+}
+
+#[cfg(feature = "fuzzing")]
+impl<'a> arbitrary::Arbitrary<'a> for MacCall {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let path = Path::arbitrary_no_arg(u)?;
+        let args = DelimArgs::arbitrary(u)?;
+        Ok(Self { path, args })
+    }
+}
+
+impl HasPrecedence for MacCall {
+    fn precedence(&self) -> OperatorPrecedence {
+        OperatorPrecedence::Call
+    }
+}
+
+impl fmt::Display for MacCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}!{}", self.path, self.args)?;
+        Ok(())
+    }
+}
+
+impl From<MacCall> for TokenStream {
+    fn from(value: MacCall) -> Self {
+        let mut ts = TokenStream::new();
+        ts.extend(TokenStream::from(value.path).into_joint());
+        ts.push(Token::Not.into_joint());
+        ts.extend(TokenStream::from(value.args));
+        ts
+    }
+}
+
+impl MacCall {
+    pub fn new(path: Path, args: impl Into<DelimArgs>) -> Self {
+        Self {
+            path,
+            args: args.into(),
+        }
+    }
+
+    /// Whether the call needs a trailing `;` in statement or item position,
+    /// i.e. whether it is not delimited by braces.
+    pub fn needs_semi(&self) -> bool {
+        self.args.delim != MacDelimiter::Brace
+    }
+
+    pub fn bracket(path: Path, tokens: Vec<impl Into<TokenStream>>) -> Self {
+        Self {
+            path,
+            args: DelimArgs::bracket(TokenStream::aggregate(tokens.into_iter().map(|t| {
+                let mut ts = t.into();
+                ts.push(Token::Comma);
+                ts
+            }))),
+        }
+    }
+}
+
+/// A register operand class (`reg`) or an explicit register (`"eax"`).
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AsmReg {
+    Class(String),
+    Explicit(String),
+}
+
+impl fmt::Display for AsmReg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Class(class) => write!(f, "{class}"),
+            Self::Explicit(reg) => write!(f, "\"{reg}\""),
+        }
+    }
+}
+
+impl From<AsmReg> for Token {
+    fn from(value: AsmReg) -> Self {
+        match value {
+            AsmReg::Class(class) => Token::ident(class),
+            AsmReg::Explicit(reg) => Token::lit(reg),
+        }
+    }
+}
+
+impl AsmReg {
+    /// `reg`
+    pub fn reg() -> Self {
+        Self::Class("reg".into())
+    }
+
+    pub fn class(class: impl Into<String>) -> Self {
+        Self::Class(class.into())
+    }
+
+    pub fn explicit(reg: impl Into<String>) -> Self {
+        Self::Explicit(reg.into())
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AsmOperandKind {
+    /// `in(reg) expr`
+    In { reg: AsmReg, expr: Expr },
+    /// `out(reg) expr`, `lateout(reg) _`
+    Out {
+        reg: AsmReg,
+        late: bool,
+        expr: Option<Expr>,
+    },
+    /// `inout(reg) expr`, `inlateout(reg) in_expr => out_expr`
+    InOut {
+        reg: AsmReg,
+        late: bool,
+        expr: Expr,
+        out_expr: Option<Option<Expr>>,
+    },
+    /// `const expr`
+    Const(Expr),
+    /// `sym path`
+    Sym(Path),
+}
+
+/// `(name =)? operand`
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AsmOperand {
+    pub name: Option<String>,
+    pub kind: AsmOperandKind,
+}
+
+impl fmt::Display for AsmOperand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        TokenStream::from(self.clone()).fmt(f)
+    }
+}
+
+impl From<AsmOperand> for TokenStream {
+    fn from(value: AsmOperand) -> Self {
+        fn push_place(ts: &mut TokenStream, expr: Option<Expr>) {
+            match expr {
+                Some(expr) => ts.extend(TokenStream::from(expr)),
+                None => ts.push(Token::ident("_")),
+            }
+        }
+        fn push_dir(ts: &mut TokenStream, dir: Token, reg: AsmReg) {
+            ts.push(dir.into_joint());
+            ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+            ts.push(Token::from(reg).into_joint());
+            ts.push(Token::CloseDelim(Delimiter::Parenthesis));
+        }
+
+        let mut ts = TokenStream::new();
+        if let Some(name) = value.name {
+            ts.push(Token::ident(name));
+            ts.push(Token::Eq);
+        }
+        match value.kind {
+            AsmOperandKind::In { reg, expr } => {
+                push_dir(&mut ts, Token::Keyword(KeywordToken::In), reg);
+                ts.extend(TokenStream::from(expr));
+            }
+            AsmOperandKind::Out { reg, late, expr } => {
+                let dir = if late { "lateout" } else { "out" };
+                push_dir(&mut ts, Token::ident(dir), reg);
+                push_place(&mut ts, expr);
+            }
+            AsmOperandKind::InOut {
+                reg,
+                late,
+                expr,
+                out_expr,
+            } => {
+                let dir = if late { "inlateout" } else { "inout" };
+                push_dir(&mut ts, Token::ident(dir), reg);
+                ts.extend(TokenStream::from(expr));
+                if let Some(out_expr) = out_expr {
+                    ts.push(Token::FatArrow);
+                    push_place(&mut ts, out_expr);
+                }
+            }
+            AsmOperandKind::Const(expr) => {
+                ts.push(Token::Keyword(KeywordToken::Const));
+                ts.extend(TokenStream::from(expr));
+            }
+            AsmOperandKind::Sym(path) => {
+                ts.push(Token::ident("sym"));
+                ts.extend(TokenStream::from(path));
+            }
+        }
+        ts
+    }
+}
+
+impl AsmOperand {
+    pub fn new(kind: AsmOperandKind) -> Self {
+        Self { name: None, kind }
+    }
+
+    /// `in(reg) expr`
+    pub fn in_(reg: AsmReg, expr: impl Into<Expr>) -> Self {
+        Self::new(AsmOperandKind::In {
+            reg,
+            expr: expr.into(),
+        })
+    }
+
+    /// `out(reg) expr`
+    pub fn out(reg: AsmReg, expr: impl Into<Expr>) -> Self {
+        Self::new(AsmOperandKind::Out {
+            reg,
+            late: false,
+            expr: Some(expr.into()),
+        })
+    }
+
+    /// `lateout(reg) expr`
+    pub fn lateout(reg: AsmReg, expr: impl Into<Expr>) -> Self {
+        Self::new(AsmOperandKind::Out {
+            reg,
+            late: true,
+            expr: Some(expr.into()),
+        })
+    }
+
+    /// `out(reg) _`, marks the register as clobbered.
+    pub fn clobber(reg: AsmReg) -> Self {
+        Self::new(AsmOperandKind::Out {
+            reg,
+            late: false,
+            expr: None,
+        })
+    }
+
+    /// `inout(reg) expr`
+    pub fn inout(reg: AsmReg, expr: impl Into<Expr>) -> Self {
+        Self::new(AsmOperandKind::InOut {
+            reg,
+            late: false,
+            expr: expr.into(),
+            out_expr: None,
+        })
+    }
+
+    /// `inout(reg) expr => out_expr`
+    pub fn inout_split(reg: AsmReg, expr: impl Into<Expr>, out_expr: impl Into<Expr>) -> Self {
+        Self::new(AsmOperandKind::InOut {
+            reg,
+            late: false,
+            expr: expr.into(),
+            out_expr: Some(Some(out_expr.into())),
+        })
+    }
+
+    /// `const expr`
+    pub fn const_(expr: impl Into<Expr>) -> Self {
+        Self::new(AsmOperandKind::Const(expr.into()))
+    }
+
+    /// `sym path`
+    pub fn sym(path: impl Into<Path>) -> Self {
+        Self::new(AsmOperandKind::Sym(path.into()))
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = Some(name.into());
+    }
+
+    /// `name = operand`
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    /// Turns `out`/`inout` into `lateout`/`inlateout`.
+    pub fn late(mut self) -> Self {
+        if let AsmOperandKind::Out { late, .. } | AsmOperandKind::InOut { late, .. } =
+            &mut self.kind
+        {
+            *late = true;
+        }
+        self
+    }
+}
+
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AsmOption {
+    Pure,
+    Nomem,
+    Readonly,
+    PreservesFlags,
+    Noreturn,
+    Nostack,
+    AttSyntax,
+    Raw,
+    MayUnwind,
+}
+
+impl fmt::Display for AsmOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pure => write!(f, "pure"),
+            Self::Nomem => write!(f, "nomem"),
+            Self::Readonly => write!(f, "readonly"),
+            Self::PreservesFlags => write!(f, "preserves_flags"),
+            Self::Noreturn => write!(f, "noreturn"),
+            Self::Nostack => write!(f, "nostack"),
+            Self::AttSyntax => write!(f, "att_syntax"),
+            Self::Raw => write!(f, "raw"),
+            Self::MayUnwind => write!(f, "may_unwind"),
+        }
+    }
+}
+
+/// `asm!("template", operands, clobber_abi("abi"), options(...))`, or `global_asm!(...)`
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Asm {
+    pub is_global: bool,
+    pub template: Vec<String>,
+    pub operands: Vec<AsmOperand>,
+    pub clobber_abis: Vec<String>,
+    pub options: Vec<AsmOption>,
+}
+
+impl HasPrecedence for Asm {
+    fn precedence(&self) -> OperatorPrecedence {
+        OperatorPrecedence::Call
+    }
+}
+
+impl fmt::Display for Asm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        MacCall::from(self.clone()).fmt(f)
+    }
+}
+
+impl From<Asm> for MacCall {
+    fn from(value: Asm) -> Self {
+        let mut args = vec![];
+        for line in value.template {
+            args.push(TokenStream::from(Token::lit(line)));
+        }
+        for operand in value.operands {
+            args.push(TokenStream::from(operand));
+        }
+        for abi in value.clobber_abis {
+            let mut ts = TokenStream::new();
+            ts.push(Token::ident("clobber_abi").into_joint());
+            ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+            ts.push(Token::lit(abi).into_joint());
+            ts.push(Token::CloseDelim(Delimiter::Parenthesis));
+            args.push(ts);
+        }
+        if !value.options.is_empty() {
+            let mut ts = TokenStream::new();
+            ts.push(Token::ident("options").into_joint());
+            ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+            for (i, option) in value.options.iter().enumerate() {
+                if i > 0 {
+                    ts.push(Token::Comma);
+                }
+                ts.push(Token::ident(option.to_string()).into_joint());
+            }
+            ts.push(Token::CloseDelim(Delimiter::Parenthesis));
+            args.push(ts);
+        }
+        let mut tokens = TokenStream::new();
+        for (i, arg) in args.into_iter().enumerate() {
+            if i > 0 {
+                tokens.push(Token::Comma);
+            }
+            tokens.extend(arg.into_joint());
+        }
+        let path = if value.is_global { "global_asm" } else { "asm" };
+        MacCall::new(Path::single(path), DelimArgs::parenthesis(tokens))
+    }
+}
+
+impl From<Asm> for TokenStream {
+    fn from(value: Asm) -> Self {
+        TokenStream::from(MacCall::from(value))
+    }
+}
+
+impl Asm {
+    pub fn new(template: Vec<String>) -> Self {
+        Self {
+            is_global: false,
+            template,
+            operands: vec![],
+            clobber_abis: vec![],
+            options: vec![],
+        }
+    }
+
+    /// `asm!("line")`
+    pub fn single(line: impl Into<String>) -> Self {
+        Self::new(vec![line.into()])
+    }
+
+    /// `global_asm!(template)`
+    pub fn global(template: Vec<String>) -> Self {
+        Self {
+            is_global: true,
+            ..Self::new(template)
+        }
+    }
+
+    pub fn add_line(&mut self, line: impl Into<String>) {
+        self.template.push(line.into());
+    }
+
+    pub fn with_line(mut self, line: impl Into<String>) -> Self {
+        self.add_line(line);
+        self
+    }
+
+    pub fn add_operand(&mut self, operand: AsmOperand) {
+        self.operands.push(operand);
+    }
+
+    pub fn with_operand(mut self, operand: AsmOperand) -> Self {
+        self.add_operand(operand);
+        self
+    }
+
+    pub fn add_clobber_abi(&mut self, abi: impl Into<String>) {
+        self.clobber_abis.push(abi.into());
+    }
+
+    pub fn with_clobber_abi(mut self, abi: impl Into<String>) -> Self {
+        self.add_clobber_abi(abi);
+        self
+    }
+
+    pub fn add_option(&mut self, option: AsmOption) {
+        self.options.push(option);
+    }
+
+    pub fn with_option(mut self, option: AsmOption) -> Self {
+        self.add_option(option);
+        self
+    }
+}
+
+/// `"template", args.., name = arg..`, the arguments of `format!`-like macros
+///
+/// The template keeps its placeholders as they are; use [`FormatArgs::push_str`] to append text with its braces escaped.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormatArgs {
+    pub template: String,
+    pub args: Vec<Expr>,
+    pub named_args: Vec<(String, Expr)>,
+}
+
+impl fmt::Display for FormatArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        TokenStream::from(self.clone()).fmt(f)
+    }
+}
+
+impl From<FormatArgs> for TokenStream {
+    fn from(value: FormatArgs) -> Self {
+        let mut ts = TokenStream::new();
+        ts.push(Token::lit(value.template).into_joint());
+        for arg in value.args {
+            ts.push(Token::Comma);
+            ts.extend(TokenStream::from(arg).into_joint());
+        }
+        for (name, arg) in value.named_args {
+            ts.push(Token::Comma);
+            ts.push(Token::ident(name));
+            ts.push(Token::Eq);
+            ts.extend(TokenStream::from(arg).into_joint());
+        }
+        ts
+    }
+}
+
+impl From<FormatArgs> for DelimArgs {
+    fn from(value: FormatArgs) -> Self {
+        DelimArgs::parenthesis(TokenStream::from(value))
+    }
+}
+
+impl FormatArgs {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            args: vec![],
+            named_args: vec![],
+        }
+    }
+
+    pub fn set_template(&mut self, template: impl Into<String>) {
+        self.template = template.into();
+    }
+
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.set_template(template);
+        self
+    }
+
+    /// Escapes the braces of `text` so that it appears verbatim in a format string.
+    pub fn escape(text: &str) -> std::string::String {
+        text.replace('{', "{{").replace('}', "}}")
+    }
+
+    /// Appends literal text to the template, escaping braces.
+    pub fn push_str(&mut self, text: &str) {
+        let escaped = Self::escape(text);
+        self.set_template(format!("{}{escaped}", self.template));
+    }
+
+    pub fn with_str(mut self, text: &str) -> Self {
+        self.push_str(text);
+        self
+    }
+
+    /// Appends `{}` to the template together with its argument.
+    pub fn push_value(&mut self, arg: impl Into<Expr>) {
+        self.set_template(format!("{}{{}}", self.template));
+        self.add_arg(arg);
+    }
+
+    pub fn with_value(mut self, arg: impl Into<Expr>) -> Self {
+        self.push_value(arg);
+        self
+    }
+
+    /// Appends `{name}` to the template together with its named argument.
+    pub fn push_named_value(&mut self, name: impl Into<String>, arg: impl Into<Expr>) {
+        let name = name.into();
+        self.set_template(format!("{}{{{name}}}", self.template));
+        self.add_named_arg(name, arg);
+    }
+
+    pub fn with_named_value(mut self, name: impl Into<String>, arg: impl Into<Expr>) -> Self {
+        self.push_named_value(name, arg);
+        self
+    }
+
+    /// Adds a positional argument without touching the template.
+    pub fn add_arg(&mut self, arg: impl Into<Expr>) {
+        self.args.push(arg.into());
+    }
+
+    pub fn with_arg(mut self, arg: impl Into<Expr>) -> Self {
+        self.add_arg(arg);
+        self
+    }
+
+    /// Adds a named argument without touching the template.
+    pub fn add_named_arg(&mut self, name: impl Into<String>, arg: impl Into<Expr>) {
+        self.named_args.push((name.into(), arg.into()));
+    }
+
+    pub fn with_named_arg(mut self, name: impl Into<String>, arg: impl Into<Expr>) -> Self {
+        self.add_named_arg(name, arg);
+        self
+    }
+
+    /// `path!(template, args..)`
+    pub fn into_mac_call(self, path: impl Into<Path>) -> MacCall {
+        MacCall::new(path.into(), self)
+    }
+
+    /// `dest, template, args..` for `write!`-like macros
+    fn with_dest(self, path: &str, dest: impl Into<Expr>) -> MacCall {
+        let mut ts = TokenStream::from(dest.into()).into_joint();
+        ts.push(Token::Comma);
+        ts.extend(TokenStream::from(self));
+        MacCall::new(Path::single(path), DelimArgs::parenthesis(ts))
+    }
+
+    /// `format!(..)`
+    pub fn format(self) -> MacCall {
+        self.into_mac_call(Path::single("format"))
+    }
+
+    /// [`FormatArgs::format`] under `policy`, e.g. `alloc::format!(..)`, or `None` without an
+    /// allocator.
+    pub fn format_in(self, policy: PathPolicy) -> Option<MacCall> {
+        policy
+            .alloc_macro("format")
+            .map(|path| self.into_mac_call(path))
+    }
+
+    /// `print!(..)`
+    pub fn print(self) -> MacCall {
+        self.into_mac_call(Path::single("print"))
+    }
+
+    /// `println!(..)`
+    pub fn println(self) -> MacCall {
+        self.into_mac_call(Path::single("println"))
+    }
+
+    /// `eprint!(..)`
+    pub fn eprint(self) -> MacCall {
+        self.into_mac_call(Path::single("eprint"))
+    }
+
+    /// `eprintln!(..)`
+    pub fn eprintln(self) -> MacCall {
+        self.into_mac_call(Path::single("eprintln"))
+    }
+
+    /// `panic!(..)`
+    pub fn panic(self) -> MacCall {
+        self.into_mac_call(Path::single("panic"))
+    }
+
+    /// `todo!(..)`
+    pub fn todo(self) -> MacCall {
+        self.into_mac_call(Path::single("todo"))
+    }
+
+    /// `unimplemented!(..)`
+    pub fn unimplemented(self) -> MacCall {
+        self.into_mac_call(Path::single("unimplemented"))
+    }
+
+    /// `unreachable!(..)`
+    pub fn unreachable(self) -> MacCall {
+        self.into_mac_call(Path::single("unreachable"))
+    }
+
+    /// `write!(dest, ..)`
+    pub fn write(self, dest: impl Into<Expr>) -> MacCall {
+        self.with_dest("write", dest)
+    }
+
+    /// `writeln!(dest, ..)`
+    pub fn writeln(self, dest: impl Into<Expr>) -> MacCall {
+        self.with_dest("writeln", dest)
+    }
+}
+
+/// A chain of method calls, e.g. `xs.iter().map(f).collect::<Vec<_>>()`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pipeline {
+    pub expr: Expr,
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.expr.fmt(f)
     }
 }
 
-impl From<Vec<Token>> for DelimArgs {
-    fn from(tokens: Vec<Token>) -> Self {
-        Self {
-            delim: MacDelimiter::Parenthesis,
-            tokens: TokenStream::from(tokens),
-        }
+impl From<Pipeline> for Expr {
+    fn from(value: Pipeline) -> Self {
+        value.expr
     }
 }
 
-impl From<DelimArgs> for TokenStream {
-    fn from(value: DelimArgs) -> Self {
-        let mut ts = TokenStream::new();
-        match value.delim {
-            MacDelimiter::Parenthesis => {
-                ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
-            }
-            MacDelimiter::Bracket => {
-                ts.push(Token::OpenDelim(Delimiter::Bracket).into_joint());
-            }
-            MacDelimiter::Brace => {
-                ts.push(Token::OpenDelim(Delimiter::Brace).into_joint());
-            }
-        }
-        ts.extend(value.tokens.into_joint());
-        match value.delim {
-            MacDelimiter::Parenthesis => {
-                ts.push(Token::CloseDelim(Delimiter::Parenthesis).into_joint());
-            }
-            MacDelimiter::Bracket => {
-                ts.push(Token::CloseDelim(Delimiter::Bracket).into_joint());
-            }
-            MacDelimiter::Brace => {
-                ts.push(Token::CloseDelim(Delimiter::Brace).into_joint());
-            }
-        }
-        ts
+impl From<Pipeline> for TokenStream {
+    fn from(value: Pipeline) -> Self {
+        TokenStream::from(value.expr)
     }
 }
 
-impl DelimArgs {
-    pub fn new(delim: MacDelimiter, tokens: TokenStream) -> Self {
-        Self { delim, tokens }
+impl Pipeline {
+    pub fn new(expr: impl Into<Expr>) -> Self {
+        Self { expr: expr.into() }
     }
 
-    pub fn parenthesis(tokens: TokenStream) -> Self {
-        Self::new(MacDelimiter::Parenthesis, tokens)
+    /// `.ident(args)`
+    pub fn then(self, ident: impl Into<String>, args: Vec<Expr>) -> Self {
+        Self::new(self.expr.method_call(PathSegment::simple(ident), args))
     }
 
-    pub fn bracket(tokens: TokenStream) -> Self {
-        Self::new(MacDelimiter::Bracket, tokens)
+    /// `.ident::<generic_args>(args)`
+    pub fn then_generic(
+        self,
+        ident: impl Into<String>,
+        generic_args: Vec<GenericArg>,
+        args: Vec<Expr>,
+    ) -> Self {
+        Self::new(self.expr.method_call_generic(ident, generic_args, args))
     }
 
-    pub fn brace(tokens: TokenStream) -> Self {
-        Self::new(MacDelimiter::Brace, tokens)
+    pub fn iter(self) -> Self {
+        self.then("iter", vec![])
     }
 
-    pub fn add_token(&mut self, token: Token) {
-        self.tokens.push(token);
+    pub fn iter_mut(self) -> Self {
+        self.then("iter_mut", vec![])
     }
 
-    pub fn remove_token(&mut self, token: &Token) {
-        self.tokens.retain(|t| t != token);
+    /// `.into_iter()`
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter(self) -> Self {
+        self.then("into_iter", vec![])
     }
 
-    pub fn get_token(&self, index: usize) -> Option<&Token> {
-        self.tokens.get(index)
+    pub fn map(self, f: impl Into<Expr>) -> Self {
+        self.then("map", vec![f.into()])
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct MacCall {
-    /// `!` not included
-    pub path: Path,
-    pub args: DelimArgs,
-}
+    pub fn filter(self, f: impl Into<Expr>) -> Self {
+        self.then("filter", vec![f.into()])
+    }
 
-#[cfg(feature = "fuzzing")]
-impl<'a> arbitrary::Arbitrary<'a> for MacCall {
-    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
-        let path = Path::arbitrary_no_arg(u)?;
-        let args = DelimArgs::arbitrary(u)?;
-        Ok(Self { path, args })
+    pub fn filter_map(self, f: impl Into<Expr>) -> Self {
+        self.then("filter_map", vec![f.into()])
     }
-}
 
-impl HasPrecedence for MacCall {
-    fn precedence(&self) -> OperatorPrecedence {
-        OperatorPrecedence::Call
+    pub fn flat_map(self, f: impl Into<Expr>) -> Self {
+        self.then("flat_map", vec![f.into()])
     }
-}
 
-impl fmt::Display for MacCall {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}!{}", self.path, self.args)?;
-        Ok(())
+    pub fn for_each(self, f: impl Into<Expr>) -> Self {
+        self.then("for_each", vec![f.into()])
     }
-}
 
-impl From<MacCall> for TokenStream {
-    fn from(value: MacCall) -> Self {
-        let mut ts = TokenStream::new();
-        ts.extend(TokenStream::from(value.path).into_joint());
-        ts.push(Token::Not.into_joint());
-        ts.extend(TokenStream::from(value.args));
-        ts
+    pub fn enumerate(self) -> Self {
+        self.then("enumerate", vec![])
     }
-}
 
-impl MacCall {
-    pub fn new(path: Path, args: impl Into<DelimArgs>) -> Self {
-        Self {
-            path,
-            args: args.into(),
-        }
+    pub fn rev(self) -> Self {
+        self.then("rev", vec![])
     }
 
-    pub fn bracket(path: Path, tokens: Vec<impl Into<TokenStream>>) -> Self {
-        Self {
-            path,
-            args: DelimArgs::bracket(TokenStream::aggregate(tokens.into_iter().map(|t| {
-                let mut ts = t.into();
-                ts.push(Token::Comma);
-                ts
-            }))),
-        }
+    pub fn cloned(self) -> Self {
+        self.then("cloned", vec![])
+    }
+
+    pub fn copied(self) -> Self {
+        self.then("copied", vec![])
+    }
+
+    pub fn take(self, n: impl Into<Expr>) -> Self {
+        self.then("take", vec![n.into()])
+    }
+
+    pub fn skip(self, n: impl Into<Expr>) -> Self {
+        self.then("skip", vec![n.into()])
+    }
+
+    pub fn zip(self, other: impl Into<Expr>) -> Self {
+        self.then("zip", vec![other.into()])
+    }
+
+    pub fn chain(self, other: impl Into<Expr>) -> Self {
+        self.then("chain", vec![other.into()])
+    }
+
+    /// `.collect()`, leaving the target type to inference
+    pub fn collect(self) -> Self {
+        self.then("collect", vec![])
+    }
+
+    /// `.collect::<ty>()`
+    pub fn collect_ty(self, ty: impl Into<Type>) -> Self {
+        self.then_generic("collect", vec![GenericArg::Type(ty.into())], vec![])
+    }
+
+    pub fn build(self) -> Expr {
+        self.expr
     }
 }
 
-/// `ident: expr`
+/// `ident: expr` or `ident` (shorthand)
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ExprField {
     pub ident: String,
     pub expr: Expr,
+    /// If true, only `ident` is printed, as long as `expr` is still the path `ident`
+    /// (e.g. after a [`VisitMut`] pass rewrote it, `ident: expr` is printed instead).
+    pub is_shorthand: bool,
 }
 
 impl fmt::Display for ExprField {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{ident}: {expr}", ident = self.ident, expr = self.expr)
+        if self.prints_shorthand() {
+            write!(f, "{ident}", ident = self.ident)
+        } else {
+            write!(f, "{ident}: {expr}", ident = self.ident, expr = self.expr)
+        }
     }
 }
 
 impl From<ExprField> for TokenStream {
     fn from(value: ExprField) -> Self {
         let mut ts = TokenStream::new();
+        if value.prints_shorthand() {
+            ts.push(Token::ident(value.ident));
+            return ts;
+        }
         ts.push(Token::ident(value.ident).into_joint());
         ts.push(Token::Colon);
         ts.extend(TokenStream::from(value.expr));
@@ -3393,23 +5990,41 @@ impl ExprField {
         Self {
             ident: ident.into(),
             expr: expr.into(),
+            is_shorthand: false,
         }
     }
 
+    /// `ident` (field init shorthand for `ident: ident`)
     pub fn shortened(ident: impl Into<String>) -> Self {
         let ident = ident.into();
         Self {
             ident: ident.clone(),
             expr: Expr::new(ExprKind::Path(Path::single(ident))),
+            is_shorthand: true,
+        }
+    }
+
+    fn prints_shorthand(&self) -> bool {
+        if !self.is_shorthand || !self.expr.attrs.is_empty() {
+            return false;
+        }
+        match &self.expr.kind {
+            ExprKind::Path(path) => match &path.segments[..] {
+                [segment] => segment.args.is_none() && *segment.ident == *self.ident,
+                _ => false,
+            },
+            _ => false,
         }
     }
 }
 
+/// `path { fields, (..rest)? }`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Struct {
     pub path: Path,
     pub fields: Vec<ExprField>,
+    pub rest: Option<Box<Expr>>,
 }
 
 impl HasPrecedence for Struct {
@@ -3427,6 +6042,16 @@ impl fmt::Display for Struct {
             }
             write!(f, "{field}")?;
         }
+        if let Some(rest) = &self.rest {
+            if !self.fields.is_empty() {
+                write!(f, ", ")?;
+            }
+            if rest.should_wrap_as_rest() {
+                write!(f, "..({rest})")?;
+            } else {
+                write!(f, "..{rest}")?;
+            }
+        }
         write!(f, " }}")
     }
 }
@@ -3440,12 +6065,25 @@ impl From<Struct> for TokenStream {
             if i > 0 {
                 ts.push(Token::Comma);
             }
-            if i == value.fields.len() - 1 {
+            if i == value.fields.len() - 1 && value.rest.is_none() {
                 ts.extend(TokenStream::from(field.clone()));
             } else {
                 ts.extend(TokenStream::from(field.clone()).into_joint());
             }
         }
+        if let Some(rest) = value.rest {
+            if !value.fields.is_empty() {
+                ts.push(Token::Comma);
+            }
+            ts.push(Token::DotDot.into_joint());
+            if rest.should_wrap_as_rest() {
+                ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+                ts.extend(TokenStream::from(*rest).into_joint());
+                ts.push(Token::CloseDelim(Delimiter::Parenthesis));
+            } else {
+                ts.extend(TokenStream::from(*rest));
+            }
+        }
         ts.push(Token::CloseDelim(Delimiter::Brace));
         ts
     }
@@ -3456,8 +6094,28 @@ impl Struct {
         Self {
             path: path.into(),
             fields,
+            rest: None,
         }
     }
+
+    pub fn add_field(&mut self, field: ExprField) {
+        self.fields.push(field);
+    }
+
+    pub fn with_field(mut self, field: ExprField) -> Self {
+        self.add_field(field);
+        self
+    }
+
+    pub fn set_rest(&mut self, rest: impl Into<Expr>) {
+        self.rest = Some(Box::new(rest.into()));
+    }
+
+    /// `path { fields, ..rest }`
+    pub fn with_rest(mut self, rest: impl Into<Expr>) -> Self {
+        self.set_rest(rest);
+        self
+    }
 }
 
 /// `[expr; len]`
@@ -3548,6 +6206,7 @@ impl Try {
     }
 }
 
+/// `(expr)`, parenthesized even where precedence does not require it
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Paren(pub Box<Expr>);
@@ -3574,6 +6233,54 @@ impl From<Paren> for TokenStream {
     }
 }
 
+impl Paren {
+    pub fn new(expr: impl Into<Expr>) -> Self {
+        Self(Box::new(expr.into()))
+    }
+}
+
+/// Raw source text for constructs the AST does not model, output as it is.
+///
+/// As an expression it is never parenthesized, so wrap it in a [`Paren`] if needed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Verbatim(pub String);
+
+impl HasPrecedence for Verbatim {
+    fn precedence(&self) -> OperatorPrecedence {
+        OperatorPrecedence::Elemental
+    }
+}
+
+impl fmt::Display for Verbatim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Verbatim> for TokenStream {
+    fn from(value: Verbatim) -> Self {
+        TokenStream::from(vec![Token::Verbatim(value.0)])
+    }
+}
+
+impl Verbatim {
+    /// Does not check the text, see [`Verbatim::try_new`] to reject text that is not valid tokens.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self(text.into())
+    }
+
+    /// Like [`Verbatim::new`], but fails if the text does not lex as tokens, e.g. if its
+    /// delimiters are unbalanced or a literal is unterminated.
+    pub fn try_new(text: impl Into<String>) -> Result<Self, RenderError> {
+        let text = text.into();
+        if is_token_text(&text) {
+            Ok(Self(text))
+        } else {
+            Err(RenderError::InvalidVerbatim(text))
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ExprKind {
     Array(Array),
@@ -3605,6 +6312,7 @@ pub enum ExprKind {
     Range(Range),
     Underscore(Underscore),
     Path(Path),
+    QPath(QPath),
     AddrOf(AddrOf),
     Break(Break),
     Continue(Continue),
@@ -3615,6 +6323,7 @@ pub enum ExprKind {
     Repeat(Repeat),
     Try(Try),
     Paren(Paren),
+    Verbatim(Verbatim),
 }
 
 #[cfg(feature = "fuzzing")]
@@ -3623,7 +6332,7 @@ impl<'a> arbitrary::Arbitrary<'a> for ExprKind {
         if crate::depth_limiter::reached() {
             return Ok(Self::Tuple(Tuple::unit()));
         }
-        match u.int_in_range(0..=37)? {
+        match u.int_in_range(0..=38)? {
             0 => Ok(Self::Array(Array::arbitrary(u)?)),
             1 => Ok(Self::Call(Call::arbitrary(u)?)),
             2 => Ok(Self::MethodCall(MethodCall::arbitrary(u)?)),
@@ -3662,6 +6371,7 @@ impl<'a> arbitrary::Arbitrary<'a> for ExprKind {
             35 => Ok(Self::Struct(Struct::arbitrary(u)?)),
             36 => Ok(Self::Repeat(Repeat::arbitrary(u)?)),
             37 => Ok(Self::Try(Try::arbitrary(u)?)),
+            38 => Ok(Self::QPath(QPath::arbitrary(u)?)),
             _ => Ok(Self::Tuple(Tuple::unit())),
         }
     }
@@ -3716,6 +6426,7 @@ impl_display_for_enum!(ExprKind;
     Range,
     Underscore,
     Path,
+    QPath,
     AddrOf,
     Break,
     Continue,
@@ -3726,6 +6437,7 @@ impl_display_for_enum!(ExprKind;
     Repeat,
     Try,
     Paren,
+    Verbatim,
 );
 impl_obvious_conversion!(ExprKind;
     Array,
@@ -3757,6 +6469,7 @@ impl_obvious_conversion!(ExprKind;
     Range,
     Underscore,
     Path,
+    QPath,
     AddrOf,
     Break,
     Continue,
@@ -3767,6 +6480,7 @@ impl_obvious_conversion!(ExprKind;
     Repeat,
     Try,
     Paren,
+    Verbatim,
 );
 impl_has_precedence_for_enum!(ExprKind;
     Array,
@@ -3798,6 +6512,7 @@ impl_has_precedence_for_enum!(ExprKind;
     Range,
     Underscore,
     Path,
+    QPath,
     AddrOf,
     Break,
     Continue,
@@ -3808,6 +6523,7 @@ impl_has_precedence_for_enum!(ExprKind;
     Repeat,
     Try,
     Paren,
+    Verbatim,
 );
 
 impl From<Block> for ExprKind {
@@ -3832,6 +6548,16 @@ impl Expr {
         }))
     }
 
+    /// `self.ident::<generic_args>(args)`
+    pub fn method_call_generic(
+        self,
+        ident: impl Into<String>,
+        generic_args: Vec<GenericArg>,
+        args: Vec<Expr>,
+    ) -> Self {
+        self.method_call(PathSegment::generic(ident, generic_args), args)
+    }
+
     pub fn cast(self, ty: impl Into<Type>) -> Self {
         Self::new(ExprKind::Cast(Cast {
             expr: Box::new(self),
@@ -3840,10 +6566,11 @@ impl Expr {
     }
 
     pub fn field(self, ident: impl Into<String>) -> Self {
-        Self::new(ExprKind::Field(Field {
-            expr: Box::new(self),
-            ident: ident.into(),
-        }))
+        Self::new(ExprKind::Field(Field::new(self, ident.into())))
+    }
+
+    pub fn field_idx(self, index: usize) -> Self {
+        Self::new(ExprKind::Field(Field::new(self, index)))
     }
 
     pub fn index(self, index: impl Into<Expr>) -> Self {
@@ -3866,6 +6593,179 @@ impl Expr {
     }
 
     pub fn paren(self) -> Self {
-        Self::new(ExprKind::Paren(Paren(Box::new(self))))
+        Self::new(ExprKind::Paren(Paren::new(self)))
+    }
+
+    /// `valuef32`, or `f32::NAN`, `f32::INFINITY` or `f32::NEG_INFINITY`.
+    pub fn f32(value: f32) -> Self {
+        match Lit::f32(value) {
+            Some(lit) => lit.into(),
+            None => Self::non_finite("f32", value.is_nan(), value.is_sign_negative()),
+        }
+    }
+
+    /// `valuef64`, or `f64::NAN`, `f64::INFINITY` or `f64::NEG_INFINITY`.
+    pub fn f64(value: f64) -> Self {
+        match Lit::f64(value) {
+            Some(lit) => lit.into(),
+            None => Self::non_finite("f64", value.is_nan(), value.is_sign_negative()),
+        }
+    }
+
+    fn non_finite(ty: &str, is_nan: bool, is_negative: bool) -> Self {
+        let constant = match (is_nan, is_negative) {
+            (true, _) => "NAN",
+            (false, false) => "INFINITY",
+            (false, true) => "NEG_INFINITY",
+        };
+        Path::single(ty).chain(constant).into()
+    }
+
+    /// `todo!()`
+    pub fn todo() -> Self {
+        MacCall::new(Path::single("todo"), DelimArgs::default()).into()
+    }
+
+    /// `unimplemented!()`
+    pub fn unimplemented() -> Self {
+        MacCall::new(Path::single("unimplemented"), DelimArgs::default()).into()
+    }
+
+    /// `unreachable!()`
+    pub fn unreachable() -> Self {
+        MacCall::new(Path::single("unreachable"), DelimArgs::default()).into()
+    }
+
+    /// `panic!("template", args..)`
+    pub fn panic_fmt(template: impl Into<String>, args: Vec<Expr>) -> Self {
+        format_args(template, args).panic().into()
+    }
+
+    /// `todo!("template", args..)`
+    pub fn todo_fmt(template: impl Into<String>, args: Vec<Expr>) -> Self {
+        format_args(template, args).todo().into()
+    }
+
+    /// `unimplemented!("template", args..)`
+    pub fn unimplemented_fmt(template: impl Into<String>, args: Vec<Expr>) -> Self {
+        format_args(template, args).unimplemented().into()
+    }
+
+    /// `unreachable!("template", args..)`
+    pub fn unreachable_fmt(template: impl Into<String>, args: Vec<Expr>) -> Self {
+        format_args(template, args).unreachable().into()
+    }
+}
+
+fn format_args(template: impl Into<String>, args: Vec<Expr>) -> FormatArgs {
+    let mut format_args = FormatArgs::new(template);
+    format_args.args = args;
+    format_args
+}
+
+/// How [`Expr::from_iterable`] writes collection initializers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum InitStyle {
+    /// `vec![a, b]`; collections without a std macro fall back to [`InitStyle::From`].
+    #[default]
+    Macro,
+    /// `Vec::from([a, b])`
+    From,
+}
+
+/// Collections that can be written as an initializer expression.
+///
+/// Entries of hash-based collections are sorted by their rendered form, so the output is stable.
+pub trait IntoInitializer: Sized {
+    /// The initializer with the paths of `policy`, or `None` if the collection doesn't exist
+    /// under it, e.g. `HashMap` outside of `std`.
+    fn into_initializer_in(self, policy: PathPolicy, style: InitStyle) -> Option<Expr>;
+
+    fn into_initializer(self, style: InitStyle) -> Expr {
+        self.into_initializer_in(PathPolicy::Std, style)
+            .expect("`std` has every collection")
+    }
+}
+
+/// `ty::from([items])`
+fn from_array(ty: Path, items: Vec<Expr>) -> Expr {
+    ty.chain("from").call1(Array::new(items)).into()
+}
+
+fn sorted_by_display(mut items: Vec<Expr>) -> Vec<Expr> {
+    items.sort_by_cached_key(|item| item.to_string());
+    items
+}
+
+fn entries(entries: impl IntoIterator<Item = (impl Into<Expr>, impl Into<Expr>)>) -> Vec<Expr> {
+    entries
+        .into_iter()
+        .map(|(k, v)| Tuple::new(vec![k.into(), v.into()]).into())
+        .collect()
+}
+
+impl<E: Into<Expr>> IntoInitializer for Vec<E> {
+    fn into_initializer_in(self, policy: PathPolicy, style: InitStyle) -> Option<Expr> {
+        let items = self.into_iter().map(Into::into).collect::<Vec<_>>();
+        let expr = match style {
+            InitStyle::Macro => {
+                let mut tokens = TokenStream::new();
+                for (i, item) in items.into_iter().enumerate() {
+                    if i > 0 {
+                        tokens.push(Token::Comma);
+                    }
+                    tokens.extend(TokenStream::from(item).into_joint());
+                }
+                MacCall::new(policy.alloc_macro("vec")?, DelimArgs::bracket(tokens)).into()
+            }
+            InitStyle::From => from_array(policy.alloc_item("vec", "Vec")?, items),
+        };
+        Some(expr)
+    }
+}
+
+impl<E: Into<Expr>> IntoInitializer for std::collections::BTreeSet<E> {
+    fn into_initializer_in(self, policy: PathPolicy, _style: InitStyle) -> Option<Expr> {
+        let ty = policy.alloc_item("collections", "BTreeSet")?;
+        Some(from_array(ty, self.into_iter().map(Into::into).collect()))
+    }
+}
+
+impl<E: Into<Expr>, S> IntoInitializer for std::collections::HashSet<E, S> {
+    fn into_initializer_in(self, policy: PathPolicy, _style: InitStyle) -> Option<Expr> {
+        let ty = policy.std_item("HashSet")?;
+        let items = self.into_iter().map(Into::into).collect();
+        Some(from_array(ty, sorted_by_display(items)))
+    }
+}
+
+impl<K: Into<Expr>, V: Into<Expr>> IntoInitializer for std::collections::BTreeMap<K, V> {
+    fn into_initializer_in(self, policy: PathPolicy, _style: InitStyle) -> Option<Expr> {
+        let ty = policy.alloc_item("collections", "BTreeMap")?;
+        Some(from_array(ty, entries(self)))
+    }
+}
+
+impl<K: Into<Expr>, V: Into<Expr>, S> IntoInitializer for std::collections::HashMap<K, V, S> {
+    fn into_initializer_in(self, policy: PathPolicy, _style: InitStyle) -> Option<Expr> {
+        let ty = policy.std_item("HashMap")?;
+        Some(from_array(ty, sorted_by_display(entries(self))))
+    }
+}
+
+impl Expr {
+    /// `vec![..]`, `BTreeMap::from([(k, v), ..])`, `HashSet::from([..])`, etc.
+    pub fn from_iterable(collection: impl IntoInitializer, style: InitStyle) -> Self {
+        collection.into_initializer(style)
+    }
+
+    /// [`Expr::from_iterable`] under `policy`, e.g. `alloc::vec![..]`, or `None` if the
+    /// collection doesn't exist under it.
+    pub fn from_iterable_in(
+        policy: PathPolicy,
+        collection: impl IntoInitializer,
+        style: InitStyle,
+    ) -> Option<Self> {
+        collection.into_initializer_in(policy, style)
     }
 }
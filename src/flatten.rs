@@ -0,0 +1,183 @@
+use crate::visit::{self, VisitMut};
+use crate::*;
+
+struct Flattener {
+    max_depth: usize,
+    count: usize,
+    /// Whether an operand with side effects has been left in place in the current statement,
+    /// so that hoisting a later operand would evaluate it too early.
+    effects: bool,
+}
+
+impl Flattener {
+    /// Hoists the subexpressions of `expr` (at nesting level `level`) that reach `max_depth`
+    /// into `let` bindings pushed to `hoisted`.
+    fn flatten(&mut self, expr: &mut Expr, level: usize, hoisted: &mut Vec<Stmt>) {
+        match &mut expr.kind {
+            ExprKind::Array(Array(exprs)) | ExprKind::Tuple(Tuple(exprs)) => {
+                for expr in exprs.iter_mut() {
+                    self.hoist(expr, level + 1, hoisted);
+                }
+            }
+            ExprKind::Call(call) => {
+                self.place(&mut call.func, level + 1, hoisted);
+                for arg in call.args.iter_mut() {
+                    self.hoist(arg, level + 1, hoisted);
+                }
+            }
+            ExprKind::MethodCall(call) => {
+                self.place(&mut call.receiver, level + 1, hoisted);
+                for arg in call.args.iter_mut() {
+                    self.hoist(arg, level + 1, hoisted);
+                }
+            }
+            ExprKind::Binary(binary) => match binary.op {
+                // the right operand of `&&` and `||` is not always evaluated
+                BinOpKind::LazyAnd | BinOpKind::LazyOr => {
+                    self.hoist(&mut binary.lhs, level + 1, hoisted);
+                    self.effects |= !is_pure(&binary.rhs);
+                }
+                // comparisons borrow their operands, so places must stay where they are
+                BinOpKind::Eq
+                | BinOpKind::Ne
+                | BinOpKind::Lt
+                | BinOpKind::Le
+                | BinOpKind::Gt
+                | BinOpKind::Ge => {
+                    for operand in [&mut binary.lhs, &mut binary.rhs] {
+                        if is_place(operand) {
+                            self.place(operand, level + 1, hoisted);
+                        } else {
+                            self.hoist(operand, level + 1, hoisted);
+                        }
+                    }
+                }
+                _ => {
+                    self.hoist(&mut binary.lhs, level + 1, hoisted);
+                    self.hoist(&mut binary.rhs, level + 1, hoisted);
+                }
+            },
+            ExprKind::Unary(Unary {
+                op: UnaryOpKind::Deref,
+                expr,
+            })
+            | ExprKind::Field(Field { expr, .. })
+            | ExprKind::AddrOf(AddrOf { expr, .. })
+            | ExprKind::Paren(Paren(expr)) => self.place(expr, level + 1, hoisted),
+            ExprKind::Unary(Unary { expr, .. })
+            | ExprKind::Cast(Cast { expr, .. })
+            | ExprKind::Try(Try { expr })
+            | ExprKind::Await(Await { expr }) => self.hoist(expr, level + 1, hoisted),
+            ExprKind::Index(index) => {
+                self.place(&mut index.expr, level + 1, hoisted);
+                self.hoist(&mut index.index, level + 1, hoisted);
+            }
+            ExprKind::Struct(struct_) => {
+                for field in struct_.fields.iter_mut() {
+                    self.hoist(&mut field.expr, level + 1, hoisted);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Like [`Flattener::flatten`], for an operand that must not be moved into a binding.
+    fn place(&mut self, expr: &mut Expr, level: usize, hoisted: &mut Vec<Stmt>) {
+        self.flatten(expr, level, hoisted);
+        self.effects |= !is_pure(expr);
+    }
+
+    /// Like [`Flattener::flatten`], but `expr` is a value operand that can itself be replaced
+    /// by a binding once it reaches `max_depth`, unless an operand evaluated before it has side
+    /// effects.
+    fn hoist(&mut self, expr: &mut Expr, level: usize, hoisted: &mut Vec<Stmt>) {
+        if level < self.max_depth || expr.depth() <= 1 || self.effects {
+            return self.place(expr, level, hoisted);
+        }
+        self.flatten(expr, 1, hoisted);
+        let ident = format!("__tmp{}", self.count);
+        self.count += 1;
+        let value = std::mem::replace(expr, Path::single(ident.clone()).into());
+        hoisted.push(Local::simple(Pat::ident(ident), value).into());
+    }
+}
+
+/// Whether `expr` denotes a place, such as `a.b[i]` or `*p`, rather than a value.
+fn is_place(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Path(_)
+        | ExprKind::QPath(_)
+        | ExprKind::Field(_)
+        | ExprKind::Index(_)
+        | ExprKind::Unary(Unary {
+            op: UnaryOpKind::Deref,
+            ..
+        }) => true,
+        ExprKind::Paren(Paren(expr)) => is_place(expr),
+        _ => false,
+    }
+}
+
+/// Whether evaluating `expr` cannot have side effects, i.e. it involves no calls, macros,
+/// assignments or control flow.
+fn is_pure(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Lit(_) | ExprKind::Path(_) | ExprKind::QPath(_) => true,
+        ExprKind::Field(Field { expr, .. })
+        | ExprKind::AddrOf(AddrOf { expr, .. })
+        | ExprKind::Paren(Paren(expr))
+        | ExprKind::Unary(Unary { expr, .. })
+        | ExprKind::Cast(Cast { expr, .. }) => is_pure(expr),
+        ExprKind::Binary(binary) => is_pure(&binary.lhs) && is_pure(&binary.rhs),
+        ExprKind::Index(index) => is_pure(&index.expr) && is_pure(&index.index),
+        ExprKind::Array(Array(exprs)) | ExprKind::Tuple(Tuple(exprs)) => exprs.iter().all(is_pure),
+        _ => false,
+    }
+}
+
+impl VisitMut for Flattener {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        visit::walk_block_mut(self, block);
+        let mut stmts = Vec::with_capacity(block.stmts.len());
+        for mut stmt in std::mem::take(&mut block.stmts) {
+            let expr = match &mut stmt {
+                Stmt::Local(Local {
+                    kind: LocalKind::Init(expr) | LocalKind::InitElse(expr, _),
+                    ..
+                })
+                | Stmt::Expr(expr)
+                | Stmt::Semi(Semi(expr)) => expr,
+                _ => {
+                    stmts.push(stmt);
+                    continue;
+                }
+            };
+            if expr.depth() > self.max_depth {
+                self.effects = false;
+                self.flatten(expr, 1, &mut stmts);
+            }
+            stmts.push(stmt);
+        }
+        block.stmts = stmts;
+    }
+}
+
+impl Crate {
+    /// Hoists subexpressions nested deeper than `max_depth` levels (see [`Expr::depth`]) into
+    /// `let __tmpN = ..;` bindings placed before their statement. Returns the number of
+    /// bindings introduced.
+    ///
+    /// Only operands that are always evaluated are hoisted, e.g. arguments and the operands of
+    /// arithmetic, but not method receivers, borrowed places (including the places compared by
+    /// `==`, `<`, ..) or the right side of `&&`. Operands are not hoisted past an earlier
+    /// operand with side effects, such as a call, so evaluation order is kept.
+    pub fn flatten_nesting(&mut self, max_depth: usize) -> usize {
+        let mut flattener = Flattener {
+            max_depth: max_depth.max(2),
+            count: 0,
+            effects: false,
+        };
+        flattener.visit_crate_mut(self);
+        flattener.count
+    }
+}
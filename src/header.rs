@@ -0,0 +1,118 @@
+use std::fmt;
+use std::string::String;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A comment banner rendered before everything else in a generated file, such as a license
+/// notice or a "do not edit" warning.
+///
+/// `{name}` in the template is replaced by the value of the variable `name`, which may span
+/// several lines. `{timestamp}` expands to the current UTC time (or `SOURCE_DATE_EPOCH`, if set)
+/// when timestamps are enabled; otherwise the lines mentioning it are left out, so that the
+/// output stays reproducible.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Header {
+    pub template: String,
+    pub vars: Vec<(String, String)>,
+    pub timestamp: bool,
+}
+
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let timestamp = self.timestamp.then(now);
+        for line in self.template.lines() {
+            let line = match &timestamp {
+                Some(timestamp) => line.replace("{timestamp}", timestamp),
+                None if line.contains("{timestamp}") => continue,
+                None => line.to_string(),
+            };
+            let line = self.vars.iter().fold(line, |line, (name, value)| {
+                line.replace(&format!("{{{name}}}"), value)
+            });
+            // values may span several lines, each of which must be commented
+            let mut lines = line.lines().peekable();
+            if lines.peek().is_none() {
+                writeln!(f, "//")?;
+            }
+            for line in lines {
+                if line.is_empty() {
+                    writeln!(f, "//")?;
+                } else {
+                    writeln!(f, "// {line}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Header {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+            vars: Vec::new(),
+            timestamp: false,
+        }
+    }
+
+    /// `// @generated by {tool} {version}. Do not edit.`, the marker tools such as GitHub and
+    /// rustfmt's `format_generated_files` recognize.
+    pub fn generated(tool: impl Into<String>, version: impl Into<String>) -> Self {
+        Self::new("@generated by {tool} {version}. Do not edit.")
+            .with_var("tool", tool)
+            .with_var("version", version)
+    }
+
+    /// Sets the value of `{name}`, replacing any previous value.
+    pub fn set_var(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        let value = value.into();
+        match self.vars.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.vars.push((name, value)),
+        }
+    }
+
+    pub fn with_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set_var(name, value);
+        self
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: bool) {
+        self.timestamp = timestamp;
+    }
+
+    pub fn with_timestamp(mut self, timestamp: bool) -> Self {
+        self.set_timestamp(timestamp);
+        self
+    }
+}
+
+/// The current time as `YYYY-MM-DDTHH:MM:SSZ`, honoring `SOURCE_DATE_EPOCH`.
+fn now() -> String {
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        });
+    let (days, secs) = (secs / 86400, secs % 86400);
+    // civil date from days since 1970-01-01, after Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
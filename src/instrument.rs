@@ -0,0 +1,123 @@
+use crate::visit::{self, VisitMut};
+use crate::*;
+
+/// What [`Crate::instrument`] adds to the functions it selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instrumentation {
+    /// `#[tracing::instrument]`, or `#[tracing::instrument(skip_all)]` for functions whose
+    /// arguments are not all `Debug`.
+    Tracing { skip_all: bool },
+    /// `log::debug!("entering `ident`");` as the first statement of the body.
+    LogEntry,
+}
+
+impl Instrumentation {
+    fn is_present(&self, attrs: &[Attribute], fn_: &Fn) -> bool {
+        match self {
+            Self::Tracing { .. } => attrs.iter().any(|attr| match &attr.kind {
+                AttrKind::Normal(item) => {
+                    item.path == Path::single("tracing").chain("instrument")
+                        || item.path == Path::single("instrument")
+                }
+                AttrKind::DocComment(_) => false,
+            }),
+            Self::LogEntry => {
+                let first = fn_.body.as_ref().and_then(|body| body.stmts.first());
+                let mac = match first {
+                    Some(Stmt::MacCallWithSemi(Semi(mac))) => mac,
+                    Some(Stmt::Semi(Semi(expr)) | Stmt::Expr(expr)) => match &expr.kind {
+                        ExprKind::MacCall(mac) => mac,
+                        _ => return false,
+                    },
+                    _ => return false,
+                };
+                mac.path == Path::single("log").chain("debug") || mac.path == Path::single("debug")
+            }
+        }
+    }
+
+    fn apply(&self, attrs: &mut Vec<Attribute>, fn_: &mut Fn) {
+        match self {
+            Self::Tracing { skip_all } => {
+                let path = Path::single("tracing").chain("instrument");
+                let attr = if *skip_all {
+                    AttributeItem::new(
+                        path,
+                        AttrArgs::Delimited(DelimArgs::parenthesis(TokenStream::from(vec![
+                            Token::ident("skip_all"),
+                        ]))),
+                    )
+                } else {
+                    AttributeItem::simple(path)
+                };
+                attrs.push(Attribute::normal(attr));
+            }
+            Self::LogEntry => {
+                let message =
+                    FormatArgs::new(FormatArgs::escape(&format!("entering `{}`", fn_.ident)));
+                let log = message.into_mac_call(Path::single("log").chain("debug"));
+                if let Some(body) = &mut fn_.body {
+                    body.insert_stmt(0, Semi(log));
+                }
+            }
+        }
+    }
+}
+
+struct Instrumenter<F> {
+    instrumentation: Instrumentation,
+    filter: F,
+    count: usize,
+}
+
+impl<F: FnMut(&Fn) -> bool> Instrumenter<F> {
+    fn instrument(&mut self, attrs: &mut Vec<Attribute>, fn_: &mut Fn) {
+        // neither `log::debug!` nor `#[tracing::instrument]` is allowed in a `const fn`
+        if fn_.body.is_none()
+            || fn_.is_const
+            || self.instrumentation.is_present(attrs, fn_)
+            || !(self.filter)(fn_)
+        {
+            return;
+        }
+        self.instrumentation.apply(attrs, fn_);
+        self.count += 1;
+    }
+}
+
+impl<F: FnMut(&Fn) -> bool> VisitMut for Instrumenter<F> {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        if let ItemKind::Fn(fn_) = &mut item.kind {
+            self.instrument(&mut item.attrs, fn_);
+        }
+        visit::walk_item_mut(self, item);
+    }
+
+    fn visit_assoc_item_mut(&mut self, item: &mut AssocItem) {
+        if let AssocItemKind::Fn(fn_) = &mut item.kind {
+            self.instrument(&mut item.attrs, fn_);
+        }
+        visit::walk_assoc_item_mut(self, item);
+    }
+}
+
+impl Crate {
+    /// Instruments every non-`const` function with a body that matches `filter`, including
+    /// associated and nested functions.
+    ///
+    /// Functions that are already instrumented, with a `#[tracing::instrument]` or a leading
+    /// `log::debug!`, are skipped. Returns the number of instrumented functions.
+    pub fn instrument(
+        &mut self,
+        instrumentation: Instrumentation,
+        filter: impl FnMut(&Fn) -> bool,
+    ) -> usize {
+        let mut instrumenter = Instrumenter {
+            instrumentation,
+            filter,
+            count: 0,
+        };
+        instrumenter.visit_crate_mut(self);
+        instrumenter.count
+    }
+}
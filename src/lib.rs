@@ -1,28 +1,88 @@
 #[cfg(feature = "tokenize")]
 mod conversion;
+mod desugar;
 mod expr;
+mod flatten;
+mod header;
+mod instrument;
+mod manual_impl;
+mod mock;
+mod parse;
+mod policy;
+mod render;
+#[cfg(feature = "rustdoc")]
+pub mod rustdoc;
+mod semantic;
+mod serde_attr;
 mod stmt;
+mod strip;
 mod token;
 mod ty;
+pub mod visit;
 
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path as Pt;
 
 pub use expr::*;
+pub use header::*;
+pub use instrument::*;
+pub use parse::*;
+pub use policy::*;
+pub use render::*;
+pub use semantic::*;
+pub use serde_attr::*;
 pub use stmt::*;
+pub use strip::*;
 pub use token::*;
 pub use ty::*;
+use visit::{Visit, VisitMut};
 
 pub mod traits {
     pub use crate::{
-        Accessible, AddVisibility, Assignable, Awaitable, BinaryOperable, Callable, Castable,
-        EmptyItem, HasItem, Ident, Indexable, IntoConst, IntoTokens, IntoTryBlock, IntoUnsafe,
-        MaybeIdent, MethodCallable, Returnable, Semicolon, Tryable, UnaryOperable, Yieldable,
+        Accessible, AddVisibility, Addressable, Assignable, Awaitable, BinaryOperable, Callable,
+        Castable, EmptyItem, HasItem, Ident, Indexable, IntoConst, IntoInitializer, IntoTokens,
+        IntoTryBlock, IntoUnsafe, MaybeIdent, MethodCallable, Normalize, Parenthesize,
+        PointerOperable, Returnable, SemanticEq, SemanticHash, Semicolon, TryRender, Tryable,
+        UnaryOperable, Yieldable,
     };
 }
 
+/// Everything needed to build ASTs with a single `use ruast::prelude::*;`.
+pub mod prelude {
+    pub use crate::traits::*;
+    pub use crate::{
+        Attribute, AttributeItem, Block, Crate, EnumDef, Expr, ExprField, Field, FieldDef, Fields,
+        Fn, FnDecl, GenericArg, GenericBound, GenericParam, Impl, Item, Lit, LoadedMod, MacCall,
+        Mod, Param, Pat, Path, PathSegment, QPath, Stmt, Struct, StructDef, Token, TokenStream,
+        TraitDef, Type, TypeParam, Use, UseTree, Variant, Visibility,
+    };
+
+    /// A single-segment path, e.g. `path("x")`.
+    pub fn path(ident: impl Into<String>) -> Path {
+        Path::single(ident.into())
+    }
+
+    /// A single-segment path type, e.g. `ty("u8")`.
+    pub fn ty(ident: impl Into<String>) -> Type {
+        Type::simple_path(ident.into())
+    }
+
+    pub fn lit_int(value: impl Into<String>) -> Lit {
+        Lit::int(value.into())
+    }
+
+    pub fn lit_str(value: impl Into<String>) -> Lit {
+        Lit::str(value.into())
+    }
+
+    pub fn lit_bool(value: bool) -> Lit {
+        Lit::bool(value.to_string())
+    }
+}
+
 macro_rules! impl_obvious_conversion {
     ($Enum: ident; $($Variant: ident $(,)?)*) => {
         $(
@@ -186,10 +246,18 @@ macro_rules! impl_hasitem_methods {
 }
 pub(crate) use impl_hasitem_methods;
 
+/// The root of an AST.
+///
+/// Every node owns its children (through `Box` and `Vec`, never `Rc`/`Arc`),
+/// so an AST cannot refer to itself and printing always terminates.
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct Crate {
+    pub header: Option<Header>,
+    /// The first line of a script, e.g. `#!/usr/bin/env -S cargo +nightly -Zscript`.
+    /// The leading `#!` may be left out.
     pub shebang: Option<String>,
+    /// Crate-level attributes, always rendered as inner attributes (`#![..]`).
     pub attrs: Vec<Attribute>,
     pub items: Vec<Item>,
 }
@@ -214,10 +282,33 @@ impl_hasitem_methods!(Crate);
 
 impl fmt::Display for Crate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(shebang) = &self.shebang {
+            let shebang = shebang.trim_end();
+            let shebang = shebang.strip_prefix("#!").unwrap_or(shebang);
+            writeln!(f, "#!{shebang}")?;
+        }
+        if let Some(header) = &self.header {
+            write!(f, "{header}")?;
+        }
         for attr in self.attrs.iter() {
-            writeln!(f, "{attr}")?;
+            writeln!(f, "{}", attr.clone().into_inner())?;
         }
         writeln!(f)?;
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            // Top-level items don't depend on each other when rendered,
+            // so they can be formatted independently and written in order.
+            let rendered = self
+                .items
+                .par_iter()
+                .map(|item| item.to_string())
+                .collect::<Vec<_>>();
+            for item in rendered {
+                writeln!(f, "{item}")?;
+            }
+        }
+        #[cfg(not(feature = "rayon"))]
         for item in self.items.iter() {
             writeln!(f, "{item}")?;
         }
@@ -229,7 +320,7 @@ impl From<Crate> for TokenStream {
     fn from(value: Crate) -> Self {
         let mut ts = TokenStream::new();
         for attr in value.attrs {
-            ts.extend(TokenStream::from(attr));
+            ts.extend(TokenStream::from(attr.into_inner()));
         }
         for item in value.items {
             ts.extend(TokenStream::from(item));
@@ -238,6 +329,694 @@ impl From<Crate> for TokenStream {
     }
 }
 
+/// Options for [`Crate::inline_consts_with`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct InlineConstOptions {
+    /// Also inline `pub`/`pub(..)` constants. They are never removed.
+    pub include_public: bool,
+    /// Keep the inlined private const items instead of removing them.
+    pub keep_items: bool,
+}
+
+fn inlinable_const_value(ty: &Type, expr: &Expr) -> Option<Expr> {
+    if !expr.attrs.is_empty() {
+        return None;
+    }
+    match &expr.kind {
+        ExprKind::Lit(lit) => Some(Expr::new(suffixed_lit(ty, lit))),
+        ExprKind::Unary(Unary {
+            op: UnaryOpKind::Neg,
+            expr: inner,
+        }) => match &inner.kind {
+            ExprKind::Lit(lit) if inner.attrs.is_empty() => {
+                Some(Expr::new(suffixed_lit(ty, lit)).neg().into())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn suffixed_lit(ty: &Type, lit: &Lit) -> Lit {
+    let Type::Path(path) = ty else {
+        return lit.clone();
+    };
+    let [segment] = &path.segments[..] else {
+        return lit.clone();
+    };
+    let ty = &*segment.ident;
+    let numeric = matches!(
+        lit.kind,
+        LitKind::Integer | LitKind::UInteger | LitKind::Float
+    );
+    if numeric && lit.suffix().is_none() && expr::NUMERIC_SUFFIXES.contains(&ty) {
+        // `1.f32` would be a field access
+        let zero = if lit.symbol.ends_with('.') { "0" } else { "" };
+        Lit::new(lit.kind.clone(), format!("{}{zero}{ty}", lit.symbol))
+    } else {
+        lit.clone()
+    }
+}
+
+/// Replaces paths to the constants collected by [`Crate::inline_consts_with`].
+///
+/// Paths are resolved by scope: `NAME` and `self::NAME` only in the crate root, `crate::NAME`
+/// anywhere and `super::NAME` with as many `super`s as the module is deep. Bindings and
+/// block-level items of the same name shadow a constant, and patterns are left alone.
+struct ConstInliner<'a> {
+    values: &'a HashMap<std::string::String, Expr>,
+    /// How many modules deep the visited item is.
+    depth: usize,
+    /// Names bound by the enclosing fns, closures, arms and `let`s, innermost last.
+    locals: Vec<HashSet<std::string::String>>,
+    /// Names of the items declared in the enclosing blocks, which nested items see as well.
+    block_items: Vec<HashSet<std::string::String>>,
+}
+
+impl<'a> ConstInliner<'a> {
+    fn new(values: &'a HashMap<std::string::String, Expr>) -> Self {
+        Self {
+            values,
+            depth: 0,
+            locals: vec![],
+            block_items: vec![],
+        }
+    }
+
+    fn is_shadowed(&self, name: &str) -> bool {
+        self.locals
+            .iter()
+            .chain(self.block_items.iter())
+            .any(|scope| scope.contains(name))
+    }
+
+    fn resolve(&self, path: &Path) -> Option<&'a Expr> {
+        let (name, prefix) = path.segments.split_last()?;
+        if path.segments.iter().any(|segment| segment.args.is_some()) {
+            return None;
+        }
+        let is_root = match prefix {
+            [] => self.depth == 0 && !self.is_shadowed(&name.ident),
+            [root] if root.ident == "crate" => true,
+            [root] if root.ident == "self" => self.depth == 0,
+            supers => {
+                supers.len() == self.depth && supers.iter().all(|segment| segment.ident == "super")
+            }
+        };
+        is_root.then(|| self.values.get(&*name.ident)).flatten()
+    }
+
+    fn with_locals(&mut self, names: HashSet<std::string::String>, f: impl FnOnce(&mut Self)) {
+        self.locals.push(names);
+        f(self);
+        self.locals.pop();
+    }
+
+    /// Visits an `if`/`while` condition, where each `let` of a `&&` chain binds for the
+    /// operands after it. Returns the names bound for the body.
+    fn visit_cond_mut(&mut self, cond: &mut Expr) -> HashSet<std::string::String> {
+        match &mut cond.kind {
+            ExprKind::Let(Let { pat, expr }) => {
+                self.visit_expr_mut(expr);
+                pat_bindings(pat)
+            }
+            ExprKind::Binary(Binary {
+                op: BinOpKind::LazyAnd,
+                lhs,
+                rhs,
+            }) => {
+                let mut names = self.visit_cond_mut(lhs);
+                let mut rhs_names = HashSet::new();
+                self.with_locals(names.clone(), |this| rhs_names = this.visit_cond_mut(rhs));
+                names.extend(rhs_names);
+                names
+            }
+            _ => {
+                self.visit_expr_mut(cond);
+                HashSet::new()
+            }
+        }
+    }
+}
+
+impl VisitMut for ConstInliner<'_> {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        // locals are not visible in nested items
+        let locals = std::mem::take(&mut self.locals);
+        if let ItemKind::Mod(Mod::Loaded(module)) = &mut item.kind {
+            let block_items = std::mem::take(&mut self.block_items);
+            self.depth += 1;
+            for item in module.items.iter_mut() {
+                self.visit_item_mut(item);
+            }
+            self.depth -= 1;
+            self.block_items = block_items;
+        } else {
+            let generics = match &item.kind {
+                ItemKind::EnumDef(def) => &def.generics.params[..],
+                ItemKind::StructDef(def) => &def.generics.params[..],
+                ItemKind::UnionDef(def) => &def.generics.params[..],
+                ItemKind::TraitDef(def) => &def.generics.params[..],
+                ItemKind::Impl(impl_) => &impl_.generics.params[..],
+                ItemKind::TyAlias(alias) => &alias.generics.params[..],
+                _ => &[],
+            };
+            let names = const_param_names(generics);
+            self.with_locals(names, |this| visit::walk_item_mut(this, item));
+        }
+        self.locals = locals;
+    }
+
+    fn visit_fn_mut(&mut self, fn_: &mut Fn) {
+        let mut names = const_param_names(&fn_.generics.params);
+        for param in fn_.fn_decl.inputs.iter() {
+            names.extend(pat_bindings(&param.pat));
+        }
+        self.with_locals(names, |this| visit::walk_fn_mut(this, fn_));
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let items = block
+            .stmts
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::Item(item) => item.ident().map(|ident| ident.to_string()),
+                _ => None,
+            })
+            .collect();
+        self.block_items.push(items);
+        self.locals.push(HashSet::new());
+        visit::walk_block_mut(self, block);
+        self.locals.pop();
+        self.block_items.pop();
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        let Stmt::Local(local) = stmt else {
+            return visit::walk_stmt_mut(self, stmt);
+        };
+        if let Some(ty) = &mut local.ty {
+            self.visit_type_mut(ty);
+        }
+        match &mut local.kind {
+            LocalKind::Decl => {}
+            LocalKind::Init(expr) => self.visit_expr_mut(expr),
+            LocalKind::InitElse(expr, block) => {
+                self.visit_expr_mut(expr);
+                self.visit_block_mut(block);
+            }
+        }
+        // in scope for the rest of the block
+        let names = pat_bindings(&local.pat);
+        if let Some(scope) = self.locals.last_mut() {
+            scope.extend(names);
+        }
+    }
+
+    fn visit_pat_mut(&mut self, _pat: &mut Pat) {}
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Path(path) => {
+                if let Some(value) = self.resolve(path) {
+                    expr.kind = value.kind.clone();
+                }
+            }
+            ExprKind::Closure(closure) => {
+                let names = closure
+                    .fn_decl
+                    .inputs
+                    .iter()
+                    .flat_map(|param| pat_bindings(&param.pat))
+                    .collect();
+                self.with_locals(names, |this| this.visit_expr_mut(&mut closure.body));
+            }
+            ExprKind::Match(match_) => {
+                self.visit_expr_mut(&mut match_.expr);
+                for arm in match_.arms.iter_mut() {
+                    self.with_locals(pat_bindings(&arm.pat), |this| {
+                        if let Some(guard) = &mut arm.guard {
+                            this.visit_expr_mut(guard);
+                        }
+                        this.visit_expr_mut(&mut arm.body);
+                    });
+                }
+            }
+            ExprKind::If(if_) => {
+                let names = self.visit_cond_mut(&mut if_.cond);
+                self.with_locals(names, |this| this.visit_block_mut(&mut if_.then));
+                if let Some(else_) = &mut if_.else_ {
+                    self.visit_expr_mut(else_);
+                }
+            }
+            ExprKind::While(while_) => {
+                let names = self.visit_cond_mut(&mut while_.cond);
+                self.with_locals(names, |this| this.visit_block_mut(&mut while_.body));
+            }
+            ExprKind::ForLoop(for_) => {
+                self.visit_expr_mut(&mut for_.expr);
+                let names = pat_bindings(&for_.pat);
+                self.with_locals(names, |this| this.visit_block_mut(&mut for_.body));
+            }
+            _ => visit::walk_expr_mut(self, expr),
+        }
+    }
+}
+
+fn const_param_names(generics: &[GenericParam]) -> HashSet<std::string::String> {
+    generics
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::ConstParam(param) => Some(param.ident.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names `pat` binds.
+fn pat_bindings(pat: &Pat) -> HashSet<std::string::String> {
+    #[derive(Default)]
+    struct Bindings(HashSet<std::string::String>);
+
+    impl<'ast> Visit<'ast> for Bindings {
+        fn visit_pat(&mut self, pat: &'ast Pat) {
+            if let Pat::Ident(ident) = pat {
+                self.0.insert(ident.ident.to_string());
+            }
+            visit::walk_pat(self, pat);
+        }
+    }
+
+    let mut bindings = Bindings::default();
+    bindings.visit_pat(pat);
+    bindings.0
+}
+
+/// Collects which of `names` are still mentioned anywhere: as the last segment of a path,
+/// in a use tree, or as an identifier in macro arguments or verbatim text.
+struct ConstRefs<'a> {
+    names: &'a HashMap<std::string::String, Expr>,
+    found: HashSet<std::string::String>,
+}
+
+impl ConstRefs<'_> {
+    fn mention(&mut self, ident: &str) {
+        if self.names.contains_key(ident) {
+            self.found.insert(ident.to_string());
+        }
+    }
+
+    fn mention_tokens(&mut self, mac: &MacCall) {
+        for token in mac.args.tokens.iter() {
+            match token {
+                Token::Ident(ident) => self.mention(ident),
+                Token::Verbatim(text) => self.mention_text(text),
+                _ => {}
+            }
+        }
+    }
+
+    fn mention_text(&mut self, text: &str) {
+        for word in text.split(|c: char| !c.is_alphanumeric() && c != '_') {
+            self.mention(word);
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ConstRefs<'_> {
+    fn visit_item(&mut self, item: &'ast Item) {
+        match &item.kind {
+            ItemKind::MacCallWithSemi(Semi(mac)) => self.mention_tokens(mac),
+            ItemKind::Verbatim(verbatim) => self.mention_text(&verbatim.0),
+            _ => {}
+        }
+        visit::walk_item(self, item);
+    }
+
+    fn visit_assoc_item(&mut self, item: &'ast AssocItem) {
+        if let AssocItemKind::MacCall(mac) = &item.kind {
+            self.mention_tokens(mac);
+        }
+        visit::walk_assoc_item(self, item);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        if let Stmt::MacCallWithSemi(Semi(mac)) = stmt {
+            self.mention_tokens(mac);
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match &expr.kind {
+            ExprKind::MacCall(mac) => self.mention_tokens(mac),
+            ExprKind::Verbatim(verbatim) => self.mention_text(&verbatim.0),
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_pat(&mut self, pat: &'ast Pat) {
+        match pat {
+            Pat::MacCall(mac) => self.mention_tokens(mac),
+            Pat::Ident(ident) => self.mention(&ident.ident),
+            _ => {}
+        }
+        visit::walk_pat(self, pat);
+    }
+
+    fn visit_type(&mut self, ty: &'ast Type) {
+        if let Type::Verbatim(verbatim) = ty {
+            self.mention_text(&verbatim.0);
+        }
+        visit::walk_type(self, ty);
+    }
+
+    fn visit_use_tree(&mut self, tree: &'ast UseTree) {
+        match tree {
+            UseTree::Name(name) => self.mention(name),
+            UseTree::Rename(rename) => self.mention(&rename.ident),
+            _ => {}
+        }
+        visit::walk_use_tree(self, tree);
+    }
+
+    fn visit_path(&mut self, path: &'ast Path) {
+        if let Some(segment) = path.segments.last() {
+            self.mention(&segment.ident);
+        }
+        visit::walk_path(self, path);
+    }
+}
+
+/// Counts string literals for [`Crate::dedup_strings`], remembering the order of first appearance.
+#[derive(Default)]
+struct StringCounter {
+    counts: HashMap<std::string::String, usize>,
+    order: Vec<std::string::String>,
+}
+
+impl<'ast> Visit<'ast> for StringCounter {
+    // attribute values must stay literals (`#[deprecated = "..."]`)
+    fn visit_attribute(&mut self, _attr: &AttributeItem) {}
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Lit(Lit {
+            kind: LitKind::Str,
+            symbol,
+        }) = &expr.kind
+        {
+            let count = self.counts.entry(symbol.to_string()).or_insert(0);
+            if *count == 0 {
+                self.order.push(symbol.to_string());
+            }
+            *count += 1;
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+struct StringHoister<'a> {
+    names: &'a HashMap<std::string::String, std::string::String>,
+}
+
+impl VisitMut for StringHoister<'_> {
+    fn visit_attribute_mut(&mut self, _attr: &mut AttributeItem) {}
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let ExprKind::Lit(Lit {
+            kind: LitKind::Str,
+            symbol,
+        }) = &expr.kind
+        {
+            if let Some(name) = self.names.get(&**symbol) {
+                expr.kind = Path::single("crate").chain(&name[..]).into();
+                return;
+            }
+        }
+        visit::walk_expr_mut(self, expr);
+    }
+}
+
+/// Collects the first segment of every path an item refers to, including derived traits.
+#[derive(Default)]
+struct PathRoots {
+    names: HashSet<std::string::String>,
+}
+
+impl<'ast> Visit<'ast> for PathRoots {
+    fn visit_attribute(&mut self, attr: &AttributeItem) {
+        for path in expr::derive_paths(attr).into_iter().flatten() {
+            self.visit_path(&path);
+        }
+        visit::walk_attribute(self, attr);
+    }
+
+    fn visit_path(&mut self, path: &Path) {
+        if let Some(segment) = path.segments.first() {
+            self.names.insert(segment.ident.to_string());
+        }
+        visit::walk_path(self, path);
+    }
+}
+
+/// Keeps the parts of a use tree that bind one of `names`. `parent` is the module a
+/// `self` leaf binds. Glob imports and `as _` imports bind no name that paths can refer to,
+/// so they are kept.
+fn retain_use_tree(
+    tree: &UseTree,
+    parent: Option<&str>,
+    names: &HashSet<std::string::String>,
+) -> Option<UseTree> {
+    let keep = match tree {
+        UseTree::Name(name) if &**name == "self" => parent.is_some_and(|p| names.contains(p)),
+        UseTree::Name(name) => names.contains(&**name),
+        UseTree::Rename(rename) => &*rename.alias == "_" || names.contains(&*rename.alias),
+        UseTree::Glob => true,
+        UseTree::Path(path) => {
+            return match retain_use_tree(path.tree(), Some(path.ident()), names)? {
+                UseTree::Name(name) if &*name == "self" => Some(UseTree::name(path.ident())),
+                tree => Some(UseTree::Path(UsePath::new(path.ident(), tree))),
+            };
+        }
+        UseTree::Group(trees) => {
+            let mut trees = trees
+                .iter()
+                .filter_map(|tree| retain_use_tree(tree, parent, names))
+                .collect::<Vec<_>>();
+            return match trees.len() {
+                0 => None,
+                1 => trees.pop(),
+                _ => Some(UseTree::Group(trees)),
+            };
+        }
+    };
+    keep.then(|| tree.clone())
+}
+
+/// A set of named crates that are emitted together.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Workspace {
+    pub members: Vec<(String, Crate)>,
+    /// The header of every written file whose crate has no [`Crate::header`] of its own.
+    pub header: Option<Header>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self {
+            members: Vec::new(),
+            header: None,
+        }
+    }
+
+    pub fn set_header(&mut self, header: Header) {
+        self.header = Some(header);
+    }
+
+    pub fn with_header(mut self, header: Header) -> Self {
+        self.set_header(header);
+        self
+    }
+
+    pub fn add_crate(&mut self, name: impl Into<String>, krate: Crate) {
+        self.members.push((name.into(), krate));
+    }
+
+    pub fn with_crate(mut self, name: impl Into<String>, krate: Crate) -> Self {
+        self.add_crate(name, krate);
+        self
+    }
+
+    pub fn get_crate(&self, name: &str) -> Option<&Crate> {
+        self.members
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, krate)| krate)
+    }
+
+    pub fn get_crate_mut(&mut self, name: &str) -> Option<&mut Crate> {
+        self.members
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .map(|(_, krate)| krate)
+    }
+
+    /// Writes each member to `dir/<name>/src/lib.rs`, moving inline modules into files of their
+    /// own (see [`Workspace::write_to_dir_with`]).
+    pub fn write_to_dir(&self, dir: impl AsRef<Pt>) -> Result<(), std::io::Error> {
+        self.write_to_dir_with(dir, |_, _| None)
+    }
+
+    /// Same as [`Workspace::write_to_dir`], but also writes `dir/<name>/Cargo.toml`
+    /// for each member for which `manifest` returns `Some(contents)`.
+    ///
+    /// Every `mod foo { .. }` becomes `mod foo;` with its contents in `foo.rs`, next to the file
+    /// of its parent module (`src/a/foo.rs` for `a::foo`). The doc comments of the module become
+    /// `//!` docs at the top of the new file; its other attributes, such as `#[cfg(..)]`, stay
+    /// on the `mod foo;` declaration.
+    ///
+    /// Every file starts with the header of its crate, or else that of the workspace.
+    pub fn write_to_dir_with(
+        &self,
+        dir: impl AsRef<Pt>,
+        mut manifest: impl FnMut(&str, &Crate) -> Option<std::string::String>,
+    ) -> Result<(), std::io::Error> {
+        let dir = dir.as_ref();
+        for (name, krate) in self.members.iter() {
+            let crate_dir = dir.join(&**name);
+            std::fs::create_dir_all(crate_dir.join("src"))?;
+            let mut root = krate.clone();
+            if root.header.is_none() {
+                root.header = self.header.clone();
+            }
+            split_modules(&mut root.items, &crate_dir.join("src"), &root.header)?;
+            let mut file = File::create(crate_dir.join("src").join("lib.rs"))?;
+            write!(file, "{root}")?;
+            if let Some(manifest) = manifest(name, krate) {
+                std::fs::write(crate_dir.join("Cargo.toml"), manifest)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replaces every inline module in `items` with `mod ident;` and writes its contents to
+/// `dir/ident.rs`, recursing into `dir/ident/` for nested modules. Each file starts with
+/// `header`.
+fn split_modules(
+    items: &mut [Item],
+    dir: &Pt,
+    header: &Option<Header>,
+) -> Result<(), std::io::Error> {
+    for item in items.iter_mut() {
+        let ItemKind::Mod(Mod::Loaded(module)) = &mut item.kind else {
+            continue;
+        };
+        let mut file = Crate::new();
+        file.header = header.clone();
+        file.items = std::mem::take(&mut module.items);
+        let ident = module.ident.clone();
+        item.kind = Mod::Unloaded(ident.clone()).into();
+        item.attrs.retain(|attr| match inner_doc(attr) {
+            Some(doc) => {
+                file.attrs.push(doc);
+                false
+            }
+            None => true,
+        });
+        if file
+            .items
+            .iter()
+            .any(|item| matches!(item.kind, ItemKind::Mod(Mod::Loaded(_))))
+        {
+            let sub_dir = dir.join(&*ident);
+            std::fs::create_dir_all(&sub_dir)?;
+            split_modules(&mut file.items, &sub_dir, header)?;
+        }
+        let mut out = File::create(dir.join(format!("{ident}.rs")))?;
+        write!(out, "{file}")?;
+    }
+    Ok(())
+}
+
+/// The inner (`//!`) form of a doc comment or `#[doc = ".."]` attribute, or `None` for other
+/// attributes.
+fn inner_doc(attr: &Attribute) -> Option<Attribute> {
+    let inner_lines = |text: &str| {
+        text.split('\n')
+            .map(|line| match line {
+                "" => "//!".to_string(),
+                line => format!("//! {line}"),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    match &attr.kind {
+        AttrKind::DocComment(_) => Some(attr.clone().into_inner()),
+        AttrKind::Normal(item) if item.path == Path::single("doc") => match &item.args {
+            AttrArgs::Eq(Expr {
+                kind:
+                    ExprKind::Lit(Lit {
+                        kind: LitKind::Str,
+                        symbol,
+                    }),
+                ..
+            }) => Some(Attribute::doc_comment(inner_lines(symbol)).into_inner()),
+            _ => None,
+        },
+        AttrKind::Normal(_) => None,
+    }
+}
+
+/// Rewrites `crate::item` paths into `crate::module::item` for items moved by [`Crate::extract_items`].
+struct ExtractedPaths<'a> {
+    module: &'a str,
+    idents: &'a HashSet<std::string::String>,
+}
+
+impl ExtractedPaths<'_> {
+    fn rewrite_use_tree(&self, tree: &mut UseTree) {
+        let moved = match tree {
+            UseTree::Name(name) => self.idents.contains(&**name),
+            UseTree::Rename(rename) => self.idents.contains(&*rename.ident),
+            UseTree::Path(path) => self.idents.contains(path.ident()),
+            UseTree::Group(trees) => {
+                for tree in trees.iter_mut() {
+                    self.rewrite_use_tree(tree);
+                }
+                false
+            }
+            UseTree::Glob => false,
+        };
+        if moved {
+            let inner = std::mem::replace(tree, UseTree::Glob);
+            *tree = UseTree::path(UsePath::new(self.module, inner));
+        }
+    }
+}
+
+impl VisitMut for ExtractedPaths<'_> {
+    fn visit_use_tree_mut(&mut self, tree: &mut UseTree) {
+        match tree {
+            UseTree::Path(path) if path.ident() == "crate" => {
+                self.rewrite_use_tree(path.tree_mut())
+            }
+            _ => visit::walk_use_tree_mut(self, tree),
+        }
+    }
+
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        if path.segments.len() >= 2
+            && path.segments[0].ident == "crate"
+            && self.idents.contains(&*path.segments[1].ident)
+        {
+            path.segments.insert(1, PathSegment::simple(self.module));
+        }
+        visit::walk_path_mut(self, path);
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub struct CompileOptions {
     pub allow: Option<String>,
@@ -265,12 +1044,278 @@ pub struct CompileOptions {
 impl Crate {
     pub fn new() -> Self {
         Self {
+            header: None,
             shebang: None,
             attrs: Vec::new(),
             items: Vec::new(),
         }
     }
 
+    pub fn add_attr(&mut self, attr: impl Into<Attribute>) {
+        self.attrs.push(attr.into().into_inner());
+    }
+
+    pub fn with_attr(mut self, attr: impl Into<Attribute>) -> Self {
+        self.add_attr(attr);
+        self
+    }
+
+    pub fn set_header(&mut self, header: Header) {
+        self.header = Some(header);
+    }
+
+    pub fn with_header(mut self, header: Header) -> Self {
+        self.set_header(header);
+        self
+    }
+
+    /// Checks that the AST does not refer to itself, so that printing it terminates.
+    ///
+    /// This always holds: every node owns its children through `Box` and `Vec`, and no node
+    /// takes an `Rc` or `Arc`, so a shared or self-referential subtree cannot be built. The
+    /// check does nothing, but documents the guarantee where a generator relies on it.
+    pub fn assert_acyclic(&self) {}
+
+    /// Adds `#![allow(..)]` for the lints that generated code commonly trips: unused code,
+    /// naming conventions and all of clippy.
+    pub fn allow_all_generated(self) -> Self {
+        self.with_attr(Attribute::allow([
+            "dead_code",
+            "non_camel_case_types",
+            "non_snake_case",
+            "non_upper_case_globals",
+            "unused_imports",
+            "unused_mut",
+            "unused_variables",
+            "clippy::all",
+        ]))
+    }
+
+    pub fn set_shebang(&mut self, shebang: impl Into<String>) {
+        self.shebang = Some(shebang.into());
+    }
+
+    pub fn with_shebang(mut self, shebang: impl Into<String>) -> Self {
+        self.set_shebang(shebang);
+        self
+    }
+
+    /// Moves the items selected by `filter` into a new module named `ident`.
+    ///
+    /// Returns the remaining crate and the new module.
+    /// The remaining crate declares the module (`mod ident;`) where the first moved item was,
+    /// followed by re-exports of the moved items, so that existing references keep resolving.
+    /// `crate::`-prefixed paths to the moved items are rewritten to go through the new module.
+    /// Moved items that were private become `pub(crate)`, and the module starts with `use super::*;`.
+    /// `macro_rules!` definitions are never moved, since macros are scoped textually:
+    /// they stay where they are, and the module is declared after any of them that preceded a moved item.
+    pub fn extract_items(
+        self,
+        ident: impl Into<String>,
+        mut filter: impl FnMut(&Item) -> bool,
+    ) -> (Crate, Mod) {
+        let ident = ident.into();
+        let mut remaining = Crate {
+            header: self.header,
+            shebang: self.shebang,
+            attrs: self.attrs,
+            items: Vec::new(),
+        };
+        let mut moved = vec![Item::inherited(Use::from(
+            Path::single("super").chain_use_glob(),
+        ))];
+        let mut position = None;
+        let mut macros_end = 0;
+        let mut moved_macros_end = 0;
+        let mut moved_idents = HashSet::new();
+        let mut public_exports = vec![];
+        let mut private_exports = vec![];
+        for mut item in self.items {
+            if matches!(item.kind, ItemKind::MacroDef(_)) {
+                remaining.items.push(item);
+                macros_end = remaining.items.len();
+                continue;
+            }
+            if !filter(&item) {
+                remaining.items.push(item);
+                continue;
+            }
+            position.get_or_insert(remaining.items.len());
+            moved_macros_end = macros_end;
+            let exported = match &item.kind {
+                ItemKind::Use(_)
+                | ItemKind::Impl(_)
+                | ItemKind::MacCallWithSemi(_)
+                | ItemKind::ExternBlock(_)
+                | ItemKind::ExternCrate(_) => None,
+                kind => kind.ident().map(str::to_owned),
+            };
+            if let Some(name) = exported {
+                match item.vis {
+                    Visibility::Public => public_exports.push(name.clone()),
+                    Visibility::Inherited => {
+                        item.vis = Visibility::crate_();
+                        private_exports.push(name.clone());
+                    }
+                    Visibility::Scoped(_) => private_exports.push(name.clone()),
+                }
+                moved_idents.insert(name);
+            }
+            moved.push(item);
+        }
+        let mut module = Mod::Loaded(LoadedMod::new(ident.clone(), moved));
+        let Some(position) = position else {
+            return (remaining, module);
+        };
+        // every macro that was visible to a moved item has to be visible to the module
+        let position = position.max(moved_macros_end);
+        let mut rewriter = ExtractedPaths {
+            module: &ident,
+            idents: &moved_idents,
+        };
+        rewriter.visit_crate_mut(&mut remaining);
+        if let Mod::Loaded(loaded) = &mut module {
+            for item in loaded.items.iter_mut() {
+                rewriter.visit_item_mut(item);
+            }
+        }
+        let mut inserted = vec![Item::inherited(Mod::Unloaded(ident.clone()))];
+        for (vis, mut exports) in [
+            (Visibility::Public, public_exports),
+            (Visibility::Inherited, private_exports),
+        ] {
+            let prefix = Path::single("self").chain(&*ident);
+            let tree = match exports.len() {
+                0 => continue,
+                1 => UseTree::from(prefix.chain(exports.remove(0))),
+                _ => prefix.chain_use_group(exports.into_iter().map(UseTree::name).collect()),
+            };
+            inserted.push(Item::new(vis, Use::from(tree)));
+        }
+        remaining.items.splice(position..position, inserted);
+        (remaining, module)
+    }
+
+    /// Replaces uses of simple constants (literals, possibly negated) with their values.
+    /// Equivalent to `inline_consts_with(InlineConstOptions::default())`.
+    pub fn inline_consts(&mut self) {
+        self.inline_consts_with(InlineConstOptions::default());
+    }
+
+    /// Replaces uses of top-level constants whose value is a literal.
+    ///
+    /// Uses are resolved by scope: `NAME` and `self::NAME` in the crate root, `crate::NAME` anywhere
+    /// and `super::NAME` from nested modules. Locals and block-level items of the same name shadow
+    /// the constant, and uses in patterns or macro arguments are left alone.
+    /// Numeric literals get the suffix of the constant's type (`1` becomes `1u8`) so that inference is unaffected.
+    /// A constant is only removed when nothing refers to it anymore.
+    pub fn inline_consts_with(&mut self, options: InlineConstOptions) {
+        let mut values = HashMap::new();
+        for item in self.items.iter() {
+            let ItemKind::ConstItem(ConstItem {
+                ident,
+                ty,
+                expr: Some(expr),
+                ..
+            }) = &item.kind
+            else {
+                continue;
+            };
+            if item.vis != Visibility::Inherited && !options.include_public {
+                continue;
+            }
+            if let Some(value) = inlinable_const_value(ty, expr) {
+                values.insert(ident.to_string(), value);
+            }
+        }
+        if values.is_empty() {
+            return;
+        }
+        ConstInliner::new(&values).visit_crate_mut(self);
+        if !options.keep_items {
+            let mut refs = ConstRefs {
+                names: &values,
+                found: HashSet::new(),
+            };
+            refs.visit_crate(self);
+            self.items.retain(|item| match &item.kind {
+                ItemKind::ConstItem(ConstItem { ident, .. }) => {
+                    item.vis != Visibility::Inherited
+                        || !values.contains_key(&**ident)
+                        || refs.found.contains(&**ident)
+                }
+                _ => true,
+            });
+        }
+    }
+
+    /// Hoists string literals longer than `min_len` that occur more than once into `const` items
+    /// (`STR_0`, `STR_1`, ...) placed at the top of the crate, and rewrites the uses to `crate::STR_n`.
+    ///
+    /// Literals inside macro invocations are tokens, not expressions, and are left alone, as are
+    /// attribute values, which must stay literals.
+    /// Returns the number of hoisted constants.
+    pub fn dedup_strings(&mut self, min_len: usize) -> usize {
+        let mut counter = StringCounter::default();
+        counter.visit_crate(self);
+        let taken = self
+            .items
+            .iter()
+            .filter_map(|item| item.ident())
+            .collect::<HashSet<_>>();
+        let mut names = HashMap::new();
+        let mut consts = vec![];
+        let mut suffix = 0;
+        for symbol in counter.order {
+            if symbol.len() <= min_len || counter.counts[&symbol] < 2 {
+                continue;
+            }
+            let name = loop {
+                let name = format!("STR_{suffix}");
+                suffix += 1;
+                if !taken.contains(&name[..]) {
+                    break name;
+                }
+            };
+            consts.push(Item::from(ConstItem::new(
+                name.clone(),
+                Type::ref_(Type::str()),
+                Some(Lit::str(symbol.clone()).into()),
+            )));
+            names.insert(symbol, name);
+        }
+        let hoisted = consts.len();
+        if hoisted > 0 {
+            StringHoister { names: &names }.visit_crate_mut(self);
+            self.items.splice(0..0, consts);
+        }
+        hoisted
+    }
+
+    /// Renders the top-level item named `ident` preceded by the `use` items of this crate it needs,
+    /// trimmed down to the names the item refers to.
+    ///
+    /// Glob imports are always kept, and imports that only bring trait methods into scope are
+    /// only kept when written as `use path::Trait as _;`.
+    pub fn render_item_with_deps(&self, ident: &str) -> Option<std::string::String> {
+        let item = self.get_item_by_id(ident)?;
+        let mut roots = PathRoots::default();
+        roots.visit_item(item);
+        let mut rendered = std::string::String::new();
+        for use_item in self.items.iter() {
+            let ItemKind::Use(Use(tree)) = &use_item.kind else {
+                continue;
+            };
+            if let Some(tree) = retain_use_tree(tree, None, &roots.names) {
+                let mut use_item = use_item.clone();
+                use_item.kind = ItemKind::Use(Use(tree));
+                rendered.push_str(&format!("{use_item}\n"));
+            }
+        }
+        rendered.push_str(&item.to_string());
+        Some(rendered)
+    }
+
     pub fn dump(self, path: impl AsRef<Pt>) -> Result<(), std::io::Error> {
         let mut file = File::create(path)?;
         write!(file, "{self}")?;
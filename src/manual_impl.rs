@@ -0,0 +1,282 @@
+use crate::*;
+
+/// `impl<T: Trait, ..> Trait for Ident<T, ..> where .. { items }`, bounding every type parameter
+/// like `#[derive(Trait)]` does.
+fn trait_impl(ident: &str, generics: &Generics, trait_: &str, item: Fn) -> Impl {
+    let self_ty = if generics.params.is_empty() {
+        Type::simple_path(ident)
+    } else {
+        Type::poly_path(
+            ident,
+            generics.params.iter().map(GenericParam::to_arg).collect(),
+        )
+    };
+    let params = generics
+        .params
+        .iter()
+        .map(GenericParam::without_default)
+        .map(|param| match param {
+            GenericParam::TypeParam(param) => {
+                GenericParam::TypeParam(param.with_bound(PolyTraitRef::simple(trait_).into()))
+            }
+            param => param,
+        })
+        .collect();
+    Impl::trait_impl(
+        params,
+        self_ty,
+        Type::simple_path(trait_),
+        Some(generics.where_clause.predicates.clone()),
+        vec![AssocItem::inherited(item)],
+    )
+}
+
+/// `fn clone(&self) -> Self { body }`
+fn clone_fn(body: impl Into<Expr>) -> Fn {
+    Fn::simple(
+        "clone",
+        FnDecl::regular(vec![Param::ref_self()], Some(Type::simple_path("Self"))),
+        Block::single(body),
+    )
+}
+
+/// `fn eq(&self, other: &Self) -> bool { body }`
+fn eq_fn(body: impl Into<Expr>) -> Fn {
+    Fn::simple(
+        "eq",
+        FnDecl::regular(
+            vec![
+                Param::ref_self(),
+                Param::ident("other", Type::ref_(Type::simple_path("Self"))),
+            ],
+            Some(Type::bool()),
+        ),
+        Block::single(body),
+    )
+}
+
+/// `lhs == rhs && ..`, or `true` without any comparisons.
+fn all_eq(pairs: Vec<(Expr, Expr)>) -> Expr {
+    pairs
+        .into_iter()
+        .map(|(lhs, rhs)| Expr::from(lhs.bin_op(BinOpKind::Eq, rhs)))
+        .reduce(|acc, eq| acc.bin_op(BinOpKind::LazyAnd, eq).into())
+        .unwrap_or_else(|| Lit::bool("true").into())
+}
+
+/// The name of a field: its identifier, or its index for tuple fields.
+fn field_name(i: usize, field: &FieldDef) -> std::string::String {
+    match &field.ident {
+        Some(ident) => ident.to_string(),
+        None => i.to_string(),
+    }
+}
+
+/// `Enum::Variant(f0, ..)` or `Enum::Variant { a, .. }`, binding each field to `prefix{name}`
+/// unless `bind` rejects it. Returns the pattern and the bindings, in field order.
+fn variant_pat(
+    enum_: &str,
+    variant: &Variant,
+    prefix: &str,
+    mut bind: impl FnMut(&FieldDef) -> bool,
+) -> (Pat, Vec<Option<Expr>>) {
+    let path = Path::single(enum_).chain(variant.ident.clone());
+    let binding = |i, field: &FieldDef| match (&field.ident, prefix) {
+        (None, "") => format!("f{i}"),
+        _ => format!("{prefix}{}", field_name(i, field)),
+    };
+    match &variant.fields {
+        Fields::Unit => (Pat::Lit(path.into()), vec![]),
+        Fields::Tuple(fields) => {
+            let mut pats = vec![];
+            let mut bindings = vec![];
+            for (i, field) in fields.iter().enumerate() {
+                if bind(field) {
+                    let name = binding(i, field);
+                    pats.push(Pat::ident(name.clone()));
+                    bindings.push(Some(Path::single(name).into()));
+                } else {
+                    pats.push(Pat::Wild);
+                    bindings.push(None);
+                }
+            }
+            (Pat::tuple_struct(path, pats), bindings)
+        }
+        Fields::Struct(fields) => {
+            let mut pat = StructPat::new(path, vec![]);
+            let mut bindings = vec![];
+            for (i, field) in fields.iter().enumerate() {
+                if !bind(field) {
+                    pat.set_rest(true);
+                    bindings.push(None);
+                    continue;
+                }
+                let name = binding(i, field);
+                let ident = field_name(i, field);
+                pat.add_field(if name == ident {
+                    PatField::shortened(ident)
+                } else {
+                    PatField::new(ident, Pat::ident(name.clone()))
+                });
+                bindings.push(Some(Path::single(name).into()));
+            }
+            (pat.into(), bindings)
+        }
+    }
+}
+
+/// `Self`, `Self(exprs..)` or `Self { field: expr, .. }`, built from one expression per field.
+fn construct(path: Path, fields: &Fields, exprs: Vec<Expr>) -> Expr {
+    match fields {
+        Fields::Unit => path.into(),
+        Fields::Tuple(_) => path.call(exprs).into(),
+        Fields::Struct(defs) => Struct::new(
+            path,
+            defs.iter()
+                .enumerate()
+                .zip(exprs)
+                .map(|((i, def), expr)| ExprField::new(field_name(i, def), expr))
+                .collect(),
+        )
+        .into(),
+    }
+}
+
+/// `base.field` or `base.0`
+fn access(base: &str, i: usize, field: &FieldDef) -> Expr {
+    let member = match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(i),
+    };
+    Field::new(Path::single(base), member).into()
+}
+
+fn clone_of(expr: Expr) -> Expr {
+    expr.method_call0(PathSegment::simple("clone")).into()
+}
+
+/// `match *self {}`, the body of a method on an enum without variants.
+fn match_never() -> Expr {
+    Match::new(Path::single("self").deref(), vec![]).into()
+}
+
+fn field_defs(fields: &Fields) -> &[FieldDef] {
+    match fields {
+        Fields::Unit => &[],
+        Fields::Tuple(fields) | Fields::Struct(fields) => fields,
+    }
+}
+
+impl StructDef {
+    /// `impl Clone for Ident { .. }`, cloning field by field.
+    pub fn clone_impl(&self) -> Impl {
+        let exprs = field_defs(&self.fields)
+            .iter()
+            .enumerate()
+            .map(|(i, field)| clone_of(access("self", i, field)))
+            .collect();
+        let body = construct(Path::single("Self"), &self.fields, exprs);
+        trait_impl(&self.ident, &self.generics, "Clone", clone_fn(body))
+    }
+
+    /// `impl PartialEq for Ident { .. }`, comparing field by field.
+    pub fn partial_eq_impl(&self) -> Impl {
+        self.partial_eq_impl_with(|_| true)
+    }
+
+    /// `impl PartialEq for Ident { .. }`, comparing only the fields `compare` accepts.
+    pub fn partial_eq_impl_with(&self, mut compare: impl FnMut(&FieldDef) -> bool) -> Impl {
+        let pairs = field_defs(&self.fields)
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| compare(field))
+            .map(|(i, field)| (access("self", i, field), access("other", i, field)))
+            .collect();
+        trait_impl(
+            &self.ident,
+            &self.generics,
+            "PartialEq",
+            eq_fn(all_eq(pairs)),
+        )
+    }
+}
+
+impl EnumDef {
+    /// `match scrutinee { Ident::Variant(f0, ..) => body, .. }`, with one arm per variant.
+    ///
+    /// Every field is bound, tuple fields to `f0`, `f1`, .. and named fields to their name, and
+    /// `body` builds the arm from the variant and these bindings, in field order.
+    pub fn exhaustive_match(
+        &self,
+        scrutinee: impl Into<Expr>,
+        mut body: impl FnMut(&Variant, Vec<Expr>) -> Expr,
+    ) -> Match {
+        let arms = self
+            .variants
+            .iter()
+            .map(|variant| {
+                let (pat, bindings) = variant_pat(&self.ident, variant, "", |_| true);
+                let expr = body(variant, bindings.into_iter().flatten().collect());
+                Arm::new(pat, None, expr)
+            })
+            .collect();
+        Match::new(scrutinee, arms)
+    }
+
+    /// `impl Clone for Ident { .. }`, matching on the variant and cloning its fields.
+    pub fn clone_impl(&self) -> Impl {
+        let body = if self.variants.is_empty() {
+            match_never()
+        } else {
+            let arms = self
+                .variants
+                .iter()
+                .map(|variant| {
+                    let (pat, bindings) = variant_pat("Self", variant, "", |_| true);
+                    let exprs = bindings.into_iter().flatten().map(clone_of).collect();
+                    let path = Path::single("Self").chain(variant.ident.clone());
+                    Arm::new(pat, None, construct(path, &variant.fields, exprs))
+                })
+                .collect();
+            Match::new(Path::single("self"), arms).into()
+        };
+        trait_impl(&self.ident, &self.generics, "Clone", clone_fn(body))
+    }
+
+    /// `impl PartialEq for Ident { .. }`, comparing the variants and then their fields.
+    pub fn partial_eq_impl(&self) -> Impl {
+        self.partial_eq_impl_with(|_| true)
+    }
+
+    /// `impl PartialEq for Ident { .. }`, comparing the variants and then the fields `compare`
+    /// accepts.
+    pub fn partial_eq_impl_with(&self, mut compare: impl FnMut(&FieldDef) -> bool) -> Impl {
+        let body = if self.variants.is_empty() {
+            match_never()
+        } else {
+            let mut arms = self
+                .variants
+                .iter()
+                .map(|variant| {
+                    let (lhs, lhs_bindings) = variant_pat("Self", variant, "self_", &mut compare);
+                    let (rhs, rhs_bindings) = variant_pat("Self", variant, "other_", &mut compare);
+                    let pairs = lhs_bindings
+                        .into_iter()
+                        .zip(rhs_bindings)
+                        .filter_map(|(lhs, rhs)| lhs.zip(rhs))
+                        .collect();
+                    Arm::new(Pat::tuple(vec![lhs, rhs]), None, all_eq(pairs))
+                })
+                .collect::<Vec<_>>();
+            if self.variants.len() > 1 {
+                arms.push(Arm::new(Pat::Wild, None, Lit::bool("false")));
+            }
+            let scrutinee = Tuple::new(vec![
+                Path::single("self").into(),
+                Path::single("other").into(),
+            ]);
+            Match::new(scrutinee, arms).into()
+        };
+        trait_impl(&self.ident, &self.generics, "PartialEq", eq_fn(body))
+    }
+}
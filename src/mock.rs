@@ -0,0 +1,262 @@
+use std::string::String;
+
+use crate::*;
+
+/// A required trait method the mock can record.
+struct MockedMethod {
+    ident: String,
+    args: Vec<(String, Type)>,
+    output: Option<Type>,
+}
+
+impl MockedMethod {
+    fn calls_field(&self) -> String {
+        format!("{}_calls", self.ident)
+    }
+
+    fn returns_field(&self) -> String {
+        format!("{}_returns", self.ident)
+    }
+
+    /// `()`, `A` or `(A, B, ..)`
+    fn recorded_ty(&self) -> Type {
+        match &self.args[..] {
+            [(_, ty)] => ty.clone(),
+            args => Type::Tuple(args.iter().map(|(_, ty)| ty.clone()).collect()),
+        }
+    }
+
+    fn recorded_expr(&self) -> Expr {
+        match &self.args[..] {
+            [(ident, _)] => Path::single(ident.clone()).into(),
+            args => Tuple::new(
+                args.iter()
+                    .map(|(ident, _)| Path::single(ident.clone()).into())
+                    .collect(),
+            )
+            .into(),
+        }
+    }
+
+    /// `Option<Box<dyn Fn(&A, &B) -> R>>`
+    fn returns_ty(&self) -> Type {
+        let inputs = self
+            .args
+            .iter()
+            .map(|(_, ty)| Type::ref_(ty.clone()))
+            .collect();
+        let closure =
+            TraitObject::dyn_(vec![PolyTraitRef::fn_(inputs, self.output.clone()).into()]);
+        Type::option(Type::box_(closure))
+    }
+
+    /// `self.method_calls.borrow_mut().push(args);`
+    fn record_stmt(&self) -> Stmt {
+        Expr::from(Path::single("self"))
+            .field(self.calls_field())
+            .method_call(PathSegment::simple("borrow_mut"), vec![])
+            .method_call(PathSegment::simple("push"), vec![self.recorded_expr()])
+            .semi()
+            .into()
+    }
+
+    fn returns_call(&self, returns: Expr) -> Expr {
+        let args = self
+            .args
+            .iter()
+            .map(|(ident, _)| Expr::from(Path::single(ident.clone())).ref_immut().into())
+            .collect();
+        returns.call(args)
+    }
+
+    fn body(&self, mock_ident: &str) -> Block {
+        let returns = Expr::from(Path::single("self")).field(self.returns_field());
+        let mut block = Block::empty();
+        if self.output.as_ref().is_none_or(Type::is_unit) {
+            let then =
+                Block::empty().with_stmt(self.returns_call(Path::single("returns").into()).semi());
+            let cond = Let::new(Pat::some("returns"), returns.ref_immut());
+            block.add_stmt(Expr::from(If::new(cond.into(), then, None)));
+            block.add_stmt(self.record_stmt());
+        } else {
+            let message = format!(
+                "no return value configured for `{mock_ident}::{}`",
+                self.ident
+            );
+            let returns = returns
+                .method_call(PathSegment::simple("as_ref"), vec![])
+                .method_call(
+                    PathSegment::simple("expect"),
+                    vec![Lit::str(message).into()],
+                );
+            block.add_stmt(Local::simple(Pat::ident("ret"), self.returns_call(returns)));
+            block.add_stmt(self.record_stmt());
+            block.add_stmt(Expr::from(Path::single("ret")));
+        }
+        block
+    }
+}
+
+/// `unimplemented!("message")`
+fn unimplemented(message: &str) -> Block {
+    Block::single(Expr::unimplemented_fmt(FormatArgs::escape(message), vec![]))
+}
+
+fn generic_args(generics: &[GenericParam]) -> Vec<GenericArg> {
+    generics.iter().map(GenericParam::to_arg).collect()
+}
+
+impl TraitDef {
+    /// The name of the struct generated by [`TraitDef::mock`], `Mock{ident}`.
+    pub fn mock_ident(&self) -> String {
+        format!("Mock{}", self.ident)
+    }
+
+    /// Generates a `Mock{ident}` struct implementing this trait, followed by its trait impl and
+    /// an inherent impl with `with_{method}_returns` setters.
+    ///
+    /// Every required method taking `self` gets a `{method}_calls: RefCell<Vec<_>>` field that
+    /// records its arguments, and a `{method}_returns: Option<Box<dyn Fn(&_) -> _>>` field that
+    /// computes its return value.
+    /// Methods with a default body are not overridden. Generic methods and associated functions
+    /// are implemented with `unimplemented!()`.
+    /// Recorded argument types must be `'static`, and associated types and consts are left to the
+    /// caller.
+    pub fn mock(&self) -> Vec<Item> {
+        let mock_ident = self.mock_ident();
+        let self_ty = Type::poly_path(mock_ident.clone(), generic_args(&self.generics.params));
+        let trait_ty = Type::poly_path(self.ident.clone(), generic_args(&self.generics.params));
+
+        let mut mock = StructDef::empty(mock_ident.clone());
+        let mut trait_impl = Impl::trait_impl(
+            self.generics
+                .params
+                .iter()
+                .map(GenericParam::without_default)
+                .collect(),
+            self_ty.clone(),
+            trait_ty,
+            Some(self.generics.where_clause.predicates.clone()),
+            vec![],
+        );
+        let mut setters = Impl::simple(self_ty, vec![]);
+        for param in self.generics.params.iter() {
+            mock.add_generic_param(param.clone());
+            setters.add_generic_param(param.without_default());
+        }
+
+        for item in self.items.iter() {
+            let AssocItemKind::Fn(fn_) = &item.kind else {
+                continue;
+            };
+            if fn_.body.is_some() {
+                continue;
+            }
+            let mut fn_ = fn_.clone();
+            let has_receiver = fn_.fn_decl.receiver().is_some();
+            let mut args = vec![];
+            let skip = usize::from(has_receiver);
+            for (i, param) in fn_.fn_decl.inputs.iter_mut().skip(skip).enumerate() {
+                let ident = match &param.pat {
+                    Pat::Ident(ident) => ident.ident.to_string(),
+                    _ => format!("arg{i}"),
+                };
+                param.pat = Pat::ident(ident.clone());
+                args.push((ident, param.ty.clone()));
+            }
+            if !has_receiver || !fn_.generics.params.is_empty() {
+                fn_.body = Some(unimplemented(&format!(
+                    "`{mock_ident}::{}` cannot be mocked",
+                    fn_.ident
+                )));
+                trait_impl.add_item(fn_);
+                continue;
+            }
+
+            let method = MockedMethod {
+                ident: fn_.ident.to_string(),
+                args,
+                output: fn_.fn_decl.output.clone(),
+            };
+            let calls_ty = Path::single("std")
+                .chain("cell")
+                .chain(PathSegment::generic(
+                    "RefCell",
+                    vec![GenericArg::Type(Type::vec(method.recorded_ty()))],
+                ));
+            mock.add_field(FieldDef::public(method.calls_field(), Type::Path(calls_ty)));
+            mock.add_field(FieldDef::public(
+                method.returns_field(),
+                method.returns_ty(),
+            ));
+
+            let mut setter_decl =
+                FnDecl::regular(vec![Param::mut_self()], Some(Type::simple_path("Self")));
+            let bound = PolyTraitRef::fn_(
+                method
+                    .args
+                    .iter()
+                    .map(|(_, ty)| Type::ref_(ty.clone()))
+                    .collect(),
+                method.output.clone(),
+            );
+            setter_decl.add_input(Param::ident(
+                "returns",
+                ImplTrait::new(vec![bound.into(), GenericBound::Outlives("static".into())]).into(),
+            ));
+            let boxed = Path::single("Box")
+                .chain("new")
+                .call1(Path::single("returns"));
+            let setter = Fn::simple(
+                format!("with_{}", method.returns_field()),
+                setter_decl,
+                Block::empty()
+                    .with_stmt(
+                        Expr::from(Path::single("self"))
+                            .field(method.returns_field())
+                            .assign(Path::single("Some").call1(boxed))
+                            .semi(),
+                    )
+                    .with_stmt(Expr::from(Path::single("self"))),
+            );
+            setters.add_item(Item::public(setter));
+
+            fn_.body = Some(method.body(&mock_ident));
+            trait_impl.add_item(fn_);
+        }
+
+        let mut marker = vec![];
+        for param in self.generics.params.iter() {
+            match param {
+                GenericParam::LifetimeParam(param) => marker.push(Type::Ref(Ref::new(
+                    Some(param.ident.clone()),
+                    MutTy::immut(Type::unit()),
+                ))),
+                GenericParam::TypeParam(param) => {
+                    marker.push(Type::simple_path(param.ident.clone()))
+                }
+                GenericParam::ConstParam(_) => {}
+            }
+        }
+        if !marker.is_empty() {
+            let marker = if marker.len() == 1 {
+                marker.remove(0)
+            } else {
+                Type::Tuple(marker)
+            };
+            let marker = Path::single("std")
+                .chain("marker")
+                .chain(PathSegment::generic(
+                    "PhantomData",
+                    vec![GenericArg::Type(marker)],
+                ));
+            mock.add_field(FieldDef::public("_marker", Type::Path(marker)));
+        }
+
+        vec![
+            Item::public(mock).with_attr(Attribute::derive(["Default"])),
+            Item::inherited(trait_impl),
+            Item::inherited(setters),
+        ]
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::*;
+
+/// A textual spec that does not describe a value of the node it was parsed as.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseError {
+    Visibility(std::string::String),
+    Abi(std::string::String),
+    BinOpKind(std::string::String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Visibility(input) => write!(
+                f,
+                "invalid visibility `{input}`, expected an empty string, `pub`, `pub(crate)`, `pub(super)`, `pub(self)` or `pub(in path)`"
+            ),
+            Self::Abi(input) => write!(
+                f,
+                "invalid ABI `{input}`, expected `extern`, `extern \"abi\"` or `\"abi\"`"
+            ),
+            Self::BinOpKind(input) => write!(
+                f,
+                "invalid binary operator `{input}`, expected an operator such as `+`, `==` or `&&`, or a compound assignment such as `+=`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && s != "_"
+}
+
+/// `a::b::c`
+fn parse_path(s: &str) -> Option<Path> {
+    let mut segments = s.split("::").map(str::trim);
+    let mut path = Path::single(segments.next().filter(|s| is_ident(s))?);
+    for segment in segments {
+        if !is_ident(segment) {
+            return None;
+        }
+        path = path.chain(segment);
+    }
+    Some(path)
+}
+
+/// `"abi"`
+fn parse_abi_lit(s: &str) -> Option<&str> {
+    s.strip_prefix('"')?
+        .strip_suffix('"')
+        .filter(|abi| !abi.contains(['"', '\\']))
+}
+
+/// Parses an empty string, `pub`, `pub(crate)`, `pub(super)`, `pub(self)` and `pub(in a::b)`.
+impl FromStr for Visibility {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseError::Visibility(s.to_string());
+        let Some(rest) = s.trim().strip_prefix("pub") else {
+            return if s.trim().is_empty() {
+                Ok(Self::Inherited)
+            } else {
+                Err(err())
+            };
+        };
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Ok(Self::Public);
+        }
+        let scope = rest
+            .strip_prefix('(')
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(err)?
+            .trim();
+        match scope {
+            "crate" => Ok(Self::crate_()),
+            "super" => Ok(Self::super_()),
+            "self" => Ok(Self::self_()),
+            _ => scope
+                .strip_prefix("in ")
+                .and_then(|path| parse_path(path.trim()))
+                .map(Self::in_path)
+                .ok_or_else(err),
+        }
+    }
+}
+
+/// Parses `extern "abi"`, `"abi"` and a bare `extern`, which is `extern "C"`.
+impl FromStr for Abi {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let lit = match s.strip_prefix("extern") {
+            Some("") => return Ok(Self::c()),
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim(),
+            Some(_) | None => s,
+        };
+        parse_abi_lit(lit)
+            .map(Self::new)
+            .ok_or_else(|| ParseError::Abi(s.to_string()))
+    }
+}
+
+/// Parses binary operators (`+`, `==`, `&&`, ..) as well as compound assignments (`+=`, `>>=`,
+/// ..), which map onto the operator they apply as in [`AssignOp`].
+impl FromStr for BinOpKind {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "+" | "+=" => Ok(Self::Add),
+            "-" | "-=" => Ok(Self::Sub),
+            "*" | "*=" => Ok(Self::Mul),
+            "/" | "/=" => Ok(Self::Div),
+            "%" | "%=" => Ok(Self::Rem),
+            "&&" => Ok(Self::LazyAnd),
+            "||" => Ok(Self::LazyOr),
+            "&" | "&=" => Ok(Self::BitAnd),
+            "|" | "|=" => Ok(Self::BitOr),
+            "^" | "^=" => Ok(Self::BitXor),
+            "<<" | "<<=" => Ok(Self::Shl),
+            ">>" | ">>=" => Ok(Self::Shr),
+            "==" => Ok(Self::Eq),
+            "<" => Ok(Self::Lt),
+            "<=" => Ok(Self::Le),
+            "!=" => Ok(Self::Ne),
+            ">=" => Ok(Self::Ge),
+            ">" => Ok(Self::Gt),
+            _ => Err(ParseError::BinOpKind(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::*;
+
+/// Which standard library crates the `*_in` constructors (e.g. [`Type::vec_in`],
+/// [`FormatArgs::format_in`]) refer to. The constructors without a policy, such as
+/// [`Type::vec`], refer to `std`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PathPolicy {
+    /// `std::..`, with prelude items such as `Vec` and `format!` left unqualified.
+    #[default]
+    Std,
+    /// `core::..`, for `#![no_std]` crates without an allocator. Allocating items such as `Vec`
+    /// don't exist, so their constructors return `None`.
+    Core,
+    /// `core::..` and `alloc::..`, for `#![no_std]` crates with `extern crate alloc`.
+    Alloc,
+}
+
+impl PathPolicy {
+    pub fn is_std(self) -> bool {
+        self == Self::Std
+    }
+
+    /// `std::{module}::{ident}` or `core::{module}::{ident}`
+    pub fn core_item(self, module: &str, ident: impl Into<PathSegment>) -> Path {
+        let root = if self.is_std() { "std" } else { "core" };
+        Path::single(root).chain(module).chain(ident)
+    }
+
+    /// `{ident}` (from the prelude or an import) or `alloc::{module}::{ident}`, or `None` under
+    /// [`PathPolicy::Core`].
+    pub fn alloc_item(self, module: &str, ident: impl Into<PathSegment>) -> Option<Path> {
+        match self {
+            Self::Std => Some(Path::single(ident)),
+            Self::Alloc => Some(Path::single("alloc").chain(module).chain(ident)),
+            Self::Core => None,
+        }
+    }
+
+    /// `std::{module}::{ident}` or `alloc::{module}::{ident}`, for allocating items outside the
+    /// prelude such as `Arc`, or `None` under [`PathPolicy::Core`].
+    pub fn alloc_item_qualified(self, module: &str, ident: impl Into<PathSegment>) -> Option<Path> {
+        let root = match self {
+            Self::Std => "std",
+            Self::Alloc => "alloc",
+            Self::Core => return None,
+        };
+        Some(Path::single(root).chain(module).chain(ident))
+    }
+
+    /// `{ident}` or `alloc::{ident}`, for the allocating macros `vec!` and `format!`, or `None`
+    /// under [`PathPolicy::Core`].
+    pub fn alloc_macro(self, ident: &str) -> Option<Path> {
+        match self {
+            Self::Std => Some(Path::single(ident)),
+            Self::Alloc => Some(Path::single("alloc").chain(ident)),
+            Self::Core => None,
+        }
+    }
+
+    /// `{ident}` (from an import), for items that only `std` has, such as `HashMap`, or `None`
+    /// under the other policies.
+    pub fn std_item(self, ident: impl Into<PathSegment>) -> Option<Path> {
+        self.is_std().then(|| Path::single(ident))
+    }
+}
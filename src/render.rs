@@ -0,0 +1,335 @@
+use std::fmt;
+
+use crate::visit::{self, Visit};
+use crate::*;
+
+/// A node state that has no valid source representation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RenderError {
+    /// [`Type::Err`], rendered as `<Err>`.
+    ErrType,
+    /// A literal of kind [`LitKind::Err`], with its symbol.
+    ErrLit(String),
+    /// `impl` or `dyn` without any bounds.
+    EmptyBounds,
+    /// A path without any segments.
+    EmptyPath,
+    /// `impl Trait` outside the parameter and return types of a function, e.g. in a `let`
+    /// type, a field or the generic arguments of an expression path (`f::<impl Trait>()`).
+    ImplTraitPosition,
+    /// A function that is both `const` and `async`, with its name.
+    ConstAsyncFn(String),
+    /// A function in an `extern` block that is `default`, `const` or `async`, has an ABI or a
+    /// body, with its name.
+    ForeignFnQualifier(String),
+    /// A type or const parameter declared twice by the same function, with its name.
+    DuplicateGenericParam(String),
+    /// [`Verbatim`] text that does not lex as tokens, e.g. with unbalanced delimiters or an
+    /// unterminated literal.
+    InvalidVerbatim(String),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ErrType => write!(f, "erroneous type"),
+            Self::ErrLit(symbol) => write!(f, "erroneous literal `{symbol}`"),
+            Self::EmptyBounds => write!(f, "`impl` or `dyn` type without bounds"),
+            Self::EmptyPath => write!(f, "path without segments"),
+            Self::ImplTraitPosition => write!(
+                f,
+                "`impl Trait` outside of function parameter and return types"
+            ),
+            Self::ConstAsyncFn(ident) => {
+                write!(f, "function `{ident}` is both `const` and `async`")
+            }
+            Self::ForeignFnQualifier(ident) => write!(
+                f,
+                "foreign function `{ident}` cannot be `default`, `const` or `async`, \
+                 have an ABI or a body"
+            ),
+            Self::DuplicateGenericParam(ident) => {
+                write!(f, "generic parameter `{ident}` is declared more than once")
+            }
+            Self::InvalidVerbatim(text) => write!(f, "verbatim text `{text}` is not valid tokens"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// Whether `text` lexes as tokens: its delimiters are balanced and matched, and its literals and
+/// block comments are terminated.
+pub(crate) fn is_token_text(text: &str) -> bool {
+    let mut chars = text.chars().peekable();
+    let mut delims = vec![];
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | '[' | '{' => delims.push(c),
+            ')' | ']' | '}' => {
+                let open = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if delims.pop() != Some(open) {
+                    return false;
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut depth = 1;
+                while depth > 0 {
+                    match (chars.next(), chars.peek()) {
+                        (Some('/'), Some('*')) => {
+                            chars.next();
+                            depth += 1;
+                        }
+                        (Some('*'), Some('/')) => {
+                            chars.next();
+                            depth -= 1;
+                        }
+                        (Some(_), _) => {}
+                        (None, _) => return false,
+                    }
+                }
+            }
+            '"' if !skip_str(&mut chars) => return false,
+            '\'' => {
+                let mut ahead = chars.clone();
+                match ahead.next() {
+                    // `'\n'`, `'\u{0}'`
+                    Some('\\') => {
+                        ahead.next();
+                        if !ahead.by_ref().any(|c| c == '\'') {
+                            return false;
+                        }
+                        chars = ahead;
+                    }
+                    // `'a'`
+                    Some(_) if ahead.peek() == Some(&'\'') => {
+                        ahead.next();
+                        chars = ahead;
+                    }
+                    // `'a`
+                    Some(c) if c.is_alphabetic() || c == '_' => {}
+                    _ => return false,
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut prefix = std::string::String::from(c);
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    prefix.push(c);
+                    chars.next();
+                }
+                if !matches!(&prefix[..], "r" | "br" | "cr") {
+                    continue;
+                }
+                // `r"..."`, `r#"..."#`, but also the raw identifier `r#ident`
+                let mut hashes = 0;
+                while chars.next_if_eq(&'#').is_some() {
+                    hashes += 1;
+                }
+                if chars.next_if_eq(&'"').is_none() {
+                    continue;
+                }
+                loop {
+                    match chars.next() {
+                        Some('"') => {
+                            let mut closing = 0;
+                            while closing < hashes && chars.next_if_eq(&'#').is_some() {
+                                closing += 1;
+                            }
+                            if closing == hashes {
+                                break;
+                            }
+                        }
+                        Some(_) => {}
+                        None => return false,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    delims.is_empty()
+}
+
+/// Skips the rest of a string literal after its opening `"`, returning whether it is terminated.
+fn skip_str(chars: &mut impl Iterator<Item = char>) -> bool {
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Collects the first [`RenderError`] found in a node.
+#[derive(Default)]
+struct Validator {
+    error: Option<RenderError>,
+    /// Whether the visited type is part of a function signature, where `impl Trait` is allowed.
+    in_signature: bool,
+    /// Whether the visited items are declared in an `extern` block.
+    in_extern_block: bool,
+}
+
+impl Validator {
+    fn fail(&mut self, error: RenderError) {
+        self.error.get_or_insert(error);
+    }
+
+    fn visit_verbatim(&mut self, verbatim: &Verbatim) {
+        if !is_token_text(&verbatim.0) {
+            self.fail(RenderError::InvalidVerbatim(verbatim.0.clone()));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Validator {
+    fn visit_item(&mut self, item: &Item) {
+        let in_extern_block = self.in_extern_block;
+        self.in_extern_block |= matches!(item.kind, ItemKind::ExternBlock(_));
+        if let ItemKind::Verbatim(verbatim) = &item.kind {
+            self.visit_verbatim(verbatim);
+        }
+        visit::walk_item(self, item);
+        self.in_extern_block = in_extern_block;
+    }
+
+    fn visit_fn(&mut self, fn_: &Fn) {
+        if fn_.is_const && fn_.is_async {
+            self.fail(RenderError::ConstAsyncFn(fn_.ident.clone()));
+        }
+        let qualified = fn_.is_const || fn_.is_async || fn_.abi.is_some() || fn_.body.is_some();
+        #[cfg(feature = "unstable-syntax")]
+        let qualified = qualified || fn_.is_default;
+        if self.in_extern_block && qualified {
+            self.fail(RenderError::ForeignFnQualifier(fn_.ident.clone()));
+        }
+        let mut idents = std::collections::HashSet::new();
+        for param in fn_.generics.params.iter() {
+            if let GenericParam::TypeParam(TypeParam { ident, .. })
+            | GenericParam::ConstParam(ConstParam { ident, .. }) = param
+            {
+                if !idents.insert(ident) {
+                    self.fail(RenderError::DuplicateGenericParam(ident.clone()));
+                }
+            }
+            self.visit_generic_param(param);
+        }
+        self.in_signature = true;
+        for param in fn_.fn_decl.inputs.iter() {
+            self.visit_pat(&param.pat);
+            self.visit_type(&param.ty);
+        }
+        if let Some(output) = &fn_.fn_decl.output {
+            self.visit_type(output);
+        }
+        self.in_signature = false;
+        visit::walk_where_clause(self, &fn_.generics.where_clause);
+        if let Some(body) = &fn_.body {
+            let in_extern_block = std::mem::replace(&mut self.in_extern_block, false);
+            self.visit_block(body);
+            self.in_extern_block = in_extern_block;
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Lit(Lit {
+            kind: LitKind::Err,
+            symbol,
+        }) = &expr.kind
+        {
+            self.fail(RenderError::ErrLit(symbol.clone()));
+        }
+        if let ExprKind::Verbatim(verbatim) = &expr.kind {
+            self.visit_verbatim(verbatim);
+        }
+        let in_signature = std::mem::replace(&mut self.in_signature, false);
+        visit::walk_expr(self, expr);
+        self.in_signature = in_signature;
+    }
+
+    fn visit_type(&mut self, ty: &Type) {
+        match ty {
+            Type::Err => self.fail(RenderError::ErrType),
+            Type::TraitObject(TraitObject { bounds, .. })
+            | Type::ImplTrait(ImplTrait { bounds, .. })
+                if bounds.is_empty() =>
+            {
+                self.fail(RenderError::EmptyBounds)
+            }
+            Type::ImplTrait(_) if !self.in_signature => self.fail(RenderError::ImplTraitPosition),
+            Type::Verbatim(verbatim) => self.visit_verbatim(verbatim),
+            _ => {}
+        }
+        // the parameters of a function pointer are not part of the enclosing signature
+        let in_signature = self.in_signature && !matches!(ty, Type::BareFn(_));
+        let in_signature = std::mem::replace(&mut self.in_signature, in_signature);
+        visit::walk_type(self, ty);
+        self.in_signature = in_signature;
+    }
+
+    fn visit_path(&mut self, path: &Path) {
+        if path.segments.is_empty() {
+            self.fail(RenderError::EmptyPath);
+        }
+        visit::walk_path(self, path);
+    }
+}
+
+/// Rendering that reports states with no valid source representation instead of printing
+/// placeholders for them.
+pub trait TryRender: fmt::Display {
+    fn validate(&self) -> Result<(), RenderError>;
+
+    fn try_render(&self) -> Result<std::string::String, RenderError> {
+        self.validate()?;
+        Ok(self.to_string())
+    }
+}
+
+macro_rules! impl_try_render {
+    ($($ty:ty => $visit:ident),* $(,)?) => {
+        $(
+            impl TryRender for $ty {
+                fn validate(&self) -> Result<(), RenderError> {
+                    let mut validator = Validator::default();
+                    validator.$visit(self);
+                    validator.error.map_or(Ok(()), Err)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_render!(
+    Crate => visit_crate,
+    Item => visit_item,
+    AssocItem => visit_assoc_item,
+    Block => visit_block,
+    Stmt => visit_stmt,
+    Expr => visit_expr,
+    Pat => visit_pat,
+);
+
+impl TryRender for Type {
+    /// A type on its own might be a parameter type, so `impl Trait` is accepted at its root.
+    fn validate(&self) -> Result<(), RenderError> {
+        let mut validator = Validator {
+            in_signature: true,
+            ..Default::default()
+        };
+        validator.visit_type(self);
+        validator.error.map_or(Ok(()), Err)
+    }
+}
@@ -0,0 +1,706 @@
+//! Conversion of rustdoc's JSON output into ruast items.
+//!
+//! The JSON is produced by `cargo +nightly rustdoc -- -Z unstable-options --output-format json`.
+//! Only signatures are available there, so the bodies of functions and methods and the values of constants and statics
+//! are `unimplemented!()` stubs: the output parses, but only the signatures are meaningful.
+//! Required trait methods and associated constants are kept without a body or value.
+//! Fields that rustdoc strips (private ones) are replaced with a single private `_private: ()` field, or a private `()`
+//! tuple field, so that the type does not look constructible with a literal.
+//! The input is read as a [`serde_json::Value`] rather than through a pinned `rustdoc-types` version,
+//! and both the current field names (`is_mutable`, `sig`, `path`, ...) and their older spellings are accepted.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::*;
+
+#[derive(Debug)]
+pub enum Error {
+    Json(serde_json::Error),
+    /// The JSON is valid but does not look like rustdoc output.
+    Format(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid JSON: {err}"),
+            Self::Format(msg) => write!(f, "invalid rustdoc JSON: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+/// Parses rustdoc JSON and converts the root module into a [`Crate`].
+pub fn from_json(json: &str) -> Result<Crate, Error> {
+    let doc = serde_json::from_str::<Value>(json)?;
+    from_value(&doc)
+}
+
+/// Converts the root module of already parsed rustdoc JSON into a [`Crate`].
+///
+/// Inherent and trait impls of the converted types are emitted after the type.
+/// Synthetic (auto trait) and blanket impls are skipped, as are macros and other items without a ruast counterpart.
+pub fn from_value(doc: &Value) -> Result<Crate, Error> {
+    let index = doc
+        .get("index")
+        .and_then(Value::as_object)
+        .ok_or(Error::Format("missing `index`"))?;
+    let root = doc.get("root").ok_or(Error::Format("missing `root`"))?;
+    let converter = Converter { index };
+    let module = converter
+        .lookup(root)
+        .and_then(|root| inner(root, "module"))
+        .ok_or(Error::Format("`root` is not a module in `index`"))?;
+    let mut krate = Crate::new();
+    krate.items = converter.module_items(module);
+    Ok(krate)
+}
+
+fn inner<'a>(item: &'a Value, kind: &str) -> Option<&'a Value> {
+    item.get("inner")?.get(kind)
+}
+
+/// Looks up the first of `keys` that is present, to accept renamed fields of older format versions.
+fn field<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().find_map(|key| value.get(key))
+}
+
+fn flag(value: &Value, keys: &[&str]) -> bool {
+    field(value, keys).and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn list(value: Option<&Value>) -> &[Value] {
+    value.and_then(Value::as_array).map_or(&[], Vec::as_slice)
+}
+
+fn strip_lifetime(lifetime: &str) -> &str {
+    lifetime.strip_prefix('\'').unwrap_or(lifetime)
+}
+
+fn path_from_str(path: &str) -> Path {
+    Path::new(
+        path.split("::")
+            .filter(|segment| !segment.is_empty())
+            .map(PathSegment::simple)
+            .collect(),
+    )
+}
+
+struct Converter<'a> {
+    index: &'a Map<std::string::String, Value>,
+}
+
+impl<'a> Converter<'a> {
+    /// Ids are strings in older format versions and integers in newer ones.
+    fn lookup(&self, id: &Value) -> Option<&'a Value> {
+        match id {
+            Value::String(id) => self.index.get(id),
+            Value::Number(id) => self.index.get(&id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn lookup_all<'b>(&'b self, ids: Option<&'b Value>) -> impl Iterator<Item = &'a Value> + 'b {
+        list(ids).iter().filter_map(|id| self.lookup(id))
+    }
+
+    fn module_items(&self, module: &Value) -> Vec<Item> {
+        let mut items = vec![];
+        for item in self.lookup_all(module.get("items")) {
+            items.extend(self.item(item));
+        }
+        items
+    }
+
+    /// The item itself, followed by its impls if it is a type.
+    fn item(&self, item: &Value) -> Vec<Item> {
+        let name = item.get("name").and_then(Value::as_str).unwrap_or("_");
+        let vis = visibility(item);
+        let Some((kind, data)) = item
+            .get("inner")
+            .and_then(Value::as_object)
+            .and_then(|inner| inner.iter().next())
+        else {
+            return vec![];
+        };
+        let kind = match &kind[..] {
+            "module" => ItemKind::Mod(Mod::Loaded(LoadedMod::new(name, self.module_items(data)))),
+            "function" => ItemKind::Fn(self.function(name, data, true)),
+            "struct" => {
+                let fields = self.struct_fields(data.get("kind"));
+                let mut def = StructDef::new(name, vec![], fields);
+                def.generics = self.generics(data.get("generics"));
+                ItemKind::StructDef(def)
+            }
+            "union" => {
+                let mut fields = self.fields(data.get("fields"));
+                if flag(data, &["has_stripped_fields", "fields_stripped"]) {
+                    fields.push(stripped_field(true));
+                }
+                let mut def = UnionDef::new(name, vec![], Fields::Struct(fields));
+                def.generics = self.generics(data.get("generics"));
+                ItemKind::UnionDef(def)
+            }
+            "enum" => {
+                let variants = self
+                    .lookup_all(data.get("variants"))
+                    .map(|variant| self.variant(variant))
+                    .collect();
+                let mut def = EnumDef::new(name, vec![], variants);
+                def.generics = self.generics(data.get("generics"));
+                ItemKind::EnumDef(def)
+            }
+            "trait" => ItemKind::TraitDef(self.trait_(name, data)),
+            "type_alias" | "typedef" => ItemKind::TyAlias(TyAlias {
+                #[cfg(feature = "unstable-syntax")]
+                is_default: false,
+                ident: name.into(),
+                generics: self.generics(data.get("generics")),
+                bounds: vec![],
+                ty: data.get("type").map(|ty| self.ty(ty)),
+            }),
+            "constant" => ItemKind::ConstItem(ConstItem::new(
+                name,
+                self.field_ty(data),
+                Some(Expr::unimplemented()),
+            )),
+            "static" => ItemKind::StaticItem(StaticItem {
+                safety: if flag(data, &["is_unsafe"]) {
+                    Safety::Unsafe
+                } else {
+                    Safety::Default
+                },
+                mutability: if flag(data, &["is_mutable", "mutable"]) {
+                    Mutability::Mut
+                } else {
+                    Mutability::Not
+                },
+                ident: name.into(),
+                ty: self.field_ty(data),
+                expr: Some(Expr::unimplemented()),
+            }),
+            "use" | "import" => ItemKind::Use(use_(data)),
+            _ => return vec![],
+        };
+        let mut items = vec![Item::new(vis, kind)];
+        for impl_ in self.lookup_all(data.get("impls")) {
+            if let Some(impl_) = inner(impl_, "impl").and_then(|impl_| self.impl_(impl_)) {
+                items.push(Item::inherited(impl_));
+            }
+        }
+        items
+    }
+
+    fn field_ty(&self, data: &Value) -> Type {
+        field(data, &["type"]).map_or(Type::Err, |ty| self.ty(ty))
+    }
+
+    /// The body is a stub if the function has one, which is always the case outside of traits.
+    fn function(&self, name: &str, data: &Value, has_body: bool) -> Fn {
+        let header = data.get("header").unwrap_or(&Value::Null);
+        let sig = field(data, &["sig", "decl"]).unwrap_or(&Value::Null);
+        let inputs = list(sig.get("inputs"))
+            .iter()
+            .filter_map(|input| {
+                let [name, ty] = input.as_array()?.as_slice() else {
+                    return None;
+                };
+                Some(self.param(name.as_str().unwrap_or("_"), ty))
+            })
+            .collect();
+        let output = sig
+            .get("output")
+            .filter(|output| !output.is_null())
+            .map(|output| self.ty(output));
+        let body = has_body.then(|| Block::single(Expr::unimplemented()));
+        let mut fn_ = Fn::new(
+            flag(header, &["is_unsafe", "unsafe_", "unsafe"]),
+            flag(header, &["is_const", "const_", "const"]),
+            flag(header, &["is_async", "async_", "async"]),
+            header.get("abi").and_then(abi),
+            name,
+            vec![],
+            FnDecl::new(inputs, output, flag(sig, &["is_c_variadic", "c_variadic"])),
+            body,
+        );
+        fn_.generics = self.generics(data.get("generics"));
+        fn_
+    }
+
+    fn param(&self, name: &str, ty: &Value) -> Param {
+        if name != "self" {
+            return Param::ident(name, self.ty(ty));
+        }
+        let is_self = |ty: &Value| ty.get("generic").and_then(Value::as_str) == Some("Self");
+        if is_self(ty) {
+            return Param::slf();
+        }
+        if let Some(ref_) = ty.get("borrowed_ref") {
+            if ref_.get("type").is_some_and(is_self) {
+                let mutable = flag(ref_, &["is_mutable", "mutable"]);
+                return match ref_.get("lifetime").and_then(Value::as_str) {
+                    Some(lifetime) if mutable => {
+                        Param::ref_mut_self_with_lifetime(strip_lifetime(lifetime))
+                    }
+                    Some(lifetime) => Param::ref_self_with_lifetime(strip_lifetime(lifetime)),
+                    None if mutable => Param::ref_mut_self(),
+                    None => Param::ref_self(),
+                };
+            }
+        }
+        Param::ident(name, self.ty(ty))
+    }
+
+    fn struct_fields(&self, kind: Option<&Value>) -> Fields {
+        let Some(kind) = kind else {
+            return Fields::Unit;
+        };
+        if let Some(fields) = kind.get("tuple") {
+            let mut tuple = self.fields(Some(fields));
+            if list(Some(fields)).iter().any(Value::is_null) {
+                tuple.push(stripped_field(false));
+            }
+            Fields::Tuple(tuple)
+        } else if let Some(plain) = field(kind, &["plain", "struct"]) {
+            let mut fields = self.fields(plain.get("fields"));
+            if flag(plain, &["has_stripped_fields", "fields_stripped"]) {
+                fields.push(stripped_field(true));
+            }
+            Fields::Struct(fields)
+        } else {
+            Fields::Unit
+        }
+    }
+
+    /// Stripped (private) fields are `null` ids and are skipped, see [`stripped_field`].
+    fn fields(&self, ids: Option<&Value>) -> Vec<FieldDef> {
+        self.lookup_all(ids)
+            .map(|field| {
+                let ty = inner(field, "struct_field").map_or(Type::Err, |ty| self.ty(ty));
+                let name = field.get("name").and_then(Value::as_str);
+                // tuple fields are named by their position
+                let name = name.filter(|name| !name.starts_with(|c: char| c.is_ascii_digit()));
+                FieldDef::new(visibility(field), name, ty)
+            })
+            .collect()
+    }
+
+    fn variant(&self, item: &Value) -> Variant {
+        let name = item.get("name").and_then(Value::as_str).unwrap_or("_");
+        let data = inner(item, "variant").unwrap_or(&Value::Null);
+        let fields = self.struct_fields(data.get("kind"));
+        let mut variant = Variant::inherited(name, fields);
+        if let Some(value) = data
+            .get("discriminant")
+            .and_then(|discriminant| discriminant.get("value"))
+            .and_then(Value::as_str)
+        {
+            variant.set_discriminant(Lit::int(value));
+        }
+        variant
+    }
+
+    fn trait_(&self, name: &str, data: &Value) -> TraitDef {
+        let supertraits = list(data.get("bounds"))
+            .iter()
+            .filter_map(|bound| match self.bound(bound)? {
+                GenericBound::Trait(poly) => Some(Type::Path(poly.trait_ref)),
+                _ => None,
+            })
+            .collect();
+        let items = self
+            .lookup_all(data.get("items"))
+            .filter_map(|item| self.assoc_item(item, true))
+            .collect();
+        let mut trait_def =
+            TraitDef::new(name, vec![], supertraits, items).with_unsafe(flag(data, &["is_unsafe"]));
+        trait_def.generics = self.generics(data.get("generics"));
+        #[cfg(feature = "unstable-syntax")]
+        let trait_def = trait_def.with_auto(flag(data, &["is_auto"]));
+        trait_def
+    }
+
+    /// Trait items only get a stub body or value if they have a default.
+    fn assoc_item(&self, item: &Value, in_trait: bool) -> Option<AssocItem> {
+        let name = item.get("name").and_then(Value::as_str)?;
+        let (kind, data) = item.get("inner")?.as_object()?.iter().next()?;
+        let kind = match &kind[..] {
+            "function" => {
+                let has_body = !in_trait || flag(data, &["has_body"]);
+                AssocItemKind::Fn(self.function(name, data, has_body))
+            }
+            "assoc_const" => {
+                let has_value = !in_trait
+                    || field(data, &["value", "default"]).is_some_and(|value| !value.is_null());
+                let value = has_value.then(Expr::unimplemented);
+                AssocItemKind::ConstItem(ConstItem::new(name, self.field_ty(data), value))
+            }
+            "assoc_type" => AssocItemKind::TyAlias(TyAlias {
+                #[cfg(feature = "unstable-syntax")]
+                is_default: false,
+                ident: name.into(),
+                generics: self.generics(data.get("generics")),
+                bounds: self.bounds(data.get("bounds")),
+                ty: field(data, &["type", "default"])
+                    .filter(|ty| !ty.is_null())
+                    .map(|ty| self.ty(ty)),
+            }),
+            _ => return None,
+        };
+        Some(Item::new(visibility(item), kind))
+    }
+
+    fn impl_(&self, data: &Value) -> Option<Impl> {
+        let blanket = data.get("blanket_impl").is_some_and(|ty| !ty.is_null());
+        if flag(data, &["is_synthetic", "synthetic"]) || blanket {
+            return None;
+        }
+        let of_trait = data
+            .get("trait")
+            .filter(|trait_| !trait_.is_null())
+            .map(|trait_| Type::Path(self.path(trait_)));
+        let items = self
+            .lookup_all(data.get("items"))
+            .filter_map(|item| self.assoc_item(item, false))
+            .collect();
+        let self_ty = data.get("for").map_or(Type::Err, |ty| self.ty(ty));
+        let mut impl_ = Impl::new(vec![], of_trait, self_ty, None, items)
+            .with_unsafe(flag(data, &["is_unsafe"]));
+        impl_.generics = self.generics(data.get("generics"));
+        Some(impl_)
+    }
+
+    /// Bounds from `where T: ..` predicates are moved onto the parameter `T` if it is declared here,
+    /// other predicates (`Vec<T>: Clone`, `for<'a> F: Fn(&'a T)`, `Self: Sized`, `'a: 'b`) stay in the where clause.
+    fn generics(&self, generics: Option<&Value>) -> Generics {
+        let Some(generics) = generics else {
+            return Generics::default();
+        };
+        let mut params = vec![];
+        for param in list(generics.get("params")) {
+            let name = param.get("name").and_then(Value::as_str).unwrap_or("_");
+            let Some(kind) = param.get("kind") else {
+                continue;
+            };
+            if let Some(lifetime) = kind.get("lifetime") {
+                let bounds = list(lifetime.get("outlives"))
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|bound| strip_lifetime(bound).into())
+                    .collect();
+                params.push(LifetimeParam::new(strip_lifetime(name), bounds).into());
+            } else if let Some(ty) = kind.get("type") {
+                // `impl Trait` in argument position
+                if flag(ty, &["is_synthetic", "synthetic"]) {
+                    continue;
+                }
+                let mut param = TypeParam::new(name, self.bounds(ty.get("bounds")));
+                if let Some(default) = ty.get("default").filter(|default| !default.is_null()) {
+                    param.set_default(self.ty(default));
+                }
+                params.push(param.into());
+            } else if let Some(const_) = kind.get("const") {
+                let mut param = ConstParam::new(name, self.field_ty(const_));
+                if let Some(default) = const_.get("default").and_then(Value::as_str) {
+                    param.set_default(Verbatim::new(default));
+                }
+                params.push(param.into());
+            }
+        }
+        let mut where_clause = WhereClause::default();
+        for predicate in list(generics.get("where_predicates")) {
+            if let Some(region) = predicate.get("region_predicate") {
+                let lifetime = region
+                    .get("lifetime")
+                    .and_then(Value::as_str)
+                    .unwrap_or("_");
+                let bounds = list(region.get("bounds"))
+                    .iter()
+                    .filter_map(|bound| bound.get("outlives").and_then(Value::as_str))
+                    .map(|bound| strip_lifetime(bound).into())
+                    .collect();
+                where_clause.add_predicate(WherePredicate::Lifetime(PredicateLifetime::new(
+                    strip_lifetime(lifetime),
+                    bounds,
+                )));
+                continue;
+            }
+            let Some(predicate) = predicate.get("bound_predicate") else {
+                continue;
+            };
+            let bounds = self.bounds(predicate.get("bounds"));
+            let bound_generic_params = self.generics_list(predicate.get("generic_params"));
+            let name = predicate
+                .get("type")
+                .and_then(|ty| ty.get("generic"))
+                .and_then(Value::as_str);
+            let param = params.iter_mut().find_map(|param| match param {
+                GenericParam::TypeParam(param) if Some(&*param.ident) == name => Some(param),
+                _ => None,
+            });
+            match param {
+                Some(param) if bound_generic_params.is_empty() => param.bounds.extend(bounds),
+                _ => {
+                    let ty = predicate.get("type").map_or(Type::Err, |ty| self.ty(ty));
+                    let mut predicate = PredicateType::new(ty, bounds);
+                    predicate.bound_generic_params = bound_generic_params;
+                    where_clause.add_predicate(WherePredicate::Type(predicate));
+                }
+            }
+        }
+        Generics {
+            params,
+            where_clause,
+        }
+    }
+
+    fn bounds(&self, bounds: Option<&Value>) -> Vec<GenericBound> {
+        list(bounds)
+            .iter()
+            .filter_map(|bound| self.bound(bound))
+            .collect()
+    }
+
+    fn bound(&self, bound: &Value) -> Option<GenericBound> {
+        if let Some(lifetime) = bound.get("outlives").and_then(Value::as_str) {
+            return Some(GenericBound::Outlives(strip_lifetime(lifetime).into()));
+        }
+        let trait_bound = bound.get("trait_bound")?;
+        let trait_ = trait_bound.get("trait")?;
+        let bound_generic_params = self.generics_list(trait_bound.get("generic_params"));
+        if let Some(parenthesized) = trait_
+            .get("args")
+            .and_then(|args| args.get("parenthesized"))
+        {
+            let ParenthesizedArgs { inputs, output } = self.parenthesized_args(parenthesized);
+            let name = field(trait_, &["path", "name"]).and_then(Value::as_str)?;
+            let mut fn_trait =
+                PolyTraitRef::parenthesized(path_from_str(name), inputs, output.map(|ty| *ty));
+            fn_trait.bound_generic_params = bound_generic_params;
+            return Some(fn_trait.into());
+        }
+        let modifier = match trait_bound.get("modifier").and_then(Value::as_str) {
+            Some("maybe") => TraitBoundModifier::Maybe,
+            #[cfg(feature = "unstable-syntax")]
+            Some("maybe_const") => TraitBoundModifier::MaybeConst,
+            _ => TraitBoundModifier::None,
+        };
+        let trait_ref = PolyTraitRef::new(bound_generic_params, self.path(trait_));
+        Some(trait_ref.with_modifier(modifier).into())
+    }
+
+    fn generics_list(&self, params: Option<&Value>) -> Vec<GenericParam> {
+        let generics = params.map(|params| serde_json::json!({ "params": params }));
+        self.generics(generics.as_ref()).params
+    }
+
+    fn path(&self, path: &Value) -> Path {
+        let name = field(path, &["path", "name"])
+            .and_then(Value::as_str)
+            .unwrap_or("_");
+        let mut path_ = path_from_str(name);
+        let args = path.get("args");
+        let args = match args.and_then(|args| args.get("parenthesized")) {
+            Some(parenthesized) => Some(self.parenthesized_args(parenthesized).into()),
+            None => args
+                .and_then(|args| args.get("angle_bracketed"))
+                .map(|args| self.generic_args(args))
+                .filter(|args| !args.is_empty())
+                .map(GenericArgs::from),
+        };
+        if let (Some(args), Some(last)) = (args, path_.segments.last_mut()) {
+            last.args = Some(args);
+        }
+        path_
+    }
+
+    fn parenthesized_args(&self, args: &Value) -> ParenthesizedArgs {
+        let inputs = list(args.get("inputs"))
+            .iter()
+            .map(|ty| self.ty(ty))
+            .collect();
+        let output = args
+            .get("output")
+            .filter(|output| !output.is_null())
+            .map(|output| self.ty(output));
+        ParenthesizedArgs::new(inputs, output)
+    }
+
+    fn generic_args(&self, args: &Value) -> Vec<GenericArg> {
+        let mut generic_args = vec![];
+        for arg in list(args.get("args")) {
+            if let Some(lifetime) = arg.get("lifetime").and_then(Value::as_str) {
+                generic_args.push(GenericArg::Lifetime(strip_lifetime(lifetime).into()));
+            } else if let Some(ty) = arg.get("type") {
+                generic_args.push(GenericArg::Type(self.ty(ty)));
+            } else if let Some(expr) = arg.get("const").and_then(|c| c.get("expr")) {
+                let expr = expr.as_str().unwrap_or("_");
+                generic_args.push(GenericArg::Const(Const(Verbatim::new(expr).into())));
+            } else if arg.as_str() == Some("infer") {
+                generic_args.push(GenericArg::Type(Type::Infer));
+            }
+        }
+        for constraint in list(field(args, &["constraints", "bindings"])) {
+            let name = constraint
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("_");
+            let Some(ty) = constraint
+                .get("binding")
+                .and_then(|binding| binding.get("equality"))
+                .and_then(|equality| equality.get("type"))
+            else {
+                continue;
+            };
+            generic_args.push(AssocConstraint::new(name, self.ty(ty)).into());
+        }
+        generic_args
+    }
+
+    fn ty(&self, ty: &Value) -> Type {
+        if ty.as_str() == Some("infer") {
+            return Type::Infer;
+        }
+        let Some((kind, data)) = ty.as_object().and_then(|ty| ty.iter().next()) else {
+            return Type::Err;
+        };
+        match &kind[..] {
+            "resolved_path" => Type::Path(self.path(data)),
+            "generic" | "primitive" => Type::simple_path(data.as_str().unwrap_or("_")),
+            "tuple" => Type::Tuple(list(Some(data)).iter().map(|ty| self.ty(ty)).collect()),
+            "slice" => Type::Slice(Box::new(self.ty(data))),
+            "array" => {
+                let len = data.get("len").and_then(Value::as_str).unwrap_or("_");
+                Type::Array(
+                    Box::new(self.field_ty(data)),
+                    Box::new(Const(Verbatim::new(len).into())),
+                )
+            }
+            "pat" => self.field_ty(data),
+            "never" => Type::Never,
+            "raw_pointer" => {
+                let kind = if flag(data, &["is_mutable", "mutable"]) {
+                    PtrKind::Mut
+                } else {
+                    PtrKind::Const
+                };
+                Type::Ptr(Ptr::new(kind, self.field_ty(data)))
+            }
+            "borrowed_ref" => {
+                let lifetime = data
+                    .get("lifetime")
+                    .and_then(Value::as_str)
+                    .map(strip_lifetime);
+                let ty = self.field_ty(data);
+                let ty = if flag(data, &["is_mutable", "mutable"]) {
+                    MutTy::mut_(ty)
+                } else {
+                    MutTy::immut(ty)
+                };
+                Type::Ref(Ref::new(lifetime, ty))
+            }
+            "impl_trait" => Type::ImplTrait(ImplTrait::new(self.bounds(Some(data)))),
+            "dyn_trait" => {
+                let mut bounds = list(data.get("traits"))
+                    .iter()
+                    .filter_map(|poly| {
+                        let bound = serde_json::json!({ "trait_bound": poly });
+                        self.bound(&bound)
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(lifetime) = data.get("lifetime").and_then(Value::as_str) {
+                    bounds.push(GenericBound::Outlives(strip_lifetime(lifetime).into()));
+                }
+                Type::TraitObject(TraitObject::dyn_(bounds))
+            }
+            "function_pointer" => {
+                let function = self.function("_", data, false);
+                let output = function.fn_decl.output.unwrap_or(Type::unit());
+                let generic_params = self.generics_list(data.get("generic_params"));
+                BareFn::new(
+                    generic_params,
+                    function.fn_decl.inputs,
+                    output,
+                    function.abi,
+                    function.is_unsafe,
+                )
+                .with_variadic(function.fn_decl.is_variadic)
+                .into()
+            }
+            "qualified_path" => {
+                let name = data.get("name").and_then(Value::as_str).unwrap_or("_");
+                let self_ty = data.get("self_type").map_or(Type::Err, |ty| self.ty(ty));
+                let trait_ = data
+                    .get("trait")
+                    .filter(|trait_| !trait_.is_null())
+                    .map(|trait_| self.path(trait_));
+                Type::QPath(QPath::new(self_ty, trait_, name))
+            }
+            _ => Type::Err,
+        }
+    }
+}
+
+fn visibility(item: &Value) -> Visibility {
+    match item.get("visibility") {
+        Some(Value::String(vis)) if vis == "public" => Visibility::Public,
+        Some(Value::String(vis)) if vis == "crate" => Visibility::crate_(),
+        Some(Value::Object(vis)) => match vis
+            .get("restricted")
+            .and_then(|restricted| restricted.get("path"))
+            .and_then(Value::as_str)
+        {
+            Some("super" | "::super") => Visibility::super_(),
+            Some(path) => Visibility::in_path(path_from_str(path)),
+            None => Visibility::Inherited,
+        },
+        _ => Visibility::Inherited,
+    }
+}
+
+/// `"Rust"` is the default ABI and is not written out.
+fn abi(abi: &Value) -> Option<String> {
+    let abi = match abi {
+        Value::String(abi) if abi == "Rust" => return None,
+        Value::String(abi) => abi.to_lowercase(),
+        Value::Object(abi) => {
+            let (name, data) = abi.iter().next()?;
+            match &name[..] {
+                "C" => "C".into(),
+                "Other" => data.as_str()?.into(),
+                _ => name.to_lowercase(),
+            }
+        }
+        _ => return None,
+    };
+    Some(abi.as_str().into())
+}
+
+fn use_(data: &Value) -> Use {
+    let source = data.get("source").and_then(Value::as_str).unwrap_or("_");
+    let path = path_from_str(source);
+    if flag(data, &["is_glob", "glob"]) {
+        return Use(path.chain_use_glob());
+    }
+    let name = data.get("name").and_then(Value::as_str);
+    let last = path.segments.last().map(|segment| &*segment.ident);
+    match name {
+        Some(name) if Some(name) != last => Use(path.chain_use_rename(name)),
+        _ => Use::from(path),
+    }
+}
+
+/// Stands in for the fields rustdoc strips, so that the type cannot be built with a literal.
+fn stripped_field(named: bool) -> FieldDef {
+    let name = named.then_some("_private");
+    FieldDef::new(Visibility::Inherited, name, Type::unit())
+}
@@ -0,0 +1,219 @@
+use std::hash::{Hash, Hasher};
+
+use crate::expr::{derive_attr, derive_paths};
+use crate::visit::{self, VisitMut};
+use crate::*;
+
+/// Built-in attributes whose relative order does not change the meaning of an item.
+const INERT_ATTRIBUTES: [&str; 22] = [
+    "allow",
+    "automatically_derived",
+    "cfg",
+    "cold",
+    "deny",
+    "deprecated",
+    "derive",
+    "export_name",
+    "expect",
+    "forbid",
+    "ignore",
+    "inline",
+    "link_name",
+    "must_use",
+    "no_mangle",
+    "non_exhaustive",
+    "repr",
+    "should_panic",
+    "test",
+    "track_caller",
+    "used",
+    "warn",
+];
+
+fn is_inert(item: &AttributeItem) -> bool {
+    match &item.path.segments[..] {
+        [segment] => INERT_ATTRIBUTES.contains(&&*segment.ident),
+        _ => false,
+    }
+}
+
+fn is_doc(item: &AttributeItem) -> bool {
+    item.path == Path::single("doc")
+}
+
+/// Drops `#[doc]`s, merges and sorts derives, and sorts inert attributes after the others,
+/// whose relative order is kept.
+fn normalize_attr_items(attrs: &mut Vec<AttributeItem>) {
+    let mut derives = vec![];
+    let mut inert = vec![];
+    let mut others = vec![];
+    for attr in attrs.drain(..) {
+        if is_doc(&attr) {
+            continue;
+        }
+        if let Some(paths) = derive_paths(&attr) {
+            derives.extend(paths);
+        } else if is_inert(&attr) {
+            inert.push(attr);
+        } else {
+            others.push(attr);
+        }
+    }
+    if !derives.is_empty() {
+        derives.sort_by_cached_key(|path| path.to_string());
+        derives.dedup();
+        inert.push(derive_attr(derives));
+    }
+    inert.sort_by_cached_key(|attr| attr.to_string());
+    attrs.extend(others);
+    attrs.extend(inert);
+}
+
+fn normalize_attrs(attrs: &mut Vec<Attribute>) {
+    let mut items = attrs
+        .drain(..)
+        .filter_map(|attr| match attr.kind {
+            AttrKind::Normal(item) => Some(item),
+            AttrKind::DocComment(_) => None,
+        })
+        .collect();
+    normalize_attr_items(&mut items);
+    attrs.extend(items.into_iter().map(Attribute::normal));
+}
+
+fn normalize_fields(fields: &mut Fields) {
+    if let Fields::Tuple(fields) | Fields::Struct(fields) = fields {
+        for field in fields.iter_mut() {
+            normalize_attrs(&mut field.attrs);
+        }
+    }
+}
+
+/// Rewrites a node into the canonical form compared by [`SemanticEq`].
+struct Normalizer;
+
+impl VisitMut for Normalizer {
+    fn visit_crate_mut(&mut self, krate: &mut Crate) {
+        normalize_attrs(&mut krate.attrs);
+        visit::walk_crate_mut(self, krate);
+    }
+
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        normalize_attrs(&mut item.attrs);
+        match &mut item.kind {
+            ItemKind::StructDef(def) => normalize_fields(&mut def.fields),
+            ItemKind::UnionDef(def) => normalize_fields(&mut def.fields),
+            ItemKind::EnumDef(def) => {
+                for variant in def.variants.iter_mut() {
+                    normalize_attrs(&mut variant.attrs);
+                    normalize_fields(&mut variant.fields);
+                }
+            }
+            _ => {}
+        }
+        visit::walk_item_mut(self, item);
+    }
+
+    fn visit_assoc_item_mut(&mut self, item: &mut AssocItem) {
+        normalize_attrs(&mut item.attrs);
+        visit::walk_assoc_item_mut(self, item);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit::walk_expr_mut(self, expr);
+        if let ExprKind::Paren(Paren(inner)) = &mut expr.kind {
+            let mut inner = std::mem::replace(&mut **inner, Tuple::new(vec![]).into());
+            inner.attrs.append(&mut expr.attrs);
+            *expr = inner;
+        }
+        if let ExprKind::Match(match_) = &mut expr.kind {
+            for arm in match_.arms.iter_mut() {
+                normalize_attr_items(&mut arm.attrs);
+            }
+        }
+        normalize_attr_items(&mut expr.attrs);
+    }
+
+    fn visit_pat_mut(&mut self, pat: &mut Pat) {
+        visit::walk_pat_mut(self, pat);
+        if let Pat::Paren(inner) = pat {
+            *pat = std::mem::replace(&mut **inner, Pat::Wild);
+        }
+    }
+}
+
+/// Nodes that can be brought into the canonical form compared by [`SemanticEq`].
+pub trait Normalize: Clone {
+    fn normalize(&mut self);
+
+    fn normalized(&self) -> Self {
+        let mut node = self.clone();
+        node.normalize();
+        node
+    }
+}
+
+macro_rules! impl_normalize {
+    ($($ty:ty => $visit:ident),* $(,)?) => {
+        $(
+            impl Normalize for $ty {
+                fn normalize(&mut self) {
+                    Normalizer.$visit(self);
+                }
+            }
+        )*
+    };
+}
+
+impl_normalize!(
+    Crate => visit_crate_mut,
+    Item => visit_item_mut,
+    AssocItem => visit_assoc_item_mut,
+    Block => visit_block_mut,
+    Stmt => visit_stmt_mut,
+    Expr => visit_expr_mut,
+    Pat => visit_pat_mut,
+    Type => visit_type_mut,
+);
+
+/// Equality that ignores cosmetic differences: doc comments, explicit parentheses,
+/// the order of derives and the order of built-in attributes.
+pub trait SemanticEq {
+    fn semantic_eq(&self, other: &Self) -> bool;
+}
+
+/// A hash consistent with [`SemanticEq`].
+pub trait SemanticHash {
+    fn semantic_hash<H: Hasher>(&self, state: &mut H);
+}
+
+impl<T: Normalize + PartialEq> SemanticEq for T {
+    fn semantic_eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl<T: Normalize + Hash> SemanticHash for T {
+    fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+/// Compares and hashes the wrapped node with [`SemanticEq`] and [`SemanticHash`],
+/// e.g. to key a `HashMap` on nodes.
+#[derive(Debug, Clone)]
+pub struct Semantic<T>(pub T);
+
+impl<T: SemanticEq> PartialEq for Semantic<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.semantic_eq(&other.0)
+    }
+}
+
+impl<T: SemanticEq> Eq for Semantic<T> {}
+
+impl<T: SemanticHash> Hash for Semantic<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.semantic_hash(state);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::string::String;
+
+use crate::*;
+
+/// `#[serde(..)]`, a container, variant or field attribute of serde, e.g.
+/// `#[serde(rename = "x", default, skip_serializing_if = "Option::is_none")]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SerdeAttr {
+    /// `name` or `name = "value"`, in order.
+    pub args: Vec<(String, Option<String>)>,
+}
+
+impl fmt::Display for SerdeAttr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Attribute::from(self.clone()).fmt(f)
+    }
+}
+
+impl From<SerdeAttr> for Meta {
+    fn from(value: SerdeAttr) -> Self {
+        let nested = value.args.into_iter().map(|(name, value)| match value {
+            Some(value) => Meta::name_value(name, Lit::str(value)),
+            None => Meta::path(name),
+        });
+        Meta::list("serde", nested)
+    }
+}
+
+impl From<SerdeAttr> for Attribute {
+    fn from(value: SerdeAttr) -> Self {
+        Meta::from(value).into()
+    }
+}
+
+impl From<SerdeAttr> for TokenStream {
+    fn from(value: SerdeAttr) -> Self {
+        TokenStream::from(Attribute::from(value))
+    }
+}
+
+impl SerdeAttr {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `name`
+    pub fn add_flag(&mut self, name: impl Into<String>) {
+        self.args.push((name.into(), None));
+    }
+
+    pub fn with_flag(mut self, name: impl Into<String>) -> Self {
+        self.add_flag(name);
+        self
+    }
+
+    /// `name = "value"`, with `value` escaped as a string literal.
+    pub fn add_value(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.args.push((name.into(), Some(value.into())));
+    }
+
+    pub fn with_value(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.add_value(name, value);
+        self
+    }
+
+    /// `rename = "name"`
+    pub fn with_rename(self, name: impl Into<String>) -> Self {
+        self.with_value("rename", name)
+    }
+
+    /// `rename_all = "case"`, e.g. `"camelCase"`.
+    pub fn with_rename_all(self, case: impl Into<String>) -> Self {
+        self.with_value("rename_all", case)
+    }
+
+    /// `alias = "name"`
+    pub fn with_alias(self, name: impl Into<String>) -> Self {
+        self.with_value("alias", name)
+    }
+
+    /// `default`
+    pub fn with_default(self) -> Self {
+        self.with_flag("default")
+    }
+
+    /// `default = "path"`, a function returning the default value.
+    pub fn with_default_fn(self, path: impl Into<String>) -> Self {
+        self.with_value("default", path)
+    }
+
+    /// `skip`
+    pub fn with_skip(self) -> Self {
+        self.with_flag("skip")
+    }
+
+    /// `skip_serializing_if = "path"`, e.g. `"Option::is_none"`.
+    pub fn with_skip_serializing_if(self, path: impl Into<String>) -> Self {
+        self.with_value("skip_serializing_if", path)
+    }
+
+    /// `flatten`
+    pub fn with_flatten(self) -> Self {
+        self.with_flag("flatten")
+    }
+
+    /// `tag = "name"`, for internally tagged enums.
+    pub fn with_tag(self, name: impl Into<String>) -> Self {
+        self.with_value("tag", name)
+    }
+
+    /// `untagged`
+    pub fn with_untagged(self) -> Self {
+        self.with_flag("untagged")
+    }
+
+    /// `deny_unknown_fields`
+    pub fn with_deny_unknown_fields(self) -> Self {
+        self.with_flag("deny_unknown_fields")
+    }
+}
@@ -4,14 +4,15 @@ use std::hash::Hash;
 use std::ops::{Index, IndexMut};
 
 use crate::expr::{
-    Async, Attribute, Call, ConstBlock, DelimArgs, Expr, MacCall, MethodCall, Path, Range,
-    TryBlock, UnsafeBlock,
+    push_label, AddrOf, Array, Async, Attribute, AttributeItem, BorrowKind, Call, ConstBlock,
+    DelimArgs, Expr, ExprKind, Label, MacCall, MethodCall, Paren, Path, Range, TryBlock, Tuple,
+    Unary, UnaryOpKind, UnsafeBlock, Verbatim,
 };
 use crate::token::{BinOpToken, Delimiter, KeywordToken, Token, TokenStream};
-use crate::ty::Type;
+use crate::ty::{fmt_binder, push_binder, GenericBound, ImplTrait, MutTy, Ref, Type};
 use crate::{
     impl_display_for_enum, impl_hasitem_methods, impl_obvious_conversion, ForLoop, GenericParam,
-    HasPrecedence, Lit, Mutability, OperatorPrecedence,
+    HasPrecedence, Lit, Mutability, OperatorPrecedence, Safety,
 };
 
 #[cfg(feature = "fuzzing")]
@@ -159,7 +160,7 @@ pub struct Local {
 
 impl fmt::Display for Local {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "let {pat}", pat = self.pat)?;
+        write!(f, "let {pat}", pat = NoTopAlt(&self.pat))?;
         if let Some(ty) = &self.ty {
             write!(f, ": {ty}")?;
         }
@@ -172,11 +173,11 @@ impl From<Local> for TokenStream {
         let mut ts = TokenStream::new();
         ts.push(Token::Keyword(KeywordToken::Let));
         if let Some(ty) = value.ty {
-            ts.extend(TokenStream::from(value.pat).into_joint());
+            ts.extend(value.pat.into_tokens_no_top_alt().into_joint());
             ts.push(Token::Colon);
             ts.extend(TokenStream::from(ty));
         } else {
-            ts.extend(TokenStream::from(value.pat));
+            ts.extend(value.pat.into_tokens_no_top_alt());
         }
         ts.extend(TokenStream::from(value.kind).into_joint());
         ts.push(Token::Semi);
@@ -269,11 +270,14 @@ impl From<LocalKind> for TokenStream {
     }
 }
 
+/// `ident: pat` or `pat` (shorthand)
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PatField {
     pub ident: String,
     pub pat: Pat,
+    /// If true, only `pat` is printed. `pat` should then bind `ident`, e.g. `ident` or `mut ident`.
+    pub is_shorthand: bool,
 }
 
 impl Ident for PatField {
@@ -284,12 +288,19 @@ impl Ident for PatField {
 
 impl fmt::Display for PatField {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{ident}: {pat}", ident = self.ident, pat = self.pat)
+        if self.is_shorthand {
+            write!(f, "{pat}", pat = self.pat)
+        } else {
+            write!(f, "{ident}: {pat}", ident = self.ident, pat = self.pat)
+        }
     }
 }
 
 impl From<PatField> for TokenStream {
     fn from(value: PatField) -> Self {
+        if value.is_shorthand {
+            return TokenStream::from(value.pat);
+        }
         let mut ts = TokenStream::new();
         ts.push(Token::ident(value.ident).into_joint());
         ts.push(Token::Colon);
@@ -298,9 +309,41 @@ impl From<PatField> for TokenStream {
     }
 }
 
+impl PatField {
+    pub fn new(ident: impl Into<String>, pat: impl Into<Pat>) -> Self {
+        Self {
+            ident: ident.into(),
+            pat: pat.into(),
+            is_shorthand: false,
+        }
+    }
+
+    /// `ident` (field shorthand for `ident: ident`)
+    pub fn shortened(ident: impl Into<String>) -> Self {
+        let ident = ident.into();
+        Self {
+            ident: ident.clone(),
+            pat: Pat::ident(ident),
+            is_shorthand: true,
+        }
+    }
+
+    /// `mut ident` (field shorthand for `ident: mut ident`)
+    pub fn shortened_mut(ident: impl Into<String>) -> Self {
+        let ident = ident.into();
+        Self {
+            ident: ident.clone(),
+            pat: Pat::Ident(IdentPat::mut_(ident, None)),
+            is_shorthand: true,
+        }
+    }
+}
+
+/// `'ref'? 'mut'? ident ('@' pat)?`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IdentPat {
+    pub is_ref: bool,
     pub is_mut: bool,
     pub ident: String,
     pub pat: Option<Box<Pat>>,
@@ -308,12 +351,15 @@ pub struct IdentPat {
 
 impl fmt::Display for IdentPat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ref {
+            write!(f, "ref ")?;
+        }
         if self.is_mut {
             write!(f, "mut ")?;
         }
         write!(f, "{ident}", ident = self.ident)?;
         if let Some(pat) = &self.pat {
-            write!(f, " @ {pat}")?;
+            write!(f, " @ {}", NoTopAlt(pat))?;
         }
         Ok(())
     }
@@ -322,6 +368,7 @@ impl fmt::Display for IdentPat {
 impl<S: Into<String>> From<S> for IdentPat {
     fn from(ident: S) -> Self {
         Self {
+            is_ref: false,
             is_mut: false,
             ident: ident.into(),
             pat: None,
@@ -332,13 +379,16 @@ impl<S: Into<String>> From<S> for IdentPat {
 impl From<IdentPat> for TokenStream {
     fn from(value: IdentPat) -> Self {
         let mut ts = TokenStream::new();
+        if value.is_ref {
+            ts.push(Token::Keyword(KeywordToken::Ref));
+        }
         if value.is_mut {
             ts.push(Token::Keyword(KeywordToken::Mut));
         }
         ts.push(Token::ident(value.ident));
         if let Some(pat) = value.pat {
             ts.push(Token::At);
-            ts.extend(TokenStream::from(*pat));
+            ts.extend(pat.into_tokens_no_top_alt());
         }
         ts
     }
@@ -347,6 +397,7 @@ impl From<IdentPat> for TokenStream {
 impl IdentPat {
     pub fn new(is_mut: bool, ident: impl Into<String>, pat: Option<impl Into<Pat>>) -> Self {
         Self {
+            is_ref: false,
             is_mut,
             ident: ident.into(),
             pat: pat.map(|x| Box::new(x.into())),
@@ -355,6 +406,7 @@ impl IdentPat {
 
     pub fn mut_(ident: impl Into<String>, pat: Option<Pat>) -> Self {
         Self {
+            is_ref: false,
             is_mut: true,
             ident: ident.into(),
             pat: pat.map(Box::new),
@@ -373,25 +425,56 @@ impl IdentPat {
         self.set_pat(pat);
         self
     }
+
+    /// `ref ident`
+    pub fn set_ref(&mut self, is_ref: bool) {
+        self.is_ref = is_ref;
+    }
+
+    pub fn with_ref(mut self) -> Self {
+        self.set_ref(true);
+        self
+    }
+
+    /// `mut ident`
+    pub fn set_mut(&mut self, is_mut: bool) {
+        self.is_mut = is_mut;
+    }
+
+    pub fn with_mut(mut self) -> Self {
+        self.set_mut(true);
+        self
+    }
 }
 
+/// `path { fields, ..? }`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StructPat {
     pub path: Path,
     pub fields: Vec<PatField>,
+    pub has_rest: bool,
 }
 
 impl fmt::Display for StructPat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{path} {{", path = self.path)?;
+        if self.fields.is_empty() && !self.has_rest {
+            return write!(f, "{path} {{}}", path = self.path);
+        }
+        write!(f, "{path} {{ ", path = self.path)?;
         for (i, field) in self.fields.iter().enumerate() {
             if i != 0 {
                 write!(f, ", ")?;
             }
             write!(f, "{field}")?;
         }
-        write!(f, "}}")
+        if self.has_rest {
+            if !self.fields.is_empty() {
+                write!(f, ", ")?;
+            }
+            write!(f, "..")?;
+        }
+        write!(f, " }}")
     }
 }
 
@@ -404,17 +487,52 @@ impl From<StructPat> for TokenStream {
             if i != 0 {
                 ts.push(Token::Comma);
             }
-            if i == value.fields.len() - 1 {
+            if i == value.fields.len() - 1 && !value.has_rest {
                 ts.extend(TokenStream::from(field.clone()));
             } else {
                 ts.extend(TokenStream::from(field.clone()).into_joint());
             }
         }
+        if value.has_rest {
+            if !value.fields.is_empty() {
+                ts.push(Token::Comma);
+            }
+            ts.push(Token::DotDot);
+        }
         ts.push(Token::CloseDelim(Delimiter::Brace));
         ts
     }
 }
 
+impl StructPat {
+    pub fn new(path: impl Into<Path>, fields: Vec<PatField>) -> Self {
+        Self {
+            path: path.into(),
+            fields,
+            has_rest: false,
+        }
+    }
+
+    pub fn add_field(&mut self, field: PatField) {
+        self.fields.push(field);
+    }
+
+    pub fn with_field(mut self, field: PatField) -> Self {
+        self.add_field(field);
+        self
+    }
+
+    pub fn set_rest(&mut self, has_rest: bool) {
+        self.has_rest = has_rest;
+    }
+
+    /// `path { fields, .. }`
+    pub fn with_rest(mut self) -> Self {
+        self.set_rest(true);
+        self
+    }
+}
+
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TupleStructPat {
@@ -481,7 +599,7 @@ impl fmt::Display for RefPat {
         } else {
             write!(f, "&")?;
         }
-        write!(f, "{}", self.pat)
+        write!(f, "{}", NoRange(&self.pat))
     }
 }
 
@@ -494,23 +612,23 @@ impl From<RefPat> for TokenStream {
         } else {
             ts.push(Token::And);
         }
-        ts.extend(TokenStream::from(*value.pat));
+        ts.extend(value.pat.into_tokens_no_range());
         ts
     }
 }
 
 impl RefPat {
-    pub fn immut(pat: Pat) -> Self {
+    pub fn immut(pat: impl Into<Pat>) -> Self {
         Self {
             is_mut: false,
-            pat: Box::new(pat),
+            pat: Box::new(pat.into()),
         }
     }
 
-    pub fn mut_(pat: Pat) -> Self {
+    pub fn mut_(pat: impl Into<Pat>) -> Self {
         Self {
             is_mut: true,
-            pat: Box::new(pat),
+            pat: Box::new(pat.into()),
         }
     }
 }
@@ -589,21 +707,23 @@ impl fmt::Display for Pat {
                     }
                     write!(f, "{pat}")?;
                 }
+                if Self::needs_trailing_comma(pats) {
+                    write!(f, ",")?;
+                }
                 write!(f, ")")
             }
             Self::Struct(struct_pat) => write!(f, "{struct_pat}"),
             Self::TupleStruct(tuple_struct_pat) => write!(f, "{tuple_struct_pat}"),
-            Self::Box(pat) => write!(f, "box {pat}"),
+            Self::Box(pat) => write!(f, "box {}", NoRange(pat)),
             Self::Ref(ref_pat) => write!(f, "{ref_pat}"),
             Self::Or(pats) => {
-                write!(f, "(")?;
                 for (i, pat) in pats.iter().enumerate() {
                     if i != 0 {
                         write!(f, " | ")?;
                     }
                     write!(f, "{pat}")?;
                 }
-                write!(f, ")")
+                Ok(())
             }
             Self::Lit(expr) => write!(f, "{expr}"),
             Self::Range(range) => write!(f, "{range}"),
@@ -617,7 +737,7 @@ impl fmt::Display for Pat {
                 }
                 write!(f, "]")
             }
-            Self::Rest => write!(f, "..."),
+            Self::Rest => write!(f, ".."),
             Self::Paren(pat) => write!(f, "({pat})"),
             Self::MacCall(mac_call) => write!(f, "{mac_call}"),
         }
@@ -639,28 +759,26 @@ impl From<Pat> for TokenStream {
             Pat::TupleStruct(tuple_struct_pat) => TokenStream::from(tuple_struct_pat),
             Pat::Or(pats) => {
                 let mut ts = TokenStream::new();
-                ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
-                for (i, pat) in pats.iter().enumerate() {
+                for (i, pat) in pats.into_iter().enumerate() {
                     if i != 0 {
                         ts.push(Token::Or);
                     }
-                    if i == pats.len() - 1 {
-                        ts.extend(TokenStream::from(pat.clone()).into_joint());
-                    } else {
-                        ts.extend(TokenStream::from(pat.clone()));
-                    }
+                    ts.extend(TokenStream::from(pat));
                 }
-                ts.push(Token::CloseDelim(Delimiter::Parenthesis));
                 ts
             }
             Pat::Tuple(pats) => {
                 let mut ts = TokenStream::new();
                 ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
-                for (i, pat) in pats.iter().enumerate() {
+                let trailing_comma = Pat::needs_trailing_comma(&pats);
+                for (i, pat) in pats.into_iter().enumerate() {
                     if i != 0 {
                         ts.push(Token::Comma);
                     }
-                    ts.extend(TokenStream::from(pat.clone()).into_joint());
+                    ts.extend(TokenStream::from(pat).into_joint());
+                }
+                if trailing_comma {
+                    ts.push(Token::Comma.into_joint());
                 }
                 ts.push(Token::CloseDelim(Delimiter::Parenthesis));
                 ts
@@ -668,7 +786,7 @@ impl From<Pat> for TokenStream {
             Pat::Box(pat) => {
                 let mut ts = TokenStream::new();
                 ts.push(Token::Keyword(KeywordToken::Box));
-                ts.extend(TokenStream::from(*pat));
+                ts.extend(pat.into_tokens_no_range());
                 ts
             }
             Pat::Ref(ref_pat) => TokenStream::from(ref_pat),
@@ -686,7 +804,7 @@ impl From<Pat> for TokenStream {
                 ts.push(Token::CloseDelim(Delimiter::Bracket));
                 ts
             }
-            Pat::Rest => TokenStream::from(vec![Token::DotDotDot]),
+            Pat::Rest => TokenStream::from(vec![Token::DotDot]),
             Pat::Paren(pat) => {
                 let mut ts = TokenStream::new();
                 ts.push(Token::OpenDelim(Delimiter::Parenthesis));
@@ -699,6 +817,86 @@ impl From<Pat> for TokenStream {
     }
 }
 
+impl From<MacCall> for Pat {
+    fn from(value: MacCall) -> Self {
+        Self::MacCall(value)
+    }
+}
+
+impl From<StructPat> for Pat {
+    fn from(value: StructPat) -> Self {
+        Self::Struct(value)
+    }
+}
+
+impl From<TupleStructPat> for Pat {
+    fn from(value: TupleStructPat) -> Self {
+        Self::TupleStruct(value)
+    }
+}
+
+impl From<Lit> for Pat {
+    fn from(value: Lit) -> Self {
+        Self::Lit(value.into())
+    }
+}
+
+impl From<RefPat> for Pat {
+    fn from(value: RefPat) -> Self {
+        Self::Ref(value)
+    }
+}
+
+/// Displays a pattern where or-patterns need parentheses, e.g. `&(A | B)`, `x @ (A | B)` or
+/// the parameter `(A | B): T`.
+struct NoTopAlt<'a>(&'a Pat);
+
+impl fmt::Display for NoTopAlt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Pat::Or(_) => write!(f, "({})", self.0),
+            pat => write!(f, "{pat}"),
+        }
+    }
+}
+
+/// Displays a pattern where range patterns need parentheses too, e.g. `&(1..=5)` or
+/// `box (1..=5)`.
+struct NoRange<'a>(&'a Pat);
+
+impl fmt::Display for NoRange<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Pat::Range(_) => write!(f, "({})", self.0),
+            pat => write!(f, "{}", NoTopAlt(pat)),
+        }
+    }
+}
+
+impl Pat {
+    fn into_tokens_no_top_alt(self) -> TokenStream {
+        if !matches!(self, Pat::Or(_)) {
+            return TokenStream::from(self);
+        }
+        self.into_parenthesized_tokens()
+    }
+
+    fn into_tokens_no_range(self) -> TokenStream {
+        if !matches!(self, Pat::Range(_)) {
+            return self.into_tokens_no_top_alt();
+        }
+        self.into_parenthesized_tokens()
+    }
+
+    fn into_parenthesized_tokens(self) -> TokenStream {
+        let mut ts = TokenStream::new();
+        ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+        ts.extend(TokenStream::from(self).into_joint());
+        ts.push(Token::CloseDelim(Delimiter::Parenthesis));
+        ts
+    }
+}
+
 impl Pat {
     pub fn ident(ident: impl Into<String>) -> Self {
         Self::Ident(IdentPat::from(ident.into()))
@@ -724,6 +922,75 @@ impl Pat {
         Self::Ident(IdentPat::mut_(ident, None))
     }
 
+    /// `ref ident`
+    pub fn by_ref(ident: impl Into<String>) -> Self {
+        Self::Ident(IdentPat::simple(ident).with_ref())
+    }
+
+    /// `ref mut ident`
+    pub fn by_ref_mut(ident: impl Into<String>) -> Self {
+        Self::Ident(IdentPat::simple(ident).with_ref().with_mut())
+    }
+
+    /// `ident @ pat`
+    pub fn at(ident: impl Into<String>, pat: impl Into<Pat>) -> Self {
+        Self::Ident(IdentPat::simple(ident).with_pat(pat))
+    }
+
+    pub fn mac_call(path: impl Into<Path>, args: impl Into<DelimArgs>) -> Self {
+        Self::MacCall(MacCall::new(path.into(), args))
+    }
+
+    /// `(pats, ..)`, with a trailing comma for a single element: `(pat,)`
+    pub fn tuple(pats: impl IntoIterator<Item = Pat>) -> Self {
+        Self::Tuple(pats.into_iter().collect())
+    }
+
+    /// `path(pats, ..)`, e.g. `Some(x)`
+    pub fn tuple_struct(path: impl Into<Path>, pats: impl IntoIterator<Item = Pat>) -> Self {
+        Self::TupleStruct(TupleStructPat::new(path.into(), pats.into_iter().collect()))
+    }
+
+    /// `Some(pat)`
+    pub fn some(pat: impl Into<Pat>) -> Self {
+        Self::tuple_struct("Some", [pat.into()])
+    }
+
+    /// `None`
+    pub fn none() -> Self {
+        Self::Lit(Path::single("None").into())
+    }
+
+    /// `Ok(pat)`
+    pub fn ok(pat: impl Into<Pat>) -> Self {
+        Self::tuple_struct("Ok", [pat.into()])
+    }
+
+    /// `Err(pat)`
+    pub fn err(pat: impl Into<Pat>) -> Self {
+        Self::tuple_struct("Err", [pat.into()])
+    }
+
+    /// `&pat`
+    pub fn ref_(pat: impl Into<Pat>) -> Self {
+        Self::Ref(RefPat::immut(pat))
+    }
+
+    /// `&mut pat`
+    pub fn ref_mut(pat: impl Into<Pat>) -> Self {
+        Self::Ref(RefPat::mut_(pat))
+    }
+
+    /// `box pat`, which requires the unstable `box_patterns` feature.
+    pub fn box_(pat: impl Into<Pat>) -> Self {
+        Self::Box(Box::new(pat.into()))
+    }
+
+    /// A one-element tuple pattern is written `(pat,)`; `(..)` needs no comma.
+    fn needs_trailing_comma(pats: &[Pat]) -> bool {
+        matches!(pats, [pat] if *pat != Pat::Rest)
+    }
+
     pub fn bind(self, kind: impl Into<LocalKind>) -> Local {
         Local::new(self, None, kind)
     }
@@ -739,7 +1006,209 @@ impl Pat {
     }
 }
 
-/// `pat ':' ty`
+impl Expr {
+    /// The pattern written like this expression, if there is one: paths, literals, tuples,
+    /// arrays, tuple struct calls, struct literals without `..base`, references and ranges.
+    ///
+    /// A single identifier such as `x` becomes a binding. Expressions with attributes have no
+    /// pattern.
+    pub fn try_into_pat(&self) -> Option<Pat> {
+        if !self.attrs.is_empty() {
+            return None;
+        }
+        let pats = |exprs: &[Expr]| exprs.iter().map(Expr::try_into_pat).collect::<Option<_>>();
+        let pat = match &self.kind {
+            ExprKind::Underscore(_) => Pat::Wild,
+            ExprKind::Path(path) => match &path.segments[..] {
+                [segment] if segment.args.is_none() => Pat::ident(segment.ident.clone()),
+                _ => Pat::Lit(self.clone()),
+            },
+            ExprKind::QPath(_) | ExprKind::Lit(_) => Pat::Lit(self.clone()),
+            ExprKind::Unary(Unary {
+                op: UnaryOpKind::Neg,
+                expr,
+            }) if matches!(expr.kind, ExprKind::Lit(_)) => Pat::Lit(self.clone()),
+            ExprKind::Tuple(Tuple(exprs)) => Pat::Tuple(pats(exprs)?),
+            ExprKind::Array(Array(exprs)) => Pat::Slice(pats(exprs)?),
+            ExprKind::Paren(Paren(expr)) => Pat::Paren(Box::new(expr.try_into_pat()?)),
+            ExprKind::Call(call) => {
+                let ExprKind::Path(path) = &call.func.kind else {
+                    return None;
+                };
+                Pat::TupleStruct(TupleStructPat::new(path.clone(), pats(&call.args)?))
+            }
+            ExprKind::Struct(struct_) if struct_.rest.is_none() => {
+                let mut pat = StructPat::new(struct_.path.clone(), vec![]);
+                for field in struct_.fields.iter() {
+                    pat.add_field(if field.is_shorthand {
+                        PatField::shortened(field.ident.clone())
+                    } else {
+                        PatField::new(field.ident.clone(), field.expr.try_into_pat()?)
+                    });
+                }
+                Pat::Struct(pat)
+            }
+            ExprKind::AddrOf(AddrOf {
+                kind: BorrowKind::Ref,
+                mutability,
+                expr,
+            }) => Pat::Ref(RefPat {
+                is_mut: *mutability == Mutability::Mut,
+                pat: Box::new(expr.try_into_pat()?),
+            }),
+            ExprKind::Range(range) => {
+                let is_bound = |bound: &Option<Box<Expr>>| {
+                    bound.as_deref().is_none_or(|bound| {
+                        matches!(bound.try_into_pat(), Some(Pat::Lit(_) | Pat::Ident(_)))
+                    })
+                };
+                if range.start.is_none() && range.end.is_none() {
+                    Pat::Rest
+                } else if is_bound(&range.start) && is_bound(&range.end) {
+                    Pat::Range(range.clone())
+                } else {
+                    return None;
+                }
+            }
+            ExprKind::MacCall(mac_call) => Pat::MacCall(mac_call.clone()),
+            _ => return None,
+        };
+        Some(pat)
+    }
+}
+
+/// The receiver of a method.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SelfParam {
+    /// `self` or `mut self`
+    Value(Mutability),
+    /// `&'lifetime self` or `&'lifetime mut self`
+    Ref(Option<String>, Mutability),
+    /// `self: ty`, e.g. `self: Box<Self>` or `self: Pin<&mut Self>`
+    Explicit(Mutability, Type),
+}
+
+impl fmt::Display for SelfParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Receiver::from(self).fmt(f)
+    }
+}
+
+/// A borrowed [`SelfParam`], so that a [`Param`] renders its receiver without cloning its type.
+#[derive(Clone, Copy)]
+enum Receiver<'a> {
+    Value(Mutability),
+    Ref(Option<&'a String>, Mutability),
+    Explicit(Mutability, &'a Type),
+}
+
+impl<'a> From<&'a SelfParam> for Receiver<'a> {
+    fn from(value: &'a SelfParam) -> Self {
+        match value {
+            SelfParam::Value(mutability) => Self::Value(*mutability),
+            SelfParam::Ref(lifetime, mutability) => Self::Ref(lifetime.as_ref(), *mutability),
+            SelfParam::Explicit(mutability, ty) => Self::Explicit(*mutability, ty),
+        }
+    }
+}
+
+impl fmt::Display for Receiver<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(mutability) => {
+                if mutability.is_mut() {
+                    write!(f, "mut ")?;
+                }
+                write!(f, "self")
+            }
+            Self::Ref(lifetime, mutability) => {
+                write!(f, "&")?;
+                if let Some(lifetime) = lifetime {
+                    write!(f, "'{lifetime} ")?;
+                }
+                if mutability.is_mut() {
+                    write!(f, "mut ")?;
+                }
+                write!(f, "self")
+            }
+            Self::Explicit(mutability, ty) => {
+                if mutability.is_mut() {
+                    write!(f, "mut ")?;
+                }
+                write!(f, "self: {ty}")
+            }
+        }
+    }
+}
+
+impl Receiver<'_> {
+    fn to_self_param(self) -> SelfParam {
+        match self {
+            Self::Value(mutability) => SelfParam::Value(mutability),
+            Self::Ref(lifetime, mutability) => SelfParam::Ref(lifetime.cloned(), mutability),
+            Self::Explicit(mutability, ty) => SelfParam::Explicit(mutability, ty.clone()),
+        }
+    }
+}
+
+impl From<SelfParam> for TokenStream {
+    fn from(value: SelfParam) -> Self {
+        let mut ts = TokenStream::new();
+        match value {
+            SelfParam::Value(mutability) => {
+                if mutability.is_mut() {
+                    ts.push(Token::Keyword(KeywordToken::Mut));
+                }
+                ts.push(Token::ident("self"));
+            }
+            SelfParam::Ref(lifetime, mutability) => {
+                ts.push(Token::And.into_joint());
+                if let Some(lifetime) = lifetime {
+                    ts.push(Token::lifetime(lifetime));
+                }
+                if mutability.is_mut() {
+                    ts.push(Token::Keyword(KeywordToken::Mut));
+                }
+                ts.push(Token::ident("self"));
+            }
+            SelfParam::Explicit(mutability, ty) => {
+                if mutability.is_mut() {
+                    ts.push(Token::Keyword(KeywordToken::Mut));
+                }
+                ts.push(Token::ident("self").into_joint());
+                ts.push(Token::Colon);
+                ts.extend(TokenStream::from(ty));
+            }
+        }
+        ts
+    }
+}
+
+impl From<SelfParam> for Param {
+    fn from(value: SelfParam) -> Self {
+        let pat = |mutability: Mutability| {
+            if mutability.is_mut() {
+                Pat::mut_self()
+            } else {
+                Pat::slf()
+            }
+        };
+        match value {
+            SelfParam::Value(mutability) => Param::new(pat(mutability), Type::ImplicitSelf),
+            SelfParam::Ref(lifetime, mutability) => Param::new(
+                Pat::slf(),
+                Type::Ref(Ref::new(
+                    lifetime,
+                    MutTy::new(mutability.is_mut(), Type::ImplicitSelf),
+                )),
+            ),
+            SelfParam::Explicit(mutability, ty) => Param::new(pat(mutability), ty),
+        }
+    }
+}
+
+/// `pat ':' ty`, or a receiver (see [`Param::receiver`])
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Param {
@@ -749,18 +1218,30 @@ pub struct Param {
 
 impl fmt::Display for Param {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(receiver) = self.receiver_ref() {
+            return write!(f, "{receiver}");
+        }
+        let pat = NoTopAlt(&self.pat);
         if self.ty == Type::ImplicitSelf {
-            write!(f, "{pat}", pat = self.pat)
+            write!(f, "{pat}")
         } else {
-            write!(f, "{pat}: {ty}", pat = self.pat, ty = self.ty)
+            write!(f, "{pat}: {ty}", ty = self.ty)
         }
     }
 }
 
 impl From<Param> for TokenStream {
     fn from(value: Param) -> Self {
+        match value.receiver_ref() {
+            // the type is moved rather than cloned
+            Some(Receiver::Explicit(mutability, _)) => {
+                return TokenStream::from(SelfParam::Explicit(mutability, value.ty));
+            }
+            Some(receiver) => return TokenStream::from(receiver.to_self_param()),
+            None => {}
+        }
         let mut ts = TokenStream::new();
-        ts.extend(TokenStream::from(value.pat).into_joint());
+        ts.extend(value.pat.into_tokens_no_top_alt().into_joint());
         if value.ty != Type::ImplicitSelf {
             ts.push(Token::Colon);
             ts.extend(TokenStream::from(value.ty));
@@ -778,6 +1259,12 @@ impl Param {
         Self::new(Pat::ident(ident), ty)
     }
 
+    /// `ident: impl Bound + ..`, an anonymous type parameter that callers can't name with a
+    /// turbofish.
+    pub fn impl_trait(ident: impl Into<String>, bounds: Vec<GenericBound>) -> Self {
+        Self::ident(ident, ImplTrait::new(bounds).into())
+    }
+
     pub fn slf() -> Self {
         Self::new(Pat::slf(), Type::ImplicitSelf)
     }
@@ -793,6 +1280,62 @@ impl Param {
     pub fn mut_self() -> Self {
         Self::new(Pat::mut_self(), Type::ImplicitSelf)
     }
+
+    /// `&'lifetime self`
+    pub fn ref_self_with_lifetime(lifetime: impl Into<String>) -> Self {
+        SelfParam::Ref(Some(lifetime.into()), Mutability::Not).into()
+    }
+
+    /// `&'lifetime mut self`
+    pub fn ref_mut_self_with_lifetime(lifetime: impl Into<String>) -> Self {
+        SelfParam::Ref(Some(lifetime.into()), Mutability::Mut).into()
+    }
+
+    /// `self: ty`, e.g. `self: Box<Self>`
+    pub fn typed_self(ty: impl Into<Type>) -> Self {
+        SelfParam::Explicit(Mutability::Not, ty.into()).into()
+    }
+
+    /// The receiver this parameter declares, if its pattern is `self`, `mut self`, `&self` or
+    /// `&mut self`. [`Type::ImplicitSelf`] stands for the type of the shorthand forms, also
+    /// behind a reference (`&'a Self`).
+    pub fn receiver(&self) -> Option<SelfParam> {
+        self.receiver_ref().map(Receiver::to_self_param)
+    }
+
+    fn receiver_ref(&self) -> Option<Receiver<'_>> {
+        let is_self = |pat: &Pat| matches!(pat, Pat::Ident(IdentPat { is_ref: false, ident, pat: None, .. }) if ident == "self");
+        match &self.pat {
+            Pat::Ref(RefPat { is_mut, pat }) if is_self(pat) => {
+                let mutability = if *is_mut {
+                    Mutability::Mut
+                } else {
+                    Mutability::Not
+                };
+                (self.ty == Type::ImplicitSelf).then_some(Receiver::Ref(None, mutability))
+            }
+            Pat::Ident(ident) if is_self(&self.pat) => {
+                let mutability = if ident.is_mut {
+                    Mutability::Mut
+                } else {
+                    Mutability::Not
+                };
+                Some(match &self.ty {
+                    Type::ImplicitSelf => Receiver::Value(mutability),
+                    Type::Ref(ref_) if *ref_.ty.ty == Type::ImplicitSelf && !ident.is_mut => {
+                        let mutability = if ref_.ty.mutable {
+                            Mutability::Mut
+                        } else {
+                            Mutability::Not
+                        };
+                        Receiver::Ref(ref_.lifetime.as_ref(), mutability)
+                    }
+                    ty => Receiver::Explicit(mutability, ty),
+                })
+            }
+            _ => None,
+        }
+    }
 }
 
 /// `'(' params (, ...)? ')' ('->' output)?`
@@ -841,7 +1384,7 @@ impl From<FnDecl> for TokenStream {
             if !value.inputs.is_empty() {
                 ts.push(Token::Comma);
             }
-            ts.push(Token::DotDotDot);
+            ts.push(Token::DotDotDot.into_joint());
         }
         ts.push(Token::CloseDelim(Delimiter::Parenthesis));
         if let Some(output) = value.output {
@@ -874,6 +1417,18 @@ impl FnDecl {
         Self::regular(Vec::new(), None)
     }
 
+    /// `(receiver, inputs..) -> output`
+    pub fn method(receiver: SelfParam, inputs: Vec<Param>, output: Option<Type>) -> Self {
+        let mut decl = Self::regular(vec![receiver.into()], output);
+        decl.inputs.extend(inputs);
+        decl
+    }
+
+    /// The receiver of a method, declared by its first parameter.
+    pub fn receiver(&self) -> Option<SelfParam> {
+        self.inputs.first().and_then(Param::receiver)
+    }
+
     pub fn add_input(&mut self, input: Param) {
         self.inputs.push(input);
     }
@@ -893,22 +1448,32 @@ impl FnDecl {
     }
 }
 
-/// `'fn' 'unsafe'? 'const'? 'async'? ('extern' "abi")? ident (<...>)? decl { ... }`
+/// `'default'? 'const'? 'async'? 'unsafe'? ('extern' "abi")? 'fn' ident (<...>)? decl { ... }`
+///
+/// The qualifiers are always rendered in this order, whatever order they were set in.
+/// [`TryRender`](crate::TryRender) rejects combinations the compiler does not accept.
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Fn {
+    /// `default fn`, a specializable item of an impl (unstable `specialization`).
+    #[cfg(feature = "unstable-syntax")]
+    pub is_default: bool,
     pub is_unsafe: bool,
     pub is_const: bool,
     pub is_async: bool,
     pub abi: Option<String>,
     pub ident: String,
-    pub generics: Vec<GenericParam>,
+    pub generics: Generics,
     pub fn_decl: FnDecl,
     pub body: Option<Block>,
 }
 
 impl fmt::Display for Fn {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "unstable-syntax")]
+        if self.is_default {
+            write!(f, "default ")?;
+        }
         if self.is_const {
             write!(f, "const ")?;
         }
@@ -921,18 +1486,9 @@ impl fmt::Display for Fn {
         if let Some(abi) = &self.abi {
             write!(f, "extern \"{abi}\" ")?;
         }
-        write!(f, "fn {}", self.ident)?;
-        if !self.generics.is_empty() {
-            write!(f, "<")?;
-            for (i, generic) in self.generics.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{generic}")?;
-            }
-            write!(f, ">")?;
-        }
+        write!(f, "fn {}{}", self.ident, self.generics)?;
         write!(f, "{}", self.fn_decl)?;
+        write_where_clause(f, &self.generics.where_clause)?;
         if let Some(body) = &self.body {
             write!(f, " {body}")?;
         } else {
@@ -946,6 +1502,10 @@ impl fmt::Display for Fn {
 impl From<Fn> for TokenStream {
     fn from(value: Fn) -> Self {
         let mut ts = TokenStream::new();
+        #[cfg(feature = "unstable-syntax")]
+        if value.is_default {
+            ts.push(Token::ident("default"));
+        }
         if value.is_const {
             ts.push(Token::Keyword(KeywordToken::Const));
         }
@@ -957,23 +1517,20 @@ impl From<Fn> for TokenStream {
         }
         if let Some(abi) = value.abi {
             ts.push(Token::Keyword(KeywordToken::Extern));
-            ts.push(Token::lit(format!("\"{abi}\"")));
+            ts.push(Token::lit(abi));
         }
         ts.push(Token::Keyword(KeywordToken::Fn));
         ts.push(Token::ident(value.ident).into_joint());
-        if !value.generics.is_empty() {
-            ts.push(Token::Lt.into_joint());
-            for (i, generic) in value.generics.iter().enumerate() {
-                if i != 0 {
-                    ts.push(Token::Comma);
-                }
-                ts.extend(TokenStream::from(generic.clone()).into_joint());
-            }
-            ts.push(Token::Gt.into_joint());
-        }
-        ts.extend(TokenStream::from(value.fn_decl));
+        value.generics.push_params(&mut ts);
+        ts.join_last();
+        let mut signature = TokenStream::from(value.fn_decl);
+        signature.extend(TokenStream::from(value.generics.where_clause));
         if let Some(body) = value.body {
+            ts.extend(signature);
             ts.extend(TokenStream::from(body));
+        } else {
+            ts.extend(signature.into_joint());
+            ts.push(Token::Semi);
         }
         ts
     }
@@ -1006,12 +1563,14 @@ impl Fn {
         body: Option<Block>,
     ) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe,
             is_const,
             is_async,
             abi,
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             fn_decl,
             body,
         }
@@ -1019,29 +1578,38 @@ impl Fn {
 
     pub fn simple(ident: impl Into<String>, fn_decl: FnDecl, body: Block) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe: false,
             is_const: false,
             is_async: false,
             abi: None,
             ident: ident.into(),
-            generics: Vec::new(),
+            generics: Generics::default(),
             fn_decl,
             body: Some(body),
         }
     }
 
+    /// `fn ident(..) -> ..;`, a function declared in an [`ExternBlock`].
+    pub fn foreign(ident: impl Into<String>, fn_decl: FnDecl) -> Self {
+        Self::new(false, false, false, None, ident, Vec::new(), fn_decl, None)
+    }
+
     pub fn new_unsafe(
         ident: impl Into<String>,
         generics: Vec<GenericParam>,
         fn_decl: FnDecl,
     ) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe: true,
             is_const: false,
             is_async: false,
             abi: None,
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             fn_decl,
             body: None,
         }
@@ -1053,12 +1621,14 @@ impl Fn {
         fn_decl: FnDecl,
     ) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe: false,
             is_const: true,
             is_async: false,
             abi: None,
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             fn_decl,
             body: None,
         }
@@ -1070,12 +1640,14 @@ impl Fn {
         fn_decl: FnDecl,
     ) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe: false,
             is_const: false,
             is_async: true,
             abi: None,
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             fn_decl,
             body: None,
         }
@@ -1087,12 +1659,14 @@ impl Fn {
         fn_decl: FnDecl,
     ) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe: false,
             is_const: false,
             is_async: false,
             abi: Some("C".into()),
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             fn_decl,
             body: None,
         }
@@ -1100,12 +1674,14 @@ impl Fn {
 
     pub fn main(output: Option<Type>, body: Block) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe: false,
             is_const: false,
             is_async: false,
             abi: None,
             ident: "main".into(),
-            generics: Vec::new(),
+            generics: Generics::default(),
             fn_decl: FnDecl::regular(Vec::new(), output),
             body: Some(body),
         }
@@ -1113,12 +1689,14 @@ impl Fn {
 
     pub fn empty(ident: impl Into<String>) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe: false,
             is_const: false,
             is_async: false,
             abi: None,
             ident: ident.into(),
-            generics: Vec::new(),
+            generics: Generics::default(),
             fn_decl: FnDecl::empty(),
             body: None,
         }
@@ -1126,12 +1704,14 @@ impl Fn {
 
     pub fn empty_method(ident: impl Into<String>, self_pat: Pat) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             is_unsafe: false,
             is_const: false,
             is_async: false,
             abi: None,
             ident: ident.into(),
-            generics: Vec::new(),
+            generics: Generics::default(),
             fn_decl: FnDecl::regular(vec![Param::new(self_pat, Type::ImplicitSelf)], None),
             body: Some(Block::empty()),
         }
@@ -1166,13 +1746,69 @@ impl Fn {
     }
 
     pub fn add_generic_param(&mut self, param: GenericParam) {
-        self.generics.push(param);
+        self.generics.add_param(param);
     }
 
     pub fn with_generic_param(mut self, param: GenericParam) -> Self {
         self.add_generic_param(param);
         self
     }
+
+    pub fn add_where_clause(&mut self, clause: WherePredicate) {
+        self.generics.add_predicate(clause);
+    }
+
+    pub fn with_where_clause(mut self, clause: WherePredicate) -> Self {
+        self.add_where_clause(clause);
+        self
+    }
+
+    pub fn set_unsafe(&mut self, is_unsafe: bool) {
+        self.is_unsafe = is_unsafe;
+    }
+
+    pub fn with_unsafe(mut self, is_unsafe: bool) -> Self {
+        self.set_unsafe(is_unsafe);
+        self
+    }
+
+    pub fn set_const(&mut self, is_const: bool) {
+        self.is_const = is_const;
+    }
+
+    pub fn with_const(mut self, is_const: bool) -> Self {
+        self.set_const(is_const);
+        self
+    }
+
+    pub fn set_async(&mut self, is_async: bool) {
+        self.is_async = is_async;
+    }
+
+    pub fn with_async(mut self, is_async: bool) -> Self {
+        self.set_async(is_async);
+        self
+    }
+
+    pub fn set_abi(&mut self, abi: Option<String>) {
+        self.abi = abi;
+    }
+
+    pub fn with_abi(mut self, abi: Option<String>) -> Self {
+        self.set_abi(abi);
+        self
+    }
+
+    #[cfg(feature = "unstable-syntax")]
+    pub fn set_default(&mut self, is_default: bool) {
+        self.is_default = is_default;
+    }
+
+    #[cfg(feature = "unstable-syntax")]
+    pub fn with_default(mut self, is_default: bool) -> Self {
+        self.set_default(is_default);
+        self
+    }
 }
 
 /// `mod ident { ... }`
@@ -1308,9 +1944,22 @@ impl Mod {
         Self::Loaded(LoadedMod::empty(ident))
     }
 
+    /// `mod ident;`, a module whose contents live in another file.
+    pub fn declaration(ident: impl Into<String>) -> Self {
+        Self::Unloaded(ident.into())
+    }
+
     pub fn ident(&self) -> &str {
         Ident::ident(self)
     }
+
+    /// The items of an inline module, or `None` for a declaration.
+    pub fn items(&self) -> Option<&[Item]> {
+        match self {
+            Self::Loaded(module) => Some(&module.items),
+            Self::Unloaded(_) => None,
+        }
+    }
 }
 
 /// This index should not be kept after the statement is removed.
@@ -1321,7 +1970,7 @@ pub struct StmtIndex(usize);
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct LabelledBlock {
-    pub label: Option<String>,
+    pub label: Option<Label>,
     pub block: Block,
 }
 
@@ -1345,7 +1994,7 @@ impl HasPrecedence for LabelledBlock {
 impl fmt::Display for LabelledBlock {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(label) = &self.label {
-            write!(f, "'{label}: ")?;
+            write!(f, "{label}: ")?;
         }
         write!(f, "{}", self.block)?;
         Ok(())
@@ -1363,10 +2012,7 @@ impl<S: Into<Stmt>> From<S> for LabelledBlock {
 impl From<LabelledBlock> for TokenStream {
     fn from(value: LabelledBlock) -> Self {
         let mut ts = TokenStream::new();
-        if let Some(label) = value.label {
-            ts.push(Token::lifetime(label));
-            ts.push(Token::Colon);
-        }
+        push_label(&mut ts, value.label);
         ts.extend(TokenStream::from(value.block));
         ts
     }
@@ -1398,10 +2044,24 @@ impl HasItem<Stmt> for LabelledBlock {
 impl_hasitem_methods!(LabelledBlock, Stmt, Deref);
 
 impl LabelledBlock {
-    pub fn new(block: Block, label: Option<String>) -> Self {
+    pub fn new(block: Block, label: Option<Label>) -> Self {
         Self { block, label }
     }
 
+    /// `'label: { ... }`
+    pub fn labelled(label: impl Into<Label>, block: Block) -> Self {
+        Self::new(block, Some(label.into()))
+    }
+
+    pub fn set_label(&mut self, label: impl Into<Label>) {
+        self.label = Some(label.into());
+    }
+
+    pub fn with_label(mut self, label: impl Into<Label>) -> Self {
+        self.set_label(label);
+        self
+    }
+
     pub fn single(expr: impl Into<Expr>) -> Self {
         Self::new(Block::single(expr.into()), None)
     }
@@ -1446,6 +2106,8 @@ impl LabelledBlock {
 /// { ... }`
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct Block {
+    /// Inner attributes, rendered as `#![..]` before the statements.
+    pub attrs: Vec<Attribute>,
     pub stmts: Vec<Stmt>,
 }
 
@@ -1457,7 +2119,10 @@ impl<'a> arbitrary::Arbitrary<'a> for Block {
         for _ in 0..i {
             stmts.push(Stmt::arbitrary_not_expr(u)?);
         }
-        Ok(Self { stmts })
+        Ok(Self {
+            attrs: Vec::new(),
+            stmts,
+        })
     }
 }
 
@@ -1469,7 +2134,10 @@ impl Block {
         for _ in 0..i {
             stmts.push(Stmt::arbitrary_item(u)?);
         }
-        Ok(Self { stmts })
+        Ok(Self {
+            attrs: Vec::new(),
+            stmts,
+        })
     }
 
     pub fn arbitrary_extern_item_block(
@@ -1480,7 +2148,10 @@ impl Block {
         for _ in 0..i {
             stmts.push(Stmt::arbitrary_extern_item(u)?);
         }
-        Ok(Self { stmts })
+        Ok(Self {
+            attrs: Vec::new(),
+            stmts,
+        })
     }
 }
 
@@ -1492,11 +2163,14 @@ impl HasPrecedence for Block {
 
 impl fmt::Display for Block {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.stmts.is_empty() {
+        if self.stmts.is_empty() && self.attrs.is_empty() {
             write!(f, "{{}}")?;
         } else {
             writeln!(f, "{{")?;
             let mut indent = indenter::indented(f).with_str("    ");
+            for attr in self.attrs.iter() {
+                writeln!(indent, "{}", attr.clone().into_inner())?;
+            }
             for stmt in self.stmts.iter() {
                 writeln!(indent, "{stmt}")?;
             }
@@ -1516,7 +2190,7 @@ impl<S: Into<Stmt>> From<S> for Block {
 
 impl From<Vec<Stmt>> for Block {
     fn from(stmts: Vec<Stmt>) -> Self {
-        Self { stmts }
+        Self::new(stmts)
     }
 }
 
@@ -1524,6 +2198,9 @@ impl From<Block> for TokenStream {
     fn from(value: Block) -> Self {
         let mut ts = TokenStream::new();
         ts.push(Token::OpenDelim(Delimiter::Brace));
+        for attr in value.attrs {
+            ts.extend(TokenStream::from(attr.into_inner()));
+        }
         for stmt in value.stmts.iter() {
             ts.extend(TokenStream::from(stmt.clone()));
             // ts.push(Token::Semi);
@@ -1560,7 +2237,10 @@ impl_hasitem_methods!(Block, Stmt, Deref);
 
 impl Block {
     pub fn new(stmts: Vec<Stmt>) -> Self {
-        Self { stmts }
+        Self {
+            attrs: Vec::new(),
+            stmts,
+        }
     }
 
     pub fn single(expr: impl Into<Expr>) -> Self {
@@ -1568,7 +2248,7 @@ impl Block {
     }
 
     pub fn empty() -> Self {
-        Self { stmts: Vec::new() }
+        Self::new(Vec::new())
     }
 
     pub fn async_(self) -> Async {
@@ -1591,6 +2271,15 @@ impl Block {
         ExternBlock::new(is_unsafe, abi, self)
     }
 
+    pub fn add_attr(&mut self, attr: impl Into<Attribute>) {
+        self.attrs.push(attr.into().into_inner());
+    }
+
+    pub fn with_attr(mut self, attr: impl Into<Attribute>) -> Self {
+        self.add_attr(attr);
+        self
+    }
+
     pub fn with_stmt(mut self, stmt: impl Into<Stmt>) -> Self {
         self.add_stmt(stmt);
         self
@@ -1610,6 +2299,53 @@ impl Block {
         self.stmts.remove(index.0)
     }
 
+    /// Inserts `stmt` at `index`, shifting the statements after it.
+    pub fn insert_stmt(&mut self, index: usize, stmt: impl Into<Stmt>) -> StmtIndex {
+        self.stmts.insert(index, stmt.into());
+        StmtIndex(index)
+    }
+
+    /// Inserts `stmt` before the first statement matching `pred`.
+    pub fn insert_before(
+        &mut self,
+        pred: impl FnMut(&Stmt) -> bool,
+        stmt: impl Into<Stmt>,
+    ) -> Option<StmtIndex> {
+        let index = self.stmts.iter().position(pred)?;
+        Some(self.insert_stmt(index, stmt))
+    }
+
+    /// Inserts `stmt` after the first statement matching `pred`.
+    pub fn insert_after(
+        &mut self,
+        pred: impl FnMut(&Stmt) -> bool,
+        stmt: impl Into<Stmt>,
+    ) -> Option<StmtIndex> {
+        let index = self.stmts.iter().position(pred)?;
+        Some(self.insert_stmt(index + 1, stmt))
+    }
+
+    pub fn try_replace_stmt(&mut self, index: usize, stmt: impl Into<Stmt>) -> Option<Stmt> {
+        let old = self.stmts.get_mut(index)?;
+        Some(std::mem::replace(old, stmt.into()))
+    }
+
+    pub fn replace_stmt(&mut self, index: StmtIndex, stmt: impl Into<Stmt>) -> Stmt {
+        std::mem::replace(&mut self.stmts[index.0], stmt.into())
+    }
+
+    /// Keeps only the statements matching `pred`, in order.
+    pub fn retain_stmts(&mut self, pred: impl FnMut(&Stmt) -> bool) {
+        self.stmts.retain(pred);
+    }
+
+    /// Removes the statements matching `pred` and returns them, in order.
+    pub fn extract_stmts(&mut self, mut pred: impl FnMut(&Stmt) -> bool) -> Vec<Stmt> {
+        let (extracted, kept) = self.stmts.drain(..).partition(|stmt| pred(stmt));
+        self.stmts = kept;
+        extracted
+    }
+
     pub fn try_remove_item_by_id(&mut self, ident: &str) -> Option<Item> {
         let index = self
             .stmts
@@ -1672,6 +2408,12 @@ impl From<FieldDef> for TokenStream {
     }
 }
 
+impl From<Type> for FieldDef {
+    fn from(value: Type) -> Self {
+        Self::anonymous(value)
+    }
+}
+
 impl MaybeIdent for FieldDef {
     fn ident(&self) -> Option<&str> {
         self.ident.as_deref()
@@ -1741,7 +2483,7 @@ impl fmt::Display for Fields {
                 let mut indent = indenter::indented(f).with_str("    ");
                 for (i, field) in fields.iter().enumerate() {
                     if i != 0 {
-                        writeln!(indent, ", ")?;
+                        writeln!(indent, ",")?;
                     }
                     write!(indent, "{field}")?;
                 }
@@ -1887,7 +2629,10 @@ impl fmt::Display for Variant {
         for attr in self.attrs.iter() {
             writeln!(f, "{attr}")?;
         }
-        write!(f, "{}{}{}", self.vis, self.ident, self.fields)?;
+        write!(f, "{}{}", self.vis, self.ident)?;
+        if self.fields != Fields::Unit {
+            write!(f, "{}", self.fields)?;
+        }
         if let Some(discriminant) = &self.discriminant {
             write!(f, " = {discriminant}")?;
         }
@@ -1902,8 +2647,12 @@ impl From<Variant> for TokenStream {
             ts.extend(TokenStream::from(attr.clone()));
         }
         ts.extend(TokenStream::from(value.vis));
-        ts.push(Token::ident(value.ident).into_joint());
-        ts.extend(TokenStream::from(value.fields));
+        if value.fields == Fields::Unit {
+            ts.push(Token::ident(value.ident));
+        } else {
+            ts.push(Token::ident(value.ident).into_joint());
+            ts.extend(TokenStream::from(value.fields));
+        }
         if let Some(discriminant) = value.discriminant {
             ts.push(Token::Eq);
             ts.extend(TokenStream::from(discriminant));
@@ -1951,22 +2700,32 @@ impl Variant {
         Self::new(vec![], Visibility::Inherited, ident, data, None)
     }
 
-    pub fn struct_(ident: impl Into<String>, fields: Vec<FieldDef>) -> Self {
+    /// `Ident`, the same as [`Variant::empty`].
+    pub fn unit(ident: impl Into<String>) -> Self {
+        Self::empty(ident)
+    }
+
+    /// `Ident { fields }`
+    pub fn struct_(ident: impl Into<String>, fields: impl IntoIterator<Item = FieldDef>) -> Self {
         Self::new(
             vec![],
             Visibility::Inherited,
             ident,
-            Fields::Struct(fields),
+            Fields::Struct(fields.into_iter().collect()),
             None,
         )
     }
 
-    pub fn tuple(ident: impl Into<String>, fields: Vec<FieldDef>) -> Self {
+    /// `Ident(fields)`, where a bare [`Type`] is an anonymous field.
+    pub fn tuple(
+        ident: impl Into<String>,
+        fields: impl IntoIterator<Item = impl Into<FieldDef>>,
+    ) -> Self {
         Self::new(
             vec![],
             Visibility::Inherited,
             ident,
-            Fields::Tuple(fields),
+            Fields::Tuple(fields.into_iter().map(Into::into).collect()),
             None,
         )
     }
@@ -2002,23 +2761,14 @@ impl Variant {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EnumDef {
     pub ident: String,
-    pub generics: Vec<GenericParam>,
+    pub generics: Generics,
     pub variants: Vec<Variant>,
 }
 
 impl fmt::Display for EnumDef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "enum {}", self.ident)?;
-        if !self.generics.is_empty() {
-            write!(f, "<")?;
-            for (i, generic) in self.generics.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{generic}")?;
-            }
-            write!(f, ">")?;
-        }
+        write!(f, "enum {}{}", self.ident, self.generics)?;
+        write_where_clause(f, &self.generics.where_clause)?;
         writeln!(f, " {{")?;
         let mut indent = indenter::indented(f).with_str("    ");
         for variant in self.variants.iter() {
@@ -2033,16 +2783,8 @@ impl From<EnumDef> for TokenStream {
         let mut ts = TokenStream::new();
         ts.push(Token::Keyword(KeywordToken::Enum));
         ts.push(Token::ident(value.ident));
-        if !value.generics.is_empty() {
-            ts.push(Token::Lt.into_joint());
-            for (i, generic) in value.generics.iter().enumerate() {
-                if i != 0 {
-                    ts.push(Token::Comma);
-                }
-                ts.extend(TokenStream::from(generic.clone()).into_joint());
-            }
-            ts.push(Token::Gt);
-        }
+        value.generics.push_params(&mut ts);
+        ts.extend(TokenStream::from(value.generics.where_clause));
         ts.push(Token::OpenDelim(Delimiter::Brace));
         for variant in value.variants.iter() {
             ts.extend(TokenStream::from(variant.clone()).into_joint());
@@ -2086,7 +2828,7 @@ impl EnumDef {
     ) -> Self {
         Self {
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             variants,
         }
     }
@@ -2133,7 +2875,16 @@ impl EnumDef {
     }
 
     pub fn add_generic_param(&mut self, param: GenericParam) {
-        self.generics.push(param);
+        self.generics.add_param(param);
+    }
+
+    pub fn add_where_clause(&mut self, clause: WherePredicate) {
+        self.generics.add_predicate(clause);
+    }
+
+    pub fn with_where_clause(mut self, clause: WherePredicate) -> Self {
+        self.add_where_clause(clause);
+        self
     }
 }
 
@@ -2141,24 +2892,14 @@ impl EnumDef {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StructDef {
     pub ident: String,
-    pub generics: Vec<GenericParam>,
+    pub generics: Generics,
     pub fields: Fields,
 }
 
 impl fmt::Display for StructDef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "struct {}", self.ident)?;
-        if !self.generics.is_empty() {
-            write!(f, "<")?;
-            for (i, generic) in self.generics.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{generic}")?;
-            }
-            write!(f, ">")?;
-        }
-        write!(f, "{}", self.fields)
+        write!(f, "struct {}{}", self.ident, self.generics)?;
+        write_fields_with_where(f, &self.fields, &self.generics.where_clause)
     }
 }
 
@@ -2167,17 +2908,11 @@ impl From<StructDef> for TokenStream {
         let mut ts = TokenStream::new();
         ts.push(Token::Keyword(KeywordToken::Struct));
         ts.push(Token::ident(value.ident));
-        if !value.generics.is_empty() {
-            ts.push(Token::Lt);
-            for (i, generic) in value.generics.iter().enumerate() {
-                if i != 0 {
-                    ts.push(Token::Comma);
-                }
-                ts.extend(TokenStream::from(generic.clone()));
-            }
-            ts.push(Token::Gt);
-        }
-        ts.extend(TokenStream::from(value.fields));
+        value.generics.push_params(&mut ts);
+        ts.extend(fields_with_where_tokens(
+            value.fields,
+            value.generics.where_clause,
+        ));
         ts
     }
 }
@@ -2209,7 +2944,7 @@ impl StructDef {
     pub fn new(ident: impl Into<String>, generics: Vec<GenericParam>, fields: Fields) -> Self {
         Self {
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             fields,
         }
     }
@@ -2250,7 +2985,16 @@ impl StructDef {
     }
 
     pub fn add_generic_param(&mut self, param: GenericParam) {
-        self.generics.push(param);
+        self.generics.add_param(param);
+    }
+
+    pub fn add_where_clause(&mut self, clause: WherePredicate) {
+        self.generics.add_predicate(clause);
+    }
+
+    pub fn with_where_clause(mut self, clause: WherePredicate) -> Self {
+        self.add_where_clause(clause);
+        self
     }
 }
 
@@ -2258,24 +3002,14 @@ impl StructDef {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UnionDef {
     pub ident: String,
-    pub generics: Vec<GenericParam>,
+    pub generics: Generics,
     pub fields: Fields,
 }
 
 impl fmt::Display for UnionDef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "union {}", self.ident)?;
-        if !self.generics.is_empty() {
-            write!(f, "<")?;
-            for (i, generic) in self.generics.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{generic}")?;
-            }
-            write!(f, ">")?;
-        }
-        write!(f, "{}", self.fields)
+        write!(f, "union {}{}", self.ident, self.generics)?;
+        write_fields_with_where(f, &self.fields, &self.generics.where_clause)
     }
 }
 
@@ -2284,17 +3018,11 @@ impl From<UnionDef> for TokenStream {
         let mut ts = TokenStream::new();
         ts.push(Token::ident("union"));
         ts.push(Token::ident(value.ident));
-        if !value.generics.is_empty() {
-            ts.push(Token::Lt);
-            for (i, generic) in value.generics.iter().enumerate() {
-                if i != 0 {
-                    ts.push(Token::Comma);
-                }
-                ts.extend(TokenStream::from(generic.clone()));
-            }
-            ts.push(Token::Gt);
-        }
-        ts.extend(TokenStream::from(value.fields));
+        value.generics.push_params(&mut ts);
+        ts.extend(fields_with_where_tokens(
+            value.fields,
+            value.generics.where_clause,
+        ));
         ts
     }
 }
@@ -2328,7 +3056,7 @@ impl UnionDef {
     pub fn new(ident: impl Into<String>, generics: Vec<GenericParam>, fields: Fields) -> Self {
         Self {
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             fields,
         }
     }
@@ -2369,32 +3097,43 @@ impl UnionDef {
     }
 
     pub fn add_generic_param(&mut self, param: GenericParam) {
-        self.generics.push(param);
+        self.generics.add_param(param);
+    }
+
+    pub fn add_where_clause(&mut self, clause: WherePredicate) {
+        self.generics.add_predicate(clause);
+    }
+
+    pub fn with_where_clause(mut self, clause: WherePredicate) -> Self {
+        self.add_where_clause(clause);
+        self
     }
 }
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TraitDef {
+    pub is_unsafe: bool,
+    /// `auto trait`, an unstable marker trait implemented for every type whose fields
+    /// implement it.
+    #[cfg(feature = "unstable-syntax")]
+    pub is_auto: bool,
     pub ident: String,
-    pub generics: Vec<GenericParam>,
+    pub generics: Generics,
     pub supertraits: Vec<Type>,
     pub items: Vec<AssocItem>,
 }
 
 impl fmt::Display for TraitDef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "trait {}", self.ident)?;
-        if !self.generics.is_empty() {
-            write!(f, "<")?;
-            for (i, generic) in self.generics.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{generic}")?;
-            }
-            write!(f, ">")?;
+        if self.is_unsafe {
+            write!(f, "unsafe ")?;
         }
+        #[cfg(feature = "unstable-syntax")]
+        if self.is_auto {
+            write!(f, "auto ")?;
+        }
+        write!(f, "trait {}{}", self.ident, self.generics)?;
         if !self.supertraits.is_empty() {
             write!(f, ": ")?;
             for (i, sup) in self.supertraits.iter().enumerate() {
@@ -2404,7 +3143,11 @@ impl fmt::Display for TraitDef {
                 write!(f, "{sup}")?;
             }
         }
-        write!(f, " {{")?;
+        write_where_clause(f, &self.generics.where_clause)?;
+        if self.items.is_empty() {
+            return write!(f, " {{}}");
+        }
+        writeln!(f, " {{")?;
         let mut indent = indenter::indented(f).with_str("    ");
         for item in self.items.iter() {
             writeln!(indent, "{item}")?;
@@ -2416,18 +3159,16 @@ impl fmt::Display for TraitDef {
 impl From<TraitDef> for TokenStream {
     fn from(value: TraitDef) -> Self {
         let mut ts = TokenStream::new();
+        if value.is_unsafe {
+            ts.push(Token::Keyword(KeywordToken::Unsafe));
+        }
+        #[cfg(feature = "unstable-syntax")]
+        if value.is_auto {
+            ts.push(Token::ident("auto"));
+        }
         ts.push(Token::Keyword(KeywordToken::Trait));
         ts.push(Token::ident(value.ident));
-        if !value.generics.is_empty() {
-            ts.push(Token::Lt.into_joint());
-            for (i, generic) in value.generics.iter().enumerate() {
-                if i != 0 {
-                    ts.push(Token::Comma);
-                }
-                ts.extend(TokenStream::from(generic.clone()).into_joint());
-            }
-            ts.push(Token::Gt);
-        }
+        value.generics.push_params(&mut ts);
         if !value.supertraits.is_empty() {
             ts.push(Token::Colon);
             for (i, sup) in value.supertraits.iter().enumerate() {
@@ -2437,6 +3178,7 @@ impl From<TraitDef> for TokenStream {
                 ts.extend(TokenStream::from(sup.clone()));
             }
         }
+        ts.extend(TokenStream::from(value.generics.where_clause));
         ts.push(Token::OpenDelim(Delimiter::Brace));
         for item in value.items.iter() {
             ts.extend(TokenStream::from(item.clone()));
@@ -2480,8 +3222,11 @@ impl TraitDef {
         items: Vec<AssocItem>,
     ) -> Self {
         Self {
+            is_unsafe: false,
+            #[cfg(feature = "unstable-syntax")]
+            is_auto: false,
             ident: ident.into(),
-            generics,
+            generics: Generics::new(generics),
             supertraits,
             items,
         }
@@ -2505,24 +3250,140 @@ impl TraitDef {
     }
 
     pub fn add_generic_param(&mut self, param: GenericParam) {
-        self.generics.push(param);
+        self.generics.add_param(param);
     }
 
     pub fn with_generic_param(mut self, param: GenericParam) -> Self {
         self.add_generic_param(param);
         self
     }
+
+    pub fn add_where_clause(&mut self, clause: WherePredicate) {
+        self.generics.add_predicate(clause);
+    }
+
+    pub fn with_where_clause(mut self, clause: WherePredicate) -> Self {
+        self.add_where_clause(clause);
+        self
+    }
+
+    /// Marks the trait `unsafe trait`, whose implementations must be `unsafe impl`.
+    pub fn set_unsafe(&mut self, is_unsafe: bool) {
+        self.is_unsafe = is_unsafe;
+    }
+
+    pub fn with_unsafe(mut self, is_unsafe: bool) -> Self {
+        self.set_unsafe(is_unsafe);
+        self
+    }
+
+    #[cfg(feature = "unstable-syntax")]
+    pub fn set_auto(&mut self, is_auto: bool) {
+        self.is_auto = is_auto;
+    }
+
+    #[cfg(feature = "unstable-syntax")]
+    pub fn with_auto(mut self, is_auto: bool) -> Self {
+        self.set_auto(is_auto);
+        self
+    }
+
+    /// Declares `type ident: bounds;`.
+    pub fn add_assoc_type(
+        &mut self,
+        ident: impl Into<String>,
+        bounds: Vec<GenericBound>,
+    ) -> ItemIndex {
+        self.add_item(TyAlias::declaration(ident, bounds))
+    }
+
+    pub fn with_assoc_type(mut self, ident: impl Into<String>, bounds: Vec<GenericBound>) -> Self {
+        self.add_assoc_type(ident, bounds);
+        self
+    }
+
+    /// Declares `const ident: ty;`, or `const ident: ty = default;`.
+    pub fn add_assoc_const(
+        &mut self,
+        ident: impl Into<String>,
+        ty: impl Into<Type>,
+        default: Option<Expr>,
+    ) -> ItemIndex {
+        self.add_item(ConstItem::new(ident, ty, default))
+    }
+
+    pub fn with_assoc_const(
+        mut self,
+        ident: impl Into<String>,
+        ty: impl Into<Type>,
+        default: Option<Expr>,
+    ) -> Self {
+        self.add_assoc_const(ident, ty, default);
+        self
+    }
+
+    /// Declares `fn ident(..) -> ..;`, a method implementors must define.
+    pub fn add_required_fn(&mut self, ident: impl Into<String>, fn_decl: FnDecl) -> ItemIndex {
+        self.add_item(Fn::foreign(ident, fn_decl))
+    }
+
+    pub fn with_required_fn(mut self, ident: impl Into<String>, fn_decl: FnDecl) -> Self {
+        self.add_required_fn(ident, fn_decl);
+        self
+    }
+
+    /// Defines `fn ident(..) -> .. { body }`, a method with a default implementation.
+    pub fn add_provided_fn(
+        &mut self,
+        ident: impl Into<String>,
+        fn_decl: FnDecl,
+        body: Block,
+    ) -> ItemIndex {
+        self.add_item(Fn::simple(ident, fn_decl, body))
+    }
+
+    pub fn with_provided_fn(
+        mut self,
+        ident: impl Into<String>,
+        fn_decl: FnDecl,
+        body: Block,
+    ) -> Self {
+        self.add_provided_fn(ident, fn_decl, body);
+        self
+    }
+
+    /// Seals the trait so that only `impls` can implement it:
+    ///
+    /// `mod private { pub trait Sealed {} }`, `impl private::Sealed for Ty {}` for each of
+    /// `impls`, and `pub trait Ident: private::Sealed { .. }`.
+    /// The module is always named `private`, so seal at most one trait per module.
+    pub fn sealed(mut self, impls: Vec<Type>) -> Vec<Item> {
+        let sealed = || Type::Path(Path::single("private").chain("Sealed"));
+        let mut items = vec![Item::inherited(Mod::new(
+            "private",
+            vec![Item::public(TraitDef::empty("Sealed"))],
+        ))];
+        for ty in impls {
+            items.push(Item::inherited(Impl::trait_for(sealed(), ty)));
+        }
+        self.supertraits.insert(0, sealed());
+        items.push(Item::public(self));
+        items
+    }
 }
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PredicateType {
+    /// `for<'a>`, the lifetimes bound for the predicate.
+    pub bound_generic_params: Vec<GenericParam>,
     pub bounded_ty: Type,
-    pub bounds: Vec<Type>,
+    pub bounds: Vec<GenericBound>,
 }
 
 impl fmt::Display for PredicateType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_binder(f, &self.bound_generic_params)?;
         write!(f, "{bounded_ty}: ", bounded_ty = self.bounded_ty)?;
         for (i, bound) in self.bounds.iter().enumerate() {
             if i != 0 {
@@ -2537,6 +3398,7 @@ impl fmt::Display for PredicateType {
 impl From<PredicateType> for TokenStream {
     fn from(value: PredicateType) -> Self {
         let mut ts = TokenStream::new();
+        push_binder(&mut ts, value.bound_generic_params);
         ts.extend(TokenStream::from(value.bounded_ty).into_joint());
         ts.push(Token::Colon);
         for (i, bound) in value.bounds.iter().enumerate() {
@@ -2550,18 +3412,28 @@ impl From<PredicateType> for TokenStream {
 }
 
 impl PredicateType {
-    pub fn new(bounded_ty: impl Into<Type>, bounds: Vec<Type>) -> Self {
+    pub fn new(bounded_ty: impl Into<Type>, bounds: Vec<GenericBound>) -> Self {
         Self {
+            bound_generic_params: vec![],
             bounded_ty: bounded_ty.into(),
             bounds,
         }
     }
 
-    pub fn add_bound(&mut self, bound: impl Into<Type>) {
+    pub fn add_bound_generic_param(&mut self, param: GenericParam) {
+        self.bound_generic_params.push(param);
+    }
+
+    pub fn with_bound_generic_param(mut self, param: GenericParam) -> Self {
+        self.add_bound_generic_param(param);
+        self
+    }
+
+    pub fn add_bound(&mut self, bound: impl Into<GenericBound>) {
         self.bounds.push(bound.into());
     }
 
-    pub fn with_bound(mut self, bound: impl Into<Type>) -> Self {
+    pub fn with_bound(mut self, bound: impl Into<GenericBound>) -> Self {
         self.add_bound(bound);
         self
     }
@@ -2645,44 +3517,228 @@ impl From<WherePredicate> for TokenStream {
     }
 }
 
+/// `where predicate, ..`, written after the signature of an item.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct WhereClause {
+    pub predicates: Vec<WherePredicate>,
+}
+
+impl fmt::Display for WhereClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return Ok(());
+        }
+        write!(f, "where ")?;
+        for (i, predicate) in self.predicates.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{predicate}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<WhereClause> for TokenStream {
+    fn from(value: WhereClause) -> Self {
+        let mut ts = TokenStream::new();
+        if value.is_empty() {
+            return ts;
+        }
+        ts.push(Token::Keyword(KeywordToken::Where));
+        let len = value.predicates.len();
+        for (i, predicate) in value.predicates.into_iter().enumerate() {
+            if i + 1 == len {
+                ts.extend(TokenStream::from(predicate));
+            } else {
+                ts.extend(TokenStream::from(predicate).into_joint());
+                ts.push(Token::Comma);
+            }
+        }
+        ts
+    }
+}
+
+impl From<Vec<WherePredicate>> for WhereClause {
+    fn from(predicates: Vec<WherePredicate>) -> Self {
+        Self { predicates }
+    }
+}
+
+impl From<Option<Vec<WherePredicate>>> for WhereClause {
+    fn from(predicates: Option<Vec<WherePredicate>>) -> Self {
+        Self::from(predicates.unwrap_or_default())
+    }
+}
+
+impl WhereClause {
+    pub fn new(predicates: Vec<WherePredicate>) -> Self {
+        Self { predicates }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty()
+    }
+
+    pub fn add_predicate(&mut self, predicate: WherePredicate) {
+        self.predicates.push(predicate);
+    }
+
+    pub fn with_predicate(mut self, predicate: WherePredicate) -> Self {
+        self.add_predicate(predicate);
+        self
+    }
+}
+
+/// The generic parameters of an item and its where clause.
+///
+/// Displays as `<param, ..>`; the where clause goes after the signature and is written by the item.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Generics {
+    pub params: Vec<GenericParam>,
+    pub where_clause: WhereClause,
+}
+
+impl fmt::Display for Generics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.params.is_empty() {
+            return Ok(());
+        }
+        write!(f, "<")?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{param}")?;
+        }
+        write!(f, ">")
+    }
+}
+
+impl From<Vec<GenericParam>> for Generics {
+    fn from(params: Vec<GenericParam>) -> Self {
+        Self::new(params)
+    }
+}
+
+impl Generics {
+    pub fn new(params: Vec<GenericParam>) -> Self {
+        Self {
+            params,
+            where_clause: WhereClause::default(),
+        }
+    }
+
+    /// No parameters and no where clause.
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty() && self.where_clause.is_empty()
+    }
+
+    pub fn add_param(&mut self, param: GenericParam) {
+        self.params.push(param);
+    }
+
+    pub fn with_param(mut self, param: GenericParam) -> Self {
+        self.add_param(param);
+        self
+    }
+
+    pub fn add_predicate(&mut self, predicate: WherePredicate) {
+        self.where_clause.add_predicate(predicate);
+    }
+
+    pub fn with_predicate(mut self, predicate: WherePredicate) -> Self {
+        self.add_predicate(predicate);
+        self
+    }
+
+    /// Appends `<param, ..>` to `ts`, joined to the name before it.
+    fn push_params(&self, ts: &mut TokenStream) {
+        if self.params.is_empty() {
+            return;
+        }
+        ts.join_last();
+        ts.push(Token::Lt.into_joint());
+        for (i, param) in self.params.iter().enumerate() {
+            if i != 0 {
+                ts.push(Token::Comma);
+            }
+            ts.extend(TokenStream::from(param.clone()).into_joint());
+        }
+        ts.push(Token::Gt);
+    }
+}
+
+/// ` where predicate, ..`, or nothing without predicates.
+fn write_where_clause(f: &mut fmt::Formatter<'_>, where_clause: &WhereClause) -> fmt::Result {
+    if where_clause.is_empty() {
+        return Ok(());
+    }
+    write!(f, " {where_clause}")
+}
+
+/// The fields of a struct or union, with the where clause before braces and after parentheses.
+fn write_fields_with_where(
+    f: &mut fmt::Formatter<'_>,
+    fields: &Fields,
+    where_clause: &WhereClause,
+) -> fmt::Result {
+    match fields {
+        Fields::Tuple(_) => {
+            write!(f, "{fields}")?;
+            write_where_clause(f, where_clause)
+        }
+        Fields::Unit if !where_clause.is_empty() => {
+            write_where_clause(f, where_clause)?;
+            write!(f, " {fields}")
+        }
+        _ => {
+            write_where_clause(f, where_clause)?;
+            write!(f, "{fields}")
+        }
+    }
+}
+
+fn fields_with_where_tokens(fields: Fields, where_clause: WhereClause) -> TokenStream {
+    let mut ts = TokenStream::new();
+    if let Fields::Tuple(_) = fields {
+        ts.extend(TokenStream::from(fields));
+        ts.extend(TokenStream::from(where_clause));
+    } else {
+        ts.extend(TokenStream::from(where_clause));
+        ts.extend(TokenStream::from(fields));
+    }
+    ts
+}
+
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Impl {
-    pub generics: Vec<GenericParam>,
+    pub is_unsafe: bool,
+    pub generics: Generics,
     pub of_trait: Option<Type>,
     pub self_ty: Type,
-    pub where_clauses: Option<Vec<WherePredicate>>,
     pub items: Vec<AssocItem>,
 }
 
 impl fmt::Display for Impl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "impl")?;
-        if !self.generics.is_empty() {
-            write!(f, "<")?;
-            for (i, generic) in self.generics.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{generic}")?;
-            }
-            write!(f, ">")?;
+        if self.is_unsafe {
+            write!(f, "unsafe ")?;
         }
+        write!(f, "impl{}", self.generics)?;
         write!(f, " ")?;
         if let Some(of_trait) = &self.of_trait {
             write!(f, "{of_trait} for ")?;
         }
-        write!(f, "{self_ty} ", self_ty = self.self_ty)?;
-        if let Some(where_clauses) = &self.where_clauses {
-            write!(f, "where ")?;
-            for (i, clause) in where_clauses.iter().enumerate() {
-                if i != 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{clause}")?;
-            }
+        write!(f, "{self_ty}", self_ty = self.self_ty)?;
+        write_where_clause(f, &self.generics.where_clause)?;
+        if self.items.is_empty() {
+            return write!(f, " {{}}");
         }
-        writeln!(f, "{{")?;
+        writeln!(f, " {{")?;
         let mut indent = indenter::indented(f).with_str("    ");
         for item in self.items.iter() {
             writeln!(indent, "{item}")?;
@@ -2694,31 +3750,17 @@ impl fmt::Display for Impl {
 impl From<Impl> for TokenStream {
     fn from(value: Impl) -> Self {
         let mut ts = TokenStream::new();
-        ts.push(Token::Keyword(KeywordToken::Impl));
-        if !value.generics.is_empty() {
-            ts.push(Token::Lt.into_joint());
-            for (i, generic) in value.generics.iter().enumerate() {
-                if i != 0 {
-                    ts.push(Token::Comma);
-                }
-                ts.extend(TokenStream::from(generic.clone()).into_joint());
-            }
-            ts.push(Token::Gt);
+        if value.is_unsafe {
+            ts.push(Token::Keyword(KeywordToken::Unsafe));
         }
+        ts.push(Token::Keyword(KeywordToken::Impl));
+        value.generics.push_params(&mut ts);
         if let Some(of_trait) = value.of_trait {
             ts.extend(TokenStream::from(of_trait));
             ts.push(Token::Keyword(KeywordToken::For));
         }
         ts.extend(TokenStream::from(value.self_ty));
-        if let Some(where_clauses) = value.where_clauses {
-            ts.push(Token::Keyword(KeywordToken::Where));
-            for (i, clause) in where_clauses.iter().enumerate() {
-                if i != 0 {
-                    ts.push(Token::Comma);
-                }
-                ts.extend(TokenStream::from(clause.clone()).into_joint());
-            }
-        }
+        ts.extend(TokenStream::from(value.generics.where_clause));
         ts.push(Token::OpenDelim(Delimiter::Brace));
         for item in value.items.iter() {
             ts.extend(TokenStream::from(item.clone()));
@@ -2757,10 +3799,13 @@ impl Impl {
         items: Vec<AssocItem>,
     ) -> Self {
         Self {
-            generics,
+            is_unsafe: false,
+            generics: Generics {
+                params: generics,
+                where_clause: where_clauses.into(),
+            },
             of_trait,
             self_ty,
-            where_clauses,
             items,
         }
     }
@@ -2773,26 +3818,34 @@ impl Impl {
         items: Vec<AssocItem>,
     ) -> Self {
         Self {
-            generics,
+            is_unsafe: false,
+            generics: Generics {
+                params: generics,
+                where_clause: where_clauses.into(),
+            },
             of_trait: Some(of_trait),
             self_ty,
-            where_clauses,
             items,
         }
     }
 
     pub fn simple(self_ty: Type, items: Vec<AssocItem>) -> Self {
         Self {
-            generics: vec![],
+            is_unsafe: false,
+            generics: Generics::default(),
             of_trait: None,
             self_ty,
-            where_clauses: None,
             items,
         }
     }
 
+    /// `impl of_trait for self_ty {}`, to be filled with the `with_*` methods.
+    pub fn trait_for(of_trait: impl Into<Type>, self_ty: impl Into<Type>) -> Self {
+        Self::trait_impl(vec![], self_ty.into(), of_trait.into(), None, vec![])
+    }
+
     pub fn add_generic_param(&mut self, param: GenericParam) {
-        self.generics.push(param);
+        self.generics.add_param(param);
     }
 
     pub fn with_generic_param(mut self, param: GenericParam) -> Self {
@@ -2801,17 +3854,99 @@ impl Impl {
     }
 
     pub fn add_where_clause(&mut self, clause: WherePredicate) {
-        if let Some(clauses) = &mut self.where_clauses {
-            clauses.push(clause);
-        } else {
-            self.where_clauses = Some(vec![clause]);
-        }
+        self.generics.add_predicate(clause);
     }
 
     pub fn with_where_clause(mut self, clause: WherePredicate) -> Self {
         self.add_where_clause(clause);
         self
     }
+
+    /// Marks the impl `unsafe impl`, as implementations of unsafe traits such as `Send` and
+    /// `Sync` must be.
+    pub fn set_unsafe(&mut self, is_unsafe: bool) {
+        self.is_unsafe = is_unsafe;
+    }
+
+    pub fn with_unsafe(mut self, is_unsafe: bool) -> Self {
+        self.set_unsafe(is_unsafe);
+        self
+    }
+
+    /// Defines `type ident = ty;`.
+    pub fn add_assoc_type(&mut self, ident: impl Into<String>, ty: impl Into<Type>) -> ItemIndex {
+        self.add_item(TyAlias::new(ident, ty))
+    }
+
+    pub fn with_assoc_type(mut self, ident: impl Into<String>, ty: impl Into<Type>) -> Self {
+        self.add_assoc_type(ident, ty);
+        self
+    }
+
+    /// Defines `const ident: ty = expr;`.
+    pub fn add_assoc_const(
+        &mut self,
+        ident: impl Into<String>,
+        ty: impl Into<Type>,
+        expr: impl Into<Expr>,
+    ) -> ItemIndex {
+        self.add_item(ConstItem::new(ident, ty, Some(expr.into())))
+    }
+
+    pub fn with_assoc_const(
+        mut self,
+        ident: impl Into<String>,
+        ty: impl Into<Type>,
+        expr: impl Into<Expr>,
+    ) -> Self {
+        self.add_assoc_const(ident, ty, expr);
+        self
+    }
+
+    /// Defines `fn ident(..) -> .. { body }`.
+    pub fn add_fn(&mut self, ident: impl Into<String>, fn_decl: FnDecl, body: Block) -> ItemIndex {
+        self.add_item(Fn::simple(ident, fn_decl, body))
+    }
+
+    pub fn with_fn(mut self, ident: impl Into<String>, fn_decl: FnDecl, body: Block) -> Self {
+        self.add_fn(ident, fn_decl, body);
+        self
+    }
+
+    /// `impl self_ty {}`, to be filled with the fluent methods below or the `with_*` methods.
+    pub fn builder(self_ty: impl Into<Type>) -> Self {
+        Self::empty(self_ty)
+    }
+
+    /// Alias of [`Impl::with_generic_param`].
+    pub fn generic(self, param: impl Into<GenericParam>) -> Self {
+        self.with_generic_param(param.into())
+    }
+
+    /// Alias of [`Impl::with_where_clause`].
+    pub fn where_(self, clause: WherePredicate) -> Self {
+        self.with_where_clause(clause)
+    }
+
+    /// Alias of [`Impl::with_fn`].
+    pub fn fn_(self, ident: impl Into<String>, fn_decl: FnDecl, body: Block) -> Self {
+        self.with_fn(ident, fn_decl, body)
+    }
+
+    /// Alias of [`Impl::with_assoc_type`].
+    pub fn assoc_type(self, ident: impl Into<String>, ty: impl Into<Type>) -> Self {
+        self.with_assoc_type(ident, ty)
+    }
+
+    /// Alias of [`Impl::with_assoc_const`].
+    pub fn assoc_const(
+        self,
+        ident: impl Into<String>,
+        ty: impl Into<Type>,
+        expr: impl Into<Expr>,
+    ) -> Self {
+        self.with_assoc_const(ident, ty, expr)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -2879,6 +4014,205 @@ impl MacroDef {
     pub fn empty(ident: impl Into<String>) -> Self {
         Self::new(ident, DelimArgs::default())
     }
+
+    /// `macro_rules! ident { rule; rule; .. }`
+    pub fn with_rules(ident: impl Into<String>, rules: Vec<MacroRule>) -> Self {
+        let mut def = Self::new(ident, DelimArgs::brace(TokenStream::new()));
+        for rule in rules {
+            def.add_rule(rule);
+        }
+        def
+    }
+
+    /// Appends a rule to the body, which is expected to be a list of rules.
+    pub fn add_rule(&mut self, rule: MacroRule) {
+        let tokens = &mut self.args.tokens;
+        if tokens
+            .last()
+            .is_some_and(|last| *last.as_unjoint() != Token::Semi)
+        {
+            tokens.push(Token::Semi);
+        }
+        tokens.extend(TokenStream::from(rule));
+        tokens.push(Token::Semi);
+    }
+
+    pub fn with_rule(mut self, rule: MacroRule) -> Self {
+        self.add_rule(rule);
+        self
+    }
+
+    /// `#[macro_export] macro_rules! ident { .. }`, usable from other crates as `krate::ident!`.
+    pub fn exported(self) -> Item {
+        Item::inherited(self).with_attr(Attribute::normal(AttributeItem::simple(Path::single(
+            "macro_export",
+        ))))
+    }
+}
+
+/// The kind of a macro metavariable, e.g. `expr` in `$x:expr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FragmentSpecifier {
+    Block,
+    Expr,
+    Ident,
+    Item,
+    Lifetime,
+    Literal,
+    Meta,
+    Pat,
+    PatParam,
+    Path,
+    Stmt,
+    Tt,
+    Ty,
+    Vis,
+}
+
+impl fmt::Display for FragmentSpecifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FragmentSpecifier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Block => "block",
+            Self::Expr => "expr",
+            Self::Ident => "ident",
+            Self::Item => "item",
+            Self::Lifetime => "lifetime",
+            Self::Literal => "literal",
+            Self::Meta => "meta",
+            Self::Pat => "pat",
+            Self::PatParam => "pat_param",
+            Self::Path => "path",
+            Self::Stmt => "stmt",
+            Self::Tt => "tt",
+            Self::Ty => "ty",
+            Self::Vis => "vis",
+        }
+    }
+}
+
+/// `*`, `+` or `?` after a macro repetition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RepetitionOp {
+    ZeroOrMore,
+    OneOrMore,
+    ZeroOrOne,
+}
+
+impl From<RepetitionOp> for Token {
+    fn from(value: RepetitionOp) -> Self {
+        match value {
+            RepetitionOp::ZeroOrMore => Token::BinOp(BinOpToken::Star),
+            RepetitionOp::OneOrMore => Token::BinOp(BinOpToken::Plus),
+            RepetitionOp::ZeroOrOne => Token::Question,
+        }
+    }
+}
+
+/// `(matcher) => { transcriber }`, a rule of a [`MacroDef`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MacroRule {
+    pub matcher: TokenStream,
+    pub transcriber: TokenStream,
+}
+
+impl fmt::Display for MacroRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", TokenStream::from(self.clone()))
+    }
+}
+
+impl From<MacroRule> for TokenStream {
+    fn from(value: MacroRule) -> Self {
+        let mut ts = TokenStream::from(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+        ts.extend(value.matcher.into_joint());
+        ts.push(Token::CloseDelim(Delimiter::Parenthesis));
+        ts.push(Token::FatArrow);
+        ts.push(Token::OpenDelim(Delimiter::Brace));
+        ts.extend(value.transcriber);
+        ts.push(Token::CloseDelim(Delimiter::Brace).into_joint());
+        ts
+    }
+}
+
+impl MacroRule {
+    pub fn new(matcher: impl Into<TokenStream>, transcriber: impl Into<TokenStream>) -> Self {
+        Self {
+            matcher: matcher.into(),
+            transcriber: transcriber.into(),
+        }
+    }
+
+    /// `$name:kind`, a metavariable in a matcher.
+    pub fn fragment(name: impl Into<String>, kind: FragmentSpecifier) -> TokenStream {
+        TokenStream::from(vec![
+            Token::Dollar.into_joint(),
+            Token::ident(name).into_joint(),
+            Token::Colon.into_joint(),
+            Token::ident(kind.as_str()),
+        ])
+    }
+
+    /// `$name`, a metavariable in a transcriber.
+    pub fn var(name: impl Into<String>) -> TokenStream {
+        TokenStream::from(vec![Token::Dollar.into_joint(), Token::ident(name)])
+    }
+
+    /// `$(tokens) separator op`, e.g. `$($x:expr),*`
+    pub fn repetition(
+        tokens: impl Into<TokenStream>,
+        separator: Option<Token>,
+        op: RepetitionOp,
+    ) -> TokenStream {
+        let mut ts = TokenStream::from(Token::Dollar.into_joint());
+        ts.extend(TokenStream::from(DelimArgs::parenthesis(tokens.into())));
+        if let Some(separator) = separator {
+            ts.push(separator.into_joint());
+        }
+        ts.push(op.into());
+        ts
+    }
+}
+
+/// `extern "abi"`, the ABI of an [`Fn`] or [`ExternBlock`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Abi(pub String);
+
+impl fmt::Display for Abi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "extern \"{}\"", self.0)
+    }
+}
+
+impl From<Abi> for TokenStream {
+    fn from(value: Abi) -> Self {
+        TokenStream::from(vec![
+            Token::Keyword(KeywordToken::Extern),
+            Token::Lit(Lit::str(value.0)),
+        ])
+    }
+}
+
+impl From<Abi> for String {
+    fn from(value: Abi) -> Self {
+        value.0
+    }
+}
+
+impl Abi {
+    pub fn new(abi: impl Into<String>) -> Self {
+        Self(abi.into())
+    }
+
+    /// `extern "C"`
+    pub fn c() -> Self {
+        Self::new("C")
+    }
 }
 
 /// `extern unsafe? "abi"? { ... }`
@@ -2906,8 +4240,8 @@ impl fmt::Display for ExternBlock {
             write!(f, "unsafe ")?;
         }
         write!(f, "extern ")?;
-        if self.abi.is_some() {
-            write!(f, "\"{}\" ", self.abi.as_ref().unwrap())?;
+        if let Some(abi) = &self.abi {
+            write!(f, "\"{abi}\" ")?;
         }
         write!(f, "{}", self.block)
     }
@@ -2920,8 +4254,8 @@ impl From<ExternBlock> for TokenStream {
             ts.push(Token::Keyword(KeywordToken::Unsafe));
         }
         ts.push(Token::Keyword(KeywordToken::Extern));
-        if value.abi.is_some() {
-            ts.push(Token::Lit(Lit::str(value.abi.unwrap())));
+        if let Some(abi) = value.abi {
+            ts.push(Token::Lit(Lit::str(abi)));
         }
         ts.extend(TokenStream::from(value.block));
         ts
@@ -2953,6 +4287,16 @@ impl ExternBlock {
         ExternBlock::new(true, abi, block)
     }
 
+    /// Declares a foreign item, e.g. a [`Fn`] without a body or a [`StaticItem::foreign`].
+    pub fn add_item(&mut self, item: impl Into<Item>) {
+        self.block.add_stmt(item.into());
+    }
+
+    pub fn with_item(mut self, item: impl Into<Item>) -> Self {
+        self.add_item(item);
+        self
+    }
+
     pub fn unsafe_c(block: Block) -> ExternBlock {
         ExternBlock::unsafe_(Some("C"), block)
     }
@@ -2996,6 +4340,31 @@ impl ExternBlock {
     pub fn unsafe_vectorcall(block: Block) -> ExternBlock {
         ExternBlock::unsafe_(Some("vectorcall"), block)
     }
+
+    /// `#[cfg(predicate)] extern "abi" { ... }` followed by `#[cfg(not(predicate))] extern "fallback_abi" { ... }`,
+    /// both declaring the items of this block.
+    pub fn per_target(
+        self,
+        predicate: impl Into<TokenStream>,
+        fallback_abi: impl Into<String>,
+    ) -> [Item; 2] {
+        let predicate = predicate.into();
+        let fallback = ExternBlock {
+            abi: Some(fallback_abi.into()),
+            ..self.clone()
+        };
+        [
+            Item::inherited(self)
+                .with_attr(Attribute::normal(AttributeItem::cfg(predicate.clone()))),
+            Item::inherited(fallback)
+                .with_attr(Attribute::normal(AttributeItem::cfg_not(predicate))),
+        ]
+    }
+
+    /// `#[cfg(windows)] extern "system" { ... }` followed by `#[cfg(not(windows))] extern "C" { ... }`
+    pub fn system_or_c(is_unsafe: bool, block: Block) -> [Item; 2] {
+        ExternBlock::new(is_unsafe, Some("system"), block).per_target(Token::ident("windows"), "C")
+    }
 }
 
 /// `extern crate ident (as alias)?;`
@@ -3283,6 +4652,21 @@ impl<K> Item<K> {
     pub fn remove_attr(&mut self, index: usize) -> Attribute {
         self.attrs.remove(index)
     }
+
+    /// Adds `/// text`, one comment line per line of `text` (see [`Attribute::doc`]).
+    pub fn add_doc(&mut self, text: &str) {
+        self.add_attr(Attribute::doc(text));
+    }
+
+    pub fn with_doc(mut self, text: &str) -> Self {
+        self.add_doc(text);
+        self
+    }
+
+    /// Adds `#[non_exhaustive]`, for structs and enums that may gain fields or variants.
+    pub fn non_exhaustive(self) -> Self {
+        self.with_attr(Attribute::non_exhaustive())
+    }
 }
 
 impl<K: MaybeIdent> Item<K> {
@@ -3309,13 +4693,15 @@ pub enum ItemKind {
     MacroDef(MacroDef),
     ExternBlock(ExternBlock),
     ExternCrate(ExternCrate),
+    #[cfg_attr(feature = "fuzzing", arbitrary(skip))]
+    Verbatim(Verbatim),
 }
 
 impl HasVisibility for ItemKind {
     fn has_visibility(&self) -> bool {
         !matches!(
             self,
-            Self::Impl(_) | Self::MacCallWithSemi(_) | Self::MacroDef(_)
+            Self::Impl(_) | Self::MacCallWithSemi(_) | Self::MacroDef(_) | Self::Verbatim(_)
         )
     }
 }
@@ -3334,8 +4720,8 @@ impl ItemKind {
     }
 }
 
-impl_obvious_conversion!(ItemKind; Use, StaticItem, ConstItem, Fn, Mod, TyAlias, EnumDef, StructDef, UnionDef, TraitDef, Impl, MacroDef, MacCallWithSemi, ExternBlock, ExternCrate);
-impl_display_for_enum!(ItemKind; Use, StaticItem, ConstItem, Fn, Mod, TyAlias, EnumDef, StructDef, UnionDef, TraitDef, Impl, MacroDef, MacCallWithSemi, ExternBlock, ExternCrate);
+impl_obvious_conversion!(ItemKind; Use, StaticItem, ConstItem, Fn, Mod, TyAlias, EnumDef, StructDef, UnionDef, TraitDef, Impl, MacroDef, MacCallWithSemi, ExternBlock, ExternCrate, Verbatim);
+impl_display_for_enum!(ItemKind; Use, StaticItem, ConstItem, Fn, Mod, TyAlias, EnumDef, StructDef, UnionDef, TraitDef, Impl, MacroDef, MacCallWithSemi, ExternBlock, ExternCrate, Verbatim);
 
 impl MaybeIdent for ItemKind {
     fn ident(&self) -> Option<&str> {
@@ -3352,6 +4738,7 @@ impl MaybeIdent for ItemKind {
             Self::TraitDef(item) => Some(&item.ident),
             Self::Impl(_) => None,
             Self::MacCallWithSemi(_) => None,
+            Self::Verbatim(_) => None,
             Self::MacroDef(item) => Some(&item.ident),
             Self::ExternBlock(_) => None,
             Self::ExternCrate(item) => Some(&item.ident),
@@ -3419,6 +4806,10 @@ impl UsePath {
     pub fn tree(&self) -> &UseTree {
         &self.tree
     }
+
+    pub fn tree_mut(&mut self) -> &mut UseTree {
+        &mut self.tree
+    }
 }
 
 /// `ident 'as' alias`
@@ -3608,10 +4999,11 @@ impl Use {
     }
 }
 
-/// `static ident: ty (= expr)?;`
+/// `('safe' | 'unsafe')? static 'mut'? ident: ty (= expr)?;`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StaticItem {
+    pub safety: Safety,
     pub mutability: Mutability,
     pub ident: String,
     pub ty: Type,
@@ -3620,13 +5012,14 @@ pub struct StaticItem {
 
 impl fmt::Display for StaticItem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "static {mutability} {ident}: {ty}",
-            ident = self.ident,
-            mutability = self.mutability,
-            ty = self.ty
-        )?;
+        if self.safety != Safety::Default {
+            write!(f, "{} ", self.safety)?;
+        }
+        write!(f, "static ")?;
+        if self.mutability.is_mut() {
+            write!(f, "mut ")?;
+        }
+        write!(f, "{ident}: {ty}", ident = self.ident, ty = self.ty)?;
         if let Some(expr) = &self.expr {
             write!(f, " = {expr}")?;
         }
@@ -3637,18 +5030,21 @@ impl fmt::Display for StaticItem {
 
 impl From<StaticItem> for TokenStream {
     fn from(value: StaticItem) -> Self {
-        let mut ts = TokenStream::new();
+        let mut ts = TokenStream::from(value.safety);
         ts.push(Token::Keyword(KeywordToken::Static));
         if value.mutability.is_mut() {
             ts.push(Token::Keyword(KeywordToken::Mut));
         }
         ts.push(Token::ident(value.ident).into_joint());
         ts.push(Token::Colon);
-        ts.extend(TokenStream::from(value.ty));
         if let Some(expr) = value.expr {
+            ts.extend(TokenStream::from(value.ty));
             ts.push(Token::Eq);
-            ts.extend(TokenStream::from(expr));
+            ts.extend(TokenStream::from(expr).into_joint());
+        } else {
+            ts.extend(TokenStream::from(value.ty).into_joint());
         }
+        ts.push(Token::Semi);
         ts
     }
 }
@@ -3659,10 +5055,52 @@ impl Ident for StaticItem {
     }
 }
 
+impl StaticItem {
+    pub fn new(ident: impl Into<String>, ty: impl Into<Type>, expr: Option<Expr>) -> Self {
+        Self {
+            safety: Safety::Default,
+            mutability: Mutability::Not,
+            ident: ident.into(),
+            ty: ty.into(),
+            expr,
+        }
+    }
+
+    /// `static mut ident: ty (= expr)?;`
+    pub fn mut_(ident: impl Into<String>, ty: impl Into<Type>, expr: Option<Expr>) -> Self {
+        Self {
+            mutability: Mutability::Mut,
+            ..Self::new(ident, ty, expr)
+        }
+    }
+
+    /// `static ident: ty;`, a static declared in an [`ExternBlock`].
+    pub fn foreign(ident: impl Into<String>, ty: impl Into<Type>) -> Self {
+        Self::new(ident, ty, None)
+    }
+
+    /// `unsafe static ident: ty;`, a foreign static that must be accessed in an `unsafe` block.
+    pub fn unsafe_foreign(ident: impl Into<String>, ty: impl Into<Type>) -> Self {
+        Self::foreign(ident, ty).with_safety(Safety::Unsafe)
+    }
+
+    pub fn set_safety(&mut self, safety: Safety) {
+        self.safety = safety;
+    }
+
+    pub fn with_safety(mut self, safety: Safety) -> Self {
+        self.set_safety(safety);
+        self
+    }
+}
+
 /// `const ident: ty (= expr)?;`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ConstItem {
+    /// `default const`, a specializable item of an impl (unstable `specialization`).
+    #[cfg(feature = "unstable-syntax")]
+    pub is_default: bool,
     pub ident: String,
     pub ty: Type,
     pub expr: Option<Expr>,
@@ -3670,6 +5108,10 @@ pub struct ConstItem {
 
 impl fmt::Display for ConstItem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "unstable-syntax")]
+        if self.is_default {
+            write!(f, "default ")?;
+        }
         write!(f, "const {ident}: {ty}", ident = self.ident, ty = self.ty)?;
         if let Some(expr) = &self.expr {
             write!(f, " = {expr}")?;
@@ -3682,14 +5124,21 @@ impl fmt::Display for ConstItem {
 impl From<ConstItem> for TokenStream {
     fn from(value: ConstItem) -> Self {
         let mut ts = TokenStream::new();
+        #[cfg(feature = "unstable-syntax")]
+        if value.is_default {
+            ts.push(Token::ident("default"));
+        }
         ts.push(Token::Keyword(KeywordToken::Const));
         ts.push(Token::ident(value.ident).into_joint());
         ts.push(Token::Colon);
-        ts.extend(TokenStream::from(value.ty));
         if let Some(expr) = value.expr {
+            ts.extend(TokenStream::from(value.ty));
             ts.push(Token::Eq);
-            ts.extend(TokenStream::from(expr));
+            ts.extend(TokenStream::from(expr).into_joint());
+        } else {
+            ts.extend(TokenStream::from(value.ty).into_joint());
         }
+        ts.push(Token::Semi);
         ts
     }
 }
@@ -3703,27 +5152,64 @@ impl Ident for ConstItem {
 impl ConstItem {
     pub fn new(ident: impl Into<String>, ty: impl Into<Type>, expr: Option<Expr>) -> Self {
         Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
             ident: ident.into(),
             ty: ty.into(),
             expr,
         }
     }
+
+    /// `const ident: ty;`, an associated const a trait declares without a default value.
+    pub fn declaration(ident: impl Into<String>, ty: impl Into<Type>) -> Self {
+        Self::new(ident, ty, None)
+    }
+
+    #[cfg(feature = "unstable-syntax")]
+    pub fn set_default(&mut self, is_default: bool) {
+        self.is_default = is_default;
+    }
+
+    #[cfg(feature = "unstable-syntax")]
+    pub fn with_default(mut self, is_default: bool) -> Self {
+        self.set_default(is_default);
+        self
+    }
 }
 
-/// `type ident = ty;`
+/// `'default'? type ident<generics>: bounds = ty where predicates;`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TyAlias {
+    /// `default type`, a specializable item of an impl (unstable `specialization`).
+    #[cfg(feature = "unstable-syntax")]
+    pub is_default: bool,
     pub ident: String,
+    pub generics: Generics,
+    pub bounds: Vec<GenericBound>,
     pub ty: Option<Type>,
 }
 
 impl fmt::Display for TyAlias {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "type {ident}", ident = self.ident)?;
+        #[cfg(feature = "unstable-syntax")]
+        if self.is_default {
+            write!(f, "default ")?;
+        }
+        write!(f, "type {}{}", self.ident, self.generics)?;
+        if !self.bounds.is_empty() {
+            write!(f, ": ")?;
+            for (i, bound) in self.bounds.iter().enumerate() {
+                if i != 0 {
+                    write!(f, " + ")?;
+                }
+                write!(f, "{bound}")?;
+            }
+        }
         if let Some(ty) = &self.ty {
             write!(f, " = {ty}")?;
         }
+        write_where_clause(f, &self.generics.where_clause)?;
         write!(f, ";")?;
         Ok(())
     }
@@ -3732,12 +5218,32 @@ impl fmt::Display for TyAlias {
 impl From<TyAlias> for TokenStream {
     fn from(value: TyAlias) -> Self {
         let mut ts = TokenStream::new();
+        #[cfg(feature = "unstable-syntax")]
+        if value.is_default {
+            ts.push(Token::ident("default"));
+        }
         ts.push(Token::Keyword(KeywordToken::Type));
         ts.push(Token::ident(value.ident));
+        value.generics.push_params(&mut ts);
+        if !value.bounds.is_empty() {
+            ts.join_last();
+        }
+        if !value.bounds.is_empty() {
+            ts.push(Token::Colon);
+            for (i, bound) in value.bounds.into_iter().enumerate() {
+                if i != 0 {
+                    ts.push(Token::BinOp(BinOpToken::Plus));
+                }
+                ts.extend(TokenStream::from(bound));
+            }
+        }
         if let Some(ty) = value.ty {
             ts.push(Token::Eq);
             ts.extend(TokenStream::from(ty));
         }
+        ts.extend(TokenStream::from(value.generics.where_clause));
+        let mut ts = ts.into_joint();
+        ts.push(Token::Semi);
         ts
     }
 }
@@ -3748,6 +5254,70 @@ impl Ident for TyAlias {
     }
 }
 
+impl TyAlias {
+    /// `type ident = ty;`, a free alias or the definition of an associated type in an impl.
+    pub fn new(ident: impl Into<String>, ty: impl Into<Type>) -> Self {
+        Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
+            ident: ident.into(),
+            generics: Generics::default(),
+            bounds: vec![],
+            ty: Some(ty.into()),
+        }
+    }
+
+    /// `type ident: bounds;`, the declaration of an associated type in a trait.
+    pub fn declaration(ident: impl Into<String>, bounds: Vec<GenericBound>) -> Self {
+        Self {
+            #[cfg(feature = "unstable-syntax")]
+            is_default: false,
+            ident: ident.into(),
+            generics: Generics::default(),
+            bounds,
+            ty: None,
+        }
+    }
+
+    pub fn add_generic_param(&mut self, param: GenericParam) {
+        self.generics.add_param(param);
+    }
+
+    pub fn with_generic_param(mut self, param: GenericParam) -> Self {
+        self.add_generic_param(param);
+        self
+    }
+
+    pub fn add_bound(&mut self, bound: GenericBound) {
+        self.bounds.push(bound);
+    }
+
+    pub fn with_bound(mut self, bound: GenericBound) -> Self {
+        self.add_bound(bound);
+        self
+    }
+
+    pub fn add_where_clause(&mut self, clause: WherePredicate) {
+        self.generics.add_predicate(clause);
+    }
+
+    pub fn with_where_clause(mut self, clause: WherePredicate) -> Self {
+        self.add_where_clause(clause);
+        self
+    }
+
+    #[cfg(feature = "unstable-syntax")]
+    pub fn set_default(&mut self, is_default: bool) {
+        self.is_default = is_default;
+    }
+
+    #[cfg(feature = "unstable-syntax")]
+    pub fn with_default(mut self, is_default: bool) -> Self {
+        self.set_default(is_default);
+        self
+    }
+}
+
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AssocItemKind {
@@ -3806,16 +5376,16 @@ impl From<Empty> for TokenStream {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Semi<E = Expr>(pub E);
 
-impl<E: fmt::Display> fmt::Display for Semi<E> {
+impl fmt::Display for Semi {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{};", self.0)
     }
 }
 
-impl<E: Into<TokenStream>> From<Semi<E>> for TokenStream {
-    fn from(value: Semi<E>) -> Self {
+impl From<Semi> for TokenStream {
+    fn from(value: Semi) -> Self {
         let mut ts = TokenStream::new();
-        ts.extend(value.0.into().into_joint());
+        ts.extend(TokenStream::from(value.0).into_joint());
         ts.push(Token::Semi);
         ts
     }
@@ -3827,8 +5397,31 @@ impl Semi {
     }
 }
 
+/// `path!(..);`, `path![..];` or `path! {..}`
 pub type MacCallWithSemi = Semi<MacCall>;
 
+impl fmt::Display for MacCallWithSemi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        if self.0.needs_semi() {
+            write!(f, ";")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<MacCallWithSemi> for TokenStream {
+    fn from(value: MacCallWithSemi) -> Self {
+        if !value.0.needs_semi() {
+            return TokenStream::from(value.0);
+        }
+        let mut ts = TokenStream::new();
+        ts.extend(TokenStream::from(value.0).into_joint());
+        ts.push(Token::Semi);
+        ts
+    }
+}
+
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Stmt {
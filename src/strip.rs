@@ -0,0 +1,190 @@
+use crate::visit::{self, VisitMut};
+use crate::*;
+
+impl Attribute {
+    /// The path of a normal attribute, e.g. `cfg` in `#[cfg(test)]`.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.kind {
+            AttrKind::Normal(item) => Some(&item.path),
+            AttrKind::DocComment(_) => None,
+        }
+    }
+
+    /// A doc comment, `#[doc = ".."]` or `#[doc(..)]`.
+    pub fn is_doc(&self) -> bool {
+        match &self.kind {
+            AttrKind::Normal(item) => item.path == Path::single("doc"),
+            AttrKind::DocComment(_) => true,
+        }
+    }
+
+    /// `#[test]` or `#[cfg(test)]`.
+    pub fn is_test_only(&self) -> bool {
+        let AttrKind::Normal(item) = &self.kind else {
+            return false;
+        };
+        if item.path == Path::single("test") {
+            return true;
+        }
+        match &item.args {
+            AttrArgs::Delimited(args) if item.path == Path::single("cfg") => {
+                args.tokens.to_string().trim() == "test"
+            }
+            _ => false,
+        }
+    }
+}
+
+/// What [`Crate::render_stripped`] leaves out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StripOptions {
+    /// Doc comments and `#[doc]` attributes.
+    pub docs: bool,
+    /// Items marked `#[test]` or `#[cfg(test)]`.
+    pub test_items: bool,
+}
+
+struct AttrStripper<F> {
+    filter: F,
+    count: usize,
+}
+
+impl<F: FnMut(&Attribute) -> bool> AttrStripper<F> {
+    fn strip(&mut self, attrs: &mut Vec<Attribute>) {
+        let len = attrs.len();
+        attrs.retain(|attr| !(self.filter)(attr));
+        self.count += len - attrs.len();
+    }
+
+    fn strip_items(&mut self, attrs: &mut Vec<AttributeItem>) {
+        let len = attrs.len();
+        attrs.retain(|item| !(self.filter)(&Attribute::normal(item.clone())));
+        self.count += len - attrs.len();
+    }
+
+    fn strip_fields(&mut self, fields: &mut Fields) {
+        if let Fields::Tuple(fields) | Fields::Struct(fields) = fields {
+            for field in fields.iter_mut() {
+                self.strip(&mut field.attrs);
+            }
+        }
+    }
+}
+
+impl<F: FnMut(&Attribute) -> bool> VisitMut for AttrStripper<F> {
+    fn visit_crate_mut(&mut self, krate: &mut Crate) {
+        self.strip(&mut krate.attrs);
+        visit::walk_crate_mut(self, krate);
+    }
+
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        self.strip(&mut item.attrs);
+        match &mut item.kind {
+            ItemKind::StructDef(StructDef { fields, .. })
+            | ItemKind::UnionDef(UnionDef { fields, .. }) => self.strip_fields(fields),
+            ItemKind::EnumDef(def) => {
+                for variant in def.variants.iter_mut() {
+                    self.strip(&mut variant.attrs);
+                    self.strip_fields(&mut variant.fields);
+                }
+            }
+            _ => {}
+        }
+        visit::walk_item_mut(self, item);
+    }
+
+    fn visit_assoc_item_mut(&mut self, item: &mut AssocItem) {
+        self.strip(&mut item.attrs);
+        visit::walk_assoc_item_mut(self, item);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        self.strip(&mut block.attrs);
+        visit::walk_block_mut(self, block);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        self.strip_items(&mut expr.attrs);
+        if let ExprKind::Match(match_) = &mut expr.kind {
+            for arm in match_.arms.iter_mut() {
+                self.strip_items(&mut arm.attrs);
+            }
+        }
+        visit::walk_expr_mut(self, expr);
+    }
+}
+
+#[derive(Default)]
+struct TestItemStripper {
+    count: usize,
+}
+
+impl TestItemStripper {
+    fn retain<T>(&mut self, items: &mut Vec<T>, mut attrs: impl FnMut(&T) -> Option<&[Attribute]>) {
+        let len = items.len();
+        items.retain(|item| {
+            !attrs(item).is_some_and(|attrs| attrs.iter().any(Attribute::is_test_only))
+        });
+        self.count += len - items.len();
+    }
+}
+
+impl VisitMut for TestItemStripper {
+    fn visit_crate_mut(&mut self, krate: &mut Crate) {
+        self.retain(&mut krate.items, |item| Some(&item.attrs));
+        visit::walk_crate_mut(self, krate);
+    }
+
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        match &mut item.kind {
+            ItemKind::Mod(Mod::Loaded(module)) => {
+                self.retain(&mut module.items, |item| Some(&item.attrs))
+            }
+            ItemKind::Impl(impl_) => self.retain(&mut impl_.items, |item| Some(&item.attrs)),
+            ItemKind::TraitDef(def) => self.retain(&mut def.items, |item| Some(&item.attrs)),
+            _ => {}
+        }
+        visit::walk_item_mut(self, item);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        self.retain(&mut block.stmts, |stmt| match stmt {
+            Stmt::Item(item) => Some(&item.attrs),
+            _ => None,
+        });
+        visit::walk_block_mut(self, block);
+    }
+}
+
+impl Crate {
+    /// Removes every attribute for which `filter` returns `true`: those of the crate, of items
+    /// and associated items, fields, variants, blocks, expressions and match arms.
+    /// Returns the number of removed attributes.
+    ///
+    /// Attributes of expressions and arms are passed to `filter` as [`Attribute::normal`].
+    pub fn strip_attrs(&mut self, filter: impl FnMut(&Attribute) -> bool) -> usize {
+        let mut stripper = AttrStripper { filter, count: 0 };
+        stripper.visit_crate_mut(self);
+        stripper.count
+    }
+
+    /// Removes the items, associated items and nested items marked `#[test]` or `#[cfg(test)]`.
+    /// Returns the number of removed items.
+    pub fn strip_test_items(&mut self) -> usize {
+        let mut stripper = TestItemStripper::default();
+        stripper.visit_crate_mut(self);
+        stripper.count
+    }
+
+    /// Renders the crate without what `options` selects, leaving the crate itself as it is.
+    pub fn render_stripped(&self, options: StripOptions) -> std::string::String {
+        let mut krate = self.clone();
+        if options.test_items {
+            krate.strip_test_items();
+        }
+        if options.docs {
+            krate.strip_attrs(Attribute::is_doc);
+        }
+        krate.to_string()
+    }
+}
@@ -26,7 +26,7 @@ pub mod depth_limiter {
 
 /// String for fuzzing. Generates only valid strings as identifiers.
 #[cfg(feature = "fuzzing")]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct String(std::string::String);
 
 #[cfg(feature = "fuzzing")]
@@ -498,6 +498,8 @@ pub enum Token {
     Keyword(KeywordToken),
     /// Note that this variant outputs the stored string as it is (without displaying a leading `///`).
     DocComment(String),
+    /// Raw source text, output as it is.
+    Verbatim(String),
     /// When print this variant as an element of a `TokenStream`, it is displayed combined with the following tokens (no spacing).
     Joint(Box<Token>),
     Eof,
@@ -542,6 +544,7 @@ impl fmt::Display for Token {
             Self::Lifetime(lifetime) => write!(f, "'{lifetime}"),
             Self::Keyword(keyword) => write!(f, "{keyword}"),
             Self::DocComment(comment) => write!(f, "{comment}"),
+            Self::Verbatim(text) => write!(f, "{text}"),
             Self::Joint(token) => write!(f, "{token}"),
             Self::Eof => write!(f, ""),
         }
@@ -553,8 +556,13 @@ impl Token {
         Self::Lit(lit.into())
     }
 
-    pub fn verbatim(lit: impl Into<String>) -> Self {
-        Self::DocComment(lit.into())
+    pub fn verbatim(text: impl Into<String>) -> Self {
+        Self::Verbatim(text.into())
+    }
+
+    /// A doc comment token, including its leading `///`, `//!` or `/**`.
+    pub fn doc_comment(comment: impl Into<String>) -> Self {
+        Self::DocComment(comment.into())
     }
 
     pub fn ident(ident: impl Into<String>) -> Self {
@@ -704,13 +712,13 @@ impl TokenStream {
 
     /// Convert the last token to a joint token.
     pub fn into_joint(mut self) -> Self {
+        self.join_last();
+        self
+    }
+
+    pub(crate) fn join_last(&mut self) {
         if let Some(last) = self.0.pop() {
-            if last.is_joint() {
-                self.0.push(last);
-            } else {
-                self.0.push(last.into_joint());
-            }
+            self.0.push(last.into_joint());
         }
-        self
     }
 }
@@ -1,9 +1,12 @@
 use std::fmt;
 
-use crate::expr::{Const, GenericArg, Lit, MacCall, Path, PathSegment};
+use crate::expr::{
+    Const, Expr, GenericArg, GenericArgs, Lit, MacCall, ParenthesizedArgs, Path, PathSegment,
+    QPath, Verbatim,
+};
 use crate::stmt::Param;
 use crate::token::{BinOpToken, Delimiter, KeywordToken, Token, TokenStream};
-use crate::{impl_display_for_enum, impl_obvious_conversion, EmptyItem};
+use crate::{impl_display_for_enum, impl_obvious_conversion, EmptyItem, PathPolicy};
 
 #[cfg(feature = "fuzzing")]
 use crate::token::String;
@@ -191,10 +194,15 @@ pub struct BareFn {
     pub output: Box<Type>,
     pub is_unsafe: bool,
     pub abi: Option<String>,
+    /// `...` after the inputs, as in `extern "C" fn(*const c_char, ...)`.
+    pub is_variadic: bool,
+    /// Omit `-> ()` when the output is the unit type. Enabled by the constructors.
+    pub elide_unit_output: bool,
 }
 
 impl fmt::Display for BareFn {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_binder(f, &self.generic_params)?;
         if self.is_unsafe {
             write!(f, "unsafe ")?;
         }
@@ -210,13 +218,25 @@ impl fmt::Display for BareFn {
             }
             write!(f, "{param}")?;
         }
-        write!(f, ") -> {}", self.output)
+        if self.is_variadic {
+            if !self.inputs.is_empty() {
+                write!(f, ", ")?;
+            }
+            write!(f, "...")?;
+        }
+        write!(f, ")")?;
+        if !self.omits_output() {
+            write!(f, " -> {}", self.output)?;
+        }
+        Ok(())
     }
 }
 
 impl From<BareFn> for TokenStream {
     fn from(value: BareFn) -> Self {
+        let omits_output = value.omits_output();
         let mut ts = TokenStream::new();
+        push_binder(&mut ts, value.generic_params);
 
         if value.is_unsafe {
             ts.push(Token::Keyword(KeywordToken::Unsafe));
@@ -235,9 +255,17 @@ impl From<BareFn> for TokenStream {
             }
             ts.extend(TokenStream::from(param.clone()).into_joint());
         }
+        if value.is_variadic {
+            if !value.inputs.is_empty() {
+                ts.push(Token::Comma);
+            }
+            ts.push(Token::DotDotDot.into_joint());
+        }
         ts.push(Token::CloseDelim(Delimiter::Parenthesis));
-        ts.push(Token::RArrow);
-        ts.extend(TokenStream::from(*value.output));
+        if !omits_output {
+            ts.push(Token::RArrow);
+            ts.extend(TokenStream::from(*value.output));
+        }
         ts
     }
 }
@@ -256,6 +284,8 @@ impl BareFn {
             output: Box::new(output.into()),
             abi,
             is_unsafe,
+            is_variadic: false,
+            elide_unit_output: true,
         }
     }
 
@@ -267,6 +297,51 @@ impl BareFn {
         BareFn::new(generic_params, inputs, output, None, false)
     }
 
+    /// `extern "C" fn(inputs) -> output`
+    pub fn c(inputs: Vec<Param>, output: impl Into<Type>) -> Self {
+        BareFn::new(vec![], inputs, output, Some("C".into()), false)
+    }
+
+    fn omits_output(&self) -> bool {
+        self.elide_unit_output && self.output.is_unit()
+    }
+
+    pub fn set_unsafe(&mut self, is_unsafe: bool) {
+        self.is_unsafe = is_unsafe;
+    }
+
+    pub fn with_unsafe(mut self, is_unsafe: bool) -> Self {
+        self.set_unsafe(is_unsafe);
+        self
+    }
+
+    pub fn set_abi(&mut self, abi: Option<String>) {
+        self.abi = abi;
+    }
+
+    pub fn with_abi(mut self, abi: impl Into<String>) -> Self {
+        self.set_abi(Some(abi.into()));
+        self
+    }
+
+    pub fn set_variadic(&mut self, is_variadic: bool) {
+        self.is_variadic = is_variadic;
+    }
+
+    pub fn with_variadic(mut self, is_variadic: bool) -> Self {
+        self.set_variadic(is_variadic);
+        self
+    }
+
+    pub fn set_elide_unit_output(&mut self, elide: bool) {
+        self.elide_unit_output = elide;
+    }
+
+    pub fn with_elide_unit_output(mut self, elide: bool) -> Self {
+        self.set_elide_unit_output(elide);
+        self
+    }
+
     pub fn add_input(&mut self, input: Param) {
         self.inputs.push(input);
     }
@@ -291,21 +366,24 @@ impl BareFn {
 pub struct TypeParam {
     pub ident: String,
     pub bounds: Vec<GenericBound>,
+    /// `= ty`, allowed on the parameters of type definitions and traits.
+    pub default: Option<Type>,
 }
 
 impl fmt::Display for TypeParam {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.ident)?;
         if !self.bounds.is_empty() {
-            write!(
-                f,
-                ": {}",
-                self.bounds
-                    .iter()
-                    .map(|b| format!("{b}"))
-                    .collect::<Vec<_>>()
-                    .join(" + ")
-            )?;
+            write!(f, ": ")?;
+            for (i, bound) in self.bounds.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " + ")?;
+                }
+                write!(f, "{bound}")?;
+            }
+        }
+        if let Some(default) = &self.default {
+            write!(f, " = {default}")?;
         }
         Ok(())
     }
@@ -314,8 +392,10 @@ impl fmt::Display for TypeParam {
 impl From<TypeParam> for TokenStream {
     fn from(value: TypeParam) -> Self {
         let mut ts = TokenStream::new();
-        ts.push(Token::ident(value.ident).into_joint());
-        if !value.bounds.is_empty() {
+        if value.bounds.is_empty() {
+            ts.push(Token::ident(value.ident));
+        } else {
+            ts.push(Token::ident(value.ident).into_joint());
             ts.push(Token::Colon);
             for (i, bound) in value.bounds.into_iter().enumerate() {
                 if i > 0 {
@@ -324,6 +404,10 @@ impl From<TypeParam> for TokenStream {
                 ts.extend(TokenStream::from(bound));
             }
         }
+        if let Some(default) = value.default {
+            ts.push(Token::Eq);
+            ts.extend(TokenStream::from(default));
+        }
         ts
     }
 }
@@ -333,6 +417,7 @@ impl TypeParam {
         Self {
             ident: ident.into(),
             bounds,
+            default: None,
         }
     }
 
@@ -340,9 +425,19 @@ impl TypeParam {
         Self {
             ident: ident.into(),
             bounds: vec![],
+            default: None,
         }
     }
 
+    pub fn set_default(&mut self, ty: impl Into<Type>) {
+        self.default = Some(ty.into());
+    }
+
+    pub fn with_default(mut self, ty: impl Into<Type>) -> Self {
+        self.set_default(ty);
+        self
+    }
+
     pub fn add_bound(&mut self, bound: GenericBound) {
         self.bounds.push(bound);
     }
@@ -358,11 +453,18 @@ impl TypeParam {
 pub struct ConstParam {
     pub ident: String,
     pub ty: Type,
+    /// `= expr`, braced unless it is a literal or an identifier.
+    pub default: Option<Const>,
 }
 
 impl fmt::Display for ConstParam {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "const {}: {}", self.ident, self.ty)
+        write!(f, "const {}: {}", self.ident, self.ty)?;
+        if let Some(default) = &self.default {
+            write!(f, " = ")?;
+            default.fmt_arg(f)?;
+        }
+        Ok(())
     }
 }
 
@@ -373,6 +475,10 @@ impl From<ConstParam> for TokenStream {
         ts.push(Token::ident(value.ident).into_joint());
         ts.push(Token::Colon);
         ts.extend(TokenStream::from(value.ty));
+        if let Some(default) = value.default {
+            ts.push(Token::Eq);
+            ts.extend(default.into_arg_tokens());
+        }
         ts
     }
 }
@@ -382,54 +488,289 @@ impl ConstParam {
         Self {
             ident: ident.into(),
             ty,
+            default: None,
         }
     }
+
+    pub fn set_default(&mut self, expr: impl Into<Expr>) {
+        self.default = Some(Const(expr.into()));
+    }
+
+    pub fn with_default(mut self, expr: impl Into<Expr>) -> Self {
+        self.set_default(expr);
+        self
+    }
+}
+
+/// `'ident: 'bounds`
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LifetimeParam {
+    pub ident: String,
+    pub bounds: Vec<String>,
+}
+
+impl fmt::Display for LifetimeParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}", self.ident)?;
+        if !self.bounds.is_empty() {
+            write!(f, ": ")?;
+            for (i, bound) in self.bounds.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " + ")?;
+                }
+                write!(f, "'{bound}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<LifetimeParam> for TokenStream {
+    fn from(value: LifetimeParam) -> Self {
+        let mut ts = TokenStream::new();
+        ts.push(Token::lifetime(value.ident).into_joint());
+        if !value.bounds.is_empty() {
+            ts.push(Token::Colon);
+            for (i, bound) in value.bounds.into_iter().enumerate() {
+                if i > 0 {
+                    ts.push(Token::BinOp(BinOpToken::Plus));
+                }
+                ts.push(Token::lifetime(bound));
+            }
+        }
+        ts
+    }
+}
+
+impl LifetimeParam {
+    pub fn new(ident: impl Into<String>, bounds: Vec<String>) -> Self {
+        Self {
+            ident: ident.into(),
+            bounds,
+        }
+    }
+
+    pub fn simple(ident: impl Into<String>) -> Self {
+        Self::new(ident, vec![])
+    }
+
+    pub fn add_bound(&mut self, lifetime: impl Into<String>) {
+        self.bounds.push(lifetime.into());
+    }
+
+    pub fn with_bound(mut self, lifetime: impl Into<String>) -> Self {
+        self.add_bound(lifetime);
+        self
+    }
 }
 
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GenericParam {
+    LifetimeParam(LifetimeParam),
     TypeParam(TypeParam),
     ConstParam(ConstParam),
 }
-impl_display_for_enum!(GenericParam; TypeParam, ConstParam);
-impl_obvious_conversion!(GenericParam; TypeParam, ConstParam);
+impl_display_for_enum!(GenericParam; LifetimeParam, TypeParam, ConstParam);
+impl_obvious_conversion!(GenericParam; LifetimeParam, TypeParam, ConstParam);
+
+impl GenericParam {
+    /// The generic argument naming this parameter, e.g. `'a`, `T` or `N`.
+    pub fn to_arg(&self) -> GenericArg {
+        match self {
+            Self::LifetimeParam(param) => GenericArg::Lifetime(param.ident.clone()),
+            Self::TypeParam(param) => GenericArg::Type(Type::simple_path(param.ident.clone())),
+            Self::ConstParam(param) => {
+                GenericArg::Const(Const(Path::single(param.ident.clone()).into()))
+            }
+        }
+    }
+
+    /// This parameter without its default, as the parameters of impls and functions are
+    /// declared.
+    pub fn without_default(&self) -> GenericParam {
+        match self {
+            Self::TypeParam(param) => Self::TypeParam(TypeParam {
+                default: None,
+                ..param.clone()
+            }),
+            Self::ConstParam(param) => Self::ConstParam(ConstParam {
+                default: None,
+                ..param.clone()
+            }),
+            param => param.clone(),
+        }
+    }
+}
+
+/// `for<params> `, written before higher-ranked trait bounds and function pointers.
+pub(crate) fn fmt_binder(f: &mut fmt::Formatter<'_>, params: &[GenericParam]) -> fmt::Result {
+    if params.is_empty() {
+        return Ok(());
+    }
+    write!(f, "for<")?;
+    for (i, param) in params.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{param}")?;
+    }
+    write!(f, "> ")
+}
+
+pub(crate) fn push_binder(ts: &mut TokenStream, params: Vec<GenericParam>) {
+    if params.is_empty() {
+        return;
+    }
+    ts.push(Token::Keyword(KeywordToken::For).into_joint());
+    ts.push(Token::Lt.into_joint());
+    for (i, param) in params.into_iter().enumerate() {
+        if i > 0 {
+            ts.push(Token::Comma);
+        }
+        ts.extend(TokenStream::from(param).into_joint());
+    }
+    ts.push(Token::Gt);
+}
 
+/// What is written before a trait bound.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TraitBoundModifier {
+    #[default]
+    None,
+    /// `?Trait`
+    Maybe,
+    /// `~const Trait`, nightly only
+    #[cfg(feature = "unstable-syntax")]
+    MaybeConst,
+    /// `!Trait`, nightly only
+    #[cfg(feature = "unstable-syntax")]
+    Negative,
+}
+
+impl fmt::Display for TraitBoundModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => Ok(()),
+            Self::Maybe => write!(f, "?"),
+            #[cfg(feature = "unstable-syntax")]
+            Self::MaybeConst => write!(f, "~const "),
+            #[cfg(feature = "unstable-syntax")]
+            Self::Negative => write!(f, "!"),
+        }
+    }
+}
+
+impl From<TraitBoundModifier> for TokenStream {
+    fn from(value: TraitBoundModifier) -> Self {
+        match value {
+            TraitBoundModifier::None => TokenStream::new(),
+            TraitBoundModifier::Maybe => TokenStream::from(vec![Token::Question.into_joint()]),
+            #[cfg(feature = "unstable-syntax")]
+            TraitBoundModifier::MaybeConst => TokenStream::from(vec![
+                Token::Tilde.into_joint(),
+                Token::Keyword(KeywordToken::Const),
+            ]),
+            #[cfg(feature = "unstable-syntax")]
+            TraitBoundModifier::Negative => TokenStream::from(vec![Token::Not.into_joint()]),
+        }
+    }
+}
+
+/// `modifier for<..> Trait`
 #[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PolyTraitRef {
+    pub modifier: TraitBoundModifier,
     pub bound_generic_params: Vec<GenericParam>,
     pub trait_ref: Path,
 }
 
 impl fmt::Display for PolyTraitRef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if !self.bound_generic_params.is_empty() {
-            // TODO:
-        }
-        write!(f, "{}", self.trait_ref)
+        write!(f, "{}", self.modifier)?;
+        fmt_binder(f, &self.bound_generic_params)?;
+        write!(f, "{}", self.trait_ref.display_type())
     }
 }
 
 impl From<PolyTraitRef> for TokenStream {
     fn from(value: PolyTraitRef) -> Self {
-        TokenStream::from(value.trait_ref)
+        let mut ts = TokenStream::from(value.modifier);
+        push_binder(&mut ts, value.bound_generic_params);
+        ts.extend(value.trait_ref.into_type_tokens());
+        ts
     }
 }
 
 impl PolyTraitRef {
     pub fn new(bound_generic_params: Vec<GenericParam>, trait_ref: impl Into<Path>) -> Self {
         Self {
+            modifier: TraitBoundModifier::None,
             bound_generic_params,
             trait_ref: trait_ref.into(),
         }
     }
 
     pub fn simple(trait_ref: impl Into<Path>) -> Self {
-        Self {
-            bound_generic_params: vec![],
-            trait_ref: trait_ref.into(),
+        Self::new(vec![], trait_ref)
+    }
+
+    /// `trait_ref(inputs) -> output`, the sugar of the `Fn` traits, on the last segment of
+    /// `trait_ref`.
+    pub fn parenthesized(
+        trait_ref: impl Into<Path>,
+        inputs: Vec<Type>,
+        output: Option<Type>,
+    ) -> Self {
+        let mut trait_ref = trait_ref.into();
+        if let Some(last) = trait_ref.segments.last_mut() {
+            last.args = Some(ParenthesizedArgs::new(inputs, output).into());
         }
+        Self::simple(trait_ref)
+    }
+
+    /// `Fn(inputs) -> output`
+    pub fn fn_(inputs: Vec<Type>, output: Option<Type>) -> Self {
+        Self::parenthesized("Fn", inputs, output)
+    }
+
+    /// `FnMut(inputs) -> output`
+    pub fn fn_mut(inputs: Vec<Type>, output: Option<Type>) -> Self {
+        Self::parenthesized("FnMut", inputs, output)
+    }
+
+    /// `FnOnce(inputs) -> output`
+    pub fn fn_once(inputs: Vec<Type>, output: Option<Type>) -> Self {
+        Self::parenthesized("FnOnce", inputs, output)
+    }
+
+    /// `?trait_ref`
+    pub fn maybe(trait_ref: impl Into<Path>) -> Self {
+        Self::simple(trait_ref).with_modifier(TraitBoundModifier::Maybe)
+    }
+
+    /// `~const trait_ref`
+    #[cfg(feature = "unstable-syntax")]
+    pub fn maybe_const(trait_ref: impl Into<Path>) -> Self {
+        Self::simple(trait_ref).with_modifier(TraitBoundModifier::MaybeConst)
+    }
+
+    /// `!trait_ref`
+    #[cfg(feature = "unstable-syntax")]
+    pub fn negative(trait_ref: impl Into<Path>) -> Self {
+        Self::simple(trait_ref).with_modifier(TraitBoundModifier::Negative)
+    }
+
+    pub fn set_modifier(&mut self, modifier: TraitBoundModifier) {
+        self.modifier = modifier;
+    }
+
+    pub fn with_modifier(mut self, modifier: TraitBoundModifier) -> Self {
+        self.set_modifier(modifier);
+        self
     }
 
     pub fn add_bound_generic_param(&mut self, param: GenericParam) {
@@ -467,6 +808,19 @@ impl From<GenericBound> for TokenStream {
     }
 }
 
+impl From<PolyTraitRef> for GenericBound {
+    fn from(value: PolyTraitRef) -> Self {
+        Self::Trait(value)
+    }
+}
+
+impl GenericBound {
+    /// `?Sized`
+    pub fn maybe_sized() -> Self {
+        PolyTraitRef::maybe("Sized").into()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TraitObject {
     pub is_dyn: bool,
@@ -492,16 +846,13 @@ impl fmt::Display for TraitObject {
         if self.is_dyn {
             write!(f, "dyn ")?;
         }
-        write!(
-            f,
-            "{bounds}",
-            bounds = self
-                .bounds
-                .iter()
-                .map(|b| format!("{b}"))
-                .collect::<Vec<_>>()
-                .join(" + ")
-        )
+        for (i, bound) in self.bounds.iter().enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{bound}")?;
+        }
+        Ok(())
     }
 }
 
@@ -544,9 +895,40 @@ impl TraitObject {
     }
 }
 
+/// A lifetime or a type or const parameter captured by `use<..>`.
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PreciseCapturingArg {
+    Lifetime(String),
+    Arg(String),
+}
+
+impl fmt::Display for PreciseCapturingArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lifetime(lifetime) => write!(f, "'{lifetime}"),
+            Self::Arg(ident) => write!(f, "{ident}"),
+        }
+    }
+}
+
+impl From<PreciseCapturingArg> for TokenStream {
+    fn from(value: PreciseCapturingArg) -> Self {
+        match value {
+            PreciseCapturingArg::Lifetime(lifetime) => {
+                TokenStream::from(vec![Token::lifetime(lifetime)])
+            }
+            PreciseCapturingArg::Arg(ident) => TokenStream::from(vec![Token::ident(ident)]),
+        }
+    }
+}
+
+/// `impl bounds.. + use<captures..>`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ImplTrait {
     pub bounds: Vec<GenericBound>,
+    /// The `use<..>` precise capturing list (edition 2024), `None` to capture implicitly.
+    pub captures: Option<Vec<PreciseCapturingArg>>,
 }
 
 #[cfg(feature = "fuzzing")]
@@ -558,29 +940,38 @@ impl<'a> arbitrary::Arbitrary<'a> for ImplTrait {
         for _ in 0..len {
             bounds.push(GenericBound::arbitrary(u)?);
         }
-        Ok(Self { bounds })
+        let captures = Option::<Vec<PreciseCapturingArg>>::arbitrary(u)?;
+        Ok(Self { bounds, captures })
     }
 }
 
 impl fmt::Display for ImplTrait {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "impl {bounds}",
-            bounds = self
-                .bounds
-                .iter()
-                .map(|b| format!("{b}"))
-                .collect::<Vec<_>>()
-                .join(" + ")
-        )
+        write!(f, "impl ")?;
+        for (i, bound) in self.bounds.iter().enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{bound}")?;
+        }
+        if let Some(captures) = &self.captures {
+            write!(f, " + use<")?;
+            for (i, capture) in captures.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{capture}")?;
+            }
+            write!(f, ">")?;
+        }
+        Ok(())
     }
 }
 
 impl EmptyItem for ImplTrait {
     type Input = ();
     fn empty(_: impl Into<Self::Input>) -> Self {
-        Self { bounds: vec![] }
+        Self::new(vec![])
     }
 }
 
@@ -594,13 +985,48 @@ impl From<ImplTrait> for TokenStream {
             }
             ts.extend(TokenStream::from(bound));
         }
+        if let Some(captures) = value.captures {
+            ts.push(Token::BinOp(BinOpToken::Plus));
+            ts.push(Token::Keyword(KeywordToken::Use).into_joint());
+            ts.push(Token::Lt.into_joint());
+            for (i, capture) in captures.into_iter().enumerate() {
+                if i > 0 {
+                    ts.push(Token::Comma);
+                }
+                ts.extend(TokenStream::from(capture).into_joint());
+            }
+            ts.push(Token::Gt);
+        }
         ts
     }
 }
 
 impl ImplTrait {
     pub fn new(bounds: Vec<GenericBound>) -> Self {
-        Self { bounds }
+        Self {
+            bounds,
+            captures: None,
+        }
+    }
+
+    /// Adds `capture` to the `use<..>` list, creating it if needed.
+    pub fn add_capture(&mut self, capture: PreciseCapturingArg) {
+        self.captures.get_or_insert_with(Vec::new).push(capture);
+    }
+
+    pub fn with_capture(mut self, capture: PreciseCapturingArg) -> Self {
+        self.add_capture(capture);
+        self
+    }
+
+    /// Sets the `use<..>` list, `Some(vec![])` for `use<>`.
+    pub fn set_captures(&mut self, captures: Option<Vec<PreciseCapturingArg>>) {
+        self.captures = captures;
+    }
+
+    pub fn with_captures(mut self, captures: Option<Vec<PreciseCapturingArg>>) -> Self {
+        self.set_captures(captures);
+        self
     }
 
     pub fn add_bound(&mut self, bound: GenericBound) {
@@ -626,13 +1052,19 @@ pub enum Type {
     /// `!`
     Never,
     Tuple(Vec<Type>),
+    /// `(T)`
+    Paren(Box<Type>),
     Path(Path),
+    /// `<T as Trait>::Item`
+    QPath(QPath),
     TraitObject(TraitObject),
     ImplTrait(ImplTrait),
     /// `_`
     Infer,
     ImplicitSelf,
     Err,
+    /// Raw source text, output as it is
+    Verbatim(Verbatim),
 }
 
 #[cfg(feature = "fuzzing")]
@@ -641,7 +1073,7 @@ impl<'a> arbitrary::Arbitrary<'a> for Type {
         if crate::depth_limiter::reached() {
             return Ok(Type::Never);
         }
-        match u.int_in_range(0..=10)? {
+        match u.int_in_range(0..=12)? {
             0 => Ok(Type::Slice(Box::new(Type::arbitrary(u)?))),
             1 => Ok(Type::Array(
                 Box::new(Type::arbitrary(u)?),
@@ -663,6 +1095,8 @@ impl<'a> arbitrary::Arbitrary<'a> for Type {
             8 => Ok(Type::Path(Path::arbitrary(u)?)),
             9 => Ok(Type::TraitObject(TraitObject::arbitrary(u)?)),
             10 => Ok(Type::ImplTrait(ImplTrait::arbitrary(u)?)),
+            11 => Ok(Type::QPath(QPath::arbitrary(u)?)),
+            12 => Ok(Type::Paren(Box::new(Type::arbitrary(u)?))),
             _ => unreachable!(),
         }
     }
@@ -678,20 +1112,25 @@ impl fmt::Display for Type {
             Self::BareFn(bare_fn) => bare_fn.fmt(f),
             Self::Macro(mac) => mac.fmt(f),
             Self::Never => write!(f, "!"),
-            Self::Tuple(tys) => write!(
-                f,
-                "({})",
-                tys.iter()
-                    .map(|ty| format!("{ty}"))
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            ),
-            Self::Path(path) => path.fmt(f),
+            Self::Tuple(tys) => {
+                write!(f, "(")?;
+                for (i, ty) in tys.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{ty}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Paren(ty) => write!(f, "({ty})"),
+            Self::Path(path) => path.display_type().fmt(f),
+            Self::QPath(qpath) => qpath.fmt_with(f, false),
             Self::TraitObject(trait_object) => trait_object.fmt(f),
             Self::ImplTrait(impl_trait) => impl_trait.fmt(f),
             Self::Infer => write!(f, "_"),
             Self::ImplicitSelf => write!(f, ""),
             Self::Err => write!(f, "<Err>"),
+            Self::Verbatim(verbatim) => verbatim.fmt(f),
         }
     }
 }
@@ -722,11 +1161,24 @@ impl From<MacCall> for Type {
         Self::Macro(mac)
     }
 }
+impl From<QPath> for Type {
+    fn from(value: QPath) -> Self {
+        Self::QPath(value)
+    }
+}
+
+impl From<Verbatim> for Type {
+    fn from(value: Verbatim) -> Self {
+        Self::Verbatim(value)
+    }
+}
+
 impl From<TraitObject> for Type {
     fn from(trait_object: TraitObject) -> Self {
         Self::TraitObject(trait_object)
     }
 }
+
 impl From<ImplTrait> for Type {
     fn from(impl_trait: ImplTrait) -> Self {
         Self::ImplTrait(impl_trait)
@@ -769,12 +1221,21 @@ impl From<Type> for TokenStream {
                 ts.push(Token::CloseDelim(Delimiter::Parenthesis));
                 ts
             }
-            Type::Path(path) => TokenStream::from(path),
+            Type::Paren(ty) => {
+                let mut ts = TokenStream::new();
+                ts.push(Token::OpenDelim(Delimiter::Parenthesis).into_joint());
+                ts.extend(TokenStream::from(*ty).into_joint());
+                ts.push(Token::CloseDelim(Delimiter::Parenthesis));
+                ts
+            }
+            Type::Path(path) => path.into_type_tokens(),
+            Type::QPath(qpath) => qpath.into_tokens_with(false),
             Type::TraitObject(trait_object) => TokenStream::from(trait_object),
             Type::ImplTrait(impl_trait) => TokenStream::from(impl_trait),
             Type::Infer => TokenStream::from(vec![Token::ident("_")]),
             Type::ImplicitSelf => TokenStream::new(),
             Type::Err => TokenStream::from(vec![Token::ident("<Err>")]),
+            Type::Verbatim(verbatim) => TokenStream::from(verbatim),
         }
     }
 }
@@ -783,6 +1244,9 @@ impl Type {
     pub fn unit() -> Type {
         Self::Tuple(vec![])
     }
+    pub fn is_unit(&self) -> bool {
+        matches!(self, Self::Tuple(tys) if tys.is_empty())
+    }
     pub fn usize() -> Type {
         Self::Path(Path::single("usize"))
     }
@@ -838,6 +1302,11 @@ impl Type {
         Self::Path(Path::single("String"))
     }
 
+    /// [`Type::string`] under `policy`, `None` without an allocator.
+    pub fn string_in(policy: PathPolicy) -> Option<Type> {
+        policy.alloc_item("string", "String").map(Type::Path)
+    }
+
     pub fn into_ref(self) -> Type {
         Type::ref_(self)
     }
@@ -886,11 +1355,118 @@ impl Type {
         Type::poly_path("Box", vec![GenericArg::Type(inner.into())])
     }
 
+    /// [`Type::box_`] under `policy`, `None` without an allocator.
+    pub fn box_in(policy: PathPolicy, inner: impl Into<Type>) -> Option<Type> {
+        let seg = PathSegment::generic("Box", vec![GenericArg::Type(inner.into())]);
+        policy.alloc_item("boxed", seg).map(Type::Path)
+    }
+
     pub fn vec(inner: impl Into<Type>) -> Type {
         Type::poly_path("Vec", vec![GenericArg::Type(inner.into())])
     }
 
+    /// [`Type::vec`] under `policy`, `None` without an allocator.
+    pub fn vec_in(policy: PathPolicy, inner: impl Into<Type>) -> Option<Type> {
+        let seg = PathSegment::generic("Vec", vec![GenericArg::Type(inner.into())]);
+        policy.alloc_item("vec", seg).map(Type::Path)
+    }
+
+    /// `Option<inner>`
+    pub fn option(inner: impl Into<Type>) -> Type {
+        Type::poly_path("Option", vec![GenericArg::Type(inner.into())])
+    }
+
+    /// `std::sync::Arc<inner>`
+    pub fn arc(inner: impl Into<Type>) -> Type {
+        let seg = PathSegment::generic("Arc", vec![GenericArg::Type(inner.into())]);
+        Type::Path(Path::single("std").chain("sync").chain(seg))
+    }
+
+    /// [`Type::arc`] under `policy`, `None` without an allocator.
+    pub fn arc_in(policy: PathPolicy, inner: impl Into<Type>) -> Option<Type> {
+        let seg = PathSegment::generic("Arc", vec![GenericArg::Type(inner.into())]);
+        policy.alloc_item_qualified("sync", seg).map(Type::Path)
+    }
+
+    /// `std::rc::Rc<inner>`
+    pub fn rc(inner: impl Into<Type>) -> Type {
+        let seg = PathSegment::generic("Rc", vec![GenericArg::Type(inner.into())]);
+        Type::Path(Path::single("std").chain("rc").chain(seg))
+    }
+
+    /// [`Type::rc`] under `policy`, `None` without an allocator.
+    pub fn rc_in(policy: PathPolicy, inner: impl Into<Type>) -> Option<Type> {
+        let seg = PathSegment::generic("Rc", vec![GenericArg::Type(inner.into())]);
+        policy.alloc_item_qualified("rc", seg).map(Type::Path)
+    }
+
+    /// `Box<dyn bounds..>`
+    pub fn dyn_box(bounds: Vec<GenericBound>) -> Type {
+        Type::box_(TraitObject::dyn_(bounds))
+    }
+
+    /// `Box<dyn std::error::Error>`. Use [`Type::dyn_box`] to add bounds such as `Send + Sync`.
+    pub fn box_dyn_error() -> Type {
+        let error = PathPolicy::Std.core_item("error", "Error");
+        Type::dyn_box(vec![PolyTraitRef::simple(error).into()])
+    }
+
+    /// [`Type::box_dyn_error`] under `policy`, `None` without an allocator.
+    pub fn box_dyn_error_in(policy: PathPolicy) -> Option<Type> {
+        let error = policy.core_item("error", "Error");
+        Type::box_in(
+            policy,
+            TraitObject::dyn_(vec![PolyTraitRef::simple(error).into()]),
+        )
+    }
+
+    /// `Result<ok, err>`
+    pub fn result(ok: impl Into<Type>, err: impl Into<Type>) -> Type {
+        Type::poly_path(
+            "Result",
+            vec![GenericArg::Type(ok.into()), GenericArg::Type(err.into())],
+        )
+    }
+
+    /// `path<ok>`, a result alias that fixes the error type, such as `io::Result<T>`.
+    pub fn result_alias(path: impl Into<Path>, ok: impl Into<Type>) -> Type {
+        let mut path = path.into();
+        if let Some(last) = path.segments.last_mut() {
+            last.args = Some(GenericArgs::AngleBracketed(vec![GenericArg::Type(
+                ok.into(),
+            )]));
+        }
+        Type::Path(path)
+    }
+
+    /// `anyhow::Result<ok>`
+    pub fn anyhow_result(ok: impl Into<Type>) -> Type {
+        Type::result_alias(Path::single("anyhow").chain("Result"), ok)
+    }
+
+    /// `<qself as trait_>::assoc`, e.g. `<Vec<T> as IntoIterator>::IntoIter`.
+    pub fn assoc(
+        qself: impl Into<Type>,
+        trait_: impl Into<Path>,
+        assoc: impl Into<PathSegment>,
+    ) -> Type {
+        Type::QPath(QPath::as_trait(qself, trait_, Path::single(assoc)))
+    }
+
+    /// `(T)`
+    pub fn paren(ty: impl Into<Type>) -> Type {
+        Self::Paren(Box::new(ty.into()))
+    }
+
+    /// Whether the type needs parentheses behind `&` or `*`, i.e. it is a trait object or an
+    /// `impl Trait` with more than one bound, as in `&(dyn Read + Send)`.
     pub fn should_wrap(&self) -> bool {
-        matches!(self, Type::ImplTrait(_) | Type::TraitObject(_))
+        match self {
+            Type::ImplTrait(ImplTrait { bounds, captures }) => {
+                bounds.len() + usize::from(captures.is_some()) > 1
+            }
+            Type::TraitObject(TraitObject { bounds, .. }) => bounds.len() > 1,
+            _ => false,
+        }
     }
 }
@@ -0,0 +1,1139 @@
+//! Traversal of ASTs.
+//!
+//! Implement [`Visit`] (or [`VisitMut`] to modify nodes in place) and override the methods for the nodes you are interested in.
+//! To keep descending into the children of an overridden node, call the corresponding `walk_*` function.
+//! [`Crate::walk`] instead lists every node along with its ancestors, for analyses that need the context of a node.
+
+use std::rc::Rc;
+
+use crate::*;
+
+pub trait Visit<'ast> {
+    fn visit_crate(&mut self, krate: &'ast Crate) {
+        walk_crate(self, krate)
+    }
+    fn visit_item(&mut self, item: &'ast Item) {
+        walk_item(self, item)
+    }
+    fn visit_assoc_item(&mut self, item: &'ast AssocItem) {
+        walk_assoc_item(self, item)
+    }
+    fn visit_fn(&mut self, fn_: &'ast Fn) {
+        walk_fn(self, fn_)
+    }
+    fn visit_attribute(&mut self, attr: &'ast AttributeItem) {
+        walk_attribute(self, attr)
+    }
+    fn visit_use_tree(&mut self, tree: &'ast UseTree) {
+        walk_use_tree(self, tree)
+    }
+    fn visit_block(&mut self, block: &'ast Block) {
+        walk_block(self, block)
+    }
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        walk_stmt(self, stmt)
+    }
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr)
+    }
+    fn visit_pat(&mut self, pat: &'ast Pat) {
+        walk_pat(self, pat)
+    }
+    fn visit_type(&mut self, ty: &'ast Type) {
+        walk_type(self, ty)
+    }
+    fn visit_generic_param(&mut self, param: &'ast GenericParam) {
+        walk_generic_param(self, param)
+    }
+    fn visit_path(&mut self, path: &'ast Path) {
+        walk_path(self, path)
+    }
+}
+
+pub fn walk_crate<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, krate: &'ast Crate) {
+    walk_attributes(v, &krate.attrs);
+    for item in krate.items.iter() {
+        v.visit_item(item);
+    }
+}
+
+fn walk_attributes<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, attrs: &'ast [Attribute]) {
+    for attr in attrs.iter() {
+        if let AttrKind::Normal(item) = &attr.kind {
+            v.visit_attribute(item);
+        }
+    }
+}
+
+fn walk_visibility<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, vis: &'ast Visibility) {
+    if let Visibility::Scoped(VisibilityScope::Path(path)) = vis {
+        v.visit_path(path);
+    }
+}
+
+pub fn walk_item<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, item: &'ast Item) {
+    walk_attributes(v, &item.attrs);
+    walk_visibility(v, &item.vis);
+    match &item.kind {
+        ItemKind::Use(use_) => v.visit_use_tree(&use_.0),
+        ItemKind::StaticItem(StaticItem { ty, expr, .. })
+        | ItemKind::ConstItem(ConstItem { ty, expr, .. }) => {
+            v.visit_type(ty);
+            if let Some(expr) = expr {
+                v.visit_expr(expr);
+            }
+        }
+        ItemKind::Fn(fn_) => v.visit_fn(fn_),
+        ItemKind::Mod(Mod::Loaded(module)) => {
+            for item in module.items.iter() {
+                v.visit_item(item);
+            }
+        }
+        ItemKind::Mod(Mod::Unloaded(_)) => {}
+        ItemKind::TyAlias(alias) => walk_ty_alias(v, alias),
+        ItemKind::EnumDef(def) => {
+            walk_generic_params(v, &def.generics.params);
+            walk_where_clause(v, &def.generics.where_clause);
+            for variant in def.variants.iter() {
+                walk_attributes(v, &variant.attrs);
+                walk_visibility(v, &variant.vis);
+                walk_fields(v, &variant.fields);
+                if let Some(discriminant) = &variant.discriminant {
+                    v.visit_expr(discriminant);
+                }
+            }
+        }
+        ItemKind::StructDef(StructDef {
+            generics, fields, ..
+        })
+        | ItemKind::UnionDef(UnionDef {
+            generics, fields, ..
+        }) => {
+            walk_generic_params(v, &generics.params);
+            walk_where_clause(v, &generics.where_clause);
+            walk_fields(v, fields);
+        }
+        ItemKind::TraitDef(def) => {
+            walk_generic_params(v, &def.generics.params);
+            for supertrait in def.supertraits.iter() {
+                v.visit_type(supertrait);
+            }
+            walk_where_clause(v, &def.generics.where_clause);
+            for item in def.items.iter() {
+                v.visit_assoc_item(item);
+            }
+        }
+        ItemKind::Impl(impl_) => {
+            walk_generic_params(v, &impl_.generics.params);
+            if let Some(of_trait) = &impl_.of_trait {
+                v.visit_type(of_trait);
+            }
+            v.visit_type(&impl_.self_ty);
+            walk_where_clause(v, &impl_.generics.where_clause);
+            for item in impl_.items.iter() {
+                v.visit_assoc_item(item);
+            }
+        }
+        ItemKind::MacCallWithSemi(Semi(mac)) => v.visit_path(&mac.path),
+        ItemKind::ExternBlock(block) => v.visit_block(&block.block),
+        ItemKind::MacroDef(_) | ItemKind::ExternCrate(_) | ItemKind::Verbatim(_) => {}
+    }
+}
+
+pub fn walk_assoc_item<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, item: &'ast AssocItem) {
+    walk_attributes(v, &item.attrs);
+    walk_visibility(v, &item.vis);
+    match &item.kind {
+        AssocItemKind::ConstItem(ConstItem { ty, expr, .. }) => {
+            v.visit_type(ty);
+            if let Some(expr) = expr {
+                v.visit_expr(expr);
+            }
+        }
+        AssocItemKind::Fn(fn_) => v.visit_fn(fn_),
+        AssocItemKind::TyAlias(alias) => walk_ty_alias(v, alias),
+        AssocItemKind::MacCall(mac) => v.visit_path(&mac.path),
+    }
+}
+
+fn walk_ty_alias<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, alias: &'ast TyAlias) {
+    walk_generic_params(v, &alias.generics.params);
+    walk_bounds(v, &alias.bounds);
+    walk_where_clause(v, &alias.generics.where_clause);
+    if let Some(ty) = &alias.ty {
+        v.visit_type(ty);
+    }
+}
+
+pub fn walk_where_clause<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, clause: &'ast WhereClause) {
+    for pred in clause.predicates.iter() {
+        if let WherePredicate::Type(pred) = pred {
+            v.visit_type(&pred.bounded_ty);
+            walk_bounds(v, &pred.bounds);
+        }
+    }
+}
+
+pub fn walk_fn<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, fn_: &'ast Fn) {
+    walk_generic_params(v, &fn_.generics.params);
+    walk_fn_decl(v, &fn_.fn_decl);
+    walk_where_clause(v, &fn_.generics.where_clause);
+    if let Some(body) = &fn_.body {
+        v.visit_block(body);
+    }
+}
+
+fn walk_fn_decl<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, decl: &'ast FnDecl) {
+    walk_params(v, &decl.inputs);
+    if let Some(output) = &decl.output {
+        v.visit_type(output);
+    }
+}
+
+fn walk_params<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, params: &'ast [Param]) {
+    for param in params.iter() {
+        v.visit_pat(&param.pat);
+        v.visit_type(&param.ty);
+    }
+}
+
+fn walk_fields<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, fields: &'ast Fields) {
+    match fields {
+        Fields::Unit => {}
+        Fields::Tuple(fields) | Fields::Struct(fields) => {
+            for field in fields.iter() {
+                walk_attributes(v, &field.attrs);
+                walk_visibility(v, &field.vis);
+                v.visit_type(&field.ty);
+            }
+        }
+    }
+}
+
+fn walk_generic_params<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, params: &'ast [GenericParam]) {
+    for param in params.iter() {
+        v.visit_generic_param(param);
+    }
+}
+
+pub fn walk_attribute<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, attr: &'ast AttributeItem) {
+    v.visit_path(&attr.path);
+    if let AttrArgs::Eq(expr) = &attr.args {
+        v.visit_expr(expr);
+    }
+}
+
+pub fn walk_use_tree<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, tree: &'ast UseTree) {
+    match tree {
+        UseTree::Path(path) => v.visit_use_tree(path.tree()),
+        UseTree::Group(trees) => {
+            for tree in trees.iter() {
+                v.visit_use_tree(tree);
+            }
+        }
+        UseTree::Name(_) | UseTree::Rename(_) | UseTree::Glob => {}
+    }
+}
+
+pub fn walk_block<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, block: &'ast Block) {
+    walk_attributes(v, &block.attrs);
+    for stmt in block.stmts.iter() {
+        v.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, stmt: &'ast Stmt) {
+    match stmt {
+        Stmt::Local(local) => {
+            v.visit_pat(&local.pat);
+            if let Some(ty) = &local.ty {
+                v.visit_type(ty);
+            }
+            match &local.kind {
+                LocalKind::Decl => {}
+                LocalKind::Init(expr) => v.visit_expr(expr),
+                LocalKind::InitElse(expr, block) => {
+                    v.visit_expr(expr);
+                    v.visit_block(block);
+                }
+            }
+        }
+        Stmt::Item(item) => v.visit_item(item),
+        Stmt::Expr(expr) | Stmt::Semi(Semi(expr)) => v.visit_expr(expr),
+        Stmt::MacCallWithSemi(Semi(mac)) => v.visit_path(&mac.path),
+        Stmt::Empty(_) => {}
+    }
+}
+
+pub fn walk_expr<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, expr: &'ast Expr) {
+    for attr in expr.attrs.iter() {
+        v.visit_attribute(attr);
+    }
+    match &expr.kind {
+        ExprKind::Array(Array(exprs)) | ExprKind::Tuple(Tuple(exprs)) => {
+            for expr in exprs.iter() {
+                v.visit_expr(expr);
+            }
+        }
+        ExprKind::Call(call) => {
+            v.visit_expr(&call.func);
+            for arg in call.args.iter() {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::MethodCall(call) => {
+            v.visit_expr(&call.receiver);
+            walk_path_segment(v, &call.seg);
+            for arg in call.args.iter() {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::Binary(Binary { lhs, rhs, .. })
+        | ExprKind::Assign(Assign { lhs, rhs })
+        | ExprKind::AssignOp(AssignOp { lhs, rhs, .. }) => {
+            v.visit_expr(lhs);
+            v.visit_expr(rhs);
+        }
+        ExprKind::Unary(Unary { expr, .. })
+        | ExprKind::Await(Await { expr })
+        | ExprKind::Field(Field { expr, .. })
+        | ExprKind::AddrOf(AddrOf { expr, .. })
+        | ExprKind::Try(Try { expr })
+        | ExprKind::Paren(Paren(expr)) => v.visit_expr(expr),
+        ExprKind::Lit(_)
+        | ExprKind::Underscore(_)
+        | ExprKind::Continue(_)
+        | ExprKind::Verbatim(_) => {}
+        ExprKind::Cast(Cast { expr, ty })
+        | ExprKind::TypeAscription(TypeAscription { expr, ty }) => {
+            v.visit_expr(expr);
+            v.visit_type(ty);
+        }
+        ExprKind::Let(Let { pat, expr }) => {
+            v.visit_pat(pat);
+            v.visit_expr(expr);
+        }
+        ExprKind::If(if_) => {
+            v.visit_expr(&if_.cond);
+            v.visit_block(&if_.then);
+            if let Some(else_) = &if_.else_ {
+                v.visit_expr(else_);
+            }
+        }
+        ExprKind::While(while_) => {
+            v.visit_expr(&while_.cond);
+            v.visit_block(&while_.body);
+        }
+        ExprKind::ForLoop(for_) => {
+            v.visit_pat(&for_.pat);
+            v.visit_expr(&for_.expr);
+            v.visit_block(&for_.body);
+        }
+        ExprKind::Loop(Loop { body: block, .. })
+        | ExprKind::ConstBlock(ConstBlock { block })
+        | ExprKind::UnsafeBlock(UnsafeBlock { block })
+        | ExprKind::LabelledBlock(LabelledBlock { block, .. })
+        | ExprKind::Async(Async { block, .. })
+        | ExprKind::TryBlock(TryBlock { block }) => v.visit_block(block),
+        ExprKind::Match(match_) => {
+            v.visit_expr(&match_.expr);
+            for arm in match_.arms.iter() {
+                for attr in arm.attrs.iter() {
+                    v.visit_attribute(attr);
+                }
+                v.visit_pat(&arm.pat);
+                if let Some(guard) = &arm.guard {
+                    v.visit_expr(guard);
+                }
+                v.visit_expr(&arm.body);
+            }
+        }
+        ExprKind::Closure(closure) => {
+            walk_fn_decl(v, &closure.fn_decl);
+            v.visit_expr(&closure.body);
+        }
+        ExprKind::Index(index) => {
+            v.visit_expr(&index.expr);
+            v.visit_expr(&index.index);
+        }
+        ExprKind::Range(range) => {
+            if let Some(start) = &range.start {
+                v.visit_expr(start);
+            }
+            if let Some(end) = &range.end {
+                v.visit_expr(end);
+            }
+        }
+        ExprKind::Path(path) => v.visit_path(path),
+        ExprKind::QPath(qpath) => walk_qpath(v, qpath),
+        ExprKind::Break(Break { expr, .. })
+        | ExprKind::Return(Return { expr })
+        | ExprKind::Yield(Yield { expr }) => {
+            if let Some(expr) = expr {
+                v.visit_expr(expr);
+            }
+        }
+        ExprKind::MacCall(mac) => v.visit_path(&mac.path),
+        ExprKind::Struct(struct_) => {
+            v.visit_path(&struct_.path);
+            for field in struct_.fields.iter() {
+                v.visit_expr(&field.expr);
+            }
+            if let Some(rest) = &struct_.rest {
+                v.visit_expr(rest);
+            }
+        }
+        ExprKind::Repeat(repeat) => {
+            v.visit_expr(&repeat.expr);
+            v.visit_expr(&repeat.len.0);
+        }
+    }
+}
+
+pub fn walk_pat<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, pat: &'ast Pat) {
+    match pat {
+        Pat::Wild | Pat::Rest => {}
+        Pat::Ident(ident) => {
+            if let Some(pat) = &ident.pat {
+                v.visit_pat(pat);
+            }
+        }
+        Pat::Struct(struct_) => {
+            v.visit_path(&struct_.path);
+            for field in struct_.fields.iter() {
+                v.visit_pat(&field.pat);
+            }
+        }
+        Pat::TupleStruct(tuple_struct) => {
+            v.visit_path(&tuple_struct.path);
+            for pat in tuple_struct.pats.iter() {
+                v.visit_pat(pat);
+            }
+        }
+        Pat::Or(pats) | Pat::Tuple(pats) | Pat::Slice(pats) => {
+            for pat in pats.iter() {
+                v.visit_pat(pat);
+            }
+        }
+        Pat::Box(pat) | Pat::Ref(RefPat { pat, .. }) | Pat::Paren(pat) => v.visit_pat(pat),
+        Pat::Lit(expr) => v.visit_expr(expr),
+        Pat::Range(range) => {
+            if let Some(start) = &range.start {
+                v.visit_expr(start);
+            }
+            if let Some(end) = &range.end {
+                v.visit_expr(end);
+            }
+        }
+        Pat::MacCall(mac) => v.visit_path(&mac.path),
+    }
+}
+
+pub fn walk_type<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, ty: &'ast Type) {
+    match ty {
+        Type::Slice(ty)
+        | Type::Paren(ty)
+        | Type::Ptr(Ptr { ty, .. })
+        | Type::Ref(Ref {
+            ty: MutTy { ty, .. },
+            ..
+        }) => v.visit_type(ty),
+        Type::Array(ty, len) => {
+            v.visit_type(ty);
+            v.visit_expr(&len.0);
+        }
+        Type::BareFn(bare_fn) => {
+            walk_generic_params(v, &bare_fn.generic_params);
+            walk_params(v, &bare_fn.inputs);
+            v.visit_type(&bare_fn.output);
+        }
+        Type::Macro(mac) => v.visit_path(&mac.path),
+        Type::Tuple(tys) => {
+            for ty in tys.iter() {
+                v.visit_type(ty);
+            }
+        }
+        Type::Path(path) => v.visit_path(path),
+        Type::QPath(qpath) => walk_qpath(v, qpath),
+        Type::TraitObject(TraitObject { bounds, .. })
+        | Type::ImplTrait(ImplTrait { bounds, .. }) => walk_bounds(v, bounds),
+        Type::Never | Type::Infer | Type::ImplicitSelf | Type::Err | Type::Verbatim(_) => {}
+    }
+}
+
+fn walk_qpath<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, qpath: &'ast QPath) {
+    v.visit_type(&qpath.qself);
+    if let Some(trait_) = &qpath.trait_ {
+        v.visit_path(trait_);
+    }
+    v.visit_path(&qpath.path);
+}
+
+fn walk_bounds<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, bounds: &'ast [GenericBound]) {
+    for bound in bounds.iter() {
+        match bound {
+            GenericBound::Trait(poly) => {
+                walk_generic_params(v, &poly.bound_generic_params);
+                v.visit_path(&poly.trait_ref);
+            }
+            GenericBound::Outlives(_) => {}
+        }
+    }
+}
+
+pub fn walk_generic_param<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, param: &'ast GenericParam) {
+    match param {
+        GenericParam::LifetimeParam(_) => {}
+        GenericParam::TypeParam(param) => {
+            walk_bounds(v, &param.bounds);
+            if let Some(default) = &param.default {
+                v.visit_type(default);
+            }
+        }
+        GenericParam::ConstParam(param) => {
+            v.visit_type(&param.ty);
+            if let Some(Const(default)) = &param.default {
+                v.visit_expr(default);
+            }
+        }
+    }
+}
+
+pub fn walk_path<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, path: &'ast Path) {
+    for segment in path.segments.iter() {
+        walk_path_segment(v, segment);
+    }
+}
+
+fn walk_path_segment<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, segment: &'ast PathSegment) {
+    match &segment.args {
+        Some(GenericArgs::AngleBracketed(args)) => {
+            for arg in args.iter() {
+                match arg {
+                    GenericArg::Lifetime(_) => {}
+                    GenericArg::Type(ty) => v.visit_type(ty),
+                    GenericArg::Const(Const(expr)) => v.visit_expr(expr),
+                    GenericArg::Constraint(constraint) => v.visit_type(&constraint.ty),
+                }
+            }
+        }
+        Some(GenericArgs::Parenthesized(args)) => {
+            for input in args.inputs.iter() {
+                v.visit_type(input);
+            }
+            if let Some(output) = &args.output {
+                v.visit_type(output);
+            }
+        }
+        None => {}
+    }
+}
+
+pub trait VisitMut {
+    fn visit_crate_mut(&mut self, krate: &mut Crate) {
+        walk_crate_mut(self, krate)
+    }
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        walk_item_mut(self, item)
+    }
+    fn visit_assoc_item_mut(&mut self, item: &mut AssocItem) {
+        walk_assoc_item_mut(self, item)
+    }
+    fn visit_fn_mut(&mut self, fn_: &mut Fn) {
+        walk_fn_mut(self, fn_)
+    }
+    fn visit_attribute_mut(&mut self, attr: &mut AttributeItem) {
+        walk_attribute_mut(self, attr)
+    }
+    fn visit_use_tree_mut(&mut self, tree: &mut UseTree) {
+        walk_use_tree_mut(self, tree)
+    }
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block_mut(self, block)
+    }
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt)
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr)
+    }
+    fn visit_pat_mut(&mut self, pat: &mut Pat) {
+        walk_pat_mut(self, pat)
+    }
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        walk_type_mut(self, ty)
+    }
+    fn visit_generic_param_mut(&mut self, param: &mut GenericParam) {
+        walk_generic_param_mut(self, param)
+    }
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        walk_path_mut(self, path)
+    }
+}
+
+pub fn walk_crate_mut<V: VisitMut + ?Sized>(v: &mut V, krate: &mut Crate) {
+    walk_attributes_mut(v, &mut krate.attrs);
+    for item in krate.items.iter_mut() {
+        v.visit_item_mut(item);
+    }
+}
+
+fn walk_attributes_mut<V: VisitMut + ?Sized>(v: &mut V, attrs: &mut [Attribute]) {
+    for attr in attrs.iter_mut() {
+        if let AttrKind::Normal(item) = &mut attr.kind {
+            v.visit_attribute_mut(item);
+        }
+    }
+}
+
+fn walk_visibility_mut<V: VisitMut + ?Sized>(v: &mut V, vis: &mut Visibility) {
+    if let Visibility::Scoped(VisibilityScope::Path(path)) = vis {
+        v.visit_path_mut(path);
+    }
+}
+
+pub fn walk_item_mut<V: VisitMut + ?Sized>(v: &mut V, item: &mut Item) {
+    walk_attributes_mut(v, &mut item.attrs);
+    walk_visibility_mut(v, &mut item.vis);
+    match &mut item.kind {
+        ItemKind::Use(use_) => v.visit_use_tree_mut(&mut use_.0),
+        ItemKind::StaticItem(StaticItem { ty, expr, .. })
+        | ItemKind::ConstItem(ConstItem { ty, expr, .. }) => {
+            v.visit_type_mut(ty);
+            if let Some(expr) = expr {
+                v.visit_expr_mut(expr);
+            }
+        }
+        ItemKind::Fn(fn_) => v.visit_fn_mut(fn_),
+        ItemKind::Mod(Mod::Loaded(module)) => {
+            for item in module.items.iter_mut() {
+                v.visit_item_mut(item);
+            }
+        }
+        ItemKind::Mod(Mod::Unloaded(_)) => {}
+        ItemKind::TyAlias(alias) => walk_ty_alias_mut(v, alias),
+        ItemKind::EnumDef(def) => {
+            walk_generic_params_mut(v, &mut def.generics.params);
+            walk_where_clause_mut(v, &mut def.generics.where_clause);
+            for variant in def.variants.iter_mut() {
+                walk_attributes_mut(v, &mut variant.attrs);
+                walk_visibility_mut(v, &mut variant.vis);
+                walk_fields_mut(v, &mut variant.fields);
+                if let Some(discriminant) = &mut variant.discriminant {
+                    v.visit_expr_mut(discriminant);
+                }
+            }
+        }
+        ItemKind::StructDef(StructDef {
+            generics, fields, ..
+        })
+        | ItemKind::UnionDef(UnionDef {
+            generics, fields, ..
+        }) => {
+            walk_generic_params_mut(v, &mut generics.params);
+            walk_where_clause_mut(v, &mut generics.where_clause);
+            walk_fields_mut(v, fields);
+        }
+        ItemKind::TraitDef(def) => {
+            walk_generic_params_mut(v, &mut def.generics.params);
+            for supertrait in def.supertraits.iter_mut() {
+                v.visit_type_mut(supertrait);
+            }
+            walk_where_clause_mut(v, &mut def.generics.where_clause);
+            for item in def.items.iter_mut() {
+                v.visit_assoc_item_mut(item);
+            }
+        }
+        ItemKind::Impl(impl_) => {
+            walk_generic_params_mut(v, &mut impl_.generics.params);
+            if let Some(of_trait) = &mut impl_.of_trait {
+                v.visit_type_mut(of_trait);
+            }
+            v.visit_type_mut(&mut impl_.self_ty);
+            walk_where_clause_mut(v, &mut impl_.generics.where_clause);
+            for item in impl_.items.iter_mut() {
+                v.visit_assoc_item_mut(item);
+            }
+        }
+        ItemKind::MacCallWithSemi(Semi(mac)) => v.visit_path_mut(&mut mac.path),
+        ItemKind::ExternBlock(block) => v.visit_block_mut(&mut block.block),
+        ItemKind::MacroDef(_) | ItemKind::ExternCrate(_) | ItemKind::Verbatim(_) => {}
+    }
+}
+
+pub fn walk_assoc_item_mut<V: VisitMut + ?Sized>(v: &mut V, item: &mut AssocItem) {
+    walk_attributes_mut(v, &mut item.attrs);
+    walk_visibility_mut(v, &mut item.vis);
+    match &mut item.kind {
+        AssocItemKind::ConstItem(ConstItem { ty, expr, .. }) => {
+            v.visit_type_mut(ty);
+            if let Some(expr) = expr {
+                v.visit_expr_mut(expr);
+            }
+        }
+        AssocItemKind::Fn(fn_) => v.visit_fn_mut(fn_),
+        AssocItemKind::TyAlias(alias) => walk_ty_alias_mut(v, alias),
+        AssocItemKind::MacCall(mac) => v.visit_path_mut(&mut mac.path),
+    }
+}
+
+fn walk_ty_alias_mut<V: VisitMut + ?Sized>(v: &mut V, alias: &mut TyAlias) {
+    walk_generic_params_mut(v, &mut alias.generics.params);
+    walk_bounds_mut(v, &mut alias.bounds);
+    walk_where_clause_mut(v, &mut alias.generics.where_clause);
+    if let Some(ty) = &mut alias.ty {
+        v.visit_type_mut(ty);
+    }
+}
+
+fn walk_where_clause_mut<V: VisitMut + ?Sized>(v: &mut V, clause: &mut WhereClause) {
+    for pred in clause.predicates.iter_mut() {
+        if let WherePredicate::Type(pred) = pred {
+            v.visit_type_mut(&mut pred.bounded_ty);
+            walk_bounds_mut(v, &mut pred.bounds);
+        }
+    }
+}
+
+pub fn walk_fn_mut<V: VisitMut + ?Sized>(v: &mut V, fn_: &mut Fn) {
+    walk_generic_params_mut(v, &mut fn_.generics.params);
+    walk_fn_decl_mut(v, &mut fn_.fn_decl);
+    walk_where_clause_mut(v, &mut fn_.generics.where_clause);
+    if let Some(body) = &mut fn_.body {
+        v.visit_block_mut(body);
+    }
+}
+
+fn walk_fn_decl_mut<V: VisitMut + ?Sized>(v: &mut V, decl: &mut FnDecl) {
+    walk_params_mut(v, &mut decl.inputs);
+    if let Some(output) = &mut decl.output {
+        v.visit_type_mut(output);
+    }
+}
+
+fn walk_params_mut<V: VisitMut + ?Sized>(v: &mut V, params: &mut [Param]) {
+    for param in params.iter_mut() {
+        v.visit_pat_mut(&mut param.pat);
+        v.visit_type_mut(&mut param.ty);
+    }
+}
+
+fn walk_fields_mut<V: VisitMut + ?Sized>(v: &mut V, fields: &mut Fields) {
+    match fields {
+        Fields::Unit => {}
+        Fields::Tuple(fields) | Fields::Struct(fields) => {
+            for field in fields.iter_mut() {
+                walk_attributes_mut(v, &mut field.attrs);
+                walk_visibility_mut(v, &mut field.vis);
+                v.visit_type_mut(&mut field.ty);
+            }
+        }
+    }
+}
+
+fn walk_generic_params_mut<V: VisitMut + ?Sized>(v: &mut V, params: &mut [GenericParam]) {
+    for param in params.iter_mut() {
+        v.visit_generic_param_mut(param);
+    }
+}
+
+pub fn walk_attribute_mut<V: VisitMut + ?Sized>(v: &mut V, attr: &mut AttributeItem) {
+    v.visit_path_mut(&mut attr.path);
+    if let AttrArgs::Eq(expr) = &mut attr.args {
+        v.visit_expr_mut(expr);
+    }
+}
+
+pub fn walk_use_tree_mut<V: VisitMut + ?Sized>(v: &mut V, tree: &mut UseTree) {
+    match tree {
+        UseTree::Path(path) => v.visit_use_tree_mut(path.tree_mut()),
+        UseTree::Group(trees) => {
+            for tree in trees.iter_mut() {
+                v.visit_use_tree_mut(tree);
+            }
+        }
+        UseTree::Name(_) | UseTree::Rename(_) | UseTree::Glob => {}
+    }
+}
+
+pub fn walk_block_mut<V: VisitMut + ?Sized>(v: &mut V, block: &mut Block) {
+    walk_attributes_mut(v, &mut block.attrs);
+    for stmt in block.stmts.iter_mut() {
+        v.visit_stmt_mut(stmt);
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitMut + ?Sized>(v: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::Local(local) => {
+            v.visit_pat_mut(&mut local.pat);
+            if let Some(ty) = &mut local.ty {
+                v.visit_type_mut(ty);
+            }
+            match &mut local.kind {
+                LocalKind::Decl => {}
+                LocalKind::Init(expr) => v.visit_expr_mut(expr),
+                LocalKind::InitElse(expr, block) => {
+                    v.visit_expr_mut(expr);
+                    v.visit_block_mut(block);
+                }
+            }
+        }
+        Stmt::Item(item) => v.visit_item_mut(item),
+        Stmt::Expr(expr) | Stmt::Semi(Semi(expr)) => v.visit_expr_mut(expr),
+        Stmt::MacCallWithSemi(Semi(mac)) => v.visit_path_mut(&mut mac.path),
+        Stmt::Empty(_) => {}
+    }
+}
+
+pub fn walk_expr_mut<V: VisitMut + ?Sized>(v: &mut V, expr: &mut Expr) {
+    for attr in expr.attrs.iter_mut() {
+        v.visit_attribute_mut(attr);
+    }
+    match &mut expr.kind {
+        ExprKind::Array(Array(exprs)) | ExprKind::Tuple(Tuple(exprs)) => {
+            for expr in exprs.iter_mut() {
+                v.visit_expr_mut(expr);
+            }
+        }
+        ExprKind::Call(call) => {
+            v.visit_expr_mut(&mut call.func);
+            for arg in call.args.iter_mut() {
+                v.visit_expr_mut(arg);
+            }
+        }
+        ExprKind::MethodCall(call) => {
+            v.visit_expr_mut(&mut call.receiver);
+            walk_path_segment_mut(v, &mut call.seg);
+            for arg in call.args.iter_mut() {
+                v.visit_expr_mut(arg);
+            }
+        }
+        ExprKind::Binary(Binary { lhs, rhs, .. })
+        | ExprKind::Assign(Assign { lhs, rhs })
+        | ExprKind::AssignOp(AssignOp { lhs, rhs, .. }) => {
+            v.visit_expr_mut(lhs);
+            v.visit_expr_mut(rhs);
+        }
+        ExprKind::Unary(Unary { expr, .. })
+        | ExprKind::Await(Await { expr })
+        | ExprKind::Field(Field { expr, .. })
+        | ExprKind::AddrOf(AddrOf { expr, .. })
+        | ExprKind::Try(Try { expr })
+        | ExprKind::Paren(Paren(expr)) => v.visit_expr_mut(expr),
+        ExprKind::Lit(_)
+        | ExprKind::Underscore(_)
+        | ExprKind::Continue(_)
+        | ExprKind::Verbatim(_) => {}
+        ExprKind::Cast(Cast { expr, ty })
+        | ExprKind::TypeAscription(TypeAscription { expr, ty }) => {
+            v.visit_expr_mut(expr);
+            v.visit_type_mut(ty);
+        }
+        ExprKind::Let(Let { pat, expr }) => {
+            v.visit_pat_mut(pat);
+            v.visit_expr_mut(expr);
+        }
+        ExprKind::If(if_) => {
+            v.visit_expr_mut(&mut if_.cond);
+            v.visit_block_mut(&mut if_.then);
+            if let Some(else_) = &mut if_.else_ {
+                v.visit_expr_mut(else_);
+            }
+        }
+        ExprKind::While(while_) => {
+            v.visit_expr_mut(&mut while_.cond);
+            v.visit_block_mut(&mut while_.body);
+        }
+        ExprKind::ForLoop(for_) => {
+            v.visit_pat_mut(&mut for_.pat);
+            v.visit_expr_mut(&mut for_.expr);
+            v.visit_block_mut(&mut for_.body);
+        }
+        ExprKind::Loop(Loop { body: block, .. })
+        | ExprKind::ConstBlock(ConstBlock { block })
+        | ExprKind::UnsafeBlock(UnsafeBlock { block })
+        | ExprKind::LabelledBlock(LabelledBlock { block, .. })
+        | ExprKind::Async(Async { block, .. })
+        | ExprKind::TryBlock(TryBlock { block }) => v.visit_block_mut(block),
+        ExprKind::Match(match_) => {
+            v.visit_expr_mut(&mut match_.expr);
+            for arm in match_.arms.iter_mut() {
+                for attr in arm.attrs.iter_mut() {
+                    v.visit_attribute_mut(attr);
+                }
+                v.visit_pat_mut(&mut arm.pat);
+                if let Some(guard) = &mut arm.guard {
+                    v.visit_expr_mut(guard);
+                }
+                v.visit_expr_mut(&mut arm.body);
+            }
+        }
+        ExprKind::Closure(closure) => {
+            walk_fn_decl_mut(v, &mut closure.fn_decl);
+            v.visit_expr_mut(&mut closure.body);
+        }
+        ExprKind::Index(index) => {
+            v.visit_expr_mut(&mut index.expr);
+            v.visit_expr_mut(&mut index.index);
+        }
+        ExprKind::Range(range) => {
+            if let Some(start) = &mut range.start {
+                v.visit_expr_mut(start);
+            }
+            if let Some(end) = &mut range.end {
+                v.visit_expr_mut(end);
+            }
+        }
+        ExprKind::Path(path) => v.visit_path_mut(path),
+        ExprKind::QPath(qpath) => walk_qpath_mut(v, qpath),
+        ExprKind::Break(Break { expr, .. })
+        | ExprKind::Return(Return { expr })
+        | ExprKind::Yield(Yield { expr }) => {
+            if let Some(expr) = expr {
+                v.visit_expr_mut(expr);
+            }
+        }
+        ExprKind::MacCall(mac) => v.visit_path_mut(&mut mac.path),
+        ExprKind::Struct(struct_) => {
+            v.visit_path_mut(&mut struct_.path);
+            for field in struct_.fields.iter_mut() {
+                v.visit_expr_mut(&mut field.expr);
+            }
+            if let Some(rest) = &mut struct_.rest {
+                v.visit_expr_mut(rest);
+            }
+        }
+        ExprKind::Repeat(repeat) => {
+            v.visit_expr_mut(&mut repeat.expr);
+            v.visit_expr_mut(&mut repeat.len.0);
+        }
+    }
+}
+
+pub fn walk_pat_mut<V: VisitMut + ?Sized>(v: &mut V, pat: &mut Pat) {
+    match pat {
+        Pat::Wild | Pat::Rest => {}
+        Pat::Ident(ident) => {
+            if let Some(pat) = &mut ident.pat {
+                v.visit_pat_mut(pat);
+            }
+        }
+        Pat::Struct(struct_) => {
+            v.visit_path_mut(&mut struct_.path);
+            for field in struct_.fields.iter_mut() {
+                v.visit_pat_mut(&mut field.pat);
+            }
+        }
+        Pat::TupleStruct(tuple_struct) => {
+            v.visit_path_mut(&mut tuple_struct.path);
+            for pat in tuple_struct.pats.iter_mut() {
+                v.visit_pat_mut(pat);
+            }
+        }
+        Pat::Or(pats) | Pat::Tuple(pats) | Pat::Slice(pats) => {
+            for pat in pats.iter_mut() {
+                v.visit_pat_mut(pat);
+            }
+        }
+        Pat::Box(pat) | Pat::Ref(RefPat { pat, .. }) | Pat::Paren(pat) => v.visit_pat_mut(pat),
+        Pat::Lit(expr) => v.visit_expr_mut(expr),
+        Pat::Range(range) => {
+            if let Some(start) = &mut range.start {
+                v.visit_expr_mut(start);
+            }
+            if let Some(end) = &mut range.end {
+                v.visit_expr_mut(end);
+            }
+        }
+        Pat::MacCall(mac) => v.visit_path_mut(&mut mac.path),
+    }
+}
+
+pub fn walk_type_mut<V: VisitMut + ?Sized>(v: &mut V, ty: &mut Type) {
+    match ty {
+        Type::Slice(ty)
+        | Type::Paren(ty)
+        | Type::Ptr(Ptr { ty, .. })
+        | Type::Ref(Ref {
+            ty: MutTy { ty, .. },
+            ..
+        }) => v.visit_type_mut(ty),
+        Type::Array(ty, len) => {
+            v.visit_type_mut(ty);
+            v.visit_expr_mut(&mut len.0);
+        }
+        Type::BareFn(bare_fn) => {
+            walk_generic_params_mut(v, &mut bare_fn.generic_params);
+            walk_params_mut(v, &mut bare_fn.inputs);
+            v.visit_type_mut(&mut bare_fn.output);
+        }
+        Type::Macro(mac) => v.visit_path_mut(&mut mac.path),
+        Type::Tuple(tys) => {
+            for ty in tys.iter_mut() {
+                v.visit_type_mut(ty);
+            }
+        }
+        Type::Path(path) => v.visit_path_mut(path),
+        Type::QPath(qpath) => walk_qpath_mut(v, qpath),
+        Type::TraitObject(TraitObject { bounds, .. })
+        | Type::ImplTrait(ImplTrait { bounds, .. }) => walk_bounds_mut(v, bounds),
+        Type::Never | Type::Infer | Type::ImplicitSelf | Type::Err | Type::Verbatim(_) => {}
+    }
+}
+
+fn walk_qpath_mut<V: VisitMut + ?Sized>(v: &mut V, qpath: &mut QPath) {
+    v.visit_type_mut(&mut qpath.qself);
+    if let Some(trait_) = &mut qpath.trait_ {
+        v.visit_path_mut(trait_);
+    }
+    v.visit_path_mut(&mut qpath.path);
+}
+
+fn walk_bounds_mut<V: VisitMut + ?Sized>(v: &mut V, bounds: &mut [GenericBound]) {
+    for bound in bounds.iter_mut() {
+        match bound {
+            GenericBound::Trait(poly) => {
+                walk_generic_params_mut(v, &mut poly.bound_generic_params);
+                v.visit_path_mut(&mut poly.trait_ref);
+            }
+            GenericBound::Outlives(_) => {}
+        }
+    }
+}
+
+pub fn walk_generic_param_mut<V: VisitMut + ?Sized>(v: &mut V, param: &mut GenericParam) {
+    match param {
+        GenericParam::LifetimeParam(_) => {}
+        GenericParam::TypeParam(param) => {
+            walk_bounds_mut(v, &mut param.bounds);
+            if let Some(default) = &mut param.default {
+                v.visit_type_mut(default);
+            }
+        }
+        GenericParam::ConstParam(param) => {
+            v.visit_type_mut(&mut param.ty);
+            if let Some(Const(default)) = &mut param.default {
+                v.visit_expr_mut(default);
+            }
+        }
+    }
+}
+
+pub fn walk_path_mut<V: VisitMut + ?Sized>(v: &mut V, path: &mut Path) {
+    for segment in path.segments.iter_mut() {
+        walk_path_segment_mut(v, segment);
+    }
+}
+
+fn walk_path_segment_mut<V: VisitMut + ?Sized>(v: &mut V, segment: &mut PathSegment) {
+    match &mut segment.args {
+        Some(GenericArgs::AngleBracketed(args)) => {
+            for arg in args.iter_mut() {
+                match arg {
+                    GenericArg::Lifetime(_) => {}
+                    GenericArg::Type(ty) => v.visit_type_mut(ty),
+                    GenericArg::Const(Const(expr)) => v.visit_expr_mut(expr),
+                    GenericArg::Constraint(constraint) => v.visit_type_mut(&mut constraint.ty),
+                }
+            }
+        }
+        Some(GenericArgs::Parenthesized(args)) => {
+            for input in args.inputs.iter_mut() {
+                v.visit_type_mut(input);
+            }
+            if let Some(output) = &mut args.output {
+                v.visit_type_mut(output);
+            }
+        }
+        None => {}
+    }
+}
+
+/// A node yielded by [`Crate::walk`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeRef<'a> {
+    Item(&'a Item),
+    AssocItem(&'a AssocItem),
+    Block(&'a Block),
+    Stmt(&'a Stmt),
+    Expr(&'a Expr),
+    Pat(&'a Pat),
+    Type(&'a Type),
+    Path(&'a Path),
+}
+
+/// The ancestors of a node yielded by [`Crate::walk`]: its parent, the parent's parent and so
+/// on up to the top-level item. Top-level items have no parent.
+#[derive(Debug, Clone, Default)]
+pub struct ParentRef<'a>(Option<Rc<(NodeRef<'a>, ParentRef<'a>)>>);
+
+impl<'a> ParentRef<'a> {
+    /// The direct parent.
+    pub fn node(&self) -> Option<NodeRef<'a>> {
+        self.0.as_ref().map(|link| link.0)
+    }
+
+    /// The ancestors of the parent.
+    pub fn parent(&self) -> Option<&ParentRef<'a>> {
+        self.0.as_ref().map(|link| &link.1)
+    }
+
+    /// All ancestors, innermost first.
+    pub fn ancestors(&self) -> impl Iterator<Item = NodeRef<'a>> + '_ {
+        std::iter::successors(Some(self), |parent| parent.parent()).map_while(ParentRef::node)
+    }
+}
+
+#[derive(Default)]
+struct Walker<'a> {
+    nodes: Vec<(NodeRef<'a>, ParentRef<'a>)>,
+    parent: ParentRef<'a>,
+}
+
+impl<'a> Walker<'a> {
+    fn enter(&mut self, node: NodeRef<'a>, walk: impl FnOnce(&mut Self)) {
+        self.nodes.push((node, self.parent.clone()));
+        let parent = ParentRef(Some(Rc::new((node, self.parent.clone()))));
+        let outer = std::mem::replace(&mut self.parent, parent);
+        walk(self);
+        self.parent = outer;
+    }
+}
+
+impl<'a> Visit<'a> for Walker<'a> {
+    fn visit_item(&mut self, item: &'a Item) {
+        self.enter(NodeRef::Item(item), |w| walk_item(w, item));
+    }
+    fn visit_assoc_item(&mut self, item: &'a AssocItem) {
+        self.enter(NodeRef::AssocItem(item), |w| walk_assoc_item(w, item));
+    }
+    fn visit_block(&mut self, block: &'a Block) {
+        self.enter(NodeRef::Block(block), |w| walk_block(w, block));
+    }
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        self.enter(NodeRef::Stmt(stmt), |w| walk_stmt(w, stmt));
+    }
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        self.enter(NodeRef::Expr(expr), |w| walk_expr(w, expr));
+    }
+    fn visit_pat(&mut self, pat: &'a Pat) {
+        self.enter(NodeRef::Pat(pat), |w| walk_pat(w, pat));
+    }
+    fn visit_type(&mut self, ty: &'a Type) {
+        self.enter(NodeRef::Type(ty), |w| walk_type(w, ty));
+    }
+    fn visit_path(&mut self, path: &'a Path) {
+        self.enter(NodeRef::Path(path), |w| walk_path(w, path));
+    }
+}
+
+impl Crate {
+    /// Every item, block, statement, expression, pattern, type and path of the crate in
+    /// visiting order, each with its ancestors.
+    pub fn walk(&self) -> impl Iterator<Item = (NodeRef<'_>, ParentRef<'_>)> {
+        let mut walker = Walker::default();
+        walker.visit_crate(self);
+        walker.nodes.into_iter()
+    }
+}
@@ -27,12 +27,14 @@ fn test() {
 fn test_general() {
     let mut krate = Crate::new();
     let i = krate.add_item(Fn {
+        #[cfg(feature = "unstable-syntax")]
+        is_default: false,
         is_unsafe: false,
         is_const: false,
         is_async: false,
         abi: None,
         ident: "main".into(),
-        generics: vec![],
+        generics: Generics::default(),
         fn_decl: FnDecl::regular(vec![], None),
         body: Some(Block::from(Stmt::Semi(Semi::new(Expr::new(MacCall {
             path: Path::single("println"),
@@ -62,6 +64,100 @@ fn test_blocks() {
     assert_snapshot!(Block::empty(), @"{}");
 }
 
+#[test]
+fn test_block_editing() {
+    let mut block = Block::empty()
+        .with_stmt(Local::simple(
+            Pat::ident("x"),
+            Path::single("compute").call(vec![]),
+        ))
+        .with_stmt(Path::single("log").call1(Path::single("x")).semi())
+        .with_stmt(Expr::from(Path::single("x").add(Lit::int("1"))));
+
+    block.insert_stmt(
+        0,
+        Local::simple(
+            Pat::ident("_span"),
+            Path::single("span").call1(Lit::str("f")),
+        ),
+    );
+    let log = |stmt: &Stmt| stmt.to_string().starts_with("log(");
+    assert!(block.insert_after(log, Empty {}).is_some());
+    let record = block
+        .insert_before(
+            |stmt| matches!(stmt, Stmt::Expr(_)),
+            Path::single("record").call1(Path::single("x")).semi(),
+        )
+        .unwrap();
+    assert!(block.insert_after(|_| false, Empty {}).is_none());
+    assert_snapshot!(block, @r###"
+    {
+        let _span = span("f");
+        let x = compute();
+        log(x);
+
+        record(x);
+        x + 1
+    }
+    "###);
+
+    let old = block.replace_stmt(
+        record,
+        Path::single("record")
+            .call1(Path::single("x").ref_immut())
+            .semi(),
+    );
+    assert_snapshot!(old, @"record(x);");
+    block.retain_stmts(|stmt| !log(stmt) && !matches!(stmt, Stmt::Empty(_)));
+    let locals = block.extract_stmts(|stmt| matches!(stmt, Stmt::Local(_)));
+    assert_eq!(locals.len(), 2);
+    assert_snapshot!(block, @r###"
+    {
+        record(&x);
+        x + 1
+    }
+    "###);
+    assert_eq!(block.try_replace_stmt(9, Empty {}), None);
+}
+
+#[test]
+fn test_float_lits() {
+    assert_snapshot!(Lit::float("1"), @"1.0");
+    assert_snapshot!(Lit::float("-2"), @"-2.0");
+    assert_snapshot!(Lit::float("1e10"), @"1e10");
+    assert_snapshot!(Lit::float("3f32"), @"3f32");
+    assert_snapshot!(Lit::f64(1.0).unwrap(), @"1.0f64");
+    assert_snapshot!(Lit::f64(0.1 + 0.2).unwrap(), @"0.30000000000000004f64");
+    assert_snapshot!(Lit::f32(1e-7).unwrap(), @"1e-7f32");
+    assert_eq!(Lit::f64(f64::NAN), None);
+    assert_eq!(Lit::f32(f32::NEG_INFINITY), None);
+    assert_snapshot!(Expr::f64(f64::NAN), @"f64::NAN");
+    assert_snapshot!(Expr::f64(f64::NEG_INFINITY), @"f64::NEG_INFINITY");
+    assert_snapshot!(Expr::f32(f32::INFINITY), @"f32::INFINITY");
+    assert_snapshot!(Expr::f64(-0.0), @"-0.0f64");
+    let roundtrip = Lit::f64(std::f64::consts::PI).unwrap().to_string();
+    assert_eq!(
+        roundtrip.trim_end_matches("f64").parse::<f64>(),
+        Ok(std::f64::consts::PI)
+    );
+}
+
+#[test]
+fn test_int_with_suffix() {
+    let max = Lit::int_with_suffix(255, IntSuffix::U8).unwrap();
+    assert_snapshot!(max, @"255u8");
+    assert_eq!(max.kind, LitKind::UInteger);
+    assert_snapshot!(Lit::int_with_suffix(-128, IntSuffix::I8).unwrap(), @"-128i8");
+    let err = Lit::int_with_suffix(300, IntSuffix::U8).unwrap_err();
+    assert_snapshot!(err, @"`300` is out of range for `u8`");
+    assert!(Lit::int_with_suffix(-1, IntSuffix::Usize).is_err());
+    assert!(Lit::int_with_suffix(i64::MAX as i128 + 1, IntSuffix::I64).is_err());
+
+    let neg = Lit::int_with_suffix(-5, IntSuffix::I32).unwrap();
+    assert_snapshot!(Expr::from(neg).method_call("abs".into(), vec![]), @"(-5i32).abs()");
+    assert_snapshot!(Expr::from(Lit::f64(-1.5).unwrap()).method_call("abs".into(), vec![]), @"(-1.5f64).abs()");
+}
+
 #[test]
 fn test_call() {
     let call = Path::single("foo").call(vec![Lit::int("42").into()]);
@@ -128,6 +224,19 @@ fn test_binop() {
     assert_snapshot!(add, @"-1 + -2");
 }
 
+#[test]
+fn test_operator_overloading() {
+    let one = || Lit::int("1");
+    let x = || Path::single("x");
+    assert_snapshot!(one() + Lit::int("2") * x(), @"1 + 2 * x");
+    assert_snapshot!((one() + x()) * x(), @"(1 + x) * x");
+    assert_snapshot!(x() - (x() - one()), @"x - (x - 1)");
+    assert_snapshot!(-(x() % Lit::int("3")) << one(), @"-(x % 3) << 1");
+    assert_snapshot!(!(x() & one() | x() ^ one()), @"!(x & 1 | x ^ 1)");
+    let eq = x().bin_op(BinOpKind::Eq, one());
+    assert_snapshot!(eq.bin_op(BinOpKind::Eq, Lit::bool("true")), @"(x == 1) == true");
+}
+
 #[test]
 fn test_try() {
     let x = Path::single("x");
@@ -179,6 +288,11 @@ fn test_field() {
     assert_snapshot!(field_raw_ref, @"&raw mut x.z");
     let raw_ref_field = x.addr_of(BorrowKind::Raw, Mutability::Mut).field("z");
     assert_snapshot!(raw_ref_field, @"(&raw mut x).z");
+
+    let tuple_field = Path::single("x").field_idx(0).field_idx(1);
+    assert_snapshot!(tuple_field, @"x.0.1");
+    let self_field = Expr::from(Path::single("self")).field_idx(0).ref_immut();
+    assert_snapshot!(self_field, @"&self.0");
 }
 
 #[test]
@@ -239,6 +353,22 @@ fn test_struct() {
     assert_snapshot!(struct_, @"MyStruct { a: 1, b: x, c: 3 }");
 }
 
+#[test]
+fn test_struct_update() {
+    let struct_ = Struct::new("Person", vec![ExprField::shortened("name")])
+        .with_rest(Path::single("Default").chain("default").call(vec![]));
+    assert_snapshot!(struct_, @"Person { name, ..Default::default() }");
+    let ts = TokenStream::from(struct_);
+    assert_snapshot!(ts, @"Person { name, ..Default::default() }");
+    // a shorthand field whose value was rewritten falls back to `ident: expr`
+    let mut rewritten = Struct::new("Point", vec![ExprField::shortened("x")]);
+    rewritten.fields[0].expr = Lit::int("1").into();
+    assert_snapshot!(rewritten, @"Point { x: 1 }");
+    assert_snapshot!(TokenStream::from(rewritten), @"Point { x: 1 }");
+    let rest_only = Struct::new("Config", vec![]).with_rest(Path::single("base"));
+    assert_snapshot!(rest_only, @"Config { ..base }");
+}
+
 #[test]
 fn test_return() {
     let x = Path::single("x");
@@ -331,6 +461,32 @@ fn test_macro_call() {
         Path::single("assign").mac_call(DelimArgs::new(MacDelimiter::Parenthesis, tokens.into()));
 
     assert_snapshot!(mac_call, @"assign!(let mut x = 42)");
+
+    let args = |delim| DelimArgs::new(delim, TokenStream::from(vec![Token::ident("x")]));
+    let pat = Pat::mac_call(Path::single("pat_macro"), args(MacDelimiter::Parenthesis));
+    let arm = Arm::new(pat, None, Lit::bool("true"));
+    let match_ = Match::new(Path::single("x"), vec![arm]);
+    assert_snapshot!(match_, @r###"
+    match x {
+        pat_macro!(x) => true,
+    }
+    "###);
+
+    let ty = Type::from(Path::single("ty_macro").mac_call(args(MacDelimiter::Bracket)));
+    assert_snapshot!(ty, @"ty_macro![x]");
+
+    let mut block = Block::empty();
+    block.add_stmt(Semi(
+        Path::single("m").mac_call(args(MacDelimiter::Parenthesis)),
+    ));
+    block.add_stmt(Semi(Path::single("m").mac_call(args(MacDelimiter::Brace))));
+    assert_snapshot!(block, @r###"
+    {
+        m!(x);
+        m!{x}
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(block), @"{ m!(x); m!{x}}");
 }
 
 #[test]
@@ -356,3 +512,3178 @@ fn test_joint_token() {
     ]);
     assert_snapshot!(ts, @"foo.bar");
 }
+
+#[test]
+fn test_crate_item_order() {
+    let mut krate = Crate::new();
+    for name in ["a", "b", "c", "d"] {
+        krate.add_item(Fn::empty(name));
+    }
+    assert_snapshot!(krate, @r###"
+    fn a();
+    fn b();
+    fn c();
+    fn d();
+    "###);
+}
+
+#[test]
+fn test_assert_acyclic() {
+    // the same subtree used twice is cloned, not shared
+    let body = Block::from(Path::single("x").call(vec![]).semi());
+    let mut krate = Crate::new();
+    krate.add_item(Fn::simple("a", FnDecl::empty(), body.clone()));
+    krate.add_item(Fn::simple("b", FnDecl::empty(), body));
+    krate.assert_acyclic();
+    assert_snapshot!(krate, @r###"
+    fn a() {
+        x();
+    }
+    fn b() {
+        x();
+    }
+    "###);
+}
+
+#[test]
+fn test_derive_policy() {
+    let derives = [
+        "MyTrait",
+        "Hash",
+        "Serialize",
+        "Debug",
+        "Clone",
+        "Deserialize",
+    ];
+    let sorted = DerivePolicy::sorted().apply(derives);
+    assert_snapshot!(sorted[0], @"#[derive(Debug, Clone, Hash, Serialize, Deserialize, MyTrait)]");
+    let split = DerivePolicy::sorted().with_max_width(40).apply(derives);
+    let split = split.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_snapshot!(split.join("\n"), @r###"
+    #[derive(Debug, Clone, Hash, Serialize)]
+    #[derive(Deserialize, MyTrait)]
+    "###);
+
+    let mut attrs = vec![
+        Attribute::doc_comment("/// doc"),
+        Attribute::from(DerivePolicy::default().apply(["Foo", "Eq"]).remove(0)),
+        Attribute::from(AttributeItem::simple("non_exhaustive")),
+        Attribute::from(
+            DerivePolicy::default()
+                .apply([
+                    Path::single("serde").chain("Serialize"),
+                    Path::single("PartialEq"),
+                ])
+                .remove(0),
+        ),
+    ];
+    DerivePolicy::sorted().apply_to(&mut attrs);
+    let attrs = attrs.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_snapshot!(attrs.join("\n"), @r###"
+    /// doc
+    #[derive(PartialEq, Eq, serde::Serialize, Foo)]
+    #[non_exhaustive]
+    "###);
+
+    let mut attrs = vec![
+        Attribute::from(DerivePolicy::default().apply(["Clone"]).remove(0)),
+        Attribute::from(DerivePolicy::default().apply(["Clone", "Debug"]).remove(0)),
+    ];
+    DerivePolicy::sorted().apply_to(&mut attrs);
+    assert_eq!(attrs.len(), 1);
+    assert_snapshot!(attrs[0], @"#[derive(Debug, Clone)]");
+}
+
+#[test]
+fn test_derive_attr() {
+    let derive = Attribute::derive(["Debug", "Clone", "serde::Serialize"]);
+    assert_snapshot!(derive, @"#[derive(Debug, Clone, serde::Serialize)]");
+    assert_snapshot!(TokenStream::from(derive.clone()), @"#[derive(Debug, Clone, serde::Serialize)]");
+    let mut attrs = vec![
+        derive,
+        Attribute::derive([Path::single("std").chain("hash").chain("Hash")]),
+    ];
+    DerivePolicy::sorted().apply_to(&mut attrs);
+    assert_snapshot!(attrs[0], @"#[derive(Debug, Clone, std::hash::Hash, serde::Serialize)]");
+}
+
+#[test]
+fn test_inner_attrs() {
+    let allow = || {
+        AttributeItem::new(
+            "allow",
+            AttrArgs::Delimited(DelimArgs::parenthesis(TokenStream::from(vec![
+                Token::ident("dead_code"),
+            ]))),
+        )
+    };
+    let krate = Crate::new()
+        .with_attr(AttributeItem::simple("no_std"))
+        .with_attr(Attribute::inner(allow()))
+        .with_item(Fn::simple(
+            "f",
+            FnDecl::empty(),
+            Block::single(Lit::int("1")).with_attr(allow()),
+        ));
+    assert_snapshot!(krate, @r###"
+    #![no_std]
+    #![allow(dead_code)]
+
+    fn f() {
+        #![allow(dead_code)]
+        1
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(krate), @"#![no_std] #![allow(dead_code)] fn f() { #![allow(dead_code)] 1 }");
+    assert_snapshot!(Attribute::inner(AttributeItem::simple("no_std")), @"#![no_std]");
+    assert_snapshot!(Block::empty().with_attr(allow()), @r###"
+    {
+        #![allow(dead_code)]
+    }
+    "###);
+}
+
+#[test]
+fn test_doc_lines() {
+    let item: Item = Item::public(Fn::empty("parse"))
+        .with_doc("Parses the input.\n\n# Errors\nFails on empty input.");
+    let krate = Crate::new()
+        .with_attr(Attribute::doc("Generated bindings.\nDo not edit."))
+        .with_item(item);
+    assert_snapshot!(krate, @r###"
+    //! Generated bindings.
+    //! Do not edit.
+
+    /// Parses the input.
+    ///
+    /// # Errors
+    /// Fails on empty input.
+    pub fn parse();
+    "###);
+    assert_snapshot!(Attribute::inner_doc("a\n\nb"), @r###"
+    //! a
+    //!
+    //! b
+    "###);
+    assert_snapshot!(Attribute::doc_block("a\nb").into_inner(), @r###"
+    /*!
+    a
+    b
+    */
+    "###);
+    let tokens = TokenStream::from(Attribute::doc_attr("a").into_inner());
+    assert_snapshot!(tokens, @"#![doc = \"a\"]");
+}
+
+#[test]
+fn test_cfg_attr() {
+    let serde = Attribute::cfg_attr_feature("serde", Attribute::derive(["Serialize"]));
+    assert_snapshot!(serde, @"#[cfg_attr(feature = \"serde\", derive(Serialize))]");
+    assert_snapshot!(TokenStream::from(serde), @"#[cfg_attr(feature = \"serde\", derive(Serialize))]");
+    let not_test = TokenStream::from(vec![
+        Token::ident("not").into_joint(),
+        Token::OpenDelim(Delimiter::Parenthesis).into_joint(),
+        Token::ident("test").into_joint(),
+        Token::CloseDelim(Delimiter::Parenthesis),
+    ]);
+    let doc = Attribute::cfg_attr(not_test, Attribute::doc_attr("Release build."));
+    assert_snapshot!(doc, @"#[cfg_attr(not(test), doc = \"Release build.\")]");
+    let comment = Attribute::cfg_attr(
+        TokenStream::from(Token::ident("test")),
+        Attribute::doc("Test build.\nNot for release."),
+    );
+    assert_snapshot!(comment, @"#[cfg_attr(test, doc = \" Test build.\\n Not for release.\")]");
+    let item: Item = Item::inherited(StructDef::empty("Config")).with_attr(Attribute::cfg_attr(
+        TokenStream::from(Token::ident("test")),
+        Attribute::repr(Repr::C),
+    ));
+    assert_snapshot!(item, @r###"
+    #[cfg_attr(test, repr(C))]
+    struct Config{}
+    "###);
+}
+
+#[test]
+fn test_builtin_attrs() {
+    let attrs = [
+        Attribute::must_use(),
+        Attribute::must_use_msg("the \"guard\" unlocks on drop"),
+        Attribute::deprecated(None, None),
+        Attribute::deprecated(Some("1.2.0"), Some("use `parse` instead")),
+        Attribute::deprecated(None, Some("unused")),
+        Attribute::non_exhaustive(),
+        Attribute::inline(),
+        Attribute::inline_always(),
+        Attribute::inline_never(),
+        Attribute::track_caller(),
+    ];
+    let rendered = attrs.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_snapshot!(rendered.join("\n"), @r###"
+    #[must_use]
+    #[must_use = "the \"guard\" unlocks on drop"]
+    #[deprecated]
+    #[deprecated(since = "1.2.0", note = "use `parse` instead")]
+    #[deprecated(note = "unused")]
+    #[non_exhaustive]
+    #[inline]
+    #[inline(always)]
+    #[inline(never)]
+    #[track_caller]
+    "###);
+    let tokens = attrs.into_iter().map(TokenStream::from);
+    assert_snapshot!(TokenStream::aggregate(tokens), @"#[must_use] #[must_use = \"the \\\"guard\\\" unlocks on drop\"] #[deprecated] #[deprecated(since = \"1.2.0\", note = \"use `parse` instead\")] #[deprecated(note = \"unused\")] #[non_exhaustive] #[inline] #[inline(always)] #[inline(never)] #[track_caller]");
+}
+
+#[test]
+fn test_lint_attrs() {
+    let allow = Attribute::allow(["dead_code", "clippy::too_many_arguments"]);
+    assert_snapshot!(allow, @"#[allow(dead_code, clippy::too_many_arguments)]");
+    assert_snapshot!(TokenStream::from(allow), @"#[allow(dead_code, clippy::too_many_arguments)]");
+    assert_snapshot!(Attribute::warn(["missing_docs"]), @"#[warn(missing_docs)]");
+    assert_snapshot!(Attribute::deny([Path::single("rustdoc").chain("broken_intra_doc_links")]), @"#[deny(rustdoc::broken_intra_doc_links)]");
+    assert_snapshot!(Attribute::forbid(["unsafe_code"]), @"#[forbid(unsafe_code)]");
+    let krate = Crate::new().allow_all_generated().with_item(Fn::empty("f"));
+    assert_snapshot!(krate, @r###"
+    #![allow(dead_code, non_camel_case_types, non_snake_case, non_upper_case_globals, unused_imports, unused_mut, unused_variables, clippy::all)]
+
+    fn f();
+    "###);
+}
+
+#[test]
+fn test_serde_attrs() {
+    let field = FieldDef::public(
+        "next_page",
+        Type::poly_path("Option", vec![GenericArg::Type(Type::string())]),
+    )
+    .with_attr(
+        SerdeAttr::new()
+            .with_rename("next-page")
+            .with_default()
+            .with_skip_serializing_if("Option::is_none"),
+    )
+    .with_attr(SerdeAttr::new().with_alias("the \"next\" page"));
+    let def = StructDef::empty("Page").with_field(field).with_field(
+        FieldDef::public("extra", Type::simple_path("Extra"))
+            .with_attr(SerdeAttr::new().with_flatten()),
+    );
+    let item: Item = Item::public(def)
+        .with_attr(Attribute::derive([
+            "serde::Serialize",
+            "serde::Deserialize",
+        ]))
+        .with_attr(
+            SerdeAttr::new()
+                .with_rename_all("camelCase")
+                .with_deny_unknown_fields()
+                .into(),
+        );
+    assert_snapshot!(item, @r###"
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(rename_all = "camelCase", deny_unknown_fields)]
+    pub struct Page {
+        #[serde(rename = "next-page", default, skip_serializing_if = "Option::is_none")]
+        #[serde(alias = "the \"next\" page")]
+        pub next_page: Option<String>,
+        #[serde(flatten)]
+        pub extra: Extra
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(SerdeAttr::new().with_tag("type").with_skip()), @"#[serde(tag = \"type\", skip)]");
+}
+
+#[test]
+fn test_meta() {
+    let cfg = Meta::list(
+        "cfg",
+        [Meta::list(
+            "all",
+            [
+                Meta::path("unix"),
+                Meta::name_value("target_pointer_width", Lit::str("64")),
+            ],
+        )],
+    );
+    assert_snapshot!(cfg, @"cfg(all(unix, target_pointer_width = \"64\"))");
+    let item = AttributeItem::from(cfg.clone());
+    assert_snapshot!(item, @"#[cfg(all(unix, target_pointer_width = \"64\"))]");
+    assert_eq!(item.to_meta(), Some(cfg));
+
+    let doc = AttributeItem::from(Meta::name_value("doc", Lit::str("x")));
+    assert_snapshot!(TokenStream::from(doc.clone()), @"#[doc = \"x\"]");
+    assert_eq!(doc.to_meta(), Some(Meta::name_value("doc", Lit::str("x"))));
+    let align = Attribute::repr(Repr::Align(8));
+    let AttrKind::Normal(align) = align.kind else {
+        unreachable!()
+    };
+    assert_eq!(
+        align.to_meta(),
+        Some(Meta::list("repr", [Meta::list("align", [Lit::int("8")])]))
+    );
+    let tokens = AttributeItem::new(
+        "foo",
+        AttrArgs::Delimited(DelimArgs::parenthesis(TokenStream::from(vec![
+            Token::ident("a"),
+            Token::BinOp(BinOpToken::Plus),
+        ]))),
+    );
+    assert_eq!(tokens.to_meta(), None);
+}
+
+#[test]
+fn test_repr_attrs() {
+    let header: Item = Item::inherited(
+        StructDef::empty("Header")
+            .with_field(FieldDef::inherited("len", Type::u16()))
+            .with_field(FieldDef::inherited("tag", Type::u8())),
+    )
+    .with_attr(Attribute::repr(Repr::C))
+    .with_attr(Attribute::repr(Repr::Packed(1)));
+    assert_snapshot!(header, @r###"
+    #[repr(C)]
+    #[repr(packed(1))]
+    struct Header {
+        len: u16,
+        tag: u8
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(header), @"#[repr(C)] #[repr(packed(1))] struct Header { len: u16, tag: u8 }");
+    let kind: Item = Item::inherited(
+        EnumDef::empty("Kind")
+            .with_variant(Variant::unit("A"))
+            .with_variant(Variant::unit("B")),
+    )
+    .with_attr(Attribute::repr(Repr::Int(IntTy::U8)));
+    assert_snapshot!(kind, @r###"
+    #[repr(u8)]
+    enum Kind {
+        A,
+        B,
+    }
+    "###);
+    assert_snapshot!(Attribute::repr(Repr::Transparent), @"#[repr(transparent)]");
+    assert_snapshot!(Attribute::repr(Repr::Align(8)), @"#[repr(align(8))]");
+}
+
+#[test]
+fn test_type_lists() {
+    let tuple = Type::Tuple(vec![Type::usize(), Type::ref_(Type::str())]);
+    assert_snapshot!(tuple, @"(usize, &str)");
+    let bounds = vec![
+        GenericBound::Trait(PolyTraitRef::simple("Send")),
+        GenericBound::Outlives("static".into()),
+    ];
+    let dyn_ = TraitObject::dyn_(bounds.clone());
+    assert_snapshot!(dyn_, @"dyn Send + 'static");
+    let impl_ = ImplTrait::new(bounds.clone());
+    assert_snapshot!(impl_, @"impl Send + 'static");
+    let param = TypeParam::new("T", bounds);
+    assert_snapshot!(param, @"T: Send + 'static");
+}
+
+#[test]
+fn test_extract_items() {
+    let mut krate = Crate::new();
+    krate.add_item(Mod::new(
+        "api",
+        vec![Item::inherited(Use::from(
+            Path::single("crate").chain_use_group(vec![UseTree::name("Foo"), UseTree::name("bar")]),
+        ))],
+    ));
+    krate.add_pub_item(StructDef::empty("Foo"));
+    krate.add_pub_item(Fn::simple("bar", FnDecl::empty(), Block::empty()));
+    krate.add_item(Fn::simple("helper", FnDecl::empty(), Block::empty()));
+    krate.add_item(Fn::main(
+        None,
+        Block::from(Path::single("crate").chain("helper").call(vec![]).semi()),
+    ));
+    let (krate, module) = krate.extract_items("generated", |item| {
+        matches!(item.ident(), Some("Foo" | "helper"))
+    });
+    assert_snapshot!(krate, @r###"
+    mod api {
+        use crate::{generated::Foo, bar};
+    }
+    mod generated;
+    pub use self::generated::Foo;
+    use self::generated::helper;
+    pub fn bar() {}
+    fn main() {
+        crate::generated::helper();
+    }
+    "###);
+    assert_snapshot!(module, @r###"
+    mod generated {
+        use super::*;
+        pub struct Foo{}
+        pub(crate) fn helper() {}
+    }
+    "###);
+    // the imports of the rewritten crate resolve, without any name imported twice
+    let mut krate = krate;
+    let index = krate
+        .items
+        .iter()
+        .position(|item| item.ident() == Some("generated"))
+        .unwrap();
+    krate.items[index] = Item::inherited(module);
+    let dir = std::env::temp_dir().join(format!("ruast_extract_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    krate
+        .compile(
+            dir.join("lib.rs"),
+            CompileOptions {
+                crate_name: Some("extracted".into()),
+                crate_type: Some("lib".into()),
+                edition: Some("2021".into()),
+                emit: Some("metadata".into()),
+                out_dir: Some(dir.to_str().unwrap().into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let compiled = dir.join("libextracted.rmeta").exists();
+    std::fs::remove_dir_all(dir).unwrap();
+    assert!(compiled);
+}
+
+#[test]
+fn test_extract_items_keeps_macros() {
+    let mut krate = Crate::new();
+    krate.add_item(Fn::simple("first", FnDecl::empty(), Block::empty()));
+    krate.add_item(MacroDef::with_rules(
+        "answer",
+        vec![MacroRule::new(
+            TokenStream::new(),
+            vec![Token::lit(Lit::int("42"))],
+        )],
+    ));
+    krate.add_item(Fn::simple(
+        "helper",
+        FnDecl::regular(vec![], Some(Type::simple_path("u32"))),
+        Block::from(Expr::new(MacCall::new(
+            Path::single("answer"),
+            DelimArgs::default(),
+        ))),
+    ));
+    krate.add_pub_item(Fn::simple(
+        "run",
+        FnDecl::regular(vec![], Some(Type::simple_path("u32"))),
+        Block::from(Path::single("helper").call(vec![])),
+    ));
+    let (krate, module) = krate.extract_items("generated", |item| {
+        matches!(item.ident(), Some("first" | "answer" | "helper"))
+    });
+    assert_snapshot!(krate, @r###"
+    macro_rules! answer {() => { 42 };}
+    mod generated;
+    use self::generated::{first, helper};
+    pub fn run() -> u32 {
+        helper()
+    }
+    "###);
+    assert_snapshot!(module, @r###"
+    mod generated {
+        use super::*;
+        pub(crate) fn first() {}
+        pub(crate) fn helper() -> u32 {
+            answer!()
+        }
+    }
+    "###);
+    let mut krate = krate;
+    let index = krate
+        .items
+        .iter()
+        .position(|item| item.ident() == Some("generated"))
+        .unwrap();
+    krate.items[index] = Item::inherited(module);
+    let dir = std::env::temp_dir().join(format!("ruast_extract_macros_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    krate
+        .compile(
+            dir.join("lib.rs"),
+            CompileOptions {
+                crate_name: Some("extracted_macros".into()),
+                crate_type: Some("lib".into()),
+                edition: Some("2021".into()),
+                emit: Some("metadata".into()),
+                out_dir: Some(dir.to_str().unwrap().into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let compiled = dir.join("libextracted_macros.rmeta").exists();
+    std::fs::remove_dir_all(dir).unwrap();
+    assert!(compiled);
+}
+
+#[test]
+fn test_manual_impls() {
+    let t = || Type::simple_path("T");
+    let cached = StructDef::new(
+        "Cached",
+        vec![TypeParam::simple("T").into()],
+        Fields::Struct(vec![
+            FieldDef::inherited("value", t()),
+            FieldDef::inherited("hits", Type::simple_path("Cell")),
+        ]),
+    );
+    assert_snapshot!(cached.clone_impl(), @r###"
+    impl<T: Clone> Clone for Cached<T> {
+        fn clone(&self) -> Self {
+            Self { value: self.value.clone(), hits: self.hits.clone() }
+        }
+    }
+    "###);
+    let eq = cached.partial_eq_impl_with(|field| field.ident.as_deref() != Some("hits"));
+    assert_snapshot!(eq, @r###"
+    impl<T: PartialEq> PartialEq for Cached<T> {
+        fn eq(&self, other: &Self) -> bool {
+            self.value == other.value
+        }
+    }
+    "###);
+
+    let wrapper = StructDef::new(
+        "Wrapper",
+        vec![],
+        Fields::Tuple(vec![FieldDef::anonymous(Type::u8())]),
+    );
+    assert_snapshot!(wrapper.clone_impl(), @r###"
+    impl Clone for Wrapper {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(wrapper.partial_eq_impl()), @"impl PartialEq for Wrapper { fn eq(&self, other: &Self) -> bool { self.0 == other.0 } }");
+
+    let shape = EnumDef::empty("Shape")
+        .with_variant(Variant::empty("Empty"))
+        .with_variant(Variant::tuple(
+            "Circle",
+            vec![FieldDef::anonymous(Type::f64())],
+        ))
+        .with_variant(Variant::struct_(
+            "Rect",
+            vec![
+                FieldDef::inherited("w", Type::f64()),
+                FieldDef::inherited("id", Type::u32()),
+            ],
+        ));
+    assert_snapshot!(shape.clone_impl(), @r###"
+    impl Clone for Shape {
+        fn clone(&self) -> Self {
+            match self {
+                Self::Empty => Self::Empty,
+                Self::Circle(f0) => Self::Circle(f0.clone()),
+                Self::Rect { w, id } => Self::Rect { w: w.clone(), id: id.clone() },
+            }
+        }
+    }
+    "###);
+    let eq = shape.partial_eq_impl_with(|field| field.ident.as_deref() != Some("id"));
+    assert_snapshot!(eq, @r###"
+    impl PartialEq for Shape {
+        fn eq(&self, other: &Self) -> bool {
+            match (self, other) {
+                (Self::Empty, Self::Empty) => true,
+                (Self::Circle(self_0), Self::Circle(other_0)) => self_0 == other_0,
+                (Self::Rect { w: self_w, .. }, Self::Rect { w: other_w, .. }) => self_w == other_w,
+                _ => false,
+            }
+        }
+    }
+    "###);
+    assert_snapshot!(EnumDef::empty("Never").partial_eq_impl(), @r###"
+    impl PartialEq for Never {
+        fn eq(&self, other: &Self) -> bool {
+            match *self {
+            }
+        }
+    }
+    "###);
+}
+
+#[test]
+fn test_exhaustive_match() {
+    let event = EnumDef::empty("Event")
+        .with_variant(Variant::empty("Quit"))
+        .with_variant(Variant::tuple(
+            "Key",
+            vec![FieldDef::anonymous(Type::simple_path("char"))],
+        ))
+        .with_variant(Variant::struct_(
+            "Move",
+            vec![
+                FieldDef::inherited("x", Type::i32()),
+                FieldDef::inherited("y", Type::i32()),
+            ],
+        ));
+    let dispatch = event.exhaustive_match(Path::single("event"), |variant, bindings| {
+        let handler = format!("on_{}", variant.ident.to_lowercase());
+        Path::single("self")
+            .method_call(PathSegment::simple(handler), bindings)
+            .into()
+    });
+    assert_snapshot!(dispatch, @r###"
+    match event {
+        Event::Quit => self.on_quit(),
+        Event::Key(f0) => self.on_key(f0),
+        Event::Move { x, y } => self.on_move(x, y),
+    }
+    "###);
+}
+
+#[test]
+fn test_api_stability() {
+    let error = EnumDef::empty("Error").with_variant(Variant::empty("Timeout"));
+    assert_snapshot!(Item::<ItemKind>::public(error).non_exhaustive(), @r###"
+    #[non_exhaustive]
+    pub enum Error {
+        Timeout,
+    }
+    "###);
+    let variant = Variant::struct_("Io", vec![FieldDef::inherited("code", Type::i32())])
+        .with_attr(Attribute::non_exhaustive());
+    assert_snapshot!(variant, @r###"
+    #[non_exhaustive]
+    Io {
+        code: i32
+    }
+    "###);
+
+    let client = TraitDef::simple("Client", vec![AssocItem::inherited(Fn::empty("send"))]);
+    let sealed = client.sealed(vec![Type::simple_path("HttpClient")]);
+    let mut krate = Crate::new();
+    krate.items = sealed;
+    assert_snapshot!(krate, @r###"
+    mod private {
+        pub trait Sealed {}
+    }
+    impl private::Sealed for HttpClient {}
+    pub trait Client: private::Sealed {
+        fn send();
+    }
+    "###);
+}
+
+#[test]
+fn test_flatten_nesting() {
+    let f = |arg: Expr| Expr::from(Path::single("f").call1(arg));
+    let deep = f(f(f(f(Lit::int("1").into()))));
+    let sum = Expr::from(deep.clone().bin_op(BinOpKind::Add, Path::single("x")));
+    let guarded = Path::single("ok").bin_op(BinOpKind::LazyAnd, f(f(f(Path::single("y").into()))));
+    let body = Block::empty()
+        .with_stmt(Local::simple(Pat::ident("a"), sum))
+        .with_stmt(Expr::from(guarded).semi())
+        .with_stmt(deep);
+    let mut krate = Crate::new().with_item(Fn::simple("g", FnDecl::empty(), body));
+    assert_eq!(krate.flatten_nesting(3), 3);
+    assert_snapshot!(krate, @r###"
+    fn g() {
+        let __tmp0 = f(1);
+        let __tmp1 = f(f(__tmp0));
+        let a = f(__tmp1) + x;
+        ok && f(f(f(y)));
+        let __tmp2 = f(f(1));
+        f(f(__tmp2))
+    }
+    "###);
+
+    let place = Path::single("a").field("b").field("c").field("d");
+    let eq = Expr::from(place.bin_op(BinOpKind::Eq, Path::single("s")));
+    let mut compared = Crate::new().with_item(Fn::simple(
+        "g",
+        FnDecl::empty(),
+        Block::from(eq.clone().semi()),
+    ));
+    assert_eq!(compared.flatten_nesting(2), 0);
+    let name = |expr: Expr| Expr::from(Path::single("name").call1(expr));
+    let body = Block::empty()
+        .with_stmt(eq.semi())
+        .with_stmt(
+            Expr::from(
+                name(name(Path::single("t").into())).bin_op(BinOpKind::Ne, Path::single("s")),
+            )
+            .semi(),
+        )
+        .with_stmt(
+            Expr::from(Path::single("f").call(vec![
+                Path::single("x").into(),
+                f(f(f(Lit::int("1").into()))),
+            ]))
+            .semi(),
+        )
+        .with_stmt(
+            Expr::from(Path::single("f").call(vec![
+                f(Path::single("x").into()),
+                f(f(f(Lit::int("1").into()))),
+            ]))
+            .semi(),
+        );
+    let mut krate = Crate::new().with_item(Fn::simple("h", FnDecl::empty(), body));
+    assert_eq!(krate.flatten_nesting(3), 2);
+    assert_snapshot!(krate, @r###"
+    fn h() {
+        a.b.c.d == s;
+        let __tmp0 = name(t);
+        name(__tmp0) != s;
+        let __tmp1 = f(f(1));
+        f(x, f(__tmp1));
+        f(f(x), f(f(f(1))));
+    }
+    "###);
+}
+
+#[test]
+fn test_walk() {
+    let read = |ptr: &str| Expr::from(Path::single(ptr)).deref();
+    let body = Block::empty()
+        .with_stmt(Local::simple(Pat::ident("a"), read("p")))
+        .with_stmt(UnsafeBlock::new(Block::single(read("q"))));
+    let krate = Crate::new().with_item(Fn::simple("f", FnDecl::empty(), body));
+    let in_unsafe = krate
+        .walk()
+        .filter_map(|(node, parent)| match node {
+            visit::NodeRef::Path(path) => {
+                let is_unsafe = parent.ancestors().any(|node| {
+                    matches!(
+                        node,
+                        visit::NodeRef::Expr(Expr {
+                            kind: ExprKind::UnsafeBlock(_),
+                            ..
+                        })
+                    )
+                });
+                Some((path.to_string(), is_unsafe))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        in_unsafe,
+        vec![("p".to_string(), false), ("q".to_string(), true)]
+    );
+    let (item, parent) = krate.walk().next().unwrap();
+    assert!(matches!(item, visit::NodeRef::Item(_)));
+    assert!(parent.node().is_none());
+}
+
+#[test]
+fn test_strip_attrs() {
+    let cfg_test = || {
+        Attribute::normal(AttributeItem::cfg(TokenStream::from(vec![Token::ident(
+            "test",
+        )])))
+    };
+    let inline = || Attribute::normal(AttributeItem::simple(Path::single("inline")));
+    let tests = Mod::new(
+        "tests",
+        vec![
+            Item::inherited(Fn::empty("it_works")).with_attr(Attribute::normal(
+                AttributeItem::simple(Path::single("test")),
+            )),
+        ],
+    );
+    let krate = Crate::new()
+        .with_item(
+            Item::public(Fn::empty("add"))
+                .with_attr(Attribute::doc("Adds two numbers."))
+                .with_attr(inline()),
+        )
+        .with_item(Item::inherited(tests).with_attr(cfg_test()));
+    let options = StripOptions {
+        docs: true,
+        test_items: true,
+    };
+    assert_snapshot!(krate.render_stripped(options), @r###"
+    #[inline]
+    pub fn add();
+    "###);
+    let mut compact = krate.clone();
+    assert_eq!(
+        compact.strip_attrs(|attr| attr.path() == Some(&Path::single("inline"))),
+        1
+    );
+    assert_eq!(compact.strip_test_items(), 1);
+    assert_snapshot!(compact, @r###"
+    /// Adds two numbers.
+    pub fn add();
+    "###);
+}
+
+#[test]
+fn test_macro_rules() {
+    let mut sum = TokenStream::from(vec![Token::lit(Lit::int("0"))]);
+    sum.extend(MacroRule::repetition(
+        vec![Token::BinOp(BinOpToken::Plus)]
+            .into_iter()
+            .chain(MacroRule::var("x"))
+            .collect::<Vec<_>>(),
+        None,
+        RepetitionOp::ZeroOrMore,
+    ));
+    let def = MacroDef::with_rules(
+        "sum",
+        vec![
+            MacroRule::new(TokenStream::new(), vec![Token::lit(Lit::int("0"))]),
+            MacroRule::new(
+                MacroRule::repetition(
+                    MacroRule::fragment("x", FragmentSpecifier::Expr),
+                    Some(Token::Comma),
+                    RepetitionOp::OneOrMore,
+                ),
+                sum,
+            ),
+        ],
+    );
+    assert_snapshot!(def, @"macro_rules! sum {() => { 0 }; ($($x:expr),+) => { 0 $(+ $x)* };}");
+    assert_snapshot!(def.exported(), @r###"
+    #[macro_export]
+    macro_rules! sum {() => { 0 }; ($($x:expr),+) => { 0 $(+ $x)* };}
+    "###);
+}
+
+#[test]
+fn test_instrument() {
+    let handler = |ident: &str| {
+        Fn::simple(
+            ident,
+            FnDecl::regular(
+                vec![Param::ident("req", Type::simple_path("Request"))],
+                None,
+            ),
+            Block::from(Path::single("process").call1(Path::single("req")).semi()),
+        )
+    };
+    let mut krate = Crate::new();
+    krate.add_item(handler("handle_get"));
+    krate.add_item(handler("helper"));
+    krate.add_item(handler("handle_const").with_const(true));
+    let method = Fn::simple(
+        "handle_post",
+        FnDecl::regular(vec![Param::ref_self()], None),
+        Block::empty(),
+    );
+    krate.add_item(Impl::simple(
+        Type::simple_path("Service"),
+        vec![AssocItem::inherited(method)],
+    ));
+    let is_handler = |fn_: &Fn| fn_.ident.starts_with("handle_");
+
+    let mut traced = krate.clone();
+    let count = traced.instrument(Instrumentation::Tracing { skip_all: true }, is_handler);
+    assert_eq!(count, 2);
+    assert_eq!(
+        traced.instrument(Instrumentation::Tracing { skip_all: false }, is_handler),
+        0
+    );
+    assert_snapshot!(traced, @r###"
+    #[tracing::instrument(skip_all)]
+    fn handle_get(req: Request) {
+        process(req);
+    }
+    fn helper(req: Request) {
+        process(req);
+    }
+    const fn handle_const(req: Request) {
+        process(req);
+    }
+    impl Service {
+        #[tracing::instrument(skip_all)]
+        fn handle_post(&self) {}
+    }
+    "###);
+
+    assert_eq!(krate.instrument(Instrumentation::LogEntry, is_handler), 2);
+    assert_eq!(krate.instrument(Instrumentation::LogEntry, is_handler), 0);
+    assert_snapshot!(krate, @r###"
+    fn handle_get(req: Request) {
+        log::debug!("entering `handle_get`");
+        process(req);
+    }
+    fn helper(req: Request) {
+        process(req);
+    }
+    const fn handle_const(req: Request) {
+        process(req);
+    }
+    impl Service {
+        fn handle_post(&self) {
+            log::debug!("entering `handle_post`");
+        }
+    }
+    "###);
+}
+
+#[test]
+fn test_render_item_with_deps() {
+    let mut krate = Crate::new();
+    krate.add_item(Use::from(
+        Path::single("std")
+            .chain("collections")
+            .chain_use_group(vec![UseTree::name("BTreeMap"), UseTree::name("HashMap")]),
+    ));
+    krate.add_item(Use::from(Path::single("std").chain("fmt").chain_use_group(
+        vec![
+            UseTree::name("self"),
+            UseTree::name("Display"),
+            UseTree::name("Formatter"),
+        ],
+    )));
+    krate.add_item(Use::from(
+        Path::single("std")
+            .chain("io")
+            .chain("Write")
+            .chain_use_rename("_"),
+    ));
+    krate.add_pub_item(Use::from(Path::single("serde").chain("Serialize")));
+    let derive = DerivePolicy::default()
+        .apply(["Debug", "Serialize"])
+        .remove(0);
+    let mut map = StructDef::empty("Map");
+    map.add_field(FieldDef::public(
+        "entries",
+        Type::poly_path(
+            "HashMap",
+            vec![
+                GenericArg::Type(Type::string()),
+                GenericArg::Type(Type::u8()),
+            ],
+        ),
+    ));
+    krate.add_item(Item::public(map).with_attr(Attribute::from(derive)));
+    krate.add_item(Fn::simple(
+        "show",
+        FnDecl::regular(
+            vec![Param::ident(
+                "f",
+                Type::ref_mut(Type::simple_path("Formatter")),
+            )],
+            Some(Type::Path(Path::single("fmt").chain("Result"))),
+        ),
+        Block::empty(),
+    ));
+
+    krate.add_item(Fn::simple(
+        "ok",
+        FnDecl::regular(
+            vec![],
+            Some(Type::Path(Path::single("fmt").chain("Result"))),
+        ),
+        Block::from(Path::single("Ok").call1(Tuple::new(vec![]))),
+    ));
+
+    assert_snapshot!(krate.render_item_with_deps("Map").unwrap(), @r###"
+    use std::collections::HashMap;
+    use std::io::Write as _;
+    pub use serde::Serialize;
+    #[derive(Debug, Serialize)]
+    pub struct Map {
+        pub entries: HashMap<String, u8>
+    }
+    "###);
+    assert_snapshot!(krate.render_item_with_deps("show").unwrap(), @r###"
+    use std::fmt::{self, Formatter};
+    use std::io::Write as _;
+    fn show(f: &mut Formatter) -> fmt::Result {}
+    "###);
+    assert_snapshot!(krate.render_item_with_deps("ok").unwrap(), @r###"
+    use std::fmt;
+    use std::io::Write as _;
+    fn ok() -> fmt::Result {
+        Ok(())
+    }
+    "###);
+    assert_eq!(krate.render_item_with_deps("missing"), None);
+}
+
+#[test]
+fn test_expr_metrics() {
+    // f(a + b * c, d)
+    let expr = Expr::from(Path::single("f")).call(vec![
+        Path::single("a")
+            .add(Path::single("b").mul(Path::single("c")))
+            .into(),
+        Path::single("d").into(),
+    ]);
+    assert_eq!(expr.depth(), 4);
+    assert_eq!(expr.node_count(), 8);
+    assert_snapshot!(expr.render_truncated(100), @"f(a + b * c, d)");
+    assert_snapshot!(expr.render_truncated(5), @"f(... + ..., d)");
+    assert_snapshot!(expr.render_truncated(3), @"...(..., ...)");
+    assert_snapshot!(expr.render_truncated(0), @"...");
+    assert_eq!(expr.truncated(5).validate(), Ok(()));
+    #[cfg(feature = "tokenize")]
+    assert_snapshot!(expr.truncated(5).to_token_stream(), @"f (... + ... , d)");
+}
+
+#[test]
+fn test_workspace_write_to_dir() {
+    let dir = std::env::temp_dir().join(format!("ruast_workspace_{}", std::process::id()));
+    let workspace = Workspace::new()
+        .with_crate("core_types", Crate::new().with_item(Fn::empty("a")))
+        .with_crate("app", Crate::new().with_item(Fn::empty("b")));
+    workspace
+        .write_to_dir_with(&dir, |name, _| {
+            (name == "app").then(|| format!("[package]\nname = \"{name}\"\n"))
+        })
+        .unwrap();
+    let lib = std::fs::read_to_string(dir.join("core_types/src/lib.rs")).unwrap();
+    assert_eq!(lib, workspace.get_crate("core_types").unwrap().to_string());
+    assert!(dir.join("app/src/lib.rs").exists());
+    assert!(dir.join("app/Cargo.toml").exists());
+    assert!(!dir.join("core_types/Cargo.toml").exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_workspace_split_modules() {
+    let dir = std::env::temp_dir().join(format!("ruast_split_{}", std::process::id()));
+    let inner = Mod::new("inner", vec![Item::inherited(Fn::empty("b"))]);
+    let tests = Item::inherited(Mod::new(
+        "tests",
+        vec![Item::inherited(Fn::empty("a")), Item::inherited(inner)],
+    ))
+    .with_attr(Attribute::doc("Unit tests.\n\nRun with `cargo test`."))
+    .with_attr(Attribute::normal(AttributeItem::new(
+        Path::single("cfg"),
+        AttrArgs::Delimited(DelimArgs::parenthesis(TokenStream::from(vec![
+            Token::ident("test"),
+        ]))),
+    )));
+    let workspace = Workspace::new().with_crate("app", Crate::new().with_item(tests));
+    workspace.write_to_dir(&dir).unwrap();
+    let read = |path: &str| std::fs::read_to_string(dir.join(path)).unwrap();
+    assert_snapshot!(read("app/src/lib.rs"), @r###"
+    #[cfg(test)]
+    mod tests;
+    "###);
+    assert_snapshot!(read("app/src/tests.rs"), @r###"
+    //! Unit tests.
+    //!
+    //! Run with `cargo test`.
+
+    fn a();
+    mod inner;
+    "###);
+    assert_snapshot!(read("app/src/tests/inner.rs"), @"fn b();");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_unsafe_impl() {
+    let sync = Impl::trait_impl(
+        vec![],
+        Type::simple_path("Foo"),
+        Type::simple_path("Sync"),
+        None,
+        vec![],
+    )
+    .with_unsafe(true);
+    assert_snapshot!(sync, @"unsafe impl Sync for Foo {}");
+    assert_snapshot!(TokenStream::from(sync), @"unsafe impl Sync for Foo { }");
+}
+
+#[test]
+fn test_enum_discriminants() {
+    let def = EnumDef::empty("E")
+        .with_variant(Variant::empty("A").with_discriminant(Lit::int("1")))
+        .with_variant(Variant::empty("B").with_discriminant(Lit::int("0x10")))
+        .with_variant(Variant::empty("C"));
+    let item: Item = Item::inherited(def).with_attr(Attribute::normal(AttributeItem::new(
+        Path::single("repr"),
+        AttrArgs::Delimited(DelimArgs::parenthesis(TokenStream::from(vec![
+            Token::ident("u8"),
+        ]))),
+    )));
+    assert_snapshot!(item, @r###"
+    #[repr(u8)]
+    enum E {
+        A = 1,
+        B = 0x10,
+        C,
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(item), @"#[repr(u8)] enum E { A = 1, B = 0x10, C, }");
+}
+
+#[test]
+fn test_enum_variant_shapes() {
+    let def = EnumDef::empty("Message")
+        .with_variant(Variant::unit("Quit").with_attr(Attribute::doc("Stops the loop.")))
+        .with_variant(Variant::tuple("Write", [Type::simple_path("String")]))
+        .with_variant(Variant::tuple(
+            "Color",
+            [Type::u8(), Type::u8(), Type::u8()],
+        ))
+        .with_variant(Variant::struct_(
+            "Move",
+            [
+                FieldDef::inherited("x", Type::i32()).with_attr(Attribute::doc("Columns.")),
+                FieldDef::inherited("y", Type::i32()).with_attr(Attribute::normal(
+                    AttributeItem::new(
+                        Path::single("serde"),
+                        AttrArgs::Delimited(DelimArgs::parenthesis(TokenStream::from(vec![
+                            Token::ident("default"),
+                        ]))),
+                    ),
+                )),
+            ],
+        ));
+    assert_snapshot!(def, @r###"
+    enum Message {
+        /// Stops the loop.
+        Quit,
+        Write(String),
+        Color(u8, u8, u8),
+        Move {
+            /// Columns.
+            x: i32,
+            #[serde(default)]
+            y: i32
+        },
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(def), @"enum Message { /// Stops the loop. Quit, Write(String), Color(u8, u8, u8), Move{ /// Columns. x: i32, #[serde(default)] y: i32 }, }");
+}
+
+#[test]
+fn test_assoc_consts() {
+    let trait_def = TraitDef::simple(
+        "Table",
+        vec![
+            AssocItem::inherited(ConstItem::declaration("NAME", Type::ref_(Type::str())))
+                .with_attr(Attribute::doc("The name of the table.")),
+            AssocItem::inherited(ConstItem::new(
+                "MAX_ROWS",
+                Type::usize(),
+                Some(Lit::int("1024").into()),
+            )),
+        ],
+    );
+    assert_snapshot!(trait_def, @r###"
+    trait Table {
+        /// The name of the table.
+        const NAME: &str;
+        const MAX_ROWS: usize = 1024;
+    }
+    "###);
+    let impl_ = Impl::trait_impl(
+        vec![],
+        Type::simple_path("Users"),
+        Type::simple_path("Table"),
+        None,
+        vec![AssocItem::inherited(ConstItem::new(
+            "NAME",
+            Type::ref_(Type::str()),
+            Some(Lit::str("users").into()),
+        ))],
+    );
+    assert_snapshot!(impl_, @r###"
+    impl Table for Users {
+        const NAME: &str = "users";
+    }
+    "###);
+    let inherent = Impl::simple(
+        Type::simple_path("Users"),
+        vec![AssocItem::public(ConstItem::new(
+            "COLUMNS",
+            Type::usize(),
+            Some(Lit::int("3").into()),
+        ))],
+    );
+    assert_snapshot!(inherent, @r###"
+    impl Users {
+        pub const COLUMNS: usize = 3;
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(trait_def), @"trait Table { /// The name of the table. const NAME: &str; const MAX_ROWS: usize = 1024; }");
+}
+
+#[cfg(feature = "unstable-syntax")]
+#[test]
+fn test_default_impl_items() {
+    let mut size = Fn::simple(
+        "size",
+        FnDecl::regular(vec![Param::ref_self()], Some(Type::usize())),
+        Block::single(Lit::int("0")),
+    );
+    size.is_unsafe = true;
+    let impl_ = Impl::trait_impl(
+        vec![TypeParam::simple("T").into()],
+        Type::simple_path("T"),
+        Type::simple_path("Size"),
+        None,
+        vec![
+            AssocItem::inherited(size.with_default(true)),
+            AssocItem::inherited(TyAlias::new("Unit", Type::unit()).with_default(true)),
+            AssocItem::inherited(
+                ConstItem::new("ALIGN", Type::usize(), Some(Lit::int("1").into()))
+                    .with_default(true),
+            ),
+        ],
+    );
+    assert_snapshot!(impl_, @r###"
+    impl<T> Size for T {
+        default unsafe fn size(&self) -> usize {
+            0
+        }
+        default type Unit = ();
+        default const ALIGN: usize = 1;
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(impl_), @"impl<T> Size for T { default unsafe fn size(&self) -> usize { 0 } default type Unit = (); default const ALIGN: usize = 1; }");
+}
+
+#[test]
+fn test_ty_alias() {
+    let t = || Type::simple_path("T");
+    let alias = TyAlias::new("Stack", Type::vec(t()))
+        .with_generic_param(TypeParam::simple("T").into())
+        .with_where_clause(WherePredicate::Type(PredicateType::new(
+            t(),
+            vec![PolyTraitRef::simple("Clone").into()],
+        )));
+    assert_snapshot!(alias, @"type Stack<T> = Vec<T> where T: Clone;");
+    assert_snapshot!(TokenStream::from(alias), @"type Stack<T> = Vec<T> where T: Clone;");
+    let iter = TyAlias::declaration("Iter", vec![PolyTraitRef::simple("Iterator").into()])
+        .with_generic_param(LifetimeParam::simple("a").into());
+    assert_snapshot!(TokenStream::from(iter.clone()), @"type Iter<'a>: Iterator;");
+
+    let item = TyAlias::declaration("Item", vec![PolyTraitRef::simple("Clone").into()]);
+    let iter = iter.with_where_clause(WherePredicate::Type(PredicateType::new(
+        Type::simple_path("Self"),
+        vec![PolyTraitRef::simple("Sized").into()],
+    )));
+    let trait_ = TraitDef::new(
+        "Container",
+        vec![],
+        vec![],
+        vec![AssocItem::inherited(item), AssocItem::inherited(iter)],
+    );
+    let impl_ = Impl::trait_impl(
+        vec![],
+        Type::simple_path("Bag"),
+        Type::simple_path("Container"),
+        None,
+        vec![AssocItem::inherited(TyAlias::new("Item", Type::u8()))],
+    );
+    let krate = Crate::new().with_item(trait_).with_item(impl_);
+    assert_snapshot!(krate, @r###"
+    trait Container {
+        type Item: Clone;
+        type Iter<'a>: Iterator where Self: Sized;
+    }
+    impl Container for Bag {
+        type Item = u8;
+    }
+    "###);
+}
+
+#[test]
+fn test_where_clauses() {
+    let t = Type::simple_path("T");
+    let clone = WherePredicate::Type(PredicateType::new(
+        t.clone(),
+        vec![PolyTraitRef::simple("Clone").into()],
+    ));
+    let into = WherePredicate::Type(PredicateType::new(
+        Type::simple_path("U"),
+        vec![PolyTraitRef::simple(PathSegment::new(
+            "Into",
+            Some(vec![GenericArg::Type(t.clone())]),
+        ))
+        .into()],
+    ));
+    let outlives = WherePredicate::Lifetime(PredicateLifetime::new("a", vec!["b".into()]));
+    let f = Fn::simple(
+        "convert",
+        FnDecl::regular(
+            vec![Param::ident("u", Type::simple_path("U"))],
+            Some(t.clone()),
+        ),
+        Block::single(Path::single("u").method_call0(PathSegment::simple("into"))),
+    )
+    .with_generic_param(TypeParam::simple("T").into())
+    .with_generic_param(TypeParam::simple("U").into())
+    .with_where_clause(clone.clone())
+    .with_where_clause(into);
+    assert_snapshot!(f, @r###"
+    fn convert<T, U>(u: U) -> T where T: Clone, U: Into<T> {
+        u.into()
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(f), @"fn convert<T, U>(u: U) -> T where T: Clone, U: Into<T> { u.into() }");
+
+    let s = StructDef::empty("Wrapper")
+        .with_generic_param(TypeParam::simple("T").into())
+        .with_field(FieldDef::inherited("inner", t.clone()))
+        .with_where_clause(clone.clone());
+    assert_snapshot!(s, @r###"
+    struct Wrapper<T> where T: Clone {
+        inner: T
+    }
+    "###);
+    let tuple = StructDef::new(
+        "Pair",
+        vec![TypeParam::simple("T").into()],
+        Fields::Tuple(vec![t.clone().into(), t.clone().into()]),
+    )
+    .with_where_clause(clone.clone());
+    assert_snapshot!(tuple, @"struct Pair<T>(T, T) where T: Clone");
+    let e = EnumDef::empty("Either")
+        .with_generic_param(TypeParam::simple("T").into())
+        .with_variant(Variant::tuple("Left", [t.clone()]))
+        .with_where_clause(clone.clone());
+    assert_snapshot!(e, @r###"
+    enum Either<T> where T: Clone {
+        Left(T),
+    }
+    "###);
+    let tr = TraitDef::empty("Borrowed")
+        .with_generic_param(LifetimeParam::new("a", vec![]).into())
+        .with_generic_param(LifetimeParam::new("b", vec![]).into())
+        .with_where_clause(outlives);
+    assert_snapshot!(tr, @"trait Borrowed<'a, 'b> where 'a: 'b {}");
+    let impl_ = Impl::simple(
+        Type::poly_path("Wrapper", vec![GenericArg::Type(t)]),
+        vec![],
+    )
+    .with_generic_param(TypeParam::simple("T").into())
+    .with_where_clause(clone.clone());
+    assert_snapshot!(impl_, @"impl<T> Wrapper<T> where T: Clone {}");
+
+    let generics = Generics::new(vec![TypeParam::simple("T").into()]).with_predicate(clone);
+    assert_snapshot!(generics, @"<T>");
+    assert_snapshot!(generics.where_clause, @"where T: Clone");
+    let mut marker = StructDef::empty("Marker");
+    marker.generics = generics.clone();
+    assert_snapshot!(marker, @"struct Marker<T> where T: Clone {}");
+    let mut alias = TyAlias::new("Alias", Type::simple_path("T"));
+    alias.generics = generics;
+    assert_snapshot!(alias, @"type Alias<T> = T where T: Clone;");
+}
+
+#[test]
+fn test_lifetime_params() {
+    let a_ref = |lifetime: &str| {
+        Type::Ref(Ref::new(
+            Some(lifetime),
+            MutTy::immut(Type::simple_path("T")),
+        ))
+    };
+    let def = StructDef::empty("Pair")
+        .with_generic_param(LifetimeParam::simple("a").into())
+        .with_generic_param(LifetimeParam::simple("b").with_bound("a").into())
+        .with_generic_param(TypeParam::simple("T").into())
+        .with_field(FieldDef::inherited("first", a_ref("a")))
+        .with_field(FieldDef::inherited("second", a_ref("b")))
+        .with_where_clause(WherePredicate::Type(
+            PredicateType::new(
+                Type::simple_path("T"),
+                vec![PolyTraitRef::simple(PathSegment::new(
+                    "PartialEq",
+                    Some(vec![GenericArg::Type(Type::Ref(Ref::new(
+                        Some("c"),
+                        MutTy::immut(Type::str()),
+                    )))]),
+                ))
+                .into()],
+            )
+            .with_bound_generic_param(LifetimeParam::simple("c").into()),
+        ));
+    assert_snapshot!(def, @r###"
+    struct Pair<'a, 'b: 'a, T> where for<'c> T: PartialEq<&'c str> {
+        first: &'a T,
+        second: &'b T
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(def), @"struct Pair<'a, 'b: 'a, T> where for<'c> T: PartialEq<&'c str> { first: &'a T, second: &'b T }");
+}
+
+#[test]
+fn test_type_param_defaults() {
+    let def = StructDef::empty("Buffer")
+        .with_generic_param(TypeParam::simple("T").with_default(Type::u32()).into())
+        .with_field(FieldDef::inherited(
+            "data",
+            Type::vec(Type::simple_path("T")),
+        ));
+    assert_snapshot!(def, @r###"
+    struct Buffer<T = u32> {
+        data: Vec<T>
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(def.clone()), @"struct Buffer<T = u32> { data: Vec<T> }");
+    assert_snapshot!(def.clone_impl(), @r###"
+    impl<T: Clone> Clone for Buffer<T> {
+        fn clone(&self) -> Self {
+            Self { data: self.data.clone() }
+        }
+    }
+    "###);
+    let add = TraitDef::empty("Add").with_generic_param(
+        TypeParam::simple("Rhs")
+            .with_default(Type::simple_path("Self"))
+            .into(),
+    );
+    assert_snapshot!(add, @"trait Add<Rhs = Self> {}");
+
+    // defaults are visited like any other type
+    let mut krate = Crate::new();
+    krate.add_pub_item(StructDef::empty("Bar"));
+    krate.add_item(
+        StructDef::empty("Foo")
+            .with_generic_param(
+                TypeParam::simple("T")
+                    .with_default(Type::Path(Path::single("crate").chain("Bar")))
+                    .into(),
+            )
+            .with_field(FieldDef::inherited("value", Type::simple_path("T"))),
+    );
+    let (krate, _) = krate.extract_items("a", |item| item.ident() == Some("Bar"));
+    assert_snapshot!(krate, @r###"
+    mod a;
+    pub use self::a::Bar;
+    struct Foo<T = crate::a::Bar> {
+        value: T
+    }
+    "###);
+    let as_ref = PolyTraitRef::simple(Path::single("AsRef")).into();
+    let def = StructDef::empty("Baz").with_generic_param(
+        TypeParam::simple("T")
+            .with_default(ImplTrait::new(vec![as_ref]))
+            .into(),
+    );
+    let item: Item = Item::inherited(def);
+    assert_eq!(item.validate(), Err(RenderError::ImplTraitPosition));
+}
+
+#[test]
+fn test_const_generics() {
+    let def = StructDef::empty("Ring")
+        .with_generic_param(TypeParam::simple("T").into())
+        .with_generic_param(
+            ConstParam::new("N", Type::usize())
+                .with_default(Lit::int("16"))
+                .into(),
+        )
+        .with_field(FieldDef::inherited(
+            "buf",
+            Type::Array(
+                Box::new(Type::simple_path("T")),
+                Box::new(Const(Path::single("N").into())),
+            ),
+        ));
+    assert_snapshot!(def, @r###"
+    struct Ring<T, const N: usize = 16> {
+        buf: [T; N]
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(def), @"struct Ring<T, const N: usize = 16> { buf: [T; N] }");
+    let ring = |arg: Expr| {
+        Type::poly_path(
+            "Ring",
+            vec![GenericArg::Type(Type::u8()), GenericArg::Const(Const(arg))],
+        )
+    };
+    let grown = ring(Path::single("N").add(Lit::int("1")).into());
+    assert_snapshot!(grown, @"Ring<u8, { N + 1 }>");
+    assert_snapshot!(TokenStream::from(grown), @"Ring<u8, { N + 1 }>");
+    assert_snapshot!(ring(Path::single("N").into()), @"Ring<u8, N>");
+    assert_snapshot!(ring(Lit::int("1").neg().into()), @"Ring<u8, -1>");
+
+    // defaults are visited like any other expression
+    let mut krate = Crate::new();
+    krate.add_item(ConstItem::new(
+        "LIMIT",
+        Type::usize(),
+        Some(Lit::int("16").into()),
+    ));
+    krate.add_item(
+        StructDef::empty("Buf").with_generic_param(
+            ConstParam::new("N", Type::usize())
+                .with_default(Path::single("crate").chain("LIMIT"))
+                .into(),
+        ),
+    );
+    assert_snapshot!(krate, @r###"
+    const LIMIT: usize = 16;
+    struct Buf<const N: usize = { crate::LIMIT }>{}
+    "###);
+    krate.inline_consts();
+    assert_snapshot!(krate, @"struct Buf<const N: usize = 16usize>{}");
+}
+
+#[test]
+fn test_nested_modules() {
+    let body = || Block::single(Path::single("x").call(vec![]));
+    let method = || Fn::simple("b", FnDecl::regular(vec![], None), body());
+    let shape = TraitDef::new(
+        "Shape",
+        vec![],
+        vec![],
+        vec![AssocItem::inherited(method())],
+    );
+    let inner = Mod::new(
+        "inner",
+        vec![
+            Item::public(shape),
+            Item::inherited(Impl::trait_impl(
+                vec![],
+                Type::simple_path("Square"),
+                Type::simple_path("Shape"),
+                None,
+                vec![AssocItem::inherited(method())],
+            )),
+        ],
+    );
+    let outer = Mod::new(
+        "outer",
+        vec![
+            Item::inherited(Mod::declaration("decl")),
+            Item::public(inner),
+        ],
+    );
+    assert!(Mod::declaration("decl").items().is_none());
+    assert_eq!(outer.items().map(<[_]>::len), Some(2));
+    let krate = Crate::new().with_item(outer);
+    assert_snapshot!(krate, @r###"
+    mod outer {
+        mod decl;
+        pub mod inner {
+            pub trait Shape {
+                fn b() {
+                    x()
+                }
+            }
+            impl Shape for Square {
+                fn b() {
+                    x()
+                }
+            }
+        }
+    }
+    "###);
+}
+
+#[test]
+fn test_header() {
+    let license = Header::new(
+        "Copyright {year} {owner}\nSPDX-License-Identifier: MIT\n\nGenerated by {tool} at {timestamp}.",
+    )
+    .with_var("year", "2024")
+    .with_var("owner", "Example Corp")
+    .with_var("tool", "codegen");
+    let krate = Crate::new()
+        .with_header(license.clone())
+        .with_item(Fn::empty("f"));
+    assert_snapshot!(krate, @r###"
+    // Copyright 2024 Example Corp
+    // SPDX-License-Identifier: MIT
+    //
+
+    fn f();
+    "###);
+    std::env::set_var("SOURCE_DATE_EPOCH", "1700000000");
+    assert_snapshot!(license.with_timestamp(true), @r###"
+    // Copyright 2024 Example Corp
+    // SPDX-License-Identifier: MIT
+    //
+    // Generated by codegen at 2023-11-14T22:13:20Z.
+    "###);
+    let multi_line = Header::new("{license}\n\nVersion {version}")
+        .with_var(
+            "license",
+            "Licensed under MIT\nor Apache-2.0\n\nat your option.",
+        )
+        .with_var("version", "1.0");
+    assert_snapshot!(multi_line, @r###"
+    // Licensed under MIT
+    // or Apache-2.0
+    //
+    // at your option.
+    //
+    // Version 1.0
+    "###);
+
+    let dir = std::env::temp_dir().join(format!("ruast_header_{}", std::process::id()));
+    let module = Mod::new("generated", vec![Item::inherited(Fn::empty("g"))]);
+    let workspace = Workspace::new()
+        .with_header(Header::generated("codegen", "1.0.0"))
+        .with_crate("app", Crate::new().with_item(module));
+    workspace.write_to_dir(&dir).unwrap();
+    let read = |path: &str| std::fs::read_to_string(dir.join(path)).unwrap();
+    assert_snapshot!(read("app/src/lib.rs"), @r###"
+    // @generated by codegen 1.0.0. Do not edit.
+
+    mod generated;
+    "###);
+    assert_snapshot!(read("app/src/generated.rs"), @r###"
+    // @generated by codegen 1.0.0. Do not edit.
+
+    fn g();
+    "###);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_shebang() {
+    let script = Crate::new()
+        .with_shebang("#!/usr/bin/env -S cargo +nightly -Zscript")
+        .with_header(Header::generated("codegen", "1.0.0"))
+        .with_attr(Attribute::doc("A script."))
+        .with_item(Fn::main(None, Block::empty()));
+    assert_snapshot!(script, @r###"
+    #!/usr/bin/env -S cargo +nightly -Zscript
+    // @generated by codegen 1.0.0. Do not edit.
+    //! A script.
+
+    fn main() {}
+    "###);
+    let script = Crate::new()
+        .with_shebang("/usr/bin/env run-cargo-script")
+        .with_item(Fn::main(None, Block::empty()));
+    assert_snapshot!(script, @r###"
+    #!/usr/bin/env run-cargo-script
+
+    fn main() {}
+    "###);
+}
+
+#[test]
+fn test_inline_consts() {
+    let mut krate = Crate::new();
+    krate.add_item(ConstItem::new(
+        "WIDTH",
+        Type::simple_path("u8"),
+        Some(Lit::int("8").into()),
+    ));
+    krate.add_item(ConstItem::new(
+        "OFFSET",
+        Type::simple_path("i64"),
+        Some(Expr::from(Lit::int("2")).neg().into()),
+    ));
+    krate.add_item(ConstItem::new(
+        "SCALE",
+        Type::simple_path("f32"),
+        Some(Lit::new(LitKind::Float, "1.").into()),
+    ));
+    krate.add_pub_item(ConstItem::new(
+        "NAME",
+        Type::ref_(Type::simple_path("str")),
+        Some(Lit::str("foo").into()),
+    ));
+    krate.add_item(Fn::main(
+        None,
+        Block::from(vec![
+            Path::single("WIDTH")
+                .add(Path::single("crate").chain("OFFSET"))
+                .semi()
+                .into(),
+            Path::single("NAME").ref_immut().semi().into(),
+            Path::single("SCALE").semi().into(),
+        ]),
+    ));
+    let mut inlined = krate.clone();
+    inlined.inline_consts();
+    assert_snapshot!(inlined, @r###"
+    pub const NAME: &str = "foo";
+    fn main() {
+        8u8 + -2i64;
+        &NAME;
+        1.0f32;
+    }
+    "###);
+    krate.inline_consts_with(InlineConstOptions {
+        include_public: true,
+        keep_items: true,
+    });
+    assert_snapshot!(krate, @r###"
+    const WIDTH: u8 = 8;
+    const OFFSET: i64 = -2;
+    const SCALE: f32 = 1.;
+    pub const NAME: &str = "foo";
+    fn main() {
+        8u8 + -2i64;
+        &"foo";
+        1.0f32;
+    }
+    "###);
+}
+
+#[test]
+fn test_inline_consts_scoped() {
+    let mut krate = Crate::new();
+    krate.add_item(ConstItem::new(
+        "W",
+        Type::simple_path("u8"),
+        Some(Lit::int("8").into()),
+    ));
+    krate.add_item(Fn::main(
+        None,
+        Block::from(vec![
+            Path::single("W").semi().into(),
+            Local::simple(Pat::ident("W"), Lit::int("9")).into(),
+            Path::single("W").semi().into(),
+        ]),
+    ));
+    krate.add_item(Mod::new(
+        "inner",
+        vec![Item::inherited(Fn::simple(
+            "f",
+            FnDecl::empty(),
+            Block::from(vec![
+                Path::single("W").semi().into(),
+                Path::single("super").chain("W").semi().into(),
+            ]),
+        ))],
+    ));
+    let mut inlined = krate.clone();
+    inlined.inline_consts();
+    assert_snapshot!(inlined, @r###"
+    const W: u8 = 8;
+    fn main() {
+        8u8;
+        let W = 9;
+        W;
+    }
+    mod inner {
+        fn f() {
+            W;
+            8u8;
+        }
+    }
+    "###);
+    let mut kept = Crate::new();
+    kept.add_item(ConstItem::new(
+        "W",
+        Type::simple_path("u8"),
+        Some(Lit::int("8").into()),
+    ));
+    kept.add_item(Mod::new(
+        "inner",
+        vec![Item::inherited(Fn::simple(
+            "f",
+            FnDecl::empty(),
+            Block::from(vec![Path::single("super")
+                .chain("super")
+                .chain("W")
+                .semi()
+                .into()]),
+        ))],
+    ));
+    kept.inline_consts();
+    assert_snapshot!(kept, @r###"
+    const W: u8 = 8;
+    mod inner {
+        fn f() {
+            super::super::W;
+        }
+    }
+    "###);
+}
+
+#[test]
+fn test_dedup_strings() {
+    let mut krate = Crate::new();
+    let greeting = "a rather long greeting";
+    krate.add_item(Fn::simple(
+        "a",
+        FnDecl::regular(vec![], Some(Type::ref_(Type::str()))),
+        Block::from(Expr::from(Lit::str(greeting))),
+    ));
+    krate.add_item(Fn::main(
+        None,
+        Block::from(vec![
+            Path::single("a")
+                .call(vec![])
+                .bin_op(BinOpKind::Eq, Lit::str(greeting))
+                .semi()
+                .into(),
+            Path::single("f")
+                .call(vec![Lit::str("short").into(), Lit::str("short").into()])
+                .semi()
+                .into(),
+        ]),
+    ));
+    assert_eq!(krate.dedup_strings(8), 1);
+    assert_snapshot!(krate, @r###"
+    const STR_0: &str = "a rather long greeting";
+    fn a() -> &str {
+        crate::STR_0
+    }
+    fn main() {
+        a() == crate::STR_0;
+        f("short", "short");
+    }
+    "###);
+
+    let note = "use the other function instead";
+    let deprecated = || {
+        Attribute::normal(AttributeItem::new(
+            Path::single("deprecated"),
+            AttrArgs::Eq(Lit::str(note).into()),
+        ))
+    };
+    let mut krate = Crate::new();
+    let body = || Block::from(Expr::from(Lit::str(note)).semi());
+    krate.add_item(
+        Item::inherited(Fn::simple("a", FnDecl::empty(), body())).with_attr(deprecated()),
+    );
+    krate.add_item(
+        Item::inherited(Fn::simple("b", FnDecl::empty(), Block::empty())).with_attr(deprecated()),
+    );
+    assert_eq!(krate.dedup_strings(8), 0);
+    assert_snapshot!(krate, @r###"
+    #[deprecated = "use the other function instead"]
+    fn a() {
+        "use the other function instead";
+    }
+    #[deprecated = "use the other function instead"]
+    fn b() {}
+    "###);
+}
+
+#[test]
+fn test_trait_mock() {
+    let method = |ident: &str, inputs: Vec<Param>, output: Option<Type>| {
+        let mut fn_ = Fn::empty(ident);
+        fn_.fn_decl = FnDecl::regular(inputs, output);
+        fn_
+    };
+    let shape = TraitDef::empty("Shape")
+        .with_generic_param(TypeParam::simple("T").into())
+        .with_item(method(
+            "area",
+            vec![
+                Param::ref_self(),
+                Param::ident("scale", Type::simple_path("T")),
+            ],
+            Some(Type::f64()),
+        ))
+        .with_item(method(
+            "name",
+            vec![Param::ref_self()],
+            Some(Type::string()),
+        ))
+        .with_item(method(
+            "resize",
+            vec![
+                Param::ref_mut_self(),
+                Param::ident("w", Type::u32()),
+                Param::ident("h", Type::u32()),
+            ],
+            None,
+        ))
+        .with_item(method("new", vec![], Some(Type::simple_path("Self"))))
+        .with_item(Fn::empty_method("describe", Pat::ref_self()));
+
+    let mut krate = Crate::new();
+    for item in shape.mock() {
+        krate.add_item(item);
+    }
+    assert_snapshot!(krate, @r###"
+    #[derive(Default)]
+    pub struct MockShape<T> {
+        pub area_calls: std::cell::RefCell<Vec<T>>,
+        pub area_returns: Option<Box<dyn Fn(&T) -> f64>>,
+        pub name_calls: std::cell::RefCell<Vec<()>>,
+        pub name_returns: Option<Box<dyn Fn() -> String>>,
+        pub resize_calls: std::cell::RefCell<Vec<(u32, u32)>>,
+        pub resize_returns: Option<Box<dyn Fn(&u32, &u32)>>,
+        pub _marker: std::marker::PhantomData<T>
+    }
+    impl<T> Shape<T> for MockShape<T> {
+        fn area(&self, scale: T) -> f64 {
+            let ret = self.area_returns.as_ref().expect("no return value configured for `MockShape::area`")(&scale);
+            self.area_calls.borrow_mut().push(scale);
+            ret
+        }
+        fn name(&self) -> String {
+            let ret = self.name_returns.as_ref().expect("no return value configured for `MockShape::name`")();
+            self.name_calls.borrow_mut().push(());
+            ret
+        }
+        fn resize(&mut self, w: u32, h: u32) {
+            if let Some(returns) = &self.resize_returns {
+                returns(&w, &h);
+            }
+            self.resize_calls.borrow_mut().push((w, h));
+        }
+        fn new() -> Self {
+            unimplemented!("`MockShape::new` cannot be mocked")
+        }
+    }
+    impl<T> MockShape<T> {
+        pub fn with_area_returns(mut self, returns: impl Fn(&T) -> f64 + 'static) -> Self {
+            self.area_returns = Some(Box::new(returns));
+            self
+        }
+        pub fn with_name_returns(mut self, returns: impl Fn() -> String + 'static) -> Self {
+            self.name_returns = Some(Box::new(returns));
+            self
+        }
+        pub fn with_resize_returns(mut self, returns: impl Fn(&u32, &u32) + 'static) -> Self {
+            self.resize_returns = Some(Box::new(returns));
+            self
+        }
+    }
+    "###);
+}
+
+#[test]
+fn test_extern_block_items() {
+    let f = Fn::foreign(
+        "f",
+        FnDecl::regular(vec![Param::ident("x", Type::i32())], Some(Type::i32())),
+    );
+    let block = ExternBlock::unsafe_c(Block::empty())
+        .with_item(f)
+        .with_item(StaticItem::foreign("ERRNO", Type::i32()))
+        .with_item(StaticItem::mut_("COUNTER", Type::u64(), None));
+    assert_snapshot!(block, @r###"
+    unsafe extern "C" {
+        fn f(x: i32) -> i32;
+        static ERRNO: i32;
+        static mut COUNTER: u64;
+    }
+    "###);
+}
+
+#[test]
+fn test_receivers() {
+    let method = |ident: &str, receiver: Param| {
+        AssocItem::inherited(Fn::new(
+            false,
+            false,
+            false,
+            None,
+            ident,
+            vec![],
+            FnDecl::regular(vec![receiver], None),
+            None,
+        ))
+    };
+    let self_ty = Type::simple_path("Self");
+    let pinned = Type::poly_path(
+        "Pin",
+        vec![GenericArg::Type(Type::ref_mut(self_ty.clone()))],
+    );
+    let trait_def = TraitDef::simple(
+        "Receivers",
+        vec![
+            method("by_value", Param::slf()),
+            method("by_mut_value", Param::mut_self()),
+            method("by_ref", Param::ref_self()),
+            method("by_ref_mut", Param::ref_mut_self()),
+            method("by_lifetime", Param::ref_mut_self_with_lifetime("a")),
+            method("boxed", Param::typed_self(Type::box_(self_ty))),
+            method("pinned", Param::typed_self(pinned)),
+        ],
+    );
+    assert_snapshot!(trait_def, @r###"
+    trait Receivers {
+        fn by_value(self);
+        fn by_mut_value(mut self);
+        fn by_ref(&self);
+        fn by_ref_mut(&mut self);
+        fn by_lifetime(&'a mut self);
+        fn boxed(self: Box<Self>);
+        fn pinned(self: Pin<&mut Self>);
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(trait_def), @"trait Receivers { fn by_value(self); fn by_mut_value(mut self); fn by_ref(&self); fn by_ref_mut(&mut self); fn by_lifetime(&'a mut self); fn boxed(self: Box<Self>); fn pinned(self: Pin<&mut Self>); }");
+    let decl = FnDecl::method(
+        SelfParam::Ref(Some("a".into()), Mutability::Not),
+        vec![Param::ident("n", Type::usize())],
+        None,
+    );
+    assert_eq!(
+        decl.receiver(),
+        Some(SelfParam::Ref(Some("a".into()), Mutability::Not))
+    );
+    assert_eq!(
+        FnDecl::regular(vec![Param::ref_self()], None).receiver(),
+        Some(SelfParam::Ref(None, Mutability::Not))
+    );
+    assert_snapshot!(decl, @"(&'a self, n: usize)");
+}
+
+#[test]
+fn test_c_variadics() {
+    let c_char = Type::simple_path("c_char");
+    let printf = Fn::foreign(
+        "printf",
+        FnDecl::variadic(
+            vec![Param::ident("fmt", Type::const_ptr(c_char.clone()))],
+            Some(Type::i32()),
+        ),
+    );
+    let block = ExternBlock::unsafe_c(Block::empty()).with_item(printf);
+    assert_snapshot!(block, @r###"
+    unsafe extern "C" {
+        fn printf(fmt: *const c_char, ...) -> i32;
+    }
+    "###);
+    let ptr = BareFn::c(
+        vec![Param::ident("fmt", Type::const_ptr(c_char))],
+        Type::i32(),
+    )
+    .with_unsafe(true)
+    .with_variadic(true);
+    assert_snapshot!(ptr, @"unsafe extern \"C\" fn(fmt: *const c_char, ...) -> i32");
+    assert_snapshot!(TokenStream::from(ptr), @"unsafe extern \"C\" fn(fmt: *const c_char, ...) -> i32");
+}
+
+#[test]
+fn test_static_items() {
+    let foo = Item::public(StaticItem::new(
+        "FOO",
+        Type::u32(),
+        Some(Lit::int("1").into()),
+    ))
+    .with_attr(Attribute::doc("The default number of retries."));
+    let counter = Item::inherited(StaticItem::mut_(
+        "COUNTER",
+        Type::u64(),
+        Some(Lit::int("0").into()),
+    ));
+    let block = ExternBlock::unsafe_c(Block::empty())
+        .with_item(Item::public(StaticItem::unsafe_foreign(
+            "environ",
+            Type::i32(),
+        )))
+        .with_item(Item::public(
+            StaticItem::foreign("VERSION", Type::u32()).with_safety(Safety::Safe),
+        ));
+    let krate = Crate::new()
+        .with_item(foo)
+        .with_item(counter)
+        .with_item(block);
+    assert_snapshot!(krate, @r###"
+    /// The default number of retries.
+    pub static FOO: u32 = 1;
+    static mut COUNTER: u64 = 0;
+    unsafe extern "C" {
+        pub unsafe static environ: i32;
+        pub safe static VERSION: u32;
+    }
+    "###);
+    let ts = TokenStream::from(StaticItem::unsafe_foreign("environ", Type::i32()));
+    assert_snapshot!(ts, @"unsafe static environ: i32;");
+}
+
+#[test]
+fn test_extern_per_target() {
+    let mut get_last_error = Fn::empty("GetLastError");
+    get_last_error.fn_decl = FnDecl::regular(vec![], Some(Type::u32()));
+    let mut krate = Crate::new();
+    for item in ExternBlock::system_or_c(true, Block::empty().with_stmt(get_last_error)) {
+        krate.add_item(item);
+    }
+    assert_snapshot!(krate, @r###"
+    #[cfg(windows)]
+    unsafe extern "system" {
+        fn GetLastError() -> u32;
+    }
+    #[cfg(not(windows))]
+    unsafe extern "C" {
+        fn GetLastError() -> u32;
+    }
+    "###);
+
+    let block = ExternBlock::safe(Some("C"), Block::empty());
+    let predicate = TokenStream::from(vec![
+        Token::ident("target_os"),
+        Token::Eq,
+        Token::lit("macos"),
+    ]);
+    let [on, off] = block.per_target(predicate, "C-unwind");
+    assert_snapshot!(on, @r###"
+    #[cfg(target_os = "macos")]
+    extern "C" {}
+    "###);
+    assert_snapshot!(off, @r###"
+    #[cfg(not(target_os = "macos"))]
+    extern "C-unwind" {}
+    "###);
+}
+
+#[cfg(feature = "rustdoc")]
+#[test]
+fn test_rustdoc_from_json() {
+    let json = r#"{
+        "root": 0,
+        "format_version": 39,
+        "index": {
+            "0": {"name": "demo", "visibility": "public", "inner": {"module": {"is_crate": true, "items": [1, 3, 5, 7]}}},
+            "1": {"name": "Point", "visibility": "public", "inner": {"struct": {
+                "generics": {"params": [], "where_predicates": []},
+                "kind": {"plain": {"fields": [2], "has_stripped_fields": true}},
+                "impls": [8]
+            }}},
+            "2": {"name": "x", "visibility": "public", "inner": {"struct_field": {"primitive": "i32"}}},
+            "3": {"name": "Shape", "visibility": "public", "inner": {"enum": {
+                "generics": {"params": [], "where_predicates": []},
+                "variants": [4],
+                "impls": []
+            }}},
+            "4": {"name": "Circle", "visibility": "default", "inner": {"variant": {"kind": {"tuple": [6, null]}, "discriminant": null}}},
+            "6": {"name": "0", "visibility": "default", "inner": {"struct_field": {"primitive": "f64"}}},
+            "5": {"name": "apply", "visibility": "public", "inner": {"function": {
+                "sig": {
+                    "inputs": [["f", {"generic": "F"}], ["s", {"borrowed_ref": {"lifetime": "'a", "is_mutable": false, "type": {"primitive": "str"}}}]],
+                    "output": {"resolved_path": {"path": "Option", "id": 20, "args": {"angle_bracketed": {"args": [{"type": {"primitive": "usize"}}], "constraints": []}}}},
+                    "is_c_variadic": false
+                },
+                "generics": {
+                    "params": [
+                        {"name": "'a", "kind": {"lifetime": {"outlives": []}}},
+                        {"name": "F", "kind": {"type": {"bounds": [], "default": null, "is_synthetic": false}}}
+                    ],
+                    "where_predicates": [{"bound_predicate": {"type": {"generic": "F"}, "bounds": [
+                        {"trait_bound": {"trait": {"path": "Fn", "id": 21, "args": {"parenthesized": {"inputs": [{"primitive": "char"}], "output": {"primitive": "bool"}}}}, "generic_params": [], "modifier": "none"}}
+                    ], "generic_params": []}}, {"bound_predicate": {"type": {"resolved_path": {"path": "Vec", "id": 23, "args": {"angle_bracketed": {"args": [{"type": {"generic": "F"}}], "constraints": []}}}}, "bounds": [
+                        {"trait_bound": {"trait": {"path": "Clone", "id": 24, "args": null}, "generic_params": [], "modifier": "none"}}
+                    ], "generic_params": []}}]
+                },
+                "header": {"is_const": false, "is_unsafe": false, "is_async": false, "abi": "Rust"},
+                "has_body": true
+            }}},
+            "7": {"name": "Area", "visibility": "public", "inner": {"trait": {
+                "is_auto": false, "is_unsafe": false, "is_dyn_compatible": true,
+                "items": [9],
+                "generics": {"params": [], "where_predicates": []},
+                "bounds": [{"trait_bound": {"trait": {"path": "Sized", "id": 22, "args": null}, "generic_params": [], "modifier": "none"}}],
+                "implementations": []
+            }}},
+            "8": {"name": null, "visibility": "default", "inner": {"impl": {
+                "is_unsafe": false, "generics": {"params": [], "where_predicates": []},
+                "provided_trait_methods": [], "trait": null,
+                "for": {"resolved_path": {"path": "Point", "id": 1, "args": null}},
+                "items": [10], "is_negative": false, "is_synthetic": false, "blanket_impl": null
+            }}},
+            "9": {"name": "area", "visibility": "default", "inner": {"function": {
+                "sig": {"inputs": [["self", {"borrowed_ref": {"lifetime": null, "is_mutable": false, "type": {"generic": "Self"}}}]], "output": {"primitive": "f64"}, "is_c_variadic": false},
+                "generics": {"params": [], "where_predicates": [{"bound_predicate": {"type": {"generic": "Self"}, "bounds": [
+                    {"trait_bound": {"trait": {"path": "Sized", "id": 22, "args": null}, "generic_params": [], "modifier": "none"}}
+                ], "generic_params": []}}]},
+                "header": {"is_const": false, "is_unsafe": false, "is_async": false, "abi": "Rust"},
+                "has_body": false
+            }}},
+            "10": {"name": "origin", "visibility": "public", "inner": {"function": {
+                "sig": {"inputs": [], "output": {"generic": "Self"}, "is_c_variadic": false},
+                "generics": {"params": [], "where_predicates": []},
+                "header": {"is_const": true, "is_unsafe": false, "is_async": false, "abi": {"C": {"unwind": false}}},
+                "has_body": true
+            }}}
+        }
+    }"#;
+    let krate = ruast::rustdoc::from_json(json).unwrap();
+    syn::parse_file(&krate.to_string()).unwrap();
+    assert_snapshot!(krate, @r###"
+    pub struct Point {
+        pub x: i32,
+        _private: ()
+    }
+    impl Point {
+        pub const extern "C" fn origin() -> Self {
+            unimplemented!()
+        }
+    }
+    pub enum Shape {
+        Circle(f64, ()),
+    }
+    pub fn apply<'a, F: Fn(char) -> bool>(f: F, s: &'a str) -> Option<usize> where Vec<F>: Clone {
+        unimplemented!()
+    }
+    pub trait Area: Sized {
+        fn area(&self) -> f64 where Self: Sized;
+    }
+    "###);
+    assert!(ruast::rustdoc::from_json("{}").is_err());
+}
+
+#[test]
+fn test_semantic_eq() {
+    use std::collections::HashSet;
+
+    let derive = |traits: &[&str]| {
+        Attribute::from(
+            DerivePolicy::default()
+                .apply(traits.iter().copied())
+                .remove(0),
+        )
+    };
+    let a: Item = Item::public(StructDef::empty("Foo"))
+        .with_attr(Attribute::doc("A foo."))
+        .with_attr(derive(&["Clone", "Debug"]))
+        .with_attr(Attribute::from(AttributeItem::simple("non_exhaustive")))
+        .with_attr(Attribute::from(AttributeItem::simple("my_attr")));
+    let b: Item = Item::public(StructDef::empty("Foo"))
+        .with_attr(Attribute::from(AttributeItem::simple("non_exhaustive")))
+        .with_attr(derive(&["Debug"]))
+        .with_attr(Attribute::from(AttributeItem::simple("my_attr")))
+        .with_attr(derive(&["Clone"]));
+    assert_ne!(a, b);
+    assert!(a.semantic_eq(&b));
+    assert_snapshot!(a.normalized(), @r###"
+    #[my_attr]
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    pub struct Foo{}
+    "###);
+
+    let c = b
+        .clone()
+        .with_attr(Attribute::from(AttributeItem::simple("other_attr")));
+    assert!(!a.semantic_eq(&c));
+
+    let x = Expr::from(Path::single("x"));
+    let lhs = Expr::from(x.clone().add(Lit::int("1")).paren().mul(Lit::int("2")));
+    let rhs = Expr::from(x.add(Lit::int("1")).mul(Lit::int("2")));
+    assert!(lhs.semantic_eq(&rhs));
+
+    let set = [a, b, c].into_iter().map(Semantic).collect::<HashSet<_>>();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn test_try_render() {
+    let ok = Expr::from(Path::single("x").add(Lit::int("1")));
+    assert_eq!(ok.try_render(), Ok("x + 1".to_string()));
+
+    let fn_ = Fn::simple(
+        "f",
+        FnDecl::regular(
+            vec![Param::ident("x", Type::Err)],
+            Some(Type::ImplTrait(ImplTrait::new(vec![]))),
+        ),
+        Block::empty(),
+    );
+    let item: Item = Item::inherited(fn_);
+    assert_snapshot!(item, @"fn f(x: <Err>) -> impl  {}");
+    let err = item.try_render().unwrap_err();
+    assert_eq!(err, RenderError::ErrType);
+    assert_snapshot!(err, @"erroneous type");
+
+    let lit = Expr::from(Lit::new(LitKind::Err, "1.2.3"));
+    assert_snapshot!(lit.try_render().unwrap_err(), @"erroneous literal `1.2.3`");
+    let ty = Type::TraitObject(TraitObject::dyn_(vec![]));
+    assert_eq!(ty.validate(), Err(RenderError::EmptyBounds));
+
+    let where_ = |ty: Type| {
+        Fn::simple("g", FnDecl::empty(), Block::empty())
+            .with_generic_param(TypeParam::simple("T").into())
+            .with_where_clause(WherePredicate::Type(PredicateType::new(
+                ty,
+                vec![PolyTraitRef::simple("Clone").into()],
+            )))
+    };
+    let item: Item = Item::inherited(where_(Type::Err));
+    assert_eq!(item.validate(), Err(RenderError::ErrType));
+    let item: Item = Item::inherited(where_(Type::TraitObject(TraitObject::dyn_(vec![]))));
+    assert_eq!(item.validate(), Err(RenderError::EmptyBounds));
+    let item: Item = Item::inherited(where_(Type::simple_path("T")));
+    assert_eq!(item.validate(), Ok(()));
+}
+
+#[test]
+fn test_fn_qualifiers() {
+    let fn_ = Fn::simple("f", FnDecl::empty(), Block::empty())
+        .with_abi(Some("C".into()))
+        .with_unsafe(true)
+        .with_const(true);
+    assert_snapshot!(fn_, @"const unsafe extern \"C\" fn f() {}");
+    assert_snapshot!(TokenStream::from(fn_.clone()), @"const unsafe extern \"C\" fn f() { }");
+    let item: Item = Item::inherited(fn_.with_async(true));
+    assert_eq!(item.validate(), Err(RenderError::ConstAsyncFn("f".into())));
+    assert_snapshot!(item.try_render().unwrap_err(), @"function `f` is both `const` and `async`");
+
+    let block = ExternBlock::unsafe_c(Block::empty())
+        .with_item(Fn::foreign("g", FnDecl::empty()).with_unsafe(true));
+    let item: Item = Item::inherited(block.clone());
+    assert!(item.validate().is_ok());
+    let block = block.with_item(Fn::foreign("h", FnDecl::empty()).with_async(true));
+    let item: Item = Item::inherited(block);
+    assert_snapshot!(item.try_render().unwrap_err(), @"foreign function `h` cannot be `default`, `const` or `async`, have an ABI or a body");
+}
+
+#[test]
+fn test_return_type_shorthands() {
+    let send_sync = || {
+        vec![
+            PolyTraitRef::simple("Error").into(),
+            PolyTraitRef::simple("Send").into(),
+            PolyTraitRef::simple("Sync").into(),
+        ]
+    };
+    let main = Fn::simple(
+        "main",
+        FnDecl::regular(
+            vec![],
+            Some(Type::result(Type::unit(), Type::box_dyn_error())),
+        ),
+        Block::single(Path::single("Ok").call1(Tuple::unit())),
+    );
+    assert_snapshot!(main, @r###"
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+    "###);
+    assert_snapshot!(Type::dyn_box(send_sync()), @"Box<dyn Error + Send + Sync>");
+    assert_snapshot!(Type::ref_(TraitObject::dyn_(send_sync())), @"&(dyn Error + Send + Sync)");
+    assert_snapshot!(Type::anyhow_result(Type::string()), @"anyhow::Result<String>");
+    assert_snapshot!(
+        Type::result_alias(Path::single("io").chain("Result"), Type::usize()),
+        @"io::Result<usize>"
+    );
+    let no_std = Type::box_dyn_error_in(PathPolicy::Alloc).unwrap();
+    assert_snapshot!(TokenStream::from(no_std), @"alloc::boxed::Box<dyn core::error::Error>");
+}
+
+#[test]
+fn test_impl_trait_param() {
+    let as_ref = || {
+        PolyTraitRef::simple(PathSegment::generic(
+            "AsRef",
+            vec![GenericArg::Type(Type::str())],
+        ))
+        .into()
+    };
+    let fn_ = Fn::simple(
+        "f",
+        FnDecl::regular(vec![Param::impl_trait("x", vec![as_ref()])], None),
+        Block::empty(),
+    );
+    let item: Item = Item::inherited(fn_);
+    assert_snapshot!(item.try_render().unwrap(), @"fn f(x: impl AsRef<str>) {}");
+    assert_snapshot!(TokenStream::from(item), @"fn f(x: impl AsRef<str>) { }");
+
+    let call = Expr::from(
+        Path::new(vec![PathSegment::generic(
+            "f",
+            vec![GenericArg::Type(ImplTrait::new(vec![as_ref()]).into())],
+        )])
+        .call1(Lit::str("x")),
+    );
+    assert_eq!(call.validate(), Err(RenderError::ImplTraitPosition));
+    let local = Local::simple(Pat::ident("x"), Lit::str("x"))
+        .with_ty(ImplTrait::new(vec![as_ref()]).into());
+    assert_eq!(
+        Stmt::from(local).validate(),
+        Err(RenderError::ImplTraitPosition)
+    );
+}
+
+#[test]
+fn test_from_str() {
+    assert_eq!("".parse(), Ok(Visibility::Inherited));
+    assert_eq!("pub".parse(), Ok(Visibility::Public));
+    assert_eq!("pub(crate)".parse(), Ok(Visibility::crate_()));
+    assert_eq!("pub( super )".parse(), Ok(Visibility::super_()));
+    let vis = "pub(in crate::a::b)".parse::<Visibility>().unwrap();
+    assert_snapshot!(vis, @"pub(in crate::a::b) ");
+    let err = "pub(in a::)".parse::<Visibility>().unwrap_err();
+    assert_snapshot!(err, @"invalid visibility `pub(in a::)`, expected an empty string, `pub`, `pub(crate)`, `pub(super)`, `pub(self)` or `pub(in path)`");
+
+    assert_eq!("extern \"C\"".parse(), Ok(Abi::c()));
+    assert_eq!("extern".parse(), Ok(Abi::c()));
+    assert_eq!("\"system\"".parse(), Ok(Abi::new("system")));
+    assert_snapshot!("extern C".parse::<Abi>().unwrap_err(), @"invalid ABI `extern C`, expected `extern`, `extern \"abi\"` or `\"abi\"`");
+
+    assert_eq!(">>".parse(), Ok(BinOpKind::Shr));
+    assert_eq!("<=".parse(), Ok(BinOpKind::Le));
+    let op = "+=".parse::<BinOpKind>().unwrap();
+    let assign = AssignOp::new(Path::single("x"), op, Lit::int("1"));
+    assert_snapshot!(Expr::from(assign), @"x += 1");
+    assert_snapshot!("&&=".parse::<BinOpKind>().unwrap_err(), @"invalid binary operator `&&=`, expected an operator such as `+`, `==` or `&&`, or a compound assignment such as `+=`");
+}
+
+#[test]
+fn test_verbatim() {
+    let mut krate = Crate::new();
+    krate.add_item(Verbatim::new(
+        "macro_rules! double { ($x:expr) => { $x * 2 }; }",
+    ));
+    let ty = Type::from(Verbatim::new("impl for<'a> Fn(&'a str)"));
+    let body = Expr::from(Verbatim::new("double!(21)"))
+        .method_call(PathSegment::simple("min"), vec![Lit::int("40").into()]);
+    krate.add_item(Fn::simple(
+        "f",
+        FnDecl::regular(vec![Param::ident("g", ty)], Some(Type::i32())),
+        Block::from(body),
+    ));
+    assert_snapshot!(krate, @r###"
+    macro_rules! double { ($x:expr) => { $x * 2 }; }
+    fn f(g: impl for<'a> Fn(&'a str)) -> i32 {
+        double!(21).min(40)
+    }
+    "###);
+}
+
+#[test]
+fn test_verbatim_validation() {
+    for text in [
+        "macro_rules! double { ($x:expr) => { $x * 2 }; }",
+        "impl for<'a> Fn(&'a str)",
+        "f(')', '\\'', \"(\\\"\")",
+        "r#\"{\"#, br\"[\", r#type",
+        "/* /* ) */ */ g() // (",
+    ] {
+        assert!(Verbatim::try_new(text).is_ok(), "{text}");
+    }
+    for text in ["f(", "(]", "x)", "\"abc", "r#\"a\"", "'", "/* /* */"] {
+        assert_eq!(
+            Verbatim::try_new(text),
+            Err(RenderError::InvalidVerbatim(text.into()))
+        );
+    }
+    let expr = Expr::from(Expr::from(Verbatim::new("f(")).add(Lit::int("1")));
+    assert_eq!(
+        expr.validate(),
+        Err(RenderError::InvalidVerbatim("f(".into()))
+    );
+    let ty = Type::from(Verbatim::new("Vec<[u8>"));
+    assert_snapshot!(ty.validate().unwrap_err(), @"verbatim text `Vec<[u8>` is not valid tokens");
+}
+
+#[test]
+fn test_path_policy() {
+    let build = |policy| {
+        let string = Type::string_in(policy)?;
+        let ty = Type::vec_in(policy, Type::box_in(policy, string)?)?;
+        let expr = Expr::from_iterable_in(policy, vec![Lit::int("1")], InitStyle::Macro)?;
+        let format = FormatArgs::new("{}").with_arg(Path::single("x"));
+        let format = format.format_in(policy)?;
+        Some(format!("{ty}\n{expr}\n{format}"))
+    };
+    assert_snapshot!(build(PathPolicy::Std).unwrap(), @r###"
+    Vec<Box<String>>
+    vec![1]
+    format!("{}", x)
+    "###);
+    assert_snapshot!(build(PathPolicy::Alloc).unwrap(), @r###"
+    alloc::vec::Vec<alloc::boxed::Box<alloc::string::String>>
+    alloc::vec![1]
+    alloc::format!("{}", x)
+    "###);
+    assert_eq!(build(PathPolicy::Core), None);
+    assert_snapshot!(Type::vec(Type::u8()), @"Vec<u8>");
+
+    assert_snapshot!(
+        PathPolicy::Core.core_item("fmt", "Debug"),
+        @"core::fmt::Debug"
+    );
+    assert_eq!(Type::vec_in(PathPolicy::Core, Type::u8()), None);
+    let hash_set = std::collections::HashSet::from([Lit::int("1")]);
+    assert_eq!(
+        Expr::from_iterable_in(PathPolicy::Alloc, hash_set.clone(), InitStyle::From),
+        None
+    );
+    assert_snapshot!(
+        Expr::from_iterable_in(PathPolicy::Std, hash_set, InitStyle::From).unwrap(),
+        @"HashSet::from([1])"
+    );
+}
+
+#[test]
+fn test_panic_helpers() {
+    let x = Expr::from(Path::single("x"));
+    let arms = vec![
+        Arm::new(Pat::Lit(Lit::int("0").into()), None, Expr::todo()),
+        Arm::new(Pat::Lit(Lit::int("1").into()), None, Expr::unimplemented()),
+        Arm::new(Pat::Lit(Lit::int("2").into()), None, Expr::unreachable()),
+        Arm::new(
+            Pat::Lit(Lit::int("3").into()),
+            None,
+            Expr::todo_fmt("\"{}\" is not done", vec![x.clone()]),
+        ),
+        Arm::new(
+            Pat::Wild,
+            None,
+            Expr::panic_fmt(
+                "bad value: {x}\n{}",
+                vec![x.clone().add(Lit::int("1")).into()],
+            ),
+        ),
+    ];
+    assert_snapshot!(Match::new(x, arms), @r###"
+    match x {
+        0 => todo!(),
+        1 => unimplemented!(),
+        2 => unreachable!(),
+        3 => todo!("\"{}\" is not done", x),
+        _ => panic!("bad value: {x}\n{}", x + 1),
+    }
+    "###);
+}
+
+#[test]
+fn test_raw_pointer_helpers() {
+    let read = Path::single("buf")
+        .raw_const()
+        .cast_ptr(Type::u32())
+        .read_unaligned();
+    assert_snapshot!(read, @"(&raw const buf).cast::<u32>().read_unaligned()");
+    let cast = Expr::from(Path::single("x"))
+        .field("data")
+        .raw_mut()
+        .cast(Type::mut_ptr(Type::u8()));
+    assert_snapshot!(cast, @"&raw mut x.data as *mut u8");
+    let header = Path::single("base")
+        .cast(Type::const_ptr(Type::u8()))
+        .cast_ptr(Type::u16())
+        .read_unaligned();
+    assert_snapshot!(header, @"(base as *const u8).cast::<u16>().read_unaligned()");
+    assert_snapshot!(TokenStream::from(read), @"(&raw const buf).cast::<u32>().read_unaligned()");
+}
+
+#[test]
+fn test_pipeline() {
+    let x = Pat::ident("x");
+    let double = Closure::simple(
+        FnDecl::regular(vec![Param::new(x.clone(), Type::Infer)], None),
+        Path::single("x").mul(Lit::int("2")),
+    );
+    let positive = Closure::simple(
+        FnDecl::regular(
+            vec![Param::new(Pat::Ref(RefPat::immut(x)), Type::Infer)],
+            None,
+        ),
+        Path::single("x").bin_op(BinOpKind::Gt, Lit::int("0")),
+    );
+    let pipeline = Pipeline::new(Path::single("xs"))
+        .iter()
+        .copied()
+        .filter(positive)
+        .map(double)
+        .collect_ty(Type::vec(Type::Infer));
+    assert_snapshot!(pipeline, @"xs.iter().copied().filter(|&x: _| { x > 0 }).map(|x: _| { x * 2 }).collect::<Vec<_>>()");
+
+    let stmt = Local::simple(
+        Pat::ident("n"),
+        Pipeline::new(Path::single("xs"))
+            .into_iter()
+            .then("count", vec![]),
+    );
+    assert_snapshot!(stmt, @"let n = xs.into_iter().count();");
+}
+
+#[test]
+fn test_struct_pat() {
+    let point = StructPat::new(
+        Path::single("Point"),
+        vec![
+            PatField::shortened("x"),
+            PatField::new("y", Pat::Lit(Lit::int("0").into())),
+        ],
+    )
+    .with_rest();
+    assert_snapshot!(point, @"Point { x, y: 0, .. }");
+    let let_ = Local::simple(
+        StructPat::new(
+            Path::single("Config"),
+            vec![PatField::shortened_mut("retries")],
+        )
+        .with_rest(),
+        Path::single("config"),
+    );
+    assert_snapshot!(let_, @"let Config { mut retries, .. } = config;");
+    let unit = StructPat::new(Path::single("Empty"), vec![]);
+    assert_snapshot!(unit, @"Empty {}");
+    assert_snapshot!(unit.with_rest(), @"Empty { .. }");
+}
+
+#[test]
+fn test_tuple_pat() {
+    let some = Pat::tuple_struct(Path::single("Some"), vec![Pat::ident("x")]);
+    let ok = Pat::tuple_struct(
+        Path::single("Ok"),
+        vec![Pat::tuple(vec![Pat::ident("a"), Pat::ident("b")])],
+    );
+    let arms = vec![
+        Arm::new(ok, None, Path::single("a")),
+        Arm::new(
+            Pat::tuple_struct(Path::single("Err"), vec![Pat::Wild]),
+            None,
+            Lit::int("0"),
+        ),
+    ];
+    assert_snapshot!(Match::new(Path::single("res"), arms), @r###"
+    match res {
+        Ok((a, b)) => a,
+        Err(_) => 0,
+    }
+    "###);
+    let triple = Pat::tuple(vec![Pat::ident("a"), Pat::Wild, Pat::ident("c")]);
+    assert_snapshot!(Local::simple(triple, Path::single("t")), @"let (a, _, c) = t;");
+    let single = Pat::tuple(vec![some]);
+    assert_snapshot!(single, @"(Some(x),)");
+    assert_snapshot!(TokenStream::from(single), @"(Some(x),)");
+    let rest = Pat::tuple(vec![Pat::ident("first"), Pat::Rest, Pat::ident("last")]);
+    assert_snapshot!(rest, @"(first, .., last)");
+    assert_snapshot!(Pat::tuple(vec![Pat::Rest]), @"(..)");
+}
+
+#[test]
+fn test_binding_modes() {
+    let some = Pat::tuple_struct(Path::single("Some"), vec![Pat::Wild]);
+    let bound = IdentPat::simple("name")
+        .with_ref()
+        .with_mut()
+        .with_pat(some);
+    assert_snapshot!(bound, @"ref mut name @ Some(_)");
+    assert_snapshot!(TokenStream::from(bound), @"ref mut name @ Some(_)");
+    assert_snapshot!(Local::simple(Pat::mut_("x"), Lit::int("0")), @"let mut x = 0;");
+    let arm = Arm::new(
+        Pat::by_ref("s"),
+        None,
+        Path::single("s").method_call0(PathSegment::simple("len")),
+    );
+    assert_snapshot!(Match::new(Path::single("text"), vec![arm]), @r###"
+    match text {
+        ref s => s.len(),
+    }
+    "###);
+    assert_snapshot!(Pat::by_ref_mut("v"), @"ref mut v");
+    assert_snapshot!(Pat::at("n", Pat::Range(Range::new(Some(Lit::int("1").into()), Some(Lit::int("9").into()), RangeLimits::Closed))), @"n @ 1 ..= 9");
+}
+
+#[test]
+fn test_ref_box_pat() {
+    let pair = Pat::ref_(Pat::tuple(vec![Pat::ident("a"), Pat::ident("b")]));
+    assert_snapshot!(pair, @"&(a, b)");
+    assert_snapshot!(Pat::ref_mut("x"), @"&mut x");
+    let some = Pat::tuple_struct(Path::single("Some"), vec![Pat::ref_("x")]);
+    assert_snapshot!(some, @"Some(&x)");
+    assert_snapshot!(Pat::box_("x"), @"box x");
+    let range = Pat::Range(Range::new(
+        Some(Lit::int("1").into()),
+        Some(Lit::int("5").into()),
+        RangeLimits::Closed,
+    ));
+    assert_snapshot!(Pat::ref_(range.clone()), @"&(1 ..= 5)");
+    assert_snapshot!(TokenStream::from(Pat::ref_(range.clone())), @"& (1..=5)");
+    assert_snapshot!(Pat::box_(range), @"box (1 ..= 5)");
+    assert_snapshot!(TokenStream::from(Pat::ref_mut("x")), @"&mut x");
+}
+
+#[test]
+fn test_fluent_pat() {
+    let arms = vec![
+        Arm::new(Pat::ok(Pat::some("x")), None, Path::single("x")),
+        Arm::new(Pat::ok(Pat::none()), None, Lit::int("0")),
+        Arm::new(Pat::err(Pat::Wild), None, Lit::int("1")),
+    ];
+    assert_snapshot!(Match::new(Path::single("res"), arms), @r###"
+    match res {
+        Ok(Some(x)) => x,
+        Ok(None) => 0,
+        Err(_) => 1,
+    }
+    "###);
+    let pair = Pat::tuple([Pat::from("a"), Pat::from(Lit::int("0"))]);
+    assert_snapshot!(pair, @"(a, 0)");
+    assert_snapshot!(TokenStream::from(Pat::some(Lit::str("s"))), @"Some(\"s\")");
+}
+
+#[test]
+fn test_try_into_pat() {
+    let point = Expr::from(Struct::new(
+        "Point",
+        vec![
+            ExprField::shortened("x"),
+            ExprField::new("y", Lit::int("0")),
+        ],
+    ));
+    assert_snapshot!(point.try_into_pat().unwrap(), @"Point { x, y: 0 }");
+    let call = Expr::from(Path::single("Some").call(vec![
+        Tuple::new(vec![Path::single("a").into(), Expr::from(Underscore {})]).into(),
+    ]));
+    assert_snapshot!(call.try_into_pat().unwrap(), @"Some((a, _))");
+    let reference = Expr::from(Path::single("Color").chain("Red")).ref_immut();
+    assert_snapshot!(Expr::from(reference).try_into_pat().unwrap(), @"&Color::Red");
+    let not_a_pat = Expr::from(Path::single("a").bin_op(BinOpKind::Add, Lit::int("1")));
+    assert_eq!(not_a_pat.try_into_pat(), None);
+    let computed = Expr::from(Path::single("f").call(vec![]).call(vec![]));
+    assert_eq!(computed.try_into_pat(), None);
+}
+
+#[test]
+fn test_or_pat() {
+    let lit = |n: &str| Pat::Lit(Lit::int(n).into());
+    let small = lit("1").or(lit("2")).or(lit("3"));
+    let arms = vec![
+        Arm::new(small.clone(), None, Lit::bool("true")),
+        Arm::new(Pat::Wild, None, Lit::bool("false")),
+    ];
+    assert_snapshot!(Match::new(Path::single("x"), arms), @r###"
+    match x {
+        1 | 2 | 3 => true,
+        _ => false,
+    }
+    "###);
+
+    let some = Pat::TupleStruct(TupleStructPat::new(
+        Path::single("Some"),
+        vec![small.clone()],
+    ));
+    assert_snapshot!(some, @"Some(1 | 2 | 3)");
+    let ref_ = Pat::Ref(RefPat::immut(small.clone()));
+    assert_snapshot!(ref_, @"&(1 | 2 | 3)");
+    let bound = IdentPat {
+        is_ref: false,
+        is_mut: false,
+        ident: "n".into(),
+        pat: Some(Box::new(small.clone())),
+    };
+    assert_snapshot!(bound, @"n @ (1 | 2 | 3)");
+    let param = Param::new(small.clone(), Type::i32());
+    assert_snapshot!(param, @"(1 | 2 | 3): i32");
+    let local = Local::simple(small.clone(), Path::single("x"));
+    assert_snapshot!(local, @"let (1 | 2 | 3) = x;");
+    assert_snapshot!(TokenStream::from(local), @"let (1 | 2 | 3) = x;");
+    assert_snapshot!(TokenStream::from(Pat::Ref(RefPat::immut(small))), @"& (1 | 2 | 3)");
+}
+
+mod prelude_only {
+    use insta::assert_snapshot;
+    use ruast::prelude::*;
+
+    #[test]
+    fn test_prelude() {
+        let mut krate = Crate::new();
+        let body = Block::from(path("x").add(lit_int("1")).ref_immut().semi());
+        krate.add_item(Fn::simple(
+            "f",
+            FnDecl::regular(vec![Param::ident("x", ty("u8"))], None),
+            body,
+        ));
+        assert_snapshot!(krate, @r###"
+        fn f(x: u8) {
+            &(x + 1);
+        }
+        "###);
+    }
+}
+
+#[test]
+fn test_paren_types() {
+    let read = || GenericBound::from(PolyTraitRef::simple("Read"));
+    let read_send = || TraitObject::dyn_(vec![read(), PolyTraitRef::simple("Send").into()]);
+    assert_snapshot!(Type::ref_(read_send()), @"&(dyn Read + Send)");
+    assert_snapshot!(Type::ref_mut(TraitObject::dyn_(vec![read()])), @"&mut dyn Read");
+    assert_snapshot!(Type::const_ptr(read_send()), @"*const (dyn Read + Send)");
+    assert_snapshot!(
+        Type::ref_(ImplTrait::new(vec![read(), GenericBound::Outlives("a".into())])),
+        @"&(impl Read + 'a)"
+    );
+    assert_snapshot!(Type::paren(Type::usize()), @"(usize)");
+    assert_snapshot!(TokenStream::from(Type::ref_(read_send())), @"&(dyn Read + Send)");
+    assert_snapshot!(TokenStream::from(Type::Slice(Box::new(Type::paren(read_send())))), @"[(dyn Read + Send)]");
+}
+
+#[test]
+fn test_qualified_types() {
+    let vec_t = Type::vec(Type::simple_path("T"));
+    let into_iter = Type::assoc(vec_t.clone(), "IntoIterator", "IntoIter");
+    assert_snapshot!(into_iter, @"<Vec<T> as IntoIterator>::IntoIter");
+    let item = Type::from(
+        QPath::as_trait(
+            Type::simple_path("T"),
+            Path::single("Iterator"),
+            Path::single("Item"),
+        )
+        .chain("Output"),
+    );
+    assert_snapshot!(item, @"<T as Iterator>::Item::Output");
+    assert_snapshot!(Type::from(QPath::inherent(vec_t, "Item")), @"<Vec<T>>::Item");
+    assert_snapshot!(Type::ref_(into_iter.clone()), @"&<Vec<T> as IntoIterator>::IntoIter");
+    let fn_ = Fn::simple(
+        "iter",
+        FnDecl::regular(
+            vec![Param::ident("v", Type::vec(Type::simple_path("T")))],
+            Some(into_iter),
+        ),
+        Block::single(Path::single("v").method_call0(PathSegment::simple("into_iter"))),
+    );
+    assert_snapshot!(fn_, @r###"
+    fn iter(v: Vec<T>) -> <Vec<T> as IntoIterator>::IntoIter {
+        v.into_iter()
+    }
+    "###);
+}
+
+#[test]
+fn test_parenthesized_args() {
+    let io_result = Type::result_alias(Path::single("io").chain("Result"), Type::unit());
+    let fn_str = PathSegment::parenthesized("Fn", vec![Type::ref_(Type::str())], Some(io_result));
+    assert_snapshot!(fn_str, @"Fn(&str) -> io::Result<()>");
+    let fn_mut = Path::single("std")
+        .chain("ops")
+        .chain(PathSegment::parenthesized(
+            "FnMut",
+            vec![Type::simple_path("T")],
+            None,
+        ));
+    assert_snapshot!(fn_mut, @"std::ops::FnMut(T)");
+    assert_snapshot!(TokenStream::from(fn_str.clone()), @"Fn(&str) -> io::Result<()>");
+
+    let run = Fn::simple(
+        "run",
+        FnDecl::regular(vec![Param::ident("f", Type::simple_path("F"))], None),
+        Block::empty(),
+    )
+    .with_generic_param(TypeParam::simple("F").into())
+    .with_generic_param(TypeParam::new("G", vec![PolyTraitRef::simple(fn_mut).into()]).into())
+    .with_where_clause(WherePredicate::Type(PredicateType::new(
+        Type::simple_path("F"),
+        vec![PolyTraitRef::simple(fn_str).into()],
+    )));
+    assert_snapshot!(run, @"fn run<F, G: std::ops::FnMut(T)>(f: F) where F: Fn(&str) -> io::Result<()> {}");
+    let output = QPath::as_trait(
+        Type::simple_path("F"),
+        PathSegment::parenthesized("FnOnce", vec![Type::u8()], None),
+        "Output",
+    );
+    assert_snapshot!(Type::from(output), @"<F as FnOnce(u8)>::Output");
+}
+
+#[cfg(feature = "unstable-syntax")]
+#[test]
+fn test_bound_modifiers() {
+    let param = TypeParam::new(
+        "T",
+        vec![
+            GenericBound::maybe_sized(),
+            PolyTraitRef::maybe_const("Default").into(),
+        ],
+    );
+    assert_snapshot!(param, @"T: ?Sized + ~const Default");
+    let negative = PolyTraitRef::negative("Send");
+    assert_snapshot!(TokenStream::from(negative.clone()), @"!Send");
+    let f = Fn::simple(
+        "f",
+        FnDecl::regular(
+            vec![Param::ident("x", Type::ref_(Type::simple_path("U")))],
+            None,
+        ),
+        Block::empty(),
+    )
+    .with_generic_param(param.into())
+    .with_generic_param(TypeParam::simple("U").into())
+    .with_where_clause(WherePredicate::Type(PredicateType::new(
+        Type::simple_path("U"),
+        vec![GenericBound::maybe_sized(), GenericBound::from(negative)],
+    )));
+    assert_snapshot!(f, @"fn f<T: ?Sized + ~const Default, U>(x: &U) where U: ?Sized + !Send {}");
+    assert_snapshot!(TokenStream::from(f), @"fn f<T: ?Sized + ~const Default, U>(x: &U) where U: ?Sized + !Send { }");
+}
+
+#[test]
+fn test_precise_capturing() {
+    let iter = ImplTrait::new(vec![PolyTraitRef::simple("Iterator").into()])
+        .with_capture(PreciseCapturingArg::Lifetime("a".into()))
+        .with_capture(PreciseCapturingArg::Arg("T".into()));
+    let f = Fn::simple(
+        "bytes",
+        FnDecl::regular(
+            vec![Param::ident(
+                "s",
+                Type::Ref(Ref::new(Some("a"), MutTy::immut(Type::str()))),
+            )],
+            Some(iter.clone().into()),
+        ),
+        Block::single(Path::single("s").method_call0(PathSegment::simple("bytes"))),
+    )
+    .with_generic_param(LifetimeParam::simple("a").into())
+    .with_generic_param(TypeParam::simple("T").into());
+    assert_snapshot!(f, @r###"
+    fn bytes<'a, T>(s: &'a str) -> impl Iterator + use<'a, T> {
+        s.bytes()
+    }
+    "###);
+    let none =
+        ImplTrait::new(vec![PolyTraitRef::simple("Sized").into()]).with_captures(Some(vec![]));
+    assert_snapshot!(none, @"impl Sized + use<>");
+    assert_snapshot!(TokenStream::from(iter.clone()), @"impl Iterator + use<'a, T>");
+    assert_snapshot!(Type::ref_(iter), @"&(impl Iterator + use<'a, T>)");
+}
+
+#[test]
+fn test_container_types() {
+    let shared = Type::arc(Type::simple_path("Mutex"));
+    assert_snapshot!(Type::option(Type::vec(Type::string())), @"Option<Vec<String>>");
+    assert_snapshot!(shared, @"std::sync::Arc<Mutex>");
+    assert_snapshot!(Type::rc(Type::str()), @"std::rc::Rc<str>");
+    assert_snapshot!(Type::result(Type::option(Type::u8()), Type::box_dyn_error()), @"Result<Option<u8>, Box<dyn std::error::Error>>");
+    let alloc = PathPolicy::Alloc;
+    let no_std = Type::arc_in(alloc, Type::rc_in(alloc, Type::u8()).unwrap()).unwrap();
+    assert_snapshot!(no_std, @"alloc::sync::Arc<alloc::rc::Rc<u8>>");
+}
+
+#[test]
+fn test_turbofish() {
+    let vec_u8 = Path::single(PathSegment::generic(
+        "Vec",
+        vec![GenericArg::Type(Type::u8())],
+    ));
+    let new = Expr::from(vec_u8.clone().chain("new").call(vec![]));
+    let local = Local::new(Pat::ident("v"), Some(Type::Path(vec_u8.clone())), new);
+    assert_snapshot!(local, @"let v: Vec<u8> = Vec::<u8>::new();");
+    assert_snapshot!(TokenStream::from(local), @"let v: Vec<u8> = Vec::<u8>::new();");
+    assert_snapshot!(vec_u8.display_type(), @"Vec<u8>");
+    let forced = Path::single(
+        PathSegment::generic("Vec", vec![GenericArg::Type(Type::u8())]).with_turbofish(true),
+    );
+    let mut krate = Crate::new();
+    krate.add_item(ConstItem::declaration("EMPTY", Type::Path(vec_u8)));
+    krate.add_item(ConstItem::declaration("FULL", Type::Path(forced)));
+    assert_snapshot!(krate.to_string().trim(), @r"
+    const EMPTY: Vec<u8>;
+    const FULL: Vec::<u8>;
+    ");
+    assert_snapshot!(TokenStream::from(krate), @"const EMPTY: Vec<u8>; const FULL: Vec::<u8>;");
+}
+
+#[test]
+fn test_desugar_impl_trait_params() {
+    let read = || vec![GenericBound::from(PolyTraitRef::simple("Read"))];
+    let mut fn_ = Fn::simple(
+        "copy",
+        FnDecl::regular(
+            vec![
+                Param::impl_trait("reader", read()),
+                Param::ident("out_buf", Type::ref_mut(ImplTrait::new(read()))),
+                Param::ident("n", Type::usize()),
+            ],
+            None,
+        ),
+        Block::empty(),
+    )
+    .with_generic_param(LifetimeParam::simple("a").into());
+    assert_eq!(fn_.desugar_impl_trait_params(), 2);
+    assert_snapshot!(fn_, @"fn copy<'a, Reader: Read, OutBuf: Read>(reader: Reader, out_buf: &mut OutBuf, n: usize) {}");
+    assert_eq!(fn_.desugar_impl_trait_params(), 0);
+
+    let as_ref_path =
+        PathSegment::generic("AsRef", vec![GenericArg::Type(Type::simple_path("Path"))]);
+    let mut open = Fn::simple(
+        "open",
+        FnDecl::regular(
+            vec![
+                Param::impl_trait("path", vec![PolyTraitRef::simple(as_ref_path).into()]),
+                Param::impl_trait("t", read()),
+            ],
+            None,
+        ),
+        Block::empty(),
+    )
+    .with_generic_param(TypeParam::simple("T").into());
+    assert_eq!(open.desugar_impl_trait_params(), 2);
+    assert_snapshot!(open, @"fn open<T, Path1: AsRef<Path>, T1: Read>(path: Path1, t: T1) {}");
+    let item: Item = Item::inherited(open);
+    assert_eq!(item.validate(), Ok(()));
+}
+
+#[test]
+fn test_async_trait_fns() {
+    let url = || Param::ident("url", Type::string());
+    let bytes = || Type::vec(Type::u8());
+    let mut fetch = Fn::empty("fetch").with_async(true);
+    fetch.fn_decl = FnDecl::method(
+        SelfParam::Ref(None, Mutability::Not),
+        vec![url()],
+        Some(bytes()),
+    );
+    let mut ids = Fn::empty("ids");
+    ids.fn_decl = FnDecl::method(
+        SelfParam::Ref(None, Mutability::Not),
+        vec![],
+        Some(
+            ImplTrait::new(vec![PolyTraitRef::simple(PathSegment::generic(
+                "Iterator",
+                vec![AssocConstraint::new("Item", Type::u32()).into()],
+            ))
+            .into()])
+            .into(),
+        ),
+    );
+    let mut def = TraitDef::simple(
+        "Client",
+        vec![
+            AssocItem::inherited(fetch.clone()),
+            AssocItem::inherited(ids),
+        ],
+    );
+    let item: Item = Item::inherited(def.clone());
+    assert_snapshot!(item.try_render().unwrap(), @r###"
+    trait Client {
+        async fn fetch(&self, url: String) -> Vec<u8>;
+        fn ids(&self) -> impl Iterator<Item = u32>;
+    }
+    "###);
+
+    assert_eq!(def.desugar_async_fns(true), 1);
+    assert_snapshot!(def, @r###"
+    trait Client {
+        fn fetch(&self, url: String) -> std::pin::Pin<Box<dyn std::future::Future<Output = Vec<u8>> + Send + '_>>;
+        fn ids(&self) -> impl Iterator<Item = u32>;
+    }
+    "###);
+
+    fetch.body = Some(Block::single(
+        Path::single("download").call1(Path::single("url")).await_(),
+    ));
+    let mut impl_ = Impl::trait_impl(
+        vec![],
+        Type::simple_path("Http"),
+        Type::simple_path("Client"),
+        None,
+        vec![AssocItem::inherited(fetch)],
+    );
+    assert_eq!(impl_.desugar_async_fns(true), 1);
+    assert_snapshot!(impl_, @r###"
+    impl Client for Http {
+        fn fetch(&self, url: String) -> std::pin::Pin<Box<dyn std::future::Future<Output = Vec<u8>> + Send + '_>> {
+            Box::pin(async move {
+                (download(url)).await
+            })
+        }
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(impl_), @"impl Client for Http { fn fetch(&self, url: String) -> std::pin::Pin<Box<dyn std::future::Future<Output = Vec<u8>> + Send + '_>> { Box::pin(async move { (download(url)) .await }) } }");
+}
+
+#[test]
+fn test_trait_builder() {
+    let receiver = || SelfParam::Ref(None, Mutability::Not);
+    let storage = TraitDef::empty("Storage")
+        .with_unsafe(true)
+        .with_generic_param(TypeParam::simple("K").into())
+        .with_supertrait(Type::simple_path("Send"))
+        .with_supertrait(Type::simple_path("Sync"))
+        .with_where_clause(WherePredicate::Type(PredicateType::new(
+            Type::simple_path("K"),
+            vec![PolyTraitRef::simple("Hash").into()],
+        )))
+        .with_assoc_type("Value", vec![PolyTraitRef::simple("Clone").into()])
+        .with_assoc_const(
+            "CAPACITY",
+            Type::simple_path("usize"),
+            Some(Lit::int("64").into()),
+        )
+        .with_required_fn(
+            "get",
+            FnDecl::method(
+                receiver(),
+                vec![Param::ident("key", Type::ref_(Type::simple_path("K")))],
+                Some(Type::option(Type::Path(
+                    Path::single("Self").chain("Value"),
+                ))),
+            ),
+        )
+        .with_provided_fn(
+            "capacity",
+            FnDecl::method(receiver(), vec![], Some(Type::simple_path("usize"))),
+            Block::from(Stmt::Expr(Path::single("Self").chain("CAPACITY").into())),
+        );
+    assert_snapshot!(storage, @r###"
+    unsafe trait Storage<K>: Send + Sync where K: Hash {
+        type Value: Clone;
+        const CAPACITY: usize = 64;
+        fn get(&self, key: &K) -> Option<Self::Value>;
+        fn capacity(&self) -> usize {
+            Self::CAPACITY
+        }
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(storage), @"unsafe trait Storage<K> : Send + Sync where K: Hash { type Value: Clone; const CAPACITY: usize = 64; fn get(&self, key: &K) -> Option<Self::Value>; fn capacity(&self) -> usize { Self::CAPACITY } }");
+    #[cfg(feature = "unstable-syntax")]
+    {
+        let marker = TraitDef::empty("Marker").with_unsafe(true).with_auto(true);
+        assert_snapshot!(marker, @"unsafe auto trait Marker {}");
+    }
+}
+
+#[test]
+fn test_impl_builder() {
+    let iter = Impl::trait_for(
+        Type::simple_path("Iterator"),
+        Type::poly_path("Counter", vec![GenericArg::Type(Type::simple_path("T"))]),
+    )
+    .with_generic_param(TypeParam::simple("T").into())
+    .with_where_clause(WherePredicate::Type(PredicateType::new(
+        Type::simple_path("T"),
+        vec![PolyTraitRef::simple("Copy").into()],
+    )))
+    .with_assoc_type("Item", Type::simple_path("T"))
+    .with_fn(
+        "next",
+        FnDecl::method(
+            SelfParam::Ref(None, Mutability::Mut),
+            vec![],
+            Some(Type::option(Type::simple_path("T"))),
+        ),
+        Block::from(Stmt::Expr(Path::single("None").into())),
+    );
+    assert_snapshot!(iter, @r###"
+    impl<T> Iterator for Counter<T> where T: Copy {
+        type Item = T;
+        fn next(&mut self) -> Option<T> {
+            None
+        }
+    }
+    "###);
+    assert_snapshot!(TokenStream::from(iter), @"impl<T> Iterator for Counter<T> where T: Copy { type Item = T; fn next(&mut self) -> Option<T> { None } }");
+    let inherent = Impl::empty(Type::simple_path("Counter")).with_assoc_const(
+        "STEP",
+        Type::simple_path("usize"),
+        Lit::int("1"),
+    );
+    assert_snapshot!(inherent, @r###"
+    impl Counter {
+        const STEP: usize = 1;
+    }
+    "###);
+    let fluent = Impl::builder(Type::poly_path(
+        "Counter",
+        vec![GenericArg::Type(Type::simple_path("T"))],
+    ))
+    .generic(TypeParam::simple("T"))
+    .where_(WherePredicate::Type(PredicateType::new(
+        Type::simple_path("T"),
+        vec![PolyTraitRef::simple("Copy").into()],
+    )))
+    .assoc_const("STEP", Type::simple_path("usize"), Lit::int("1"))
+    .fn_(
+        "step",
+        FnDecl::method(
+            SelfParam::Value(Mutability::Not),
+            vec![],
+            Some(Type::usize()),
+        ),
+        Block::from(Stmt::Expr(Path::single("Self").chain("STEP").into())),
+    );
+    assert_snapshot!(fluent, @r###"
+    impl<T> Counter<T> where T: Copy {
+        const STEP: usize = 1;
+        fn step(self) -> usize {
+            Self::STEP
+        }
+    }
+    "###);
+    let with_assoc_type =
+        Impl::trait_for(Type::simple_path("Iterator"), Type::simple_path("Counter"))
+            .assoc_type("Item", Type::u32());
+    assert_snapshot!(with_assoc_type, @r###"
+    impl Iterator for Counter {
+        type Item = u32;
+    }
+    "###);
+}
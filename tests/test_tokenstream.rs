@@ -53,7 +53,7 @@ fn test_ptr_to_tokenstream() {
 fn test_barefn_to_tokenstream() {
     let simple_fn = BareFn::safe(vec![], vec![], Type::unit());
     let ts = TokenStream::from(simple_fn);
-    assert_snapshot!(ts, @"fn() -> ()");
+    assert_snapshot!(ts, @"fn()");
 
     let unsafe_fn = BareFn::new(vec![], vec![], Type::i32(), None, true);
     let ts = TokenStream::from(unsafe_fn);
@@ -62,6 +62,27 @@ fn test_barefn_to_tokenstream() {
     let extern_fn = BareFn::new(vec![], vec![], Type::i32(), Some("C".into()), false);
     let ts = TokenStream::from(extern_fn);
     assert_snapshot!(ts, @"extern \"C\" fn() -> i32");
+
+    let c_fn = BareFn::c(vec![], Type::unit()).with_unsafe(true);
+    let ts = TokenStream::from(c_fn.clone());
+    assert_snapshot!(ts, @"unsafe extern \"C\" fn()");
+    assert_eq!(ts.to_string(), c_fn.to_string());
+
+    let explicit_unit = BareFn::safe(vec![], vec![], Type::unit()).with_elide_unit_output(false);
+    let ts = TokenStream::from(explicit_unit);
+    assert_snapshot!(ts, @"fn() -> ()");
+
+    let higher_ranked = BareFn::c(
+        vec![Param::ident(
+            "s",
+            Type::Ref(Ref::new(Some("a"), MutTy::immut(Type::str()))),
+        )],
+        Type::unit(),
+    )
+    .with_generic_param(LifetimeParam::simple("a").into());
+    assert_snapshot!(higher_ranked, @"for<'a> extern \"C\" fn(s: &'a str)");
+    let ts = TokenStream::from(higher_ranked);
+    assert_snapshot!(ts, @"for<'a> extern \"C\" fn(s: &'a str)");
 }
 
 #[test]
@@ -107,6 +128,26 @@ fn test_genericbound_to_tokenstream() {
     let lifetime_bound = GenericBound::Outlives("static".into());
     let ts = TokenStream::from(lifetime_bound);
     assert_snapshot!(ts, @"'static");
+
+    let fn_bound = TypeParam::simple("F")
+        .with_bound(PolyTraitRef::fn_(vec![Type::ref_(Type::str())], Some(Type::bool())).into())
+        .with_bound(PolyTraitRef::simple("Send").into());
+    assert_snapshot!(fn_bound, @"F: Fn(&str) -> bool + Send");
+    let ts = TokenStream::from(fn_bound);
+    assert_snapshot!(ts, @"F: Fn(&str) -> bool + Send");
+
+    let higher_ranked = GenericBound::from(
+        PolyTraitRef::fn_mut(vec![Type::ref_(Type::str())], None)
+            .with_bound_generic_param(LifetimeParam::simple("a").with_bound("b").into()),
+    );
+    assert_snapshot!(higher_ranked, @"for<'a: 'b> FnMut(&str)");
+    let ts = TokenStream::from(higher_ranked);
+    assert_snapshot!(ts, @"for<'a: 'b> FnMut(&str)");
+
+    let poly =
+        PolyTraitRef::simple("Tr").with_bound_generic_param(LifetimeParam::simple("a").into());
+    let ts = TokenStream::from(poly);
+    assert_snapshot!(ts, @"for<'a> Tr");
 }
 
 #[test]
@@ -174,7 +215,7 @@ fn test_attrargs_to_tokenstream() {
 
     let eq_args = AttrArgs::Eq(Expr::new(Lit::str("value")));
     let ts = TokenStream::from(eq_args);
-    assert_snapshot!(ts, @"\"value\" =");
+    assert_snapshot!(ts, @"= \"value\"");
 }
 
 #[test]
@@ -206,6 +247,37 @@ fn test_attributeitem_to_tokenstream() {
     assert_snapshot!(ts, @r#"#[cfg(feature = "serde")]"#);
 }
 
+#[test]
+fn test_doc_attributes() {
+    let doc = Attribute::doc("Returns the `len\r\nof the \"list\".\n\n```\nlet x = 1;");
+    assert_snapshot!(doc, @r###"
+    /// Returns the \`len
+    /// of the "list".
+    ///
+    /// ```
+    /// let x = 1;
+    /// ```
+    "###);
+
+    let ts = TokenStream::from(Attribute::doc_attr("a \"quoted\" `word`\nsecond line"));
+    assert_snapshot!(ts, @"#[doc = \"a \\\"quoted\\\" `word`\\nsecond line\"]");
+
+    let block = Attribute::doc_block("ends early */ here");
+    assert_snapshot!(block, @r###"
+    /**
+    ends early *\/ here
+    */
+    "###);
+
+    assert_eq!(validate_doc("fine `code`"), Ok(()));
+    assert_eq!(validate_doc("a\r\nb"), Err(DocError::CarriageReturn));
+    assert_eq!(
+        Attribute::try_doc("one\ntwo ` three"),
+        Err(DocError::UnmatchedBacktick(2))
+    );
+    assert_eq!(validate_doc("```rust\n` x"), Err(DocError::UnclosedFence));
+}
+
 #[test]
 fn test_expr_to_tokenstream() {
     let expr = Expr::new(Lit::int("42"));
@@ -340,6 +412,30 @@ fn test_loop_to_tokenstream() {
     assert_snapshot!(ts, @"loop { 1 }");
 }
 
+#[test]
+fn test_labelled_loops_to_tokenstream() {
+    let brk = Break::new(Some("outer".into()), None).semi();
+    let inner = While::new(Lit::bool("true"), Block::from(brk)).with_label("inner");
+    let outer = Loop::new(Block::from(Stmt::Expr(Expr::new(inner)))).with_label("outer");
+    assert_snapshot!(outer, @r###"
+    'outer: loop {
+        'inner: while true {
+            break 'outer;
+        }
+    }
+    "###);
+    let ts = TokenStream::from(outer);
+    assert_snapshot!(ts, @"'outer: loop { 'inner: while true { break 'outer; } }");
+
+    let for_stmt = ForLoop::new(Pat::Wild, Path::single("items"), Block::empty()).with_label("l");
+    let ts = TokenStream::from(for_stmt);
+    assert_snapshot!(ts, @"'l: for _ in items { }");
+
+    let block = LabelledBlock::labelled("b", Block::from(Stmt::Expr(Expr::new(Lit::int("1")))));
+    let ts = TokenStream::from(block);
+    assert_snapshot!(ts, @"'b: { 1 }");
+}
+
 #[test]
 fn test_constblock_to_tokenstream() {
     let const_block = ConstBlock::new(Block::from(Stmt::Expr(Expr::new(Lit::int("42")))));
@@ -379,7 +475,7 @@ fn test_match_to_tokenstream() {
 fn test_closure_to_tokenstream() {
     let closure = Closure::simple(FnDecl::regular(vec![], None), Lit::int("42"));
     let ts = TokenStream::from(closure);
-    assert_snapshot!(ts, @"|| -> { 42 }");
+    assert_snapshot!(ts, @"|| { 42 }");
 
     let closure_with_params = Closure::simple(
         FnDecl::regular(
@@ -388,8 +484,13 @@ fn test_closure_to_tokenstream() {
         ),
         Lit::int("x"),
     );
-    let ts = TokenStream::from(closure_with_params);
-    assert_snapshot!(ts, @"|x: i32| -> { x }");
+    let ts = TokenStream::from(closure_with_params.clone());
+    assert_snapshot!(ts, @"|x: i32| { x }");
+
+    let closure_with_output = closure_with_params.with_output(Type::u32());
+    assert_snapshot!(closure_with_output, @"|x: i32| -> u32 { x }");
+    let ts = TokenStream::from(closure_with_output);
+    assert_snapshot!(ts, @"|x: i32| -> u32 { x }");
 }
 
 #[test]
@@ -413,6 +514,24 @@ fn test_tryblock_to_tokenstream() {
     assert_snapshot!(ts, @"try { 42 }");
 }
 
+#[test]
+fn test_paren_to_tokenstream() {
+    let paren = Paren::new(Path::single("x"));
+    let ts = TokenStream::from(paren.clone());
+    assert_snapshot!(ts, @"(x)");
+
+    let nested = Paren::new(paren).add(Lit::int("1")).paren().neg();
+    let ts = TokenStream::from(nested);
+    assert_snapshot!(ts, @"-(((x)) + 1)");
+}
+
+#[test]
+fn test_verbatim_to_tokenstream() {
+    let verbatim = Verbatim::new("a  +  b");
+    let ts = TokenStream::from(Expr::from(verbatim).add(Lit::int("1")));
+    assert_snapshot!(ts, @"a  +  b + 1");
+}
+
 #[test]
 fn test_field_to_tokenstream() {
     let field = Field::new(Path::single("obj"), "field");
@@ -422,6 +541,11 @@ fn test_field_to_tokenstream() {
     let nested_field = Field::new(Field::new(Path::single("obj"), "field"), "subfield");
     let ts = TokenStream::from(nested_field);
     assert_snapshot!(ts, @"obj.field.subfield");
+
+    let tuple_field = Field::new(Path::single("pair").field_idx(0), 1);
+    let ts = TokenStream::from(tuple_field);
+    assert!(ts.iter().last().is_some_and(|token| token.is_lit()));
+    assert_snapshot!(ts, @"pair.0.1");
 }
 
 #[test]
@@ -528,6 +652,115 @@ fn test_lit_to_tokenstream() {
     let bool_lit = Lit::bool("true");
     let ts = TokenStream::from(bool_lit);
     assert_snapshot!(ts, @"true");
+
+    let raw_lit = Lit::raw_str(r"\d+\.\d+");
+    let ts = TokenStream::from(raw_lit);
+    assert_snapshot!(ts, @"r\"\\d+\\.\\d+\"");
+
+    let raw_lit = Lit::raw_str(r##"say "#hi""##);
+    assert_eq!(raw_lit.kind, LitKind::StrRaw(2));
+    let ts = TokenStream::from(raw_lit);
+    assert_snapshot!(ts, @"r##\"say \"#hi\"\"##");
+
+    let ts = TokenStream::from(Lit::raw_bstr("a\\b"));
+    assert_snapshot!(ts, @"br\"a\\b\"");
+
+    let ts = TokenStream::from(Lit::raw_cstr(r#"C:\"x""#));
+    assert_snapshot!(ts, @"cr#\"C:\\\"x\"\"#");
+
+    let ts = TokenStream::from(Lit::bstr("bytes"));
+    assert_snapshot!(ts, @"b\"bytes\"");
+
+    let ts = TokenStream::from(Lit::cstr("hello"));
+    assert_snapshot!(ts, @"c\"hello\"");
+
+    let ts = TokenStream::from(Lit::byte("x"));
+    assert_snapshot!(ts, @"b'x'");
+
+    let ts = TokenStream::from(Lit::char('x'));
+    assert_snapshot!(ts, @"'x'");
+
+    let ts = TokenStream::from(Lit::char('\''));
+    assert_snapshot!(ts, @"'\\''");
+
+    let lit = Lit::u8(3);
+    assert_eq!(lit.suffix(), Some("u8"));
+    let ts = TokenStream::from(lit);
+    assert_snapshot!(ts, @"3u8");
+
+    let ts = TokenStream::from(Lit::i64(-7));
+    assert_snapshot!(ts, @"-7i64");
+
+    let ts = TokenStream::from(Lit::f32(1.5).unwrap());
+    assert_snapshot!(ts, @"1.5f32");
+
+    let ts = TokenStream::from(Lit::f64(2.0).unwrap());
+    assert_snapshot!(ts, @"2.0f64");
+
+    assert_eq!(Lit::int("0x1f32").suffix(), None);
+    assert_eq!(Lit::int("42").suffix(), None);
+}
+
+#[test]
+fn test_lit_escaping() {
+    let lit = Lit::str("he said \"hi\"\n\\");
+    assert_snapshot!(lit, @"\"he said \\\"hi\\\"\\n\\\\\"");
+
+    let lit = Lit::char('\n');
+    assert_snapshot!(lit, @"'\\n'");
+
+    let lit = Lit::char('"');
+    assert_snapshot!(lit, @"'\"'");
+
+    let lit = Lit::bstr("é\u{7}");
+    assert_snapshot!(lit, @"b\"\\xc3\\xa9\\x07\"");
+
+    let lit = Lit::raw_str("C:\\path");
+    assert_snapshot!(lit, @"r\"C:\\path\"");
+
+    let doc = Attribute::doc_comment("/// first\r\nfn injected() {}");
+    assert_snapshot!(doc, @r###"
+    /// first
+    /// fn injected() {}
+    "###);
+
+    let doc = Attribute::doc_comment("/** a */ b */");
+    assert_snapshot!(doc, @"/** a *\\/ b */");
+
+    let doc = Attribute::doc_comment("/*/");
+    assert_snapshot!(doc, @r###"
+    /*/
+    */
+    "###);
+
+    let doc = Attribute::doc_block("x /* y");
+    assert_snapshot!(doc, @r###"
+    /**
+    x /\* y
+    */
+    "###);
+}
+
+#[test]
+fn test_expr_from_iterable() {
+    use std::collections::{BTreeMap, HashSet};
+
+    let items = vec![Lit::int("1"), Lit::int("2"), Lit::int("3")];
+    let expr = Expr::from_iterable(items.clone(), InitStyle::Macro);
+    assert_snapshot!(expr, @"vec![1, 2, 3]");
+    let ts = TokenStream::from(Expr::from_iterable(items, InitStyle::From));
+    assert_snapshot!(ts, @"Vec::from([1, 2, 3])");
+
+    let map = BTreeMap::from([
+        (Lit::str("b"), Lit::int("2")),
+        (Lit::str("a"), Lit::int("1")),
+    ]);
+    let expr = Expr::from_iterable(map, InitStyle::Macro);
+    assert_snapshot!(expr, @"BTreeMap::from([(\"a\", 1), (\"b\", 2)])");
+
+    let set = HashSet::from([Lit::str("y"), Lit::str("x"), Lit::str("z")]);
+    let expr = Expr::from_iterable(set, InitStyle::default());
+    assert_snapshot!(expr, @"HashSet::from([\"x\", \"y\", \"z\"])");
 }
 
 #[test]
@@ -580,6 +813,23 @@ fn test_methodcall_to_tokenstream() {
     assert_snapshot!(ts, @"obj.method(1)");
 }
 
+#[test]
+fn test_methodcall_generic_to_tokenstream() {
+    let collect = Path::single("iter").method_call_generic(
+        "collect",
+        vec![Type::vec(Type::Infer).into()],
+        vec![],
+    );
+    assert_snapshot!(collect, @"iter.collect::<Vec<_>>()");
+    let ts = TokenStream::from(collect);
+    assert_snapshot!(ts, @"iter.collect::<Vec<_>>()");
+
+    let parse = Expr::from(Path::single("s"))
+        .method_call_generic("parse", vec![Type::u32().into()], vec![])
+        .try_();
+    assert_snapshot!(parse, @"s.parse::<u32>()?");
+}
+
 #[test]
 fn test_path_to_tokenstream() {
     let simple_path = Path::single("foo");
@@ -591,6 +841,33 @@ fn test_path_to_tokenstream() {
     assert_snapshot!(ts, @"std::collections::HashMap");
 }
 
+#[test]
+fn test_qpath_to_tokenstream() {
+    let default = Expr::from(QPath::as_trait(
+        Type::simple_path("Foo"),
+        Path::single("Default"),
+        "default",
+    ))
+    .call(vec![]);
+    assert_snapshot!(default, @"<Foo as Default>::default()");
+    let ts = TokenStream::from(default);
+    assert_snapshot!(ts, @"<Foo as Default>::default()");
+
+    let item = Type::from(QPath::as_trait(
+        Type::simple_path("T"),
+        Path::single("std").chain("iter").chain("Iterator"),
+        "Item",
+    ));
+    assert_snapshot!(item, @"<T as std::iter::Iterator>::Item");
+    let ts = TokenStream::from(item);
+    assert_snapshot!(ts, @"<T as std::iter::Iterator>::Item");
+
+    let inherent = Type::from(QPath::inherent(Type::vec(Type::u8()), "Target").chain("Output"));
+    assert_snapshot!(inherent, @"<Vec<u8>>::Target::Output");
+    let ts = TokenStream::from(inherent);
+    assert_snapshot!(ts, @"<Vec<u8>>::Target::Output");
+}
+
 #[test]
 fn test_pathsegment_to_tokenstream() {
     let simple_segment = PathSegment::simple("foo");
@@ -647,6 +924,34 @@ fn test_continue_to_tokenstream() {
     assert_snapshot!(ts, @"continue 'loop1");
 }
 
+#[test]
+fn test_label_to_tokenstream() {
+    assert_eq!(Label::new("'outer"), Label::new("outer"));
+    assert_eq!(Label::new("'outer").ident(), "outer");
+    assert!(Label::try_new("'a").is_ok());
+    let err = Label::try_new("static").unwrap_err();
+    assert_eq!(
+        err,
+        InvalidLabel {
+            ident: "static".into()
+        }
+    );
+    assert_eq!(err.to_string(), "`'static` is not a valid label");
+    assert!(Label::try_new("'_").is_err());
+    assert!(Label::try_new("1a").is_err());
+    assert!(Label::try_new("").is_err());
+    assert_eq!(Label::new("a b").ident(), "a b");
+
+    let continue_outer = Continue::new(Some("'outer".into()));
+    assert_snapshot!(continue_outer, @"continue 'outer");
+    let ts = TokenStream::from(continue_outer);
+    assert_snapshot!(ts, @"continue 'outer");
+
+    let loop_ = Loop::new(Block::empty()).with_label("'outer");
+    let ts = TokenStream::from(loop_);
+    assert_snapshot!(ts, @"'outer: loop { }");
+}
+
 #[test]
 fn test_genericarg_to_tokenstream() {
     let lifetime_arg = GenericArg::Lifetime("static".into());
@@ -687,6 +992,55 @@ fn test_maccall_to_tokenstream() {
     assert_snapshot!(ts, @r#"println!("Hello")"#);
 }
 
+#[test]
+fn test_asm_to_tokenstream() {
+    let asm = Asm::single("mov {0}, {1}")
+        .with_line("add {0}, {number}")
+        .with_operand(AsmOperand::out(AsmReg::reg(), Path::single("o")))
+        .with_operand(AsmOperand::in_(AsmReg::reg(), Path::single("i")))
+        .with_operand(AsmOperand::const_(Lit::int("5")).with_name("number"))
+        .with_operand(
+            AsmOperand::inout_split(
+                AsmReg::explicit("eax"),
+                Path::single("x"),
+                Path::single("y"),
+            )
+            .late(),
+        )
+        .with_operand(AsmOperand::clobber(AsmReg::explicit("edx")))
+        .with_clobber_abi("C")
+        .with_option(AsmOption::Pure)
+        .with_option(AsmOption::Nomem)
+        .with_option(AsmOption::Nostack);
+    assert_snapshot!(asm, @"asm!(\"mov {0}, {1}\", \"add {0}, {number}\", out(reg) o, in(reg) i, number = const 5, inlateout(\"eax\") x => y, out(\"edx\") _, clobber_abi(\"C\"), options(pure, nomem, nostack))");
+    let ts = TokenStream::from(asm);
+    assert_snapshot!(ts, @"asm!(\"mov {0}, {1}\", \"add {0}, {number}\", out(reg) o, in(reg) i, number = const 5, inlateout(\"eax\") x => y, out(\"edx\") _, clobber_abi(\"C\"), options(pure, nomem, nostack))");
+
+    let global =
+        Asm::global(vec!["nop".into()]).with_operand(AsmOperand::sym(Path::single("main")));
+    let ts = TokenStream::from(global);
+    assert_snapshot!(ts, @"global_asm!(\"nop\", sym main)");
+}
+
+#[test]
+fn test_format_args_to_tokenstream() {
+    let mac = FormatArgs::new("")
+        .with_str("x = ")
+        .with_value(Path::single("x"))
+        .with_str(", {y} = \"")
+        .with_named_value("y", Lit::int("2"))
+        .with_str("\"\n")
+        .println();
+    assert_snapshot!(mac, @"println!(\"x = {}, {{y}} = \\\"{y}\\\"\\n\", x, y = 2)");
+
+    let mac = FormatArgs::new("{}: {value:?}")
+        .with_str(" {}")
+        .with_arg(Lit::str("key"))
+        .with_named_arg("value", Path::single("v"))
+        .write(Path::single("f"));
+    assert_snapshot!(mac, @"write!(f, \"{}: {value:?} {{}}\", \"key\", value = v)");
+}
+
 #[test]
 fn test_exprfield_to_tokenstream() {
     let expr_field = ExprField::new("name", Lit::str("value"));
@@ -758,7 +1112,7 @@ fn test_type_ref_variants_to_tokenstream() {
 fn test_type_barefn_to_tokenstream() {
     let fn_ty = Type::BareFn(BareFn::safe(vec![], vec![], Type::unit()));
     let ts = TokenStream::from(fn_ty);
-    assert_snapshot!(ts, @"fn() -> ()");
+    assert_snapshot!(ts, @"fn()");
 
     let unsafe_fn_ty = Type::BareFn(BareFn::new(
         vec![],
@@ -835,7 +1189,7 @@ fn test_complex_nested_types_to_tokenstream() {
     );
     let ref_mut_ty = Type::Ref(Ref::new(Some("static"), MutTy::mut_(array_ty)));
     let ts = TokenStream::from(ref_mut_ty);
-    assert_snapshot!(ts, @"&'static mut [Box::<dyn Send + Sync>; 10]");
+    assert_snapshot!(ts, @"&'static mut [Box<dyn Send + Sync>; 10]");
 }
 
 #[test]
@@ -909,9 +1263,13 @@ fn test_patfield_to_tokenstream() {
     let pat_field = PatField {
         ident: "name".into(),
         pat: Pat::ident("value"),
+        is_shorthand: false,
     };
     let ts = TokenStream::from(pat_field);
     assert_snapshot!(ts, @"name: value");
+
+    let ts = TokenStream::from(PatField::shortened_mut("name"));
+    assert_snapshot!(ts, @"mut name");
 }
 
 #[test]
@@ -932,10 +1290,18 @@ fn test_structpat_to_tokenstream() {
         fields: vec![PatField {
             ident: "x".into(),
             pat: Pat::ident("a"),
+            is_shorthand: false,
         }],
+        has_rest: false,
     };
     let ts = TokenStream::from(struct_pat);
     assert_snapshot!(ts, @"Point { x: a }");
+
+    let struct_pat = StructPat::new(Path::single("Point"), vec![PatField::shortened("x")])
+        .with_field(PatField::new("y", Pat::Lit(Lit::int("0").into())))
+        .with_rest();
+    let ts = TokenStream::from(struct_pat);
+    assert_snapshot!(ts, @"Point { x, y: 0, .. }");
 }
 
 #[test]
@@ -1138,7 +1504,10 @@ fn test_traitdef_to_tokenstream() {
 
 #[test]
 fn test_predicatetype_to_tokenstream() {
-    let predicate = PredicateType::new(Type::simple_path("T"), vec![Type::simple_path("Clone")]);
+    let predicate = PredicateType::new(
+        Type::simple_path("T"),
+        vec![PolyTraitRef::simple("Clone").into()],
+    );
     let ts = TokenStream::from(predicate);
     assert_snapshot!(ts, @"T: Clone");
 }
@@ -1154,7 +1523,7 @@ fn test_predicatelifetime_to_tokenstream() {
 fn test_wherepredicate_to_tokenstream() {
     let predicate = WherePredicate::Type(PredicateType::new(
         Type::simple_path("T"),
-        vec![Type::simple_path("Clone")],
+        vec![PolyTraitRef::simple("Clone").into()],
     ));
     let ts = TokenStream::from(predicate);
     assert_snapshot!(ts, @"T: Clone");
@@ -1268,30 +1637,28 @@ fn test_use_to_tokenstream() {
 #[test]
 fn test_staticitem_to_tokenstream() {
     let static_item = StaticItem {
+        safety: Safety::Default,
         mutability: Mutability::Not,
         ident: "MY_STATIC".into(),
         ty: Type::i32(),
         expr: Some(Expr::new(Lit::int("42"))),
     };
     let ts = TokenStream::from(static_item);
-    assert_snapshot!(ts, @"static MY_STATIC: i32 = 42");
+    assert_snapshot!(ts, @"static MY_STATIC: i32 = 42;");
 }
 
 #[test]
 fn test_constitem_to_tokenstream() {
     let const_item = ConstItem::new("MY_CONST", Type::i32(), Some(Expr::new(Lit::int("42"))));
     let ts = TokenStream::from(const_item);
-    assert_snapshot!(ts, @"const MY_CONST: i32 = 42");
+    assert_snapshot!(ts, @"const MY_CONST: i32 = 42;");
 }
 
 #[test]
 fn test_tyalias_to_tokenstream() {
-    let ty_alias = TyAlias {
-        ident: "MyType".into(),
-        ty: Some(Type::i32()),
-    };
+    let ty_alias = TyAlias::new("MyType", Type::i32());
     let ts = TokenStream::from(ty_alias);
-    assert_snapshot!(ts, @"type MyType = i32");
+    assert_snapshot!(ts, @"type MyType = i32;");
 }
 
 #[test]